@@ -78,6 +78,20 @@ pub struct DevServerOptions {
     #[cfg_attr(feature = "serializable", serde(default))]
     pub full_stats: bool,
 
+    /// The memory limit in MB for Turbo Engine. Inactive tasks are unloaded
+    /// when the memory usage exceeds it.
+    #[cfg_attr(feature = "cli", clap(long))]
+    #[cfg_attr(feature = "serializable", serde(default))]
+    pub memory_limit: Option<usize>,
+
+    /// A directory to persist the Turbo Engine cache in. Restarting the dev
+    /// server restores the cache from there, so unchanged modules don't need
+    /// to be compiled again.
+    #[cfg(feature = "persistent_cache")]
+    #[cfg_attr(feature = "cli", clap(long, value_parser))]
+    #[cfg_attr(feature = "serializable", serde(default))]
+    pub cache_dir: Option<PathBuf>,
+
     // ==
     // = Inherited options from next-dev, need revisit later.
     // ==
@@ -5,6 +5,7 @@ pub mod devserver_options;
 mod turbo_tasks_viz;
 
 use std::{
+    any::Any,
     collections::HashSet,
     env::current_dir,
     future::{join, Future},
@@ -25,11 +26,12 @@ use next_core::{
 use owo_colors::OwoColorize;
 use turbo_malloc::TurboMalloc;
 use turbo_tasks::{
+    backend::Backend,
     util::{FormatBytes, FormatDuration},
     RawVc, StatsType, TransientInstance, TransientValue, TurboTasks, TurboTasksBackendApi, Value,
 };
 use turbo_tasks_fs::{DiskFileSystemVc, FileSystemVc};
-use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_memory::{stats::Stats, MemoryBackend};
 use turbopack_cli_utils::issue::{ConsoleUi, ConsoleUiVc, LogOptions};
 use turbopack_core::{
     environment::ServerAddr,
@@ -54,8 +56,8 @@ pub enum EntryRequest {
     Module(String, String),
 }
 
-pub struct NextDevServerBuilder {
-    turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+pub struct NextDevServerBuilder<B: Backend + 'static> {
+    turbo_tasks: Arc<TurboTasks<B>>,
     project_dir: String,
     root_dir: String,
     entry_requests: Vec<EntryRequest>,
@@ -69,12 +71,12 @@ pub struct NextDevServerBuilder {
     allow_retry: bool,
 }
 
-impl NextDevServerBuilder {
+impl<B: Backend + 'static> NextDevServerBuilder<B> {
     pub fn new(
-        turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+        turbo_tasks: Arc<TurboTasks<B>>,
         project_dir: String,
         root_dir: String,
-    ) -> NextDevServerBuilder {
+    ) -> NextDevServerBuilder<B> {
         NextDevServerBuilder {
             turbo_tasks,
             project_dir,
@@ -93,47 +95,47 @@ impl NextDevServerBuilder {
         }
     }
 
-    pub fn entry_request(mut self, entry_asset_path: EntryRequest) -> NextDevServerBuilder {
+    pub fn entry_request(mut self, entry_asset_path: EntryRequest) -> NextDevServerBuilder<B> {
         self.entry_requests.push(entry_asset_path);
         self
     }
 
-    pub fn eager_compile(mut self, eager_compile: bool) -> NextDevServerBuilder {
+    pub fn eager_compile(mut self, eager_compile: bool) -> NextDevServerBuilder<B> {
         self.eager_compile = eager_compile;
         self
     }
 
-    pub fn hostname(mut self, hostname: IpAddr) -> NextDevServerBuilder {
+    pub fn hostname(mut self, hostname: IpAddr) -> NextDevServerBuilder<B> {
         self.hostname = Some(hostname);
         self
     }
 
-    pub fn port(mut self, port: u16) -> NextDevServerBuilder {
+    pub fn port(mut self, port: u16) -> NextDevServerBuilder<B> {
         self.port = Some(port);
         self
     }
 
-    pub fn browserslist_query(mut self, browserslist_query: String) -> NextDevServerBuilder {
+    pub fn browserslist_query(mut self, browserslist_query: String) -> NextDevServerBuilder<B> {
         self.browserslist_query = browserslist_query;
         self
     }
 
-    pub fn log_level(mut self, log_level: IssueSeverity) -> NextDevServerBuilder {
+    pub fn log_level(mut self, log_level: IssueSeverity) -> NextDevServerBuilder<B> {
         self.log_level = log_level;
         self
     }
 
-    pub fn show_all(mut self, show_all: bool) -> NextDevServerBuilder {
+    pub fn show_all(mut self, show_all: bool) -> NextDevServerBuilder<B> {
         self.show_all = show_all;
         self
     }
 
-    pub fn allow_retry(mut self, allow_retry: bool) -> NextDevServerBuilder {
+    pub fn allow_retry(mut self, allow_retry: bool) -> NextDevServerBuilder<B> {
         self.allow_retry = allow_retry;
         self
     }
 
-    pub fn log_detail(mut self, log_detail: bool) -> NextDevServerBuilder {
+    pub fn log_detail(mut self, log_detail: bool) -> NextDevServerBuilder<B> {
         self.log_detail = log_detail;
         self
     }
@@ -198,17 +200,24 @@ impl NextDevServerBuilder {
             log_level: self.log_level,
         };
         let entry_requests = Arc::new(self.entry_requests);
+        // The task graph visualization is only supported by the in-memory
+        // backend.
+        let viz_turbo_tasks = TransientInstance::new(
+            (turbo_tasks.clone() as Arc<dyn Any + Send + Sync>)
+                .downcast::<TurboTasks<MemoryBackend>>()
+                .ok(),
+        );
         let console_ui = Arc::new(ConsoleUi::new(log_options));
         let console_ui_to_dev_server = console_ui.clone();
         let server_addr = Arc::new(server.addr);
-        let tasks = turbo_tasks.clone();
+        let tasks = turbo_tasks;
         let source = move || {
             source(
                 root_dir.clone(),
                 project_dir.clone(),
                 entry_requests.clone().into(),
                 eager_compile,
-                turbo_tasks.clone().into(),
+                viz_turbo_tasks.clone(),
                 console_ui.clone().into(),
                 browserslist_query.clone(),
                 server_addr.clone().into(),
@@ -254,7 +263,7 @@ async fn source(
     project_dir: String,
     entry_requests: TransientInstance<Vec<EntryRequest>>,
     eager_compile: bool,
-    turbo_tasks: TransientInstance<TurboTasks<MemoryBackend>>,
+    viz_turbo_tasks: TransientInstance<Option<Arc<TurboTasks<MemoryBackend>>>>,
     console_ui: TransientInstance<ConsoleUi>,
     browserslist_query: String,
     server_addr: TransientInstance<SocketAddr>,
@@ -321,11 +330,6 @@ async fn source(
         next_config,
         server_addr,
     );
-    let viz = turbo_tasks_viz::TurboTasksSource {
-        turbo_tasks: turbo_tasks.into(),
-    }
-    .cell()
-    .into();
     let static_source =
         StaticAssetsContentSourceVc::new(String::new(), project_path.join("public")).into();
     let manifest_source = DevManifestContentSource {
@@ -352,18 +356,26 @@ async fn source(
         CombinedContentSourceVc::new(vec![static_source, page_source]).into(),
     )
     .into();
+    let mut routes = vec![
+        ("__turbopack__/".to_string(), introspect),
+        (
+            "__nextjs_original-stack-frame".to_string(),
+            source_map_trace,
+        ),
+        // TODO: Load path from next.config.js
+        ("_next/image".to_string(), img_source),
+        ("__turbopack_sourcemap__/".to_string(), source_maps),
+    ];
+    if let Some(turbo_tasks) = &*viz_turbo_tasks {
+        let viz = turbo_tasks_viz::TurboTasksSource {
+            turbo_tasks: turbo_tasks.clone(),
+        }
+        .cell()
+        .into();
+        routes.push(("__turbo_tasks__/".to_string(), viz));
+    }
     let source = RouterContentSource {
-        routes: vec![
-            ("__turbopack__/".to_string(), introspect),
-            ("__turbo_tasks__/".to_string(), viz),
-            (
-                "__nextjs_original-stack-frame".to_string(),
-                source_map_trace,
-            ),
-            // TODO: Load path from next.config.js
-            ("_next/image".to_string(), img_source),
-            ("__turbopack_sourcemap__/".to_string(), source_maps),
-        ],
+        routes,
         fallback: main_source,
     }
     .cell()
@@ -381,6 +393,11 @@ pub fn register() {
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
 
+/// How long to wait for the persistent cache to be written when shutting
+/// down the devserver.
+#[cfg(feature = "persistent_cache")]
+const CACHE_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Start a devserver with the given options.
 pub async fn start_server(options: &DevServerOptions) -> Result<()> {
     let start = Instant::now();
@@ -409,8 +426,92 @@ pub async fn start_server(options: &DevServerOptions) -> Result<()> {
         dir.clone()
     };
 
-    let tt = TurboTasks::new(MemoryBackend::new());
+    #[cfg(feature = "persistent_cache")]
+    if let Some(cache_dir) = &options.cache_dir {
+        use turbo_tasks_memory::MemoryBackendWithPersistedGraph;
+        use turbo_tasks_rocksdb::RocksDbPersistedGraph;
+
+        if options.memory_limit.is_some() {
+            return Err(anyhow!("--memory-limit can't be combined with --cache-dir"));
+        }
 
+        let tt = TurboTasks::new(MemoryBackendWithPersistedGraph::new(
+            RocksDbPersistedGraph::new(cache_dir).context("unable to open the cache directory")?,
+        ));
+        println!(
+            "{event_type} - restored cache {elapsed}",
+            event_type = "event".purple(),
+            elapsed = FormatDuration(start.elapsed()),
+        );
+
+        tokio::select! {
+            result = run_server(options, tt.clone(), dir, root_dir, start) => result?,
+            _ = tokio::signal::ctrl_c() => {}
+        }
+
+        // Persisting is done in the background, so wait for it (with a
+        // timeout) to keep as much of the cache as possible.
+        let flush_start = Instant::now();
+        let _ = tokio::time::timeout(CACHE_FLUSH_TIMEOUT, tt.wait_background_done()).await;
+        tt.stop_and_wait().await;
+        println!(
+            "{event_type} - flushed cache {elapsed}",
+            event_type = "event".purple(),
+            elapsed = FormatDuration(flush_start.elapsed()),
+        );
+        return Ok(());
+    }
+
+    let memory_limit = options
+        .memory_limit
+        .map(|memory_limit| {
+            memory_limit
+                .checked_mul(1024 * 1024)
+                .context("the memory limit is too large")
+        })
+        .transpose()?;
+    let tt = TurboTasks::new(match memory_limit {
+        Some(memory_limit) => MemoryBackend::with_memory_limit(memory_limit)?,
+        None => MemoryBackend::new(),
+    });
+    if memory_limit.is_some() {
+        tokio::spawn(log_gc_stats(tt.clone()));
+    }
+
+    run_server(options, tt, dir, root_dir, start).await
+}
+
+/// How often to check if garbage collection unloaded tasks.
+const GC_STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Logs each garbage collection run of the backend.
+async fn log_gc_stats(tt: Arc<TurboTasks<MemoryBackend>>) {
+    let mut runs = 0;
+    loop {
+        tokio::time::sleep(GC_STATS_INTERVAL).await;
+        let mut stats = Stats::new();
+        stats.add_gc(tt.backend());
+        let gc = stats.gc();
+        if gc.runs != runs {
+            runs = gc.runs;
+            println!(
+                "\x1b[2K{event_type} - unloaded {tasks} tasks ({before} -> {after})",
+                event_type = "event".purple(),
+                tasks = gc.last_collected_tasks,
+                before = FormatBytes(gc.last_memory_before),
+                after = FormatBytes(gc.last_memory_after),
+            );
+        }
+    }
+}
+
+async fn run_server<B: Backend + 'static>(
+    options: &DevServerOptions,
+    tt: Arc<TurboTasks<B>>,
+    dir: String,
+    root_dir: String,
+    start: Instant,
+) -> Result<()> {
     let stats_type = match options.full_stats {
         true => StatsType::Full,
         false => StatsType::Essential,
@@ -521,7 +622,7 @@ pub async fn start_server(options: &DevServerOptions) -> Result<()> {
 #[cfg(feature = "profile")]
 // When profiling, exits the process when no new updates have been received for
 // a given timeout and there are no more tasks in progress.
-async fn profile_timeout<T>(tt: &TurboTasks<MemoryBackend>, future: impl Future<Output = T>) -> T {
+async fn profile_timeout<B: Backend, T>(tt: &TurboTasks<B>, future: impl Future<Output = T>) -> T {
     /// How long to wait in between updates before force-exiting the process
     /// during profiling.
     const PROFILE_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
@@ -540,8 +641,8 @@ async fn profile_timeout<T>(tt: &TurboTasks<MemoryBackend>, future: impl Future<
 }
 
 #[cfg(not(feature = "profile"))]
-fn profile_timeout<T>(
-    _tt: &TurboTasks<MemoryBackend>,
+fn profile_timeout<B: Backend, T>(
+    _tt: &TurboTasks<B>,
     future: impl Future<Output = T>,
 ) -> impl Future<Output = T> {
     future
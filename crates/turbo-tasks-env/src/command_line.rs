@@ -26,6 +26,7 @@ fn env_snapshot() -> IndexMap<String, String> {
 impl ProcessEnv for CommandLineProcessEnv {
     #[turbo_tasks::function]
     fn read_all(&self) -> EnvMapVc {
+        turbo_tasks::mark_external_input();
         EnvMapVc::cell(env_snapshot())
     }
 }
@@ -5,6 +5,10 @@ use turbo_tasks::primitives::{OptionStringVc, StringVc};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::issue::{Issue, IssueSeverityVc, IssueVc};
 
+mod url_resolver;
+
+pub use url_resolver::{HttpUrlResolver, HttpUrlResolverVc, UrlImportIssue, UrlImportIssueVc};
+
 pub fn register() {
     turbo_tasks::register();
     turbo_tasks_fs::register();
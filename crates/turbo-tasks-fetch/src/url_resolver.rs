@@ -0,0 +1,310 @@
+use std::{collections::BTreeMap, io::Read};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    trace::TraceRawVcs,
+    CompletionVc, State, ValueToString,
+};
+use turbo_tasks_fs::{util::normalize_path, File, FileContent, FileJsonContent, FileSystemPathVc};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+use turbopack_core::{
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    resolve::{
+        options::{UrlResolver, UrlResolverVc},
+        ResolveResult, ResolveResultVc,
+    },
+    source_asset::SourceAssetVc,
+};
+
+use crate::fetch;
+
+const LOCKFILE_NAME: &str = "lock.json";
+
+/// Extensions that are processed as they are. Other urls are treated as
+/// JavaScript, since CDNs like esm.sh serve modules without an extension.
+const MODULE_EXTENSIONS: [&str; 11] = [
+    "js", "mjs", "cjs", "jsx", "ts", "mts", "cts", "tsx", "json", "css", "wasm",
+];
+
+/// The integrity of the downloaded modules, keyed by url.
+type Lockfile = BTreeMap<String, LockfileEntry>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TraceRawVcs)]
+struct LockfileEntry {
+    /// A subresource integrity hash of the module content, e.g.
+    /// `sha512-<base64>`.
+    integrity: String,
+}
+
+/// Resolves `http:` and `https:` imports by downloading them into a cache
+/// directory. The cache directory also contains a `lock.json` which pins the
+/// integrity of each url, so it can be committed and builds succeed without
+/// network access.
+#[turbo_tasks::value(shared)]
+pub struct HttpUrlResolver {
+    cache_dir: FileSystemPathVc,
+    frozen: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl HttpUrlResolverVc {
+    /// When `frozen` is set, urls that are missing in the cache are an error
+    /// instead of being downloaded.
+    #[turbo_tasks::function]
+    pub fn new(cache_dir: FileSystemPathVc, frozen: bool) -> Self {
+        Self::cell(HttpUrlResolver { cache_dir, frozen })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl UrlResolver for HttpUrlResolver {
+    #[turbo_tasks::function]
+    fn root(&self) -> FileSystemPathVc {
+        self.cache_dir
+    }
+
+    #[turbo_tasks::function]
+    async fn resolve_url(&self, url: &str) -> Result<ResolveResultVc> {
+        let Some(asset_path) = url_asset_path(url) else {
+            return Ok(self.unresolveable(url, "The url has no host.".to_string()));
+        };
+        let path = self.cache_dir.join(&asset_path);
+        let lockfile_path = self.cache_dir.join(LOCKFILE_NAME);
+        let lockfile = read_lockfile(lockfile_path).await?;
+        let locked = lockfile.get(url).cloned();
+
+        if let Some(entry) = &locked {
+            if let FileContent::Content(file) = &*path.read().await? {
+                let mut content = Vec::new();
+                file.content().read().read_to_end(&mut content)?;
+                if integrity(&content) != entry.integrity {
+                    return Ok(self.unresolveable(
+                        url,
+                        format!(
+                            "The cached content does not match the integrity {} in {}.",
+                            entry.integrity, LOCKFILE_NAME
+                        ),
+                    ));
+                }
+                return Ok(
+                    ResolveResult::Single(SourceAssetVc::new(path).into(), Vec::new()).into(),
+                );
+            }
+        }
+
+        if self.frozen {
+            return Ok(self.unresolveable(
+                url,
+                "The url is not in the cache and downloading is disabled.".to_string(),
+            ));
+        }
+
+        let response =
+            match &*fetch(StringVc::cell(url.to_string()), OptionStringVc::cell(None)).await? {
+                Ok(response) => response.await?,
+                Err(err) => {
+                    err.to_issue(IssueSeverity::Error.into(), self.cache_dir)
+                        .as_issue()
+                        .emit();
+                    return Ok(ResolveResult::unresolveable().into());
+                }
+            };
+        let content = response.body.await?.0.clone();
+        let entry = LockfileEntry {
+            integrity: integrity(&content),
+        };
+        if let Some(locked) = locked {
+            if locked != entry {
+                return Ok(self.unresolveable(
+                    url,
+                    format!(
+                        "The downloaded content does not match the integrity {} in {}.",
+                        locked.integrity, LOCKFILE_NAME
+                    ),
+                ));
+            }
+        }
+
+        path.write(File::from(content).into()).await?;
+        let writer = LockfileWriterVc::new(lockfile_path);
+        writer.await?.add(lockfile, url, entry);
+        writer.write().await?;
+
+        Ok(ResolveResult::Single(SourceAssetVc::new(path).into(), Vec::new()).into())
+    }
+}
+
+impl HttpUrlResolver {
+    fn unresolveable(&self, url: &str, detail: String) -> ResolveResultVc {
+        UrlImportIssue {
+            context: self.cache_dir,
+            url: url.to_string(),
+            detail,
+        }
+        .cell()
+        .as_issue()
+        .emit();
+        ResolveResult::unresolveable().into()
+    }
+}
+
+async fn read_lockfile(lockfile_path: FileSystemPathVc) -> Result<Lockfile> {
+    Ok(match &*lockfile_path.read_json().await? {
+        FileJsonContent::Content(json) => serde_json::from_value(json.clone())?,
+        FileJsonContent::NotFound => Lockfile::new(),
+        FileJsonContent::Unparseable => {
+            bail!("{} is not valid JSON", lockfile_path.to_string().await?)
+        }
+    })
+}
+
+/// The single writer of a lockfile. Resolves of different urls run
+/// concurrently and might read the lockfile before the writes of the others
+/// are observed, so they add their entries to the state of the writer, which
+/// is shared by all resolves of the lockfile, instead of writing the lockfile
+/// themselves.
+#[turbo_tasks::value(eq = "manual", cell = "new", serialization = "none")]
+struct LockfileWriter {
+    path: FileSystemPathVc,
+    /// The content of the lockfile, once an entry has been added.
+    lockfile: State<Option<Lockfile>>,
+}
+
+#[turbo_tasks::value_impl]
+impl LockfileWriterVc {
+    #[turbo_tasks::function]
+    fn new(path: FileSystemPathVc) -> Self {
+        LockfileWriter {
+            path,
+            lockfile: State::new(None),
+        }
+        .cell()
+    }
+
+    /// Writes the entries added so far. The lockfile isn't read here, so the
+    /// write doesn't invalidate the writer.
+    #[turbo_tasks::function]
+    async fn write(self) -> Result<CompletionVc> {
+        let this = self.await?;
+        let lockfile = this.lockfile.get();
+        let Some(lockfile) = &*lockfile else {
+            return Ok(CompletionVc::new());
+        };
+        let content = File::from(serde_json::to_string_pretty(lockfile)?);
+        Ok(this.path.write(content.into()))
+    }
+}
+
+impl LockfileWriter {
+    /// Adds the entry of `url` to the lockfile. `read_lockfile` is the content
+    /// of the lockfile read by the resolve, which the lockfile starts with.
+    fn add(&self, read_lockfile: Lockfile, url: &str, entry: LockfileEntry) {
+        self.lockfile.update_conditionally(|lockfile| {
+            let lockfile = lockfile.get_or_insert(read_lockfile);
+            lockfile.insert(url.to_string(), entry.clone()) != Some(entry)
+        });
+    }
+}
+
+fn integrity(content: &[u8]) -> String {
+    format!("sha512-{}", base64::encode(Sha512::digest(content)))
+}
+
+/// Returns the path of the asset of a url as `<scheme>/<host>/<path>`, so
+/// relative imports from the asset map to urls again. The query is hashed
+/// into the file name.
+fn url_asset_path(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let rest = rest.split_once('#').map_or(rest, |(rest, _)| rest);
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    let (host, url_path) = rest.split_once('/').unwrap_or((rest, ""));
+    if host.is_empty() {
+        return None;
+    }
+
+    let mut path = normalize_path(url_path)?;
+    if path.is_empty() {
+        path.push_str("index");
+    } else if url_path.ends_with('/') {
+        path.push_str("/index");
+    }
+    let (stem, extension) = match path.rsplit_once('.') {
+        Some((stem, extension)) if MODULE_EXTENSIONS.contains(&extension) => (stem, extension),
+        _ => (path.as_str(), "js"),
+    };
+    let query_hash = query
+        .map(|query| format!("_{}", encode_hex(hash_xxh3_hash64(query))))
+        .unwrap_or_default();
+
+    Some(format!("{scheme}/{host}/{stem}{query_hash}.{extension}"))
+}
+
+#[turbo_tasks::value(shared)]
+pub struct UrlImportIssue {
+    pub context: FileSystemPathVc,
+    pub url: String,
+    pub detail: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UrlImportIssue {
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!("Unable to import {}", self.url))
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("fetch".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(self.detail.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::url_asset_path;
+
+    #[test]
+    fn asset_paths() {
+        assert_eq!(
+            url_asset_path("https://esm.sh/react@18.2.0").as_deref(),
+            Some("https/esm.sh/react@18.2.0.js")
+        );
+        assert_eq!(
+            url_asset_path("https://example.com/lib/a.mjs#main").as_deref(),
+            Some("https/example.com/lib/a.mjs")
+        );
+        assert_eq!(
+            url_asset_path("http://localhost:3000/").as_deref(),
+            Some("http/localhost:3000/index.js")
+        );
+        assert!(url_asset_path("https://example.com/a.css?v=1")
+            .unwrap()
+            .starts_with("https/example.com/a_"));
+        assert!(url_asset_path("https://example.com/a.css?v=1")
+            .unwrap()
+            .ends_with(".css"));
+        assert_eq!(url_asset_path("https:///a.js"), None);
+        assert_eq!(url_asset_path("https://example.com/../a.js"), None);
+    }
+}
@@ -0,0 +1,200 @@
+#![cfg(test)]
+
+use turbo_tasks::{TryJoinIterExt, Value};
+use turbo_tasks_fetch::{register, HttpUrlResolverVc};
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystemPathVc, FileSystemVc};
+use turbo_tasks_testing::{register, run};
+use turbopack_core::resolve::{
+    options::{ResolveOptions, ResolveOptionsVc},
+    parse::RequestVc,
+    resolve, ResolveResult,
+};
+
+register!();
+
+#[tokio::test]
+async fn resolves_from_cache_without_network() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache_dir = temp_dir.path().to_string_lossy().to_string();
+
+    let server = httpmock::MockServer::start();
+    let module_mock = server.mock(|when, then| {
+        when.path("/lib/module");
+        then.status(200).body("export * from './dep.js';");
+    });
+    let dep_mock = server.mock(|when, then| {
+        when.path("/lib/dep.js");
+        then.status(200).body("export default 42;");
+    });
+    let url = server.url("/lib/module");
+    let missing_url = server.url("/lib/missing.js");
+    let host = format!("{}:{}", server.host(), server.port());
+
+    // The futures of `run!` can't borrow from the test
+    let first_run = (cache_dir.clone(), url.clone(), host.clone());
+    run! {
+        register();
+        let (cache_dir, url, host) = first_run;
+
+        let root = cache_root(&cache_dir);
+        let options = resolve_options(HttpUrlResolverVc::new(root, false));
+
+        let module = resolve_single(root, &url, options).await?;
+        assert_eq!(module.await?.path, format!("http/{host}/lib/module.js"));
+
+        let dep = resolve_single(module.parent(), "./dep.js", options).await?;
+        assert_eq!(dep.await?.path, format!("http/{host}/lib/dep.js"));
+    }
+
+    module_mock.assert_hits(1);
+    dep_mock.assert_hits(1);
+    let lockfile: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(temp_dir.path().join("lock.json")).unwrap())
+            .unwrap();
+    assert!(lockfile[&url]["integrity"]
+        .as_str()
+        .unwrap()
+        .starts_with("sha512-"));
+
+    run! {
+        register();
+        let (cache_dir, url, missing_url, host) = (cache_dir, url, missing_url, host);
+
+        let root = cache_root(&cache_dir);
+        let options = resolve_options(HttpUrlResolverVc::new(root, true));
+
+        let module = resolve_single(root, &url, options).await?;
+        let dep = resolve_single(module.parent(), "./dep.js", options).await?;
+        assert_eq!(dep.await?.path, format!("http/{host}/lib/dep.js"));
+
+        let result = resolve(root, RequestVc::parse_string(missing_url), options).await?;
+        assert!(result.is_unresolveable());
+    }
+
+    // Nothing is downloaded again
+    module_mock.assert_hits(1);
+    dep_mock.assert_hits(1);
+}
+
+#[tokio::test]
+async fn concurrent_downloads_keep_all_lockfile_entries() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache_dir = temp_dir.path().to_string_lossy().to_string();
+
+    let server = httpmock::MockServer::start();
+    let mocks = (0..8)
+        .map(|i| {
+            server.mock(|when, then| {
+                when.path(format!("/lib/module{i}.js"));
+                then.status(200).body(format!("export default {i};"));
+            })
+        })
+        .collect::<Vec<_>>();
+    let urls = (0..8)
+        .map(|i| server.url(format!("/lib/module{i}.js")))
+        .collect::<Vec<_>>();
+
+    let run_urls = urls.clone();
+    run! {
+        register();
+        let (cache_dir, urls) = (cache_dir, run_urls);
+
+        let root = cache_root(&cache_dir);
+        let options = resolve_options(HttpUrlResolverVc::new(root, false));
+
+        urls.iter()
+            .map(|url| resolve_single(root, url, options))
+            .try_join()
+            .await?;
+    }
+
+    for mock in mocks {
+        mock.assert_hits(1);
+    }
+    // Read the lockfile from disk, as all of the writes need to have landed.
+    let lockfile: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(temp_dir.path().join("lock.json")).unwrap())
+            .unwrap();
+    for url in urls {
+        assert!(lockfile[&url]["integrity"].is_string(), "missing {url}");
+    }
+}
+
+#[tokio::test]
+async fn concurrent_resolves_are_cached_for_frozen_builds() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache_dir = temp_dir.path().to_string_lossy().to_string();
+
+    let server = httpmock::MockServer::start();
+    let a_mock = server.mock(|when, then| {
+        when.path("/a.js");
+        then.status(200).body("export default 'a';");
+    });
+    let b_mock = server.mock(|when, then| {
+        when.path("/b.js");
+        then.status(200).body("export default 'b';");
+    });
+    let a_url = server.url("/a.js");
+    let b_url = server.url("/b.js");
+
+    let first_run = (cache_dir.clone(), a_url.clone(), b_url.clone());
+    run! {
+        register();
+        let (cache_dir, a_url, b_url) = first_run;
+
+        let root = cache_root(&cache_dir);
+        let options = resolve_options(HttpUrlResolverVc::new(root, false));
+
+        let (a, b) = tokio::try_join!(
+            resolve_single(root, &a_url, options),
+            resolve_single(root, &b_url, options)
+        )?;
+        assert_ne!(a.await?.path, b.await?.path);
+    }
+
+    a_mock.assert_hits(1);
+    b_mock.assert_hits(1);
+
+    // Both entries are in the lockfile, so nothing is downloaded again
+    run! {
+        register();
+        let (cache_dir, a_url, b_url) = (cache_dir, a_url, b_url);
+
+        let root = cache_root(&cache_dir);
+        let options = resolve_options(HttpUrlResolverVc::new(root, true));
+
+        resolve_single(root, &a_url, options).await?;
+        resolve_single(root, &b_url, options).await?;
+    }
+
+    a_mock.assert_hits(1);
+    b_mock.assert_hits(1);
+}
+
+fn cache_root(path: &str) -> FileSystemPathVc {
+    std::convert::Into::<FileSystemVc>::into(DiskFileSystemVc::new(
+        "cache".to_owned(),
+        path.to_owned(),
+    ))
+    .root()
+}
+
+fn resolve_options(url_resolver: HttpUrlResolverVc) -> ResolveOptionsVc {
+    ResolveOptions {
+        url_resolver: Some(url_resolver.into()),
+        ..Default::default()
+    }
+    .cell()
+}
+
+async fn resolve_single(
+    context: FileSystemPathVc,
+    request: &str,
+    options: ResolveOptionsVc,
+) -> anyhow::Result<FileSystemPathVc> {
+    let request_vc = RequestVc::parse(Value::new(request.to_string().into()));
+    let ResolveResult::Single(asset, _) = &*resolve(context, request_vc, options).await? else {
+        panic!("expected a single asset for {request}")
+    };
+    Ok(asset.path())
+}
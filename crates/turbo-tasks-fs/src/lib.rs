@@ -9,7 +9,9 @@ pub mod attach;
 pub mod embed;
 pub mod glob;
 mod invalidator_map;
+pub mod memory;
 mod mutex_map;
+pub mod overlay;
 mod read_glob;
 mod retry;
 pub mod rope;
@@ -94,6 +96,7 @@ impl DiskFileSystem {
     /// registers the path as an invalidator for the current task,
     /// has to be called within a turbo-tasks function
     fn register_invalidator(&self, path: impl AsRef<Path>, file: bool) {
+        turbo_tasks::mark_external_input();
         let invalidator = turbo_tasks::get_invalidator();
         if file {
             self.invalidator_map.insert(path_to_key(path), invalidator);
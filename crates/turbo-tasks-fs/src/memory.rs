@@ -0,0 +1,459 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    mem::take,
+};
+
+use anyhow::{bail, Result};
+use auto_hash_map::AutoMap;
+use parking_lot::{Mutex, MutexGuard};
+use turbo_tasks::{
+    get_invalidator, primitives::StringVc, CompletionVc, Invalidator, TransientInstance,
+    ValueToString, ValueToStringVc,
+};
+
+use crate::{
+    util::{join_path, normalize_path},
+    DirectoryContentVc, DirectoryEntry, File, FileContent, FileContentVc, FileMeta, FileMetaVc,
+    FileSystem, FileSystemPathVc, FileSystemVc, LinkContent, LinkContentVc, LinkType,
+};
+
+/// The maximum number of symlinks that are followed when resolving a path.
+const MAX_SYMLINK_DEPTH: usize = 40;
+
+/// A [FileSystem] that keeps all files in memory. The files are stored in a
+/// [MemoryFiles] instance, which can also be modified from outside of
+/// turbo-tasks, e. g. by a test or a service that receives sources from
+/// users. Tasks reading a changed path are invalidated.
+#[turbo_tasks::value(serialization = "none")]
+pub struct MemoryFileSystem {
+    name: String,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    files: TransientInstance<MemoryFiles>,
+}
+
+#[turbo_tasks::value_impl]
+impl MemoryFileSystemVc {
+    #[turbo_tasks::function]
+    pub fn new(name: String, files: TransientInstance<MemoryFiles>) -> Self {
+        MemoryFileSystem { name, files }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for MemoryFileSystem {
+    #[turbo_tasks::function]
+    async fn read(&self, fs_path: FileSystemPathVc) -> Result<FileContentVc> {
+        let path = &fs_path.await?.path;
+        let mut inner = self.files.lock_with_invalidator();
+        Ok(match inner
+            .resolve(path)
+            .and_then(|path| inner.entries.get(&path))
+        {
+            Some(MemoryEntry::File(file)) => FileContent::Content(file.clone()),
+            _ => FileContent::NotFound,
+        }
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(&self, fs_path: FileSystemPathVc) -> Result<LinkContentVc> {
+        let path = &fs_path.await?.path;
+        let mut inner = self.files.lock_with_invalidator();
+        inner.register_invalidator(path);
+        Ok(match inner.entries.get(path) {
+            Some(MemoryEntry::Symlink { target, link_type }) => LinkContent::Link {
+                target: target.clone(),
+                link_type: *link_type,
+            },
+            _ => LinkContent::NotFound,
+        }
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(&self, fs_path: FileSystemPathVc) -> Result<DirectoryContentVc> {
+        let path = &fs_path.await?.path;
+        let mut inner = self.files.lock_with_invalidator();
+        let Some(dir) = inner.resolve(path) else {
+            return Ok(DirectoryContentVc::not_found());
+        };
+        inner.register_dir_invalidator(&dir);
+        if !dir.is_empty() && !matches!(inner.entries.get(&dir), Some(MemoryEntry::Directory)) {
+            return Ok(DirectoryContentVc::not_found());
+        }
+
+        let entries = inner
+            .children(&dir)
+            .map(|(name, entry)| {
+                let entry_path = fs_path.join(name);
+                let entry = match entry {
+                    MemoryEntry::File(_) => DirectoryEntry::File(entry_path),
+                    MemoryEntry::Directory => DirectoryEntry::Directory(entry_path),
+                    MemoryEntry::Symlink { .. } => DirectoryEntry::Symlink(entry_path),
+                };
+                (name.to_string(), entry)
+            })
+            .collect::<AutoMap<_, _>>();
+
+        Ok(DirectoryContentVc::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    async fn write(
+        &self,
+        fs_path: FileSystemPathVc,
+        content: FileContentVc,
+    ) -> Result<CompletionVc> {
+        let path = &fs_path.await?.path;
+        let content = content.await?;
+        let changed = match &*content {
+            FileContent::Content(file) => self.files.write_file(path, file.clone())?,
+            FileContent::NotFound => self.files.remove(path),
+        };
+        Ok(if changed {
+            CompletionVc::new()
+        } else {
+            CompletionVc::unchanged()
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn write_link(
+        &self,
+        fs_path: FileSystemPathVc,
+        target: LinkContentVc,
+    ) -> Result<CompletionVc> {
+        let path = &fs_path.await?.path;
+        let changed = match &*target.await? {
+            LinkContent::Link { target, link_type } => {
+                self.files.write_link(path, target, *link_type)?
+            }
+            LinkContent::Invalid => bail!("invalid symlink target for {}", path),
+            LinkContent::NotFound => self.files.remove(path),
+        };
+        Ok(if changed {
+            CompletionVc::new()
+        } else {
+            CompletionVc::unchanged()
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(&self, fs_path: FileSystemPathVc) -> Result<FileMetaVc> {
+        let path = &fs_path.await?.path;
+        let mut inner = self.files.lock_with_invalidator();
+        Ok(match inner
+            .resolve(path)
+            .and_then(|path| inner.entries.get(&path))
+        {
+            Some(MemoryEntry::File(file)) => file.meta.clone(),
+            Some(_) => FileMeta::default(),
+            None if path.is_empty() => FileMeta::default(),
+            None => bail!("path {} not found, can't read metadata", path),
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for MemoryFileSystem {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> StringVc {
+        StringVc::cell(self.name.clone())
+    }
+}
+
+/// The files of a [MemoryFileSystem]. Paths are relative to the root of the
+/// file system and use `/` as separator. Parent directories are created
+/// implicitly.
+#[derive(Default)]
+pub struct MemoryFiles {
+    inner: Mutex<MemoryFilesInner>,
+}
+
+impl MemoryFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes a file. Returns `true` when the content has changed.
+    pub fn write_file(&self, path: &str, file: impl Into<File>) -> Result<bool> {
+        let path = normalize(path)?;
+        let file = file.into();
+        self.update(|inner| {
+            if matches!(inner.entries.get(&path), Some(MemoryEntry::File(old)) if *old == file) {
+                return Ok(false);
+            }
+            inner.insert(path, MemoryEntry::File(file))?;
+            Ok(true)
+        })
+    }
+
+    /// Creates a symlink at `path` pointing to `target`. Relative targets are
+    /// resolved from the directory containing the link, absolute targets
+    /// (with [LinkType::ABSOLUTE]) from the root of the file system. Returns
+    /// `true` when the link has changed.
+    pub fn write_link(&self, path: &str, target: &str, link_type: LinkType) -> Result<bool> {
+        let path = normalize(path)?;
+        let entry = MemoryEntry::Symlink {
+            target: target.to_string(),
+            link_type,
+        };
+        self.update(|inner| {
+            if inner.entries.get(&path) == Some(&entry) {
+                return Ok(false);
+            }
+            inner.insert(path, entry)?;
+            Ok(true)
+        })
+    }
+
+    /// Creates a directory and its parents. Returns `true` when the directory
+    /// didn't exist before.
+    pub fn create_dir(&self, path: &str) -> Result<bool> {
+        let path = normalize(path)?;
+        if path.is_empty() {
+            return Ok(false);
+        }
+        self.update(|inner| {
+            if matches!(inner.entries.get(&path), Some(MemoryEntry::Directory)) {
+                return Ok(false);
+            }
+            inner.insert(path, MemoryEntry::Directory)?;
+            Ok(true)
+        })
+    }
+
+    /// Removes a file, symlink or directory including its content. Returns
+    /// `true` when something was removed.
+    pub fn remove(&self, path: &str) -> bool {
+        let Ok(path) = normalize(path) else {
+            return false;
+        };
+        self.update(|inner| Ok(inner.remove(&path)))
+            .unwrap_or_default()
+    }
+
+    /// Calls `update` and invalidates all tasks that have read the changed
+    /// paths afterwards.
+    fn update<T>(&self, update: impl FnOnce(&mut MemoryFilesInner) -> Result<T>) -> Result<T> {
+        let mut inner = self.inner.lock();
+        let result = update(&mut inner);
+        let invalidators = take(&mut inner.pending_invalidations);
+        drop(inner);
+        for invalidator in invalidators {
+            invalidator.invalidate();
+        }
+        result
+    }
+
+    /// Locks the files for reading. Paths read with the returned guard
+    /// register the current task as dependent, so this has to be called
+    /// within a turbo-tasks function.
+    fn lock_with_invalidator(&self) -> MemoryFilesGuard<'_> {
+        MemoryFilesGuard {
+            inner: self.inner.lock(),
+        }
+    }
+}
+
+fn normalize(path: &str) -> Result<String> {
+    match normalize_path(path) {
+        Some(path) => Ok(path),
+        None => bail!("path {} leaves the root of the file system", path),
+    }
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+#[derive(PartialEq, Eq)]
+enum MemoryEntry {
+    File(File),
+    Directory,
+    Symlink { target: String, link_type: LinkType },
+}
+
+#[derive(Default)]
+struct MemoryFilesInner {
+    entries: BTreeMap<String, MemoryEntry>,
+    /// Tasks that have read a path, either as file, symlink or to follow a
+    /// symlink.
+    invalidators: HashMap<String, HashSet<Invalidator>>,
+    /// Tasks that have read the entries of a directory.
+    dir_invalidators: HashMap<String, HashSet<Invalidator>>,
+    /// Invalidators that are called once the lock is released.
+    pending_invalidations: Vec<Invalidator>,
+}
+
+impl MemoryFilesInner {
+    /// Returns the direct children of a directory.
+    fn children<'a>(&'a self, dir: &'a str) -> impl Iterator<Item = (&'a str, &'a MemoryEntry)> {
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{dir}/")
+        };
+        self.entries
+            .range(prefix.clone()..)
+            .take_while(move |(path, _)| path.starts_with(&prefix))
+            .filter_map(move |(path, entry)| {
+                let name = &path[prefix_len(dir)..];
+                (!name.contains('/')).then_some((name, entry))
+            })
+    }
+
+    fn insert(&mut self, path: String, entry: MemoryEntry) -> Result<()> {
+        if path.is_empty() {
+            bail!("the root of the file system can't be replaced");
+        }
+        let mut dir = parent(&path).to_string();
+        let mut missing_dirs = Vec::new();
+        while !dir.is_empty() {
+            match self.entries.get(&dir) {
+                Some(MemoryEntry::Directory) => break,
+                Some(_) => bail!("{} is not a directory", dir),
+                None => {
+                    let next = parent(&dir).to_string();
+                    missing_dirs.push(dir);
+                    dir = next;
+                }
+            }
+        }
+        for dir in missing_dirs {
+            self.invalidate(&dir, true);
+            self.entries.insert(dir, MemoryEntry::Directory);
+        }
+        // Replacing a directory removes its content
+        let entry_type_changed = match self.entries.get(&path) {
+            Some(MemoryEntry::Directory) => {
+                self.remove(&path);
+                true
+            }
+            Some(old) => std::mem::discriminant(old) != std::mem::discriminant(&entry),
+            None => true,
+        };
+        self.invalidate(&path, entry_type_changed);
+        self.entries.insert(path, entry);
+        Ok(())
+    }
+
+    fn remove(&mut self, path: &str) -> bool {
+        if self.entries.remove(path).is_none() {
+            return false;
+        }
+        self.invalidate(path, true);
+        let prefix = format!("{path}/");
+        let descendants = self
+            .entries
+            .range(prefix.clone()..)
+            .take_while(|(p, _)| p.starts_with(&prefix))
+            .map(|(p, _)| p.clone())
+            .collect::<Vec<_>>();
+        for descendant in descendants {
+            self.entries.remove(&descendant);
+            self.invalidate(&descendant, true);
+        }
+        true
+    }
+
+    /// Queues the invalidation of the tasks that read `path`. When
+    /// `entry_changed` is set, an entry has been added, removed or changed its
+    /// type, so the parent directory listing is invalidated too.
+    fn invalidate(&mut self, path: &str, entry_changed: bool) {
+        if let Some(invalidators) = self.invalidators.remove(path) {
+            self.pending_invalidations.extend(invalidators);
+        }
+        if let Some(invalidators) = self.dir_invalidators.remove(path) {
+            self.pending_invalidations.extend(invalidators);
+        }
+        if entry_changed {
+            if let Some(invalidators) = self.dir_invalidators.remove(parent(path)) {
+                self.pending_invalidations.extend(invalidators);
+            }
+        }
+    }
+}
+
+fn prefix_len(dir: &str) -> usize {
+    if dir.is_empty() {
+        0
+    } else {
+        dir.len() + 1
+    }
+}
+
+struct MemoryFilesGuard<'a> {
+    inner: MutexGuard<'a, MemoryFilesInner>,
+}
+
+impl<'a> MemoryFilesGuard<'a> {
+    fn register_invalidator(&mut self, path: &str) {
+        let invalidator = get_invalidator();
+        self.inner
+            .invalidators
+            .entry(path.to_string())
+            .or_default()
+            .insert(invalidator);
+    }
+
+    fn register_dir_invalidator(&mut self, path: &str) {
+        let invalidator = get_invalidator();
+        self.inner
+            .dir_invalidators
+            .entry(path.to_string())
+            .or_default()
+            .insert(invalidator);
+    }
+
+    /// Follows all symlinks in `path` and returns the real path. All visited
+    /// paths are registered as dependencies. Returns `None` when a symlink
+    /// leaves the root of the file system or too many symlinks are followed.
+    fn resolve(&mut self, path: &str) -> Option<String> {
+        let mut resolved = String::new();
+        let mut remaining = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .rev()
+            .collect::<Vec<_>>();
+        let mut followed = 0;
+        while let Some(segment) = remaining.pop() {
+            let current = join_path(&resolved, &segment)?;
+            self.register_invalidator(&current);
+            let Some(MemoryEntry::Symlink { target, link_type }) = self.inner.entries.get(&current)
+            else {
+                resolved = current;
+                continue;
+            };
+            followed += 1;
+            if followed > MAX_SYMLINK_DEPTH {
+                return None;
+            }
+            let target = if link_type.contains(LinkType::ABSOLUTE) {
+                normalize_path(target)?
+            } else {
+                join_path(parent(&current), target)?
+            };
+            remaining.extend(
+                target
+                    .split('/')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .rev(),
+            );
+            resolved = String::new();
+        }
+        self.register_invalidator(&resolved);
+        Some(resolved)
+    }
+}
+
+impl<'a> std::ops::Deref for MemoryFilesGuard<'a> {
+    type Target = MemoryFilesInner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
@@ -0,0 +1,241 @@
+use anyhow::{bail, Result};
+use auto_hash_map::{AutoMap, AutoSet};
+use turbo_tasks::{
+    primitives::{BoolVc, StringVc},
+    CompletionVc, CompletionsVc, ValueToString, ValueToStringVc,
+};
+
+use crate::{
+    DirectoryContent, DirectoryContentVc, DirectoryEntry, FileContent, FileContentVc, FileMetaVc,
+    FileSystem, FileSystemEntryType, FileSystemPathVc, FileSystemVc, LinkContent, LinkContentVc,
+};
+
+/// The prefix of the files in the upper layer that mark a file of the lower
+/// layer as deleted. This is the same convention as used by container image
+/// layers, e. g. `.wh.index.js` hides `index.js`.
+const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// A [FileSystem] which layers an `upper` [FileSystem] on top of a `lower`
+/// one. Reads are served from the upper layer and fall back to the lower
+/// layer, e. g. to prefer unsaved editor buffers over the files on disk.
+/// Writes only modify the upper layer. Deleting a file that exists in the
+/// lower layer creates a whiteout file in the upper layer, which is removed
+/// again when the path is written. Nothing can be written below a deleted
+/// path, and directories of the lower layer can't be deleted, since writing
+/// into a deleted directory would make its other files visible again.
+#[turbo_tasks::value]
+pub struct OverlayFileSystem {
+    upper: FileSystemVc,
+    lower: FileSystemVc,
+}
+
+#[turbo_tasks::value_impl]
+impl OverlayFileSystemVc {
+    #[turbo_tasks::function]
+    pub fn new(upper: FileSystemVc, lower: FileSystemVc) -> Self {
+        OverlayFileSystem { upper, lower }.cell()
+    }
+
+    /// Returns the path in the upper layer for a path of the
+    /// [OverlayFileSystem].
+    #[turbo_tasks::function]
+    pub async fn upper_path(self, path: FileSystemPathVc) -> Result<FileSystemPathVc> {
+        Ok(self.await?.upper.root().join(&path.await?.path))
+    }
+
+    /// Returns the path in the lower layer for a path of the
+    /// [OverlayFileSystem].
+    #[turbo_tasks::function]
+    pub async fn lower_path(self, path: FileSystemPathVc) -> Result<FileSystemPathVc> {
+        Ok(self.await?.lower.root().join(&path.await?.path))
+    }
+
+    /// Returns the path of the whiteout file which hides the path.
+    #[turbo_tasks::function]
+    async fn whiteout_path(self, path: FileSystemPathVc) -> Result<FileSystemPathVc> {
+        let path = path.await?;
+        let (parent, name) = path.path.rsplit_once('/').unwrap_or(("", &path.path));
+        Ok(self
+            .await?
+            .upper
+            .root()
+            .join(parent)
+            .join(&format!("{WHITEOUT_PREFIX}{name}")))
+    }
+
+    /// Returns true when the path or one of its parent directories has been
+    /// deleted in the upper layer.
+    #[turbo_tasks::function]
+    async fn is_whiteout(self, path: FileSystemPathVc) -> Result<BoolVc> {
+        if path.await?.is_root() {
+            return Ok(BoolVc::cell(false));
+        }
+        let whiteout_type = self.whiteout_path(path).get_type().await?;
+        if *whiteout_type != FileSystemEntryType::NotFound {
+            return Ok(BoolVc::cell(true));
+        }
+        Ok(self.is_whiteout(path.parent()))
+    }
+}
+
+impl OverlayFileSystemVc {
+    /// Removes the whiteout file of the path, so a written entry isn't
+    /// hidden. Fails when a parent directory of the path has been deleted.
+    async fn remove_whiteout(self, path: FileSystemPathVc) -> Result<CompletionVc> {
+        let path_value = path.await?;
+        if !path_value.is_root() && *self.is_whiteout(path.parent()).await? {
+            bail!(
+                "{} can't be written, a parent directory has been deleted",
+                path_value.path
+            );
+        }
+        Ok(self.whiteout_path(path).write(FileContent::NotFound.cell()))
+    }
+
+    /// Creates a whiteout file for the entry of the lower layer at `path`, if
+    /// there is one.
+    async fn whiteout_lower(self, path: FileSystemPathVc) -> Result<Option<CompletionVc>> {
+        match *self.lower_path(path).get_type().await? {
+            FileSystemEntryType::NotFound => Ok(None),
+            FileSystemEntryType::Directory => bail!(
+                "the directory {} can't be deleted, it exists in the lower layer",
+                path.await?.path
+            ),
+            _ => Ok(Some(
+                self.whiteout_path(path)
+                    .write(FileContent::Content("".into()).cell()),
+            )),
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn read(self_vc: OverlayFileSystemVc, path: FileSystemPathVc) -> Result<FileContentVc> {
+        if *self_vc.is_whiteout(path).await? {
+            return Ok(FileContent::NotFound.cell());
+        }
+        let upper = self_vc.upper_path(path).read();
+        if let FileContent::Content(_) = &*upper.await? {
+            return Ok(upper);
+        }
+        Ok(self_vc.lower_path(path).read())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(
+        self_vc: OverlayFileSystemVc,
+        path: FileSystemPathVc,
+    ) -> Result<LinkContentVc> {
+        if *self_vc.is_whiteout(path).await? {
+            return Ok(LinkContent::NotFound.cell());
+        }
+        let upper = self_vc.upper_path(path).read_link();
+        if !matches!(&*upper.await?, LinkContent::NotFound) {
+            return Ok(upper);
+        }
+        Ok(self_vc.lower_path(path).read_link())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(
+        self_vc: OverlayFileSystemVc,
+        path: FileSystemPathVc,
+    ) -> Result<DirectoryContentVc> {
+        if *self_vc.is_whiteout(path).await? {
+            return Ok(DirectoryContentVc::not_found());
+        }
+        let upper = self_vc.upper_path(path).read_dir().await?;
+        let lower = self_vc.lower_path(path).read_dir().await?;
+
+        let mut entries = AutoMap::new();
+        let mut whiteouts = AutoSet::new();
+        let mut found = false;
+        // The upper layer is merged first, so its entries take precedence
+        for content in [&*upper, &*lower] {
+            let DirectoryContent::Entries(layer_entries) = content else {
+                continue;
+            };
+            found = true;
+            for (name, entry) in layer_entries.iter() {
+                if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+                    whiteouts.insert(hidden.to_string());
+                    continue;
+                }
+                if whiteouts.contains(name) || entries.contains_key(name) {
+                    continue;
+                }
+                let entry_path = path.join(name);
+                let entry = match entry {
+                    DirectoryEntry::File(_) => DirectoryEntry::File(entry_path),
+                    DirectoryEntry::Directory(_) => DirectoryEntry::Directory(entry_path),
+                    DirectoryEntry::Symlink(_) => DirectoryEntry::Symlink(entry_path),
+                    DirectoryEntry::Other(_) => DirectoryEntry::Other(entry_path),
+                    DirectoryEntry::Error => DirectoryEntry::Error,
+                };
+                entries.insert(name.clone(), entry);
+            }
+        }
+        if !found {
+            return Ok(DirectoryContentVc::not_found());
+        }
+
+        Ok(DirectoryContentVc::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    async fn write(
+        self_vc: OverlayFileSystemVc,
+        path: FileSystemPathVc,
+        content: FileContentVc,
+    ) -> Result<CompletionVc> {
+        // The upper layer is only written once the path has been checked
+        let mut completions = match &*content.await? {
+            FileContent::Content(_) => vec![self_vc.remove_whiteout(path).await?],
+            // Hide the file of the lower layer
+            FileContent::NotFound => self_vc.whiteout_lower(path).await?.into_iter().collect(),
+        };
+        completions.push(self_vc.upper_path(path).write(content));
+        Ok(CompletionsVc::cell(completions).all())
+    }
+
+    #[turbo_tasks::function]
+    async fn write_link(
+        self_vc: OverlayFileSystemVc,
+        path: FileSystemPathVc,
+        target: LinkContentVc,
+    ) -> Result<CompletionVc> {
+        let mut completions = match &*target.await? {
+            LinkContent::Link { .. } => vec![self_vc.remove_whiteout(path).await?],
+            LinkContent::Invalid => bail!("invalid symlink target"),
+            LinkContent::NotFound => self_vc.whiteout_lower(path).await?.into_iter().collect(),
+        };
+        completions.push(self_vc.upper_path(path).write_link(target));
+        Ok(CompletionsVc::cell(completions).all())
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(self_vc: OverlayFileSystemVc, path: FileSystemPathVc) -> Result<FileMetaVc> {
+        if *self_vc.is_whiteout(path).await? {
+            bail!("path {} not found, can't read metadata", path.await?.path);
+        }
+        let upper_path = self_vc.upper_path(path);
+        if *upper_path.get_type().await? != FileSystemEntryType::NotFound {
+            return Ok(upper_path.metadata());
+        }
+        Ok(self_vc.lower_path(path).metadata())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "{}-over-{}",
+            self.upper.to_string().await?,
+            self.lower.to_string().await?
+        )))
+    }
+}
@@ -0,0 +1,157 @@
+#![feature(min_specialization)]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use anyhow::Result;
+use turbo_tasks::primitives::StringVc;
+use turbo_tasks_fs::{
+    memory::{MemoryFileSystemVc, MemoryFiles},
+    register, DirectoryContent, DirectoryEntry, File, FileContent, FileSystemPathVc, FileSystemVc,
+    LinkType,
+};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+static TEXT_READS: AtomicUsize = AtomicUsize::new(0);
+static DIR_READS: AtomicUsize = AtomicUsize::new(0);
+
+fn memory_root(files: &Arc<MemoryFiles>) -> FileSystemPathVc {
+    let fs: FileSystemVc =
+        MemoryFileSystemVc::new("memory".to_string(), files.clone().into()).into();
+    fs.root()
+}
+
+#[tokio::test]
+async fn read_and_write() {
+    run! {
+        register();
+
+        let files = Arc::new(MemoryFiles::new());
+        files.write_file("dir/a.txt", "a")?;
+        let root = memory_root(&files);
+        assert_eq!(*read_text(root.join("dir/a.txt")).await?, "a");
+        assert_eq!(*read_text(root.join("dir/missing.txt")).await?, "<not found>");
+        // Directories aren't files
+        assert_eq!(*read_text(root.join("dir")).await?, "<not found>");
+
+        root.join("new/b.txt")
+            .write(FileContent::Content(File::from("b")).cell())
+            .await?;
+        assert_eq!(*read_text(root.join("new/b.txt")).strongly_consistent().await?, "b");
+
+        root.join("new/b.txt").write(FileContent::NotFound.cell()).await?;
+        assert_eq!(
+            *read_text(root.join("new/b.txt")).strongly_consistent().await?,
+            "<not found>"
+        );
+    }
+}
+
+#[tokio::test]
+async fn read_dir() {
+    run! {
+        register();
+
+        let files = Arc::new(MemoryFiles::new());
+        files.write_file("dir/a.txt", "a")?;
+        files.write_file("dir/sub/b.txt", "b")?;
+        files.write_link("dir/link", "a.txt", LinkType::UNSET)?;
+        let root = memory_root(&files);
+
+        let DirectoryContent::Entries(entries) = &*root.join("dir").read_dir().await? else {
+            panic!("dir should be a directory");
+        };
+        let mut entries = entries
+            .iter()
+            .map(|(name, entry)| {
+                let kind = match entry {
+                    DirectoryEntry::File(_) => "file",
+                    DirectoryEntry::Directory(_) => "directory",
+                    DirectoryEntry::Symlink(_) => "symlink",
+                    _ => "other",
+                };
+                format!("{name} ({kind})")
+            })
+            .collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, ["a.txt (file)", "link (symlink)", "sub (directory)"]);
+
+        // Symlinks are followed
+        assert_eq!(*read_text(root.join("dir/link")).await?, "a");
+        assert!(matches!(
+            &*root.join("dir/a.txt").read_dir().await?,
+            DirectoryContent::NotFound
+        ));
+        assert!(matches!(
+            &*root.join("missing").read_dir().await?,
+            DirectoryContent::NotFound
+        ));
+    }
+}
+
+#[tokio::test]
+async fn external_changes_invalidate_readers() {
+    run! {
+        register();
+
+        let files = Arc::new(MemoryFiles::new());
+        files.write_file("a.txt", "1")?;
+        let root = memory_root(&files);
+        let path = root.join("a.txt");
+
+        assert_eq!(*counted_read_text(path).strongly_consistent().await?, "1");
+        assert_eq!(*counted_list(root).strongly_consistent().await?, "a.txt");
+
+        // Writing the same content doesn't invalidate anything
+        assert!(!files.write_file("a.txt", "1")?);
+        assert_eq!(*counted_read_text(path).strongly_consistent().await?, "1");
+        assert_eq!(TEXT_READS.load(Ordering::SeqCst), 1);
+
+        // Changing a file only invalidates the readers of the file
+        assert!(files.write_file("a.txt", "2")?);
+        assert_eq!(*counted_read_text(path).strongly_consistent().await?, "2");
+        assert_eq!(*counted_list(root).strongly_consistent().await?, "a.txt");
+        assert_eq!(TEXT_READS.load(Ordering::SeqCst), 2);
+        assert_eq!(DIR_READS.load(Ordering::SeqCst), 1);
+
+        // Adding and removing entries invalidates the directory listing
+        files.write_file("b.txt", "b")?;
+        assert_eq!(*counted_list(root).strongly_consistent().await?, "a.txt, b.txt");
+        assert!(files.remove("a.txt"));
+        assert_eq!(*counted_list(root).strongly_consistent().await?, "b.txt");
+        assert_eq!(*counted_read_text(path).strongly_consistent().await?, "<not found>");
+        assert_eq!(DIR_READS.load(Ordering::SeqCst), 3);
+    }
+}
+
+#[turbo_tasks::function]
+async fn read_text(path: FileSystemPathVc) -> Result<StringVc> {
+    Ok(StringVc::cell(match &*path.read().await? {
+        FileContent::Content(file) => file.content().to_str()?.into_owned(),
+        FileContent::NotFound => "<not found>".to_string(),
+    }))
+}
+
+#[turbo_tasks::function]
+async fn counted_read_text(path: FileSystemPathVc) -> Result<StringVc> {
+    TEXT_READS.fetch_add(1, Ordering::SeqCst);
+    Ok(StringVc::cell(read_text(path).await?.clone()))
+}
+
+#[turbo_tasks::function]
+async fn counted_list(dir: FileSystemPathVc) -> Result<StringVc> {
+    DIR_READS.fetch_add(1, Ordering::SeqCst);
+    let DirectoryContent::Entries(entries) = &*dir.read_dir().await? else {
+        return Ok(StringVc::cell("<not found>".to_string()));
+    };
+    let mut names = entries
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(StringVc::cell(names.join(", ")))
+}
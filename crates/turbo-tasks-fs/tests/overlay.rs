@@ -0,0 +1,154 @@
+#![feature(min_specialization)]
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use turbo_tasks::primitives::StringVc;
+use turbo_tasks_fs::{
+    memory::{MemoryFileSystemVc, MemoryFiles},
+    overlay::OverlayFileSystemVc,
+    register, DirectoryContent, File, FileContent, FileSystemPathVc, FileSystemVc,
+};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+fn memory_fs(files: &Arc<MemoryFiles>) -> FileSystemVc {
+    MemoryFileSystemVc::new("memory".to_string(), files.clone().into()).into()
+}
+
+fn memory_root(files: &Arc<MemoryFiles>) -> FileSystemPathVc {
+    memory_fs(files).root()
+}
+
+fn overlay_root(upper: &Arc<MemoryFiles>, lower: &Arc<MemoryFiles>) -> FileSystemPathVc {
+    let fs: FileSystemVc = OverlayFileSystemVc::new(memory_fs(upper), memory_fs(lower)).into();
+    fs.root()
+}
+
+#[tokio::test]
+async fn delete_creates_whiteout() {
+    run! {
+        register();
+
+        let upper = Arc::new(MemoryFiles::new());
+        let lower = Arc::new(MemoryFiles::new());
+        lower.write_file("dir/a.txt", "a")?;
+        lower.write_file("dir/b.txt", "b")?;
+        let root = overlay_root(&upper, &lower);
+
+        root.join("dir/a.txt").write(FileContent::NotFound.cell()).await?;
+        assert_eq!(*read_text(root.join("dir/a.txt")).await?, "<not found>");
+        assert_eq!(*list(root.join("dir")).await?, "b.txt");
+        assert_eq!(*read_text(memory_root(&upper).join("dir/.wh.a.txt")).await?, "");
+        // The lower layer is never modified
+        assert_eq!(*read_text(memory_root(&lower).join("dir/a.txt")).await?, "a");
+
+        let Err(error) = root.join("dir/a.txt").metadata().await else {
+            panic!("a deleted file has no metadata");
+        };
+        assert!(format!("{error:?}").contains("path dir/a.txt not found"));
+    }
+}
+
+#[tokio::test]
+async fn recreate_removes_whiteout() {
+    run! {
+        register();
+
+        let upper = Arc::new(MemoryFiles::new());
+        let lower = Arc::new(MemoryFiles::new());
+        lower.write_file("a.txt", "lower")?;
+        let root = overlay_root(&upper, &lower);
+
+        root.join("a.txt").write(FileContent::NotFound.cell()).await?;
+        root.join("a.txt")
+            .write(FileContent::Content(File::from("upper")).cell())
+            .await?;
+        assert_eq!(*read_text(root.join("a.txt")).await?, "upper");
+        assert_eq!(*list(root).await?, "a.txt");
+        assert_eq!(*read_text(memory_root(&upper).join(".wh.a.txt")).await?, "<not found>");
+    }
+}
+
+#[tokio::test]
+async fn nested_paths() {
+    run! {
+        register();
+
+        let upper = Arc::new(MemoryFiles::new());
+        let lower = Arc::new(MemoryFiles::new());
+        lower.write_file("dir/a.txt", "a")?;
+        lower.write_file("file", "file")?;
+        let root = overlay_root(&upper, &lower);
+
+        // Directories of both layers are merged
+        root.join("dir/sub/b.txt")
+            .write(FileContent::Content(File::from("b")).cell())
+            .await?;
+        assert_eq!(*list(root.join("dir")).await?, "a.txt, sub");
+        assert_eq!(*read_text(root.join("dir/sub/b.txt")).await?, "b");
+
+
+        // Directories of the lower layer can't be deleted, and the upper layer
+        // stays unchanged
+        assert!(root.join("dir").write(FileContent::NotFound.cell()).await.is_err());
+        assert_eq!(*list(root.join("dir")).await?, "a.txt, sub");
+    }
+}
+
+#[tokio::test]
+async fn recreate_keeps_other_whiteouts() {
+    run! {
+        register();
+
+        let upper = Arc::new(MemoryFiles::new());
+        let lower = Arc::new(MemoryFiles::new());
+        lower.write_file("dir/a.txt", "a")?;
+        lower.write_file("dir/b.txt", "b")?;
+        lower.write_file("file", "file")?;
+        let root = overlay_root(&upper, &lower);
+
+        root.join("dir/a.txt").write(FileContent::NotFound.cell()).await?;
+        root.join("dir/b.txt").write(FileContent::NotFound.cell()).await?;
+        root.join("dir/a.txt")
+            .write(FileContent::Content(File::from("upper")).cell())
+            .await?;
+        assert_eq!(*list(root.join("dir")).await?, "a.txt");
+        assert_eq!(*read_text(root.join("dir/b.txt")).await?, "<not found>");
+        assert_eq!(*read_text(memory_root(&upper).join("dir/.wh.b.txt")).await?, "");
+
+        // Writing below a deleted path would make it visible again
+        root.join("file").write(FileContent::NotFound.cell()).await?;
+        let Err(error) = root.join("file/c.txt")
+            .write(FileContent::Content(File::from("c")).cell())
+            .await
+        else {
+            panic!("a path below a deleted file can't be written");
+        };
+        assert!(format!("{error:?}").contains("a parent directory has been deleted"));
+        assert_eq!(*read_text(root.join("file")).await?, "<not found>");
+        assert_eq!(*read_text(memory_root(&upper).join("file/c.txt")).await?, "<not found>");
+    }
+}
+
+#[turbo_tasks::function]
+async fn read_text(path: FileSystemPathVc) -> Result<StringVc> {
+    Ok(StringVc::cell(match &*path.read().await? {
+        FileContent::Content(file) => file.content().to_str()?.into_owned(),
+        FileContent::NotFound => "<not found>".to_string(),
+    }))
+}
+
+#[turbo_tasks::function]
+async fn list(dir: FileSystemPathVc) -> Result<StringVc> {
+    let DirectoryContent::Entries(entries) = &*dir.read_dir().await? else {
+        return Ok(StringVc::cell("<not found>".to_string()));
+    };
+    let mut names = entries
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(StringVc::cell(names.join(", ")))
+}
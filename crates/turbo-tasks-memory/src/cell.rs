@@ -73,6 +73,21 @@ impl Cell {
         }
     }
 
+    /// Checks if no task depends on the cell and nobody waits for its content,
+    /// so the content can be dropped without breaking invalidation.
+    pub fn is_unused(&self) -> bool {
+        match self {
+            Cell::Empty => true,
+            Cell::Recomputing { .. } | Cell::Full(box FullCell::Recomputing { .. }) => false,
+            Cell::InitialValue {
+                dependent_tasks, ..
+            }
+            | Cell::Full(box FullCell::UpdatedValue {
+                dependent_tasks, ..
+            }) => dependent_tasks.is_empty(),
+        }
+    }
+
     fn recompute(
         &mut self,
         updates: u32,
@@ -0,0 +1,121 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use turbo_malloc::TurboMalloc;
+use turbo_tasks::TurboTasksBackendApi;
+
+use crate::{stats::GcStats, MemoryBackend};
+
+/// Garbage collection frees memory until the usage is below this percentage of
+/// the memory limit.
+const TARGET_PERCENTAGE: usize = 75;
+
+/// The number of tasks that are unloaded between checks of the memory usage.
+const CHECK_MEMORY_EVERY: usize = 100;
+
+/// The minimum time between two garbage collection runs, to avoid running
+/// continuously when there is nothing left to unload.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Unloads inactive tasks when the memory usage exceeds a limit.
+pub(crate) struct GarbageCollector {
+    memory_limit: Option<usize>,
+    scheduled: AtomicBool,
+    last_run: Mutex<Option<Instant>>,
+    stats: Mutex<GcStats>,
+}
+
+impl GarbageCollector {
+    /// Without a `memory_limit` tasks are never unloaded.
+    pub fn new(memory_limit: Option<usize>) -> Self {
+        Self {
+            memory_limit,
+            scheduled: AtomicBool::new(false),
+            last_run: Mutex::new(None),
+            stats: Mutex::new(GcStats::default()),
+        }
+    }
+
+    /// Returns true when tasks might be unloaded, so reading them needs to
+    /// schedule them again.
+    pub fn is_enabled(&self) -> bool {
+        self.memory_limit.is_some()
+    }
+
+    /// Returns true when the memory usage exceeds the limit and a run should
+    /// be scheduled. Only one run is scheduled at a time.
+    pub fn should_schedule(&self) -> bool {
+        let Some(memory_limit) = self.memory_limit else {
+            return false;
+        };
+        if TurboMalloc::memory_usage() <= memory_limit {
+            return false;
+        }
+        if let Some(last_run) = *self.last_run.lock() {
+            if last_run.elapsed() < MIN_INTERVAL {
+                return false;
+            }
+        }
+        !self.scheduled.swap(true, Ordering::AcqRel)
+    }
+
+    pub fn stats(&self) -> GcStats {
+        self.stats.lock().clone()
+    }
+
+    /// Unloads the least recently used tasks that are not needed anymore,
+    /// until the memory usage is below the target.
+    pub fn run(&self, backend: &MemoryBackend, turbo_tasks: &dyn TurboTasksBackendApi) {
+        if let Some(memory_limit) = self.memory_limit {
+            let target = memory_limit / 100 * TARGET_PERCENTAGE;
+            self.collect(backend, turbo_tasks, Some(target));
+        }
+        *self.last_run.lock() = Some(Instant::now());
+        self.scheduled.store(false, Ordering::Release);
+    }
+
+    /// Unloads the least recently used tasks that are not needed anymore,
+    /// until the memory usage is below `target`. Without a target all of them
+    /// are unloaded. Returns the number of unloaded tasks.
+    pub fn collect(
+        &self,
+        backend: &MemoryBackend,
+        turbo_tasks: &dyn TurboTasksBackendApi,
+        target: Option<usize>,
+    ) -> usize {
+        let memory_before = TurboMalloc::memory_usage();
+
+        let mut candidates = Vec::new();
+        backend.with_all_cached_tasks(|id| {
+            if let Some(last_access) = backend.with_task(id, |task| task.gc_candidate(backend)) {
+                candidates.push((last_access, id));
+            }
+        });
+        candidates.sort_unstable();
+
+        let mut collected = 0;
+        for (i, (_, id)) in candidates.into_iter().enumerate() {
+            if let Some(target) = target {
+                if i % CHECK_MEMORY_EVERY == 0 && TurboMalloc::memory_usage() <= target {
+                    break;
+                }
+            }
+            if backend.with_task(id, |task| task.unload(backend, turbo_tasks)) {
+                collected += 1;
+            }
+        }
+
+        {
+            let mut stats = self.stats.lock();
+            stats.runs += 1;
+            stats.collected_tasks += collected;
+            stats.last_collected_tasks = collected;
+            stats.last_memory_before = memory_before;
+            stats.last_memory_after = TurboMalloc::memory_usage();
+        }
+        collected
+    }
+}
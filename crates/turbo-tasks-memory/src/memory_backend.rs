@@ -14,6 +14,7 @@ use auto_hash_map::AutoSet;
 use dashmap::{mapref::entry::Entry, DashMap};
 use rustc_hash::FxHasher;
 use tokio::task::futures::TaskLocalFuture;
+use turbo_malloc::TurboMalloc;
 use turbo_tasks::{
     backend::{
         Backend, BackendJobId, CellContent, PersistentTaskType, TaskExecutionSpec,
@@ -26,8 +27,10 @@ use turbo_tasks::{
 
 use crate::{
     cell::RecomputingCell,
+    gc::GarbageCollector,
     output::Output,
     scope::{TaskScope, TaskScopeId},
+    stats::GcStats,
     task::{
         run_add_to_scope_queue, run_remove_from_scope_queue, Task, TaskDependency,
         DEPENDENCIES_TO_TRACK,
@@ -42,6 +45,7 @@ pub struct MemoryBackend {
     backend_jobs: NoMoveVec<Job>,
     backend_job_id_factory: IdFactory<BackendJobId>,
     task_cache: DashMap<Arc<PersistentTaskType>, TaskId, BuildHasherDefault<FxHasher>>,
+    gc: GarbageCollector,
 }
 
 impl Default for MemoryBackend {
@@ -52,6 +56,25 @@ impl Default for MemoryBackend {
 
 impl MemoryBackend {
     pub fn new() -> Self {
+        Self::new_with_gc(GarbageCollector::new(None))
+    }
+
+    /// Creates a backend that unloads inactive tasks when the memory usage
+    /// exceeds `memory_limit` bytes.
+    ///
+    /// The memory usage is measured by [TurboMalloc], so it needs to be the
+    /// global allocator.
+    pub fn with_memory_limit(memory_limit: usize) -> Result<Self> {
+        if TurboMalloc::memory_usage() == 0 {
+            bail!(
+                "A memory limit requires turbo_malloc::TurboMalloc to be the global allocator, \
+                 otherwise the memory usage can't be measured"
+            );
+        }
+        Ok(Self::new_with_gc(GarbageCollector::new(Some(memory_limit))))
+    }
+
+    fn new_with_gc(gc: GarbageCollector) -> Self {
         let memory_task_scopes = NoMoveVec::new();
         let scope_id_factory = IdFactory::new();
         let initial_scope: TaskScopeId = scope_id_factory.get();
@@ -66,7 +89,23 @@ impl MemoryBackend {
             backend_jobs: NoMoveVec::new(),
             backend_job_id_factory: IdFactory::new(),
             task_cache: DashMap::default(),
+            gc,
+        }
+    }
+
+    /// Returns what garbage collection has unloaded so far.
+    pub(crate) fn gc_stats(&self) -> GcStats {
+        self.gc.stats()
+    }
+
+    /// Unloads all tasks that are not needed by an active task, independent of
+    /// the memory usage. Returns the number of unloaded tasks. Does nothing
+    /// when the backend was created without a memory limit.
+    pub fn collect_garbage(&self, turbo_tasks: &dyn TurboTasksBackendApi) -> usize {
+        if !self.gc.is_enabled() {
+            return 0;
         }
+        self.gc.collect(self, turbo_tasks, None)
     }
 
     fn connect_task_child(
@@ -98,7 +137,12 @@ impl MemoryBackend {
         func: F,
     ) -> Result<Result<T, EventListener>> {
         self.with_task(id, |task| {
-            task.get_or_wait_output(strongly_consistent, func, note, self, turbo_tasks)
+            let result =
+                task.get_or_wait_output(strongly_consistent, func, note, self, turbo_tasks)?;
+            if result.is_ok() && self.gc.is_enabled() {
+                task.register_read(turbo_tasks);
+            }
+            Ok(result)
         })
     }
 
@@ -242,9 +286,14 @@ impl Backend for MemoryBackend {
         instant: Instant,
         turbo_tasks: &dyn TurboTasksBackendApi,
     ) -> bool {
-        self.with_task(task, |task| {
+        let schedule = self.with_task(task, |task| {
             task.execution_completed(duration, instant, self, turbo_tasks)
-        })
+        });
+        if self.gc.should_schedule() {
+            turbo_tasks
+                .schedule_backend_background_job(self.create_backend_job(Job::GarbageCollection));
+        }
+        schedule
     }
 
     fn try_read_task_output(
@@ -305,10 +354,19 @@ impl Backend for MemoryBackend {
                         move || format!("reading {} {} from {}", task_id, index, reader),
                     )
                 }) {
-                    Ok(content) => Ok(Ok(content)),
+                    Ok(content) => {
+                        if self.gc.is_enabled() {
+                            task.register_read(turbo_tasks);
+                        }
+                        Ok(Ok(content))
+                    }
                     Err(RecomputingCell { listener, schedule }) => {
                         if schedule {
                             task.invalidate(self, turbo_tasks);
+                            if self.gc.is_enabled() {
+                                // The task might have been unloaded and is not active
+                                task.schedule_when_dirty_from_scope(self, turbo_tasks);
+                            }
                         }
                         Ok(Err(listener))
                     }
@@ -341,10 +399,18 @@ impl Backend for MemoryBackend {
                     move || format!("reading {} {} untracked", task_id, index),
                 )
             }) {
-                Ok(content) => Ok(Ok(content)),
+                Ok(content) => {
+                    if self.gc.is_enabled() {
+                        task.register_read(turbo_tasks);
+                    }
+                    Ok(Ok(content))
+                }
                 Err(RecomputingCell { listener, schedule }) => {
                     if schedule {
                         task.invalidate(self, turbo_tasks);
+                        if self.gc.is_enabled() {
+                            task.schedule_when_dirty_from_scope(self, turbo_tasks);
+                        }
                     }
                     Ok(Err(listener))
                 }
@@ -502,6 +568,8 @@ pub(crate) enum Job {
     /// Remove tasks from a scope. Scheduled by `run_remove_from_scope_queue` to
     /// split off work.
     RemoveFromScopeQueue(VecDeque<TaskId>, TaskScopeId),
+    /// Unloads inactive tasks to free memory.
+    GarbageCollection,
 }
 
 impl Job {
@@ -534,6 +602,9 @@ impl Job {
             Job::RemoveFromScopeQueue(queue, id) => {
                 run_remove_from_scope_queue(queue, id, backend, turbo_tasks);
             }
+            Job::GarbageCollection => {
+                backend.gc.run(backend, turbo_tasks);
+            }
         }
     }
 }
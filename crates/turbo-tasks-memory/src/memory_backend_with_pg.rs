@@ -7,7 +7,7 @@ use std::{
     pin::Pin,
     sync::{
         atomic::{AtomicU32, AtomicUsize, Ordering},
-        Mutex, MutexGuard,
+        Arc, Mutex, MutexGuard, Weak,
     },
     time::{Duration, Instant},
 };
@@ -27,7 +27,7 @@ use turbo_tasks::{
         PersistedGraphApi, ReadTaskState, TaskCell, TaskData,
     },
     util::{IdFactory, NoMoveVec, SharedError},
-    CellId, RawVc, TaskId, TraitTypeId, TurboTasksBackendApi,
+    CellId, PersistableState, RawVc, StateId, TaskId, TraitTypeId, TurboTasksBackendApi,
 };
 
 type RootTaskFn =
@@ -74,6 +74,9 @@ struct MemoryTaskState {
     output_dependent: AutoSet<TaskId>,
     dependencies: AutoSet<RawVc>,
     children: AutoSet<TaskId>,
+    /// The task read external state in its current execution, see
+    /// [turbo_tasks::mark_external_input]
+    external_input: bool,
     event: Event,
     event_cells: Event,
 }
@@ -89,6 +92,7 @@ impl MemoryTaskState {
             output_dependent: Default::default(),
             dependencies: Default::default(),
             children: Default::default(),
+            external_input: Default::default(),
             event: Event::new(move || format!("MemoryTaskState({task})::event")),
             event_cells: Event::new(move || format!("MemoryTaskState({task})::event_cells")),
         }
@@ -145,6 +149,8 @@ pub struct MemoryBackendWithPersistedGraph<P: PersistedGraph> {
     need_persisting: DashSet<TaskId>,
     /// Task sorted by importance, sharded to avoid lock contention
     persist_queue_by_duration: [Mutex<BinaryHeap<(Duration, TaskId)>>; 64],
+    /// States that changed since they were persisted
+    states_to_persist: Mutex<HashMap<StateId, Weak<dyn PersistableState>>>,
     persist_capacity: AtomicUsize,
     persist_job: BackendJobId,
     partial_lookups: DashMap<PersistentTaskType, bool>,
@@ -172,6 +178,7 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
             persist_queue1_queued: DashSet::new(),
             need_persisting: DashSet::new(),
             persist_queue_by_duration: [(); 64].map(|_| Mutex::new(BinaryHeap::new())),
+            states_to_persist: Mutex::new(HashMap::new()),
             persist_capacity: AtomicUsize::new(num_cpus::get()),
             persist_job,
             partial_lookups: DashMap::new(),
@@ -277,6 +284,8 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
                     output_dependent: AutoSet::new(),
                     dependencies: data.dependencies.into_iter().collect(),
                     children: data.children.into_iter().collect(),
+                    // The persisted graph already knows if it's an external input
+                    external_input: false,
                     need_persist: Default::default(),
                     has_changes: Default::default(),
                     event: Event::new(move || format!("MemoryTaskState({task})::event")),
@@ -737,6 +746,16 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
     }
 
     fn persist(&self, turbo_tasks: &dyn TurboTasksBackendApi) -> bool {
+        let states = take(&mut *self.states_to_persist.lock().unwrap());
+        if !states.is_empty() {
+            // States of dropped tasks don't need to be persisted anymore
+            let states = states
+                .into_iter()
+                .filter_map(|(state, value)| Some((state, value.upgrade()?)))
+                .collect();
+            self.pg_persist_states(states, turbo_tasks);
+            return true;
+        }
         loop {
             if let Ok(mut task) = self.persist_queue1.pop() {
                 self.persist_queue1_queued.remove(&task);
@@ -755,6 +774,7 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
                                 ref children,
                                 ref dependencies,
                                 ref cells,
+                                external_input,
                                 ..
                             }),
                         ..
@@ -798,6 +818,7 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
                                         let task_state =
                                             turbo_tasks::persisted_graph::PersistTaskState {
                                                 externally_active,
+                                                external_input,
                                             };
                                         if let Some(PersistResult {
                                             tasks_to_activate,
@@ -871,6 +892,7 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
 
     fn has_persist_work(&self) -> bool {
         !self.persist_queue1.is_empty()
+            || !self.states_to_persist.lock().unwrap().is_empty()
             || self
                 .persist_queue_by_duration
                 .iter()
@@ -907,6 +929,8 @@ impl<P: PersistedGraph> Backend for MemoryBackendWithPersistedGraph<P> {
         let (tasks_to_activate, tasks_to_deactivate) =
             self.pg_get_pending_active_update(turbo_tasks);
         let tasks = self.pg_get_active_external_tasks(turbo_tasks);
+        // Files and env vars might have changed while the graph wasn't in use
+        self.pg_make_external_inputs_dirty(turbo_tasks);
         let dirty_tasks = self.pg_get_dirty_active_tasks(turbo_tasks);
 
         for task in tasks.iter() {
@@ -1006,6 +1030,40 @@ impl<P: PersistedGraph> Backend for MemoryBackendWithPersistedGraph<P> {
         }
     }
 
+    fn invalidate_state_serialization(
+        &self,
+        state: StateId,
+        value: Weak<dyn PersistableState>,
+        turbo_tasks: &dyn TurboTasksBackendApi,
+    ) {
+        let task_info = self.tasks.get(*state.task).unwrap();
+        if !matches!(task_info.task_type, TaskType::Persistent(_)) {
+            return;
+        }
+        // Newer values of the same state replace the queued one
+        self.states_to_persist.lock().unwrap().insert(state, value);
+        self.increase_persist_workers(1, turbo_tasks);
+    }
+
+    fn read_persisted_state(
+        &self,
+        state: StateId,
+        turbo_tasks: &dyn TurboTasksBackendApi,
+    ) -> Option<Vec<u8>> {
+        self.pg_read_state(state, turbo_tasks)
+    }
+
+    fn mark_task_as_external_input(&self, task: TaskId, turbo_tasks: &dyn TurboTasksBackendApi) {
+        let task_info = self.tasks.get(*task).unwrap();
+        if !matches!(task_info.task_type, TaskType::Persistent(_)) {
+            return;
+        }
+        // This is only written to the persisted graph together with the task, to
+        // avoid a write for every file read
+        let (mut state, _) = self.mem_state_mut(task, turbo_tasks);
+        state.memory.as_mut().unwrap().external_input = true;
+    }
+
     fn get_task_description(&self, task: TaskId) -> String {
         let task_info = self.tasks.get(*task).unwrap();
         format!("{:?}", task_info.task_type)
@@ -1038,6 +1096,7 @@ impl<P: PersistedGraph> Backend for MemoryBackendWithPersistedGraph<P> {
             println!("start {} {:?}", task, task_info.task_type);
         }
         mem_state.freshness = TaskFreshness::NeverExecuted;
+        mem_state.external_input = false;
         let deps = take(&mut mem_state.dependencies);
         let children = take(&mut mem_state.children);
         drop(state);
@@ -1611,6 +1670,22 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
             .unwrap()
     }
 
+    fn pg_read_state(
+        &self,
+        state: StateId,
+        turbo_tasks: &dyn TurboTasksBackendApi,
+    ) -> Option<Vec<u8>> {
+        self.pg
+            .read_state(
+                state,
+                &MemoryBackendPersistedGraphApi {
+                    backend: self,
+                    turbo_tasks,
+                },
+            )
+            .unwrap()
+    }
+
     fn pg_lookup_one(
         &self,
         task_type: &PersistentTaskType,
@@ -1675,6 +1750,22 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
             .unwrap()
     }
 
+    fn pg_persist_states(
+        &self,
+        states: Vec<(StateId, Arc<dyn PersistableState>)>,
+        turbo_tasks: &dyn TurboTasksBackendApi,
+    ) {
+        self.pg
+            .persist_states(
+                states,
+                &MemoryBackendPersistedGraphApi {
+                    backend: self,
+                    turbo_tasks,
+                },
+            )
+            .unwrap()
+    }
+
     #[must_use]
     fn pg_activate_when_needed(
         &self,
@@ -1756,6 +1847,15 @@ impl<P: PersistedGraph> MemoryBackendWithPersistedGraph<P> {
             .unwrap()
     }
 
+    fn pg_make_external_inputs_dirty(&self, turbo_tasks: &dyn TurboTasksBackendApi) {
+        self.pg
+            .make_external_inputs_dirty(&MemoryBackendPersistedGraphApi {
+                backend: self,
+                turbo_tasks,
+            })
+            .unwrap()
+    }
+
     #[must_use]
     fn pg_make_dependent_dirty(
         &self,
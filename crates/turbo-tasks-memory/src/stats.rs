@@ -89,8 +89,24 @@ impl Default for ExportedTaskStats {
     }
 }
 
+/// What the garbage collection of the [MemoryBackend] has unloaded so far.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GcStats {
+    /// The number of garbage collection runs.
+    pub runs: usize,
+    /// The number of tasks unloaded over all runs.
+    pub collected_tasks: usize,
+    /// The number of tasks unloaded by the last run.
+    pub last_collected_tasks: usize,
+    /// The memory usage before the last run, in bytes.
+    pub last_memory_before: usize,
+    /// The memory usage after the last run, in bytes.
+    pub last_memory_after: usize,
+}
+
 pub struct Stats {
     tasks: HashMap<StatsTaskType, ExportedTaskStats>,
+    gc: GcStats,
 }
 
 impl Default for Stats {
@@ -103,9 +119,20 @@ impl Stats {
     pub fn new() -> Self {
         Self {
             tasks: Default::default(),
+            gc: Default::default(),
         }
     }
 
+    /// Returns what garbage collection has unloaded, as of the last call to
+    /// [Stats::add_gc].
+    pub fn gc(&self) -> &GcStats {
+        &self.gc
+    }
+
+    pub fn add_gc(&mut self, backend: &MemoryBackend) {
+        self.gc = backend.gc_stats();
+    }
+
     pub fn add(&mut self, backend: &MemoryBackend, task: &Task) {
         self.add_conditional(backend, task, |_, _| true)
     }
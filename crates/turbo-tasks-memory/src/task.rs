@@ -1365,6 +1365,110 @@ impl Task {
         }
     }
 
+    /// Records that the output or a cell of the task was read, so garbage
+    /// collection unloads the least recently used tasks first.
+    pub(crate) fn register_read(&self, turbo_tasks: &dyn TurboTasksBackendApi) {
+        if let TaskMetaStateWriteGuard::Full(mut state) = self.state_mut() {
+            state
+                .stats
+                .register_read(turbo_tasks.program_duration_until(Instant::now()));
+        }
+    }
+
+    /// Checks if the task can be unloaded by garbage collection: It's done, not
+    /// in any active scope, and neither its output nor its cells are read by
+    /// other tasks. Returns when the task was last executed or read, so the
+    /// least recently used tasks can be unloaded first.
+    pub(crate) fn gc_candidate(&self, backend: &MemoryBackend) -> Option<Duration> {
+        if !matches!(self.ty, TaskType::Persistent(_)) {
+            return None;
+        }
+        let mut state = self.try_state()?;
+        let state = state.as_full()?;
+        if !Self::is_unloadable(state)
+            || state
+                .scopes
+                .iter()
+                .any(|scope| backend.with_scope(scope, |scope| scope.state.lock().is_active()))
+        {
+            return None;
+        }
+        Some(state.stats.last_access_relative_to_start())
+    }
+
+    fn is_unloadable(state: &TaskState) -> bool {
+        matches!(state.state_type, Done { .. })
+            && matches!(state.scopes, TaskScopes::Inner(..))
+            // Collectibles would disappear from the scopes until the task is
+            // executed again
+            && state.collectibles.as_ref().is_none()
+            && state.output.dependent_tasks.is_empty()
+            && state.cells.values().flatten().all(Cell::is_unused)
+    }
+
+    /// Unloads the output and cells of a task that is a
+    /// [Task::gc_candidate]. The task stays in its scopes as a dirty task and
+    /// is executed again when it's read. Returns true if the task was
+    /// unloaded.
+    pub(crate) fn unload(
+        &self,
+        backend: &MemoryBackend,
+        turbo_tasks: &dyn TurboTasksBackendApi,
+    ) -> bool {
+        if !matches!(self.ty, TaskType::Persistent(_)) {
+            return false;
+        }
+        let mut guard = self.state.write();
+        let TaskMetaState::Full(state) = &mut *guard else {
+            return false;
+        };
+        if !Self::is_unloadable(state) {
+            return false;
+        }
+        if self.scopes_dirty_or_active(true, &state.scopes, backend) {
+            // A scope has been activated in the meantime, revert the increment
+            // of unfinished tasks
+            for scope in state.scopes.iter() {
+                backend.with_scope(scope, |scope| {
+                    scope.decrement_unfinished_tasks(backend);
+                })
+            }
+            return false;
+        }
+
+        let TaskMetaState::Full(box TaskState {
+            scopes,
+            state_type: Done { dependencies },
+            children,
+            stats,
+            ..
+        }) = replace(
+            &mut *guard,
+            // placeholder
+            TaskMetaState::Unloaded(UnloadedTaskState {
+                stats_type: StatsType::Essential,
+            }),
+        ) else {
+            unreachable!()
+        };
+        if !children.is_empty() {
+            remove_from_scopes(children, &scopes, backend, turbo_tasks);
+        }
+        let stats_type = stats.stats_type();
+        *guard = match scopes {
+            TaskScopes::Inner(ref set, _) if set.is_empty() => {
+                TaskMetaState::Unloaded(UnloadedTaskState { stats_type })
+            }
+            scopes => TaskMetaState::Partial(box PartialTaskState { stats_type, scopes }),
+        };
+        drop(guard);
+
+        if !dependencies.is_empty() {
+            self.clear_dependencies(dependencies, backend);
+        }
+        true
+    }
+
     /// For testing purposes
     pub fn reset_executions(&self) {
         if let TaskMetaStateWriteGuard::Full(mut state) = self.state_mut() {
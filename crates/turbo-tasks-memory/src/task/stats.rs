@@ -41,6 +41,7 @@ impl TaskStats {
             Self::Full(stats) => {
                 stats.total_duration += duration;
                 stats.last_duration = duration;
+                stats.last_execution_relative_to_start = duration_since_start.into();
             }
             Self::Essential(stats) => {
                 stats.last_duration = duration.into();
@@ -49,6 +50,45 @@ impl TaskStats {
         }
     }
 
+    /// Returns the last execution of the task relative to the start of the
+    /// program.
+    pub fn last_execution_relative_to_start(&self) -> Duration {
+        match self {
+            Self::Full(stats) => stats.last_execution_relative_to_start(),
+            Self::Essential(stats) => stats.last_execution_relative_to_start(),
+        }
+    }
+
+    /// Registers a read of the output or a cell of the task.
+    pub fn register_read(&mut self, duration_since_start: Duration) {
+        match self {
+            Self::Full(stats) => {
+                stats.last_read_relative_to_start = duration_since_start.into();
+            }
+            Self::Essential(stats) => {
+                stats.last_read_relative_to_start = duration_since_start.into();
+            }
+        }
+    }
+
+    /// Returns the last execution or read of the task relative to the start
+    /// of the program, whichever is later.
+    pub fn last_access_relative_to_start(&self) -> Duration {
+        let last_read = match self {
+            Self::Full(stats) => stats.last_read_relative_to_start(),
+            Self::Essential(stats) => stats.last_read_relative_to_start(),
+        };
+        self.last_execution_relative_to_start().max(last_read)
+    }
+
+    /// Returns the kind of stats that are recorded.
+    pub fn stats_type(&self) -> StatsType {
+        match self {
+            Self::Full(_) => StatsType::Full,
+            Self::Essential(_) => StatsType::Essential,
+        }
+    }
+
     /// Resets stats to their default, zero-value.
     pub fn reset(&mut self) {
         match self {
@@ -60,6 +100,7 @@ impl TaskStats {
             Self::Essential(stats) => {
                 stats.last_duration = SmallDuration::MIN;
                 stats.last_execution_relative_to_start = SmallDuration::MIN;
+                stats.last_read_relative_to_start = SmallDuration::MIN;
             }
         }
     }
@@ -72,6 +113,9 @@ pub struct TaskStatsEssential {
     /// The last execution of the task relative to the start of the program,
     /// with a precision of 1 millisecond.
     last_execution_relative_to_start: SmallDuration<1_000_000>,
+    /// The last read of the output or a cell of the task relative to the
+    /// start of the program, with a precision of 1 millisecond.
+    last_read_relative_to_start: SmallDuration<1_000_000>,
 }
 
 impl TaskStatsEssential {
@@ -82,10 +126,15 @@ impl TaskStatsEssential {
 
     /// Returns the last execution of the task relative to the start of the
     /// program.
-    #[allow(dead_code)] // NOTE(alexkirsz) This will be useful for GC.
     pub fn last_execution_relative_to_start(&self) -> Duration {
         self.last_execution_relative_to_start.into()
     }
+
+    /// Returns the last read of the output or a cell of the task relative to
+    /// the start of the program.
+    pub fn last_read_relative_to_start(&self) -> Duration {
+        self.last_read_relative_to_start.into()
+    }
 }
 
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
@@ -99,6 +148,9 @@ pub struct TaskStatsFull {
     /// The last execution of the task relative to the start of the program,
     /// with a precision of 1 millisecond.
     last_execution_relative_to_start: SmallDuration<1_000_000>,
+    /// The last read of the output or a cell of the task relative to the
+    /// start of the program, with a precision of 1 millisecond.
+    last_read_relative_to_start: SmallDuration<1_000_000>,
 }
 
 impl TaskStatsFull {
@@ -119,8 +171,44 @@ impl TaskStatsFull {
 
     /// Returns the last execution of the task relative to the start of the
     /// program.
-    #[allow(dead_code)] // NOTE(alexkirsz) This will be useful for GC.
     pub fn last_execution_relative_to_start(&self) -> Duration {
         self.last_execution_relative_to_start.into()
     }
+
+    /// Returns the last read of the output or a cell of the task relative to
+    /// the start of the program.
+    pub fn last_read_relative_to_start(&self) -> Duration {
+        self.last_read_relative_to_start.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use turbo_tasks::StatsType;
+
+    use super::TaskStats;
+
+    #[test]
+    fn last_access_includes_reads() {
+        for stats_type in [StatsType::Essential, StatsType::Full] {
+            let mut stats = TaskStats::new(stats_type);
+            stats.register_execution(Duration::from_millis(1), Duration::from_secs(1));
+            assert_eq!(
+                stats.last_access_relative_to_start(),
+                Duration::from_secs(1)
+            );
+
+            stats.register_read(Duration::from_secs(5));
+            assert_eq!(
+                stats.last_access_relative_to_start(),
+                Duration::from_secs(5)
+            );
+            assert_eq!(
+                stats.last_execution_relative_to_start(),
+                Duration::from_secs(1)
+            );
+        }
+    }
 }
@@ -0,0 +1,109 @@
+#![feature(min_specialization)]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use anyhow::Result;
+use turbo_malloc::TurboMalloc;
+use turbo_tasks::{Completion, CompletionVc, RawVc, TurboTasks};
+use turbo_tasks_memory::{stats::Stats, MemoryBackend};
+use turbo_tasks_testing::register;
+
+// The memory limit of the backend needs the memory usage to be measured.
+#[global_allocator]
+static ALLOC: TurboMalloc = TurboMalloc;
+
+register!();
+
+static READER_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+static LEAF_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[tokio::test]
+async fn unloads_unused_tasks() {
+    lazy_static::initialize(&REGISTER);
+    // The limit is never exceeded, so tasks are only unloaded by
+    // `collect_garbage`.
+    let tt = TurboTasks::new(MemoryBackend::with_memory_limit(usize::MAX).unwrap());
+
+    read_untracked(&tt, reader).await;
+    assert_eq!(READER_EXECUTIONS.load(Ordering::SeqCst), 1);
+    assert_eq!(LEAF_EXECUTIONS.load(Ordering::SeqCst), 1);
+
+    // The leaf is read by the reader, so only the reader can be unloaded.
+    // Unloading it removes its dependency on the leaf.
+    assert_eq!(tt.backend().collect_garbage(&*tt), 1);
+    assert_eq!(tt.backend().collect_garbage(&*tt), 1);
+    assert_eq!(tt.backend().collect_garbage(&*tt), 0);
+
+    let mut stats = Stats::new();
+    stats.add_gc(tt.backend());
+    assert_eq!(stats.gc().runs, 3);
+    assert_eq!(stats.gc().collected_tasks, 2);
+
+    // Unloaded tasks are executed again when they are needed.
+    read_untracked(&tt, reader).await;
+    assert_eq!(READER_EXECUTIONS.load(Ordering::SeqCst), 2);
+    assert_eq!(LEAF_EXECUTIONS.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn keeps_active_tasks() {
+    lazy_static::initialize(&REGISTER);
+    let tt = TurboTasks::new(MemoryBackend::with_memory_limit(usize::MAX).unwrap());
+
+    let tt_clone = tt.clone();
+    tt.run_once(async move {
+        let raw: RawVc = unread_leaf().into();
+        raw.into_read_untracked::<Completion>(&*turbo_tasks::turbo_tasks())
+            .await?;
+        // The once task is in an active scope, and so are its children.
+        assert_eq!(tt_clone.backend().collect_garbage(&*tt_clone), 0);
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn needs_a_memory_limit() {
+    lazy_static::initialize(&REGISTER);
+    let tt = TurboTasks::new(MemoryBackend::new());
+
+    read_untracked(&tt, unread_leaf).await;
+    assert_eq!(tt.backend().collect_garbage(&*tt), 0);
+}
+
+/// Reads the output of a task without depending on it, so it's only kept
+/// alive by being a child of the once task. Waits until the once task has left
+/// its scope, which deactivates the task.
+async fn read_untracked(tt: &Arc<TurboTasks<MemoryBackend>>, task: fn() -> CompletionVc) {
+    tt.run_once(async move {
+        let raw: RawVc = task().into();
+        raw.into_read_untracked::<Completion>(&*turbo_tasks::turbo_tasks())
+            .await?;
+        Ok(())
+    })
+    .await
+    .unwrap();
+    tt.wait_foreground_done().await;
+}
+
+#[turbo_tasks::function]
+async fn reader() -> Result<CompletionVc> {
+    READER_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    leaf().await?;
+    Ok(CompletionVc::new())
+}
+
+#[turbo_tasks::function]
+fn leaf() -> CompletionVc {
+    LEAF_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    CompletionVc::new()
+}
+
+#[turbo_tasks::function]
+fn unread_leaf() -> CompletionVc {
+    CompletionVc::new()
+}
@@ -212,6 +212,8 @@ table!(externally_active_tasks, (()) => [usize]);
 table!(potential_active_external_tasks, (()) => [usize]);
 table!(potential_dirty_active_tasks, (()) => [usize]);
 table!(pending_active_update, (()) => [usize]);
+table!(external_input_tasks, (()) => [usize]);
+table!(states, (usize, u32) => (Vec<u8>));
 
 database!(
     last_task_id,
@@ -225,5 +227,7 @@ database!(
     externally_active_tasks,
     potential_active_external_tasks,
     potential_dirty_active_tasks,
-    pending_active_update
+    pending_active_update,
+    external_input_tasks,
+    states
 );
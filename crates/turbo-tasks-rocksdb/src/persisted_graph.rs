@@ -2,7 +2,10 @@ use std::{
     collections::HashSet,
     fmt::Debug,
     path::Path,
-    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+    sync::{
+        atomic::{AtomicU8, AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
 use anyhow::{anyhow, Error, Result};
@@ -15,7 +18,7 @@ use turbo_tasks::{
         PersistedGraphApi, ReadTaskState, TaskCell, TaskData,
     },
     util::{InfiniteVec, SharedError},
-    with_task_id_mapping, FunctionId, IdMapping, TaskId,
+    with_task_id_mapping, FunctionId, IdMapping, PersistableState, StateId, TaskId,
 };
 
 use super::db::{Database, TaskState, TaskStateChange};
@@ -420,6 +423,11 @@ impl PersistedGraph for RocksDbPersistedGraph {
             }
             db.dependencies.write(b, &db_task, &data.dependencies)?;
             db.pending_active_update.remove(b, &(), &db_task)?;
+            // Tasks are not removed again when they stop reading external state,
+            // that only makes them dirty unnecessarily on startup
+            if state.external_input {
+                db.external_input_tasks.insert(b, &(), &db_task)?;
+            }
             b.write()?;
             let ty = db.task_type.get(&db_task)?.unwrap();
             match ty {
@@ -441,6 +449,33 @@ impl PersistedGraph for RocksDbPersistedGraph {
         })
     }
 
+    fn persist_states(
+        &self,
+        states: Vec<(StateId, Arc<dyn PersistableState>)>,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<()> {
+        let mapping = PgApiMapping::new(self, api);
+        with_task_id_mapping(&mapping, || {
+            let db = &self.database;
+            let b = &mut db.batch();
+            for (state, value) in states {
+                let db_task = mapping.forward(state.task);
+                db.states
+                    .write(b, (&db_task, &state.index), &value.serialize_state()?)?;
+            }
+            b.write()?;
+            Ok(())
+        })
+    }
+
+    fn read_state(&self, state: StateId, api: &dyn PersistedGraphApi) -> Result<Option<Vec<u8>>> {
+        let db_task = PgApiReadOnlyMapping::new(self, api).forward(state.task);
+        if db_task == 0 {
+            return Ok(None);
+        }
+        self.database.states.get((&db_task, &state.index))
+    }
+
     fn activate_when_needed(
         &self,
         task: TaskId,
@@ -664,6 +699,20 @@ impl PersistedGraph for RocksDbPersistedGraph {
         })
     }
 
+    fn make_external_inputs_dirty(&self, _api: &dyn PersistedGraphApi) -> Result<()> {
+        // For startup
+        let db = &self.database;
+        let b = &mut db.batch();
+        let tasks = db.external_input_tasks.get_all(&())?;
+        for db_task in tasks.iter() {
+            db.state.merge(b, db_task, &TaskStateChange::MakeDirty)?;
+            db.potential_dirty_active_tasks.insert(b, &(), db_task)?;
+        }
+        b.write()?;
+        self.stats.dirties.fetch_add(tasks.len(), Ordering::Relaxed);
+        Ok(())
+    }
+
     fn make_clean(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<()> {
         let mapping = PgApiReadOnlyMapping::new(self, api);
         let db_task = mapping.forward(task);
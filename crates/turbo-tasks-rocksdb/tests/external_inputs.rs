@@ -0,0 +1,53 @@
+#![feature(min_specialization)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use turbo_tasks::{CompletionVc, TurboTasks};
+use turbo_tasks_memory::MemoryBackendWithPersistedGraph;
+use turbo_tasks_rocksdb::RocksDbPersistedGraph;
+use turbo_tasks_testing::register;
+
+register!();
+
+static EXTERNAL_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+static INTERNAL_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[tokio::test]
+async fn external_inputs_are_dirty_after_restore() {
+    lazy_static::initialize(&REGISTER);
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    for _ in 0..2 {
+        let tt = TurboTasks::new(MemoryBackendWithPersistedGraph::new(
+            RocksDbPersistedGraph::new(cache_dir.path()).unwrap(),
+        ));
+        tt.run_once(async {
+            external().await?;
+            internal().await?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+        // Tasks are persisted in the background
+        tt.wait_background_done().await;
+        tt.stop_and_wait().await;
+    }
+
+    // Only the task that read external state is executed again, the other one
+    // is restored from the cache.
+    assert_eq!(EXTERNAL_EXECUTIONS.load(Ordering::SeqCst), 2);
+    assert_eq!(INTERNAL_EXECUTIONS.load(Ordering::SeqCst), 1);
+}
+
+#[turbo_tasks::function]
+fn external() -> CompletionVc {
+    turbo_tasks::mark_external_input();
+    EXTERNAL_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    CompletionVc::new()
+}
+
+#[turbo_tasks::function]
+fn internal() -> CompletionVc {
+    INTERNAL_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    CompletionVc::new()
+}
@@ -0,0 +1,65 @@
+#![feature(min_specialization)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use turbo_tasks::{primitives::U32Vc, State, TurboTasks};
+use turbo_tasks_memory::MemoryBackendWithPersistedGraph;
+use turbo_tasks_rocksdb::RocksDbPersistedGraph;
+use turbo_tasks_testing::register;
+
+register!();
+
+static COUNTER_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+static READ_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[tokio::test]
+async fn state_is_restored_with_its_dependents() {
+    lazy_static::initialize(&REGISTER);
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    for (old_value, new_value) in [(1, 2), (2, 3)] {
+        let tt = TurboTasks::new(MemoryBackendWithPersistedGraph::new(
+            RocksDbPersistedGraph::new(cache_dir.path()).unwrap(),
+        ));
+        tt.run_once(async move {
+            let counter = CounterVc::new();
+            assert_eq!(*read(counter).await?, old_value);
+            counter.await?.value.set(new_value);
+            assert_eq!(*read(counter).strongly_consistent().await?, new_value);
+            Ok(())
+        })
+        .await
+        .unwrap();
+        // Tasks and states are persisted in the background
+        tt.wait_background_done().await;
+        tt.stop_and_wait().await;
+    }
+
+    // The counter is restored with the value that was set after it was
+    // created, and the restored reader is still invalidated by the next change.
+    assert_eq!(COUNTER_EXECUTIONS.load(Ordering::SeqCst), 1);
+    assert_eq!(READ_EXECUTIONS.load(Ordering::SeqCst), 3);
+}
+
+#[turbo_tasks::value(eq = "manual", cell = "new")]
+struct Counter {
+    value: State<u32>,
+}
+
+#[turbo_tasks::value_impl]
+impl CounterVc {
+    #[turbo_tasks::function]
+    fn new() -> Self {
+        COUNTER_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+        Self::cell(Counter {
+            value: State::new(1),
+        })
+    }
+}
+
+#[turbo_tasks::function]
+async fn read(counter: CounterVc) -> Result<U32Vc> {
+    READ_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    Ok(U32Vc::cell(*counter.await?.value.get()))
+}
@@ -20,7 +20,8 @@ use turbo_tasks::{
     event::{Event, EventListener},
     registry,
     test_helpers::{current_task_for_testing, with_turbo_tasks_for_testing},
-    CellId, RawVc, TaskId, TraitTypeId, TurboTasksApi, TurboTasksCallApi,
+    CellId, PersistableState, RawVc, StateId, TaskId, TraitTypeId, TurboTasksApi,
+    TurboTasksCallApi,
 };
 
 enum Task {
@@ -104,6 +105,14 @@ impl TurboTasksApi for VcStorage {
         unreachable!()
     }
 
+    fn invalidate_state_serialization(&self, _state: StateId, _value: Weak<dyn PersistableState>) {
+        // ignore
+    }
+
+    fn read_persisted_state(&self, _state: StateId) -> Option<Vec<u8>> {
+        None
+    }
+
     fn notify_scheduled_tasks(&self) {
         // ignore
     }
@@ -203,6 +212,10 @@ impl TurboTasksApi for VcStorage {
         let cell = map.entry((task, index)).or_default();
         *cell = content;
     }
+
+    fn mark_own_task_as_external_input(&self) {
+        // ignore
+    }
 }
 
 impl VcStorage {
@@ -4,7 +4,7 @@ use std::{
     fmt::{Debug, Display},
     future::Future,
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, Weak},
     time::{Duration, Instant},
 };
 
@@ -14,9 +14,13 @@ use serde::{Deserialize, Serialize};
 
 pub use crate::id::BackendJobId;
 use crate::{
-    event::EventListener, manager::TurboTasksBackendApi, raw_vc::CellId, registry,
-    task_input::SharedReference, FunctionId, RawVc, ReadRef, TaskId, TaskIdProvider, TaskInput,
-    TraitTypeId,
+    event::EventListener,
+    manager::TurboTasksBackendApi,
+    raw_vc::CellId,
+    registry,
+    state::{PersistableState, StateId},
+    task_input::SharedReference,
+    FunctionId, RawVc, ReadRef, TaskId, TaskIdProvider, TaskInput, TraitTypeId,
 };
 
 /// Different Task types
@@ -169,6 +173,37 @@ pub trait Backend: Sync + Send {
 
     fn invalidate_task(&self, task: TaskId, turbo_tasks: &dyn TurboTasksBackendApi);
 
+    /// Marks the stored value of a [crate::State] as outdated. States are
+    /// stored separately from the task that created them, so the task itself
+    /// doesn't need to be stored again. Backends that persist tasks need to
+    /// store the state again, as long as `value` is alive.
+    #[allow(unused_variables)]
+    fn invalidate_state_serialization(
+        &self,
+        state: StateId,
+        value: Weak<dyn PersistableState>,
+        turbo_tasks: &dyn TurboTasksBackendApi,
+    ) {
+    }
+
+    /// Reads the value of a [crate::State] that was stored by a previous
+    /// process, see [Backend::invalidate_state_serialization].
+    #[allow(unused_variables)]
+    fn read_persisted_state(
+        &self,
+        state: StateId,
+        turbo_tasks: &dyn TurboTasksBackendApi,
+    ) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Marks the task as reading state from outside of turbo-tasks (e. g. the
+    /// filesystem or env vars). Backends that restore tasks from a previous
+    /// process need to invalidate these tasks, since the state might have
+    /// changed in between.
+    #[allow(unused_variables)]
+    fn mark_task_as_external_input(&self, task: TaskId, turbo_tasks: &dyn TurboTasksBackendApi) {}
+
     fn invalidate_tasks(&self, tasks: Vec<TaskId>, turbo_tasks: &dyn TurboTasksBackendApi);
 
     fn get_task_description(&self, task: TaskId) -> String;
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::HashMap,
     future::Future,
     hash::Hash,
@@ -28,6 +28,7 @@ use crate::{
     id_factory::IdFactory,
     raw_vc::{CellId, RawVc},
     registry,
+    state::{PersistableState, StateId},
     task_input::{SharedReference, TaskInput},
     timed_future::{self, TimedFuture},
     trace::TraceRawVcs,
@@ -58,6 +59,14 @@ pub trait TurboTasksCallApi: Sync + Send {
 pub trait TurboTasksApi: TurboTasksCallApi + Sync + Send {
     fn invalidate(&self, task: TaskId);
 
+    /// Marks the stored value of a [crate::State] as outdated, e. g. when the
+    /// value has changed.
+    fn invalidate_state_serialization(&self, state: StateId, value: Weak<dyn PersistableState>);
+
+    /// Reads the value of a [crate::State] that was stored by a previous
+    /// process.
+    fn read_persisted_state(&self, state: StateId) -> Option<Vec<u8>>;
+
     /// Eagerly notifies all tasks that were scheduled for notifications via
     /// `schedule_notify_tasks_set()`
     fn notify_scheduled_tasks(&self);
@@ -110,6 +119,8 @@ pub trait TurboTasksApi: TurboTasksCallApi + Sync + Send {
 
     fn read_current_task_cell(&self, index: CellId) -> Result<CellContent>;
     fn update_current_task_cell(&self, index: CellId, content: CellContent);
+
+    fn mark_own_task_as_external_input(&self);
 }
 
 /// The type of stats reporting.
@@ -229,6 +240,8 @@ task_local! {
 
     static CURRENT_TASK_ID: TaskId;
 
+    static STATE_COUNTER: Cell<u32>;
+
     /// Affected [Task]s, that are tracked during task execution
     /// These tasks will be invalidated when the execution finishes
     /// or before reading a cell value
@@ -389,12 +402,16 @@ impl<B: Backend> TurboTasks<B> {
                 if let Some(execution) = this.backend.try_start_task_execution(task_id, &*this) {
                     // Setup thread locals
                     let (result, duration, instant) = CELL_COUNTERS
-                        .scope(Default::default(), async {
-                            let (result, duration, instant) =
-                                TimedFuture::new(AssertUnwindSafe(execution.future).catch_unwind())
-                                    .await;
-                            (result, duration, instant)
-                        })
+                        .scope(
+                            Default::default(),
+                            STATE_COUNTER.scope(Default::default(), async {
+                                let (result, duration, instant) = TimedFuture::new(
+                                    AssertUnwindSafe(execution.future).catch_unwind(),
+                                )
+                                .await;
+                                (result, duration, instant)
+                            }),
+                        )
                         .await;
                     if cfg!(feature = "log_function_stats") && duration.as_millis() > 1000 {
                         println!(
@@ -732,6 +749,15 @@ impl<B: Backend> TurboTasksApi for TurboTasks<B> {
         self.backend.invalidate_task(task, self);
     }
 
+    fn invalidate_state_serialization(&self, state: StateId, value: Weak<dyn PersistableState>) {
+        self.backend
+            .invalidate_state_serialization(state, value, self);
+    }
+
+    fn read_persisted_state(&self, state: StateId) -> Option<Vec<u8>> {
+        self.backend.read_persisted_state(state, self)
+    }
+
     fn notify_scheduled_tasks(&self) {
         let _ = TASKS_TO_NOTIFY.try_with(|tasks| {
             let tasks = tasks.take();
@@ -845,6 +871,11 @@ impl<B: Backend> TurboTasksApi for TurboTasks<B> {
             self,
         );
     }
+
+    fn mark_own_task_as_external_input(&self) {
+        self.backend
+            .mark_task_as_external_input(current_task("turbo_tasks::mark_external_input()"), self);
+    }
 }
 
 impl<B: Backend> TurboTasksBackendApi for TurboTasks<B> {
@@ -1084,7 +1115,13 @@ pub fn with_turbo_tasks_for_testing<T>(
 ) -> impl Future<Output = T> {
     TURBO_TASKS.scope(
         tt,
-        CURRENT_TASK_ID.scope(current_task, CELL_COUNTERS.scope(Default::default(), f)),
+        CURRENT_TASK_ID.scope(
+            current_task,
+            CELL_COUNTERS.scope(
+                Default::default(),
+                STATE_COUNTER.scope(Default::default(), f),
+            ),
+        ),
     )
 }
 
@@ -1103,6 +1140,24 @@ pub fn get_invalidator() -> Invalidator {
     }
 }
 
+/// Get the id for a new [crate::State] of the current [Task].
+pub(crate) fn next_state_id() -> StateId {
+    let task = current_task("turbo_tasks::State::new()");
+    let index = STATE_COUNTER.with(|counter| {
+        let index = counter.get();
+        counter.set(index + 1);
+        index
+    });
+    StateId { task, index }
+}
+
+/// Marks the current task as reading external state that is not tracked by
+/// turbo-tasks, e. g. files or env vars. Such tasks are invalidated when the
+/// task graph is restored from a persistent cache.
+pub fn mark_external_input() {
+    with_turbo_tasks(|tt| tt.mark_own_task_as_external_input());
+}
+
 /// Marks the current task as stateful. This prevents the tasks from being
 /// dropped without persisting the state.
 pub fn mark_stateful() {
@@ -1,9 +1,11 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     backend::{CellContent, PersistentTaskType},
-    CellId, RawVc, TaskId,
+    CellId, PersistableState, RawVc, StateId, TaskId,
 };
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -32,6 +34,9 @@ pub struct ReadTaskState {
 
 pub struct PersistTaskState {
     pub externally_active: bool,
+    /// The task read external state (files, env vars, ...) in its last
+    /// execution, see [crate::mark_external_input].
+    pub external_input: bool,
 }
 
 /*
@@ -130,6 +135,17 @@ pub trait PersistedGraph: Sync + Send {
         api: &dyn PersistedGraphApi,
     ) -> Result<Option<PersistResult>>;
 
+    /// store the values of states, which are stored separately from the task
+    /// that created them, see [crate::State]
+    fn persist_states(
+        &self,
+        states: Vec<(StateId, Arc<dyn PersistableState>)>,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<()>;
+
+    /// read the stored value of a state
+    fn read_state(&self, state: StateId, api: &dyn PersistedGraphApi) -> Result<Option<Vec<u8>>>;
+
     /// Activate a task in the persisted graph when active_parents > 0 or it's
     /// externally kept alive.
     fn activate_when_needed(
@@ -173,6 +189,13 @@ pub trait PersistedGraph: Sync + Send {
     /// return a list of active tasks that should be scheduled
     fn make_dependent_dirty(&self, vc: RawVc, api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>>;
 
+    /// make all tasks that were persisted with
+    /// [PersistTaskState::external_input] dirty, since the state might
+    /// have changed while the graph wasn't in use.
+    /// This is usually called at the beginning before the dirty active tasks
+    /// are collected.
+    fn make_external_inputs_dirty(&self, api: &dyn PersistedGraphApi) -> Result<()>;
+
     /// Get all tasks that are active, but not persisted.
     /// This is usually called at beginning to create and schedule
     /// tasks that are missing in the persisted graph
@@ -266,6 +289,18 @@ impl PersistedGraph for () {
         Ok(None)
     }
 
+    fn persist_states(
+        &self,
+        _states: Vec<(StateId, Arc<dyn PersistableState>)>,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn read_state(&self, _state: StateId, _api: &dyn PersistedGraphApi) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
     fn activate_when_needed(
         &self,
         _task: TaskId,
@@ -313,6 +348,10 @@ impl PersistedGraph for () {
         Ok(Vec::new())
     }
 
+    fn make_external_inputs_dirty(&self, _api: &dyn PersistedGraphApi) -> Result<()> {
+        Ok(())
+    }
+
     fn get_active_external_tasks(&self, _api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
         Ok(Vec::new())
     }
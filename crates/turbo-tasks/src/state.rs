@@ -1,13 +1,40 @@
-use std::{fmt::Debug, mem::take};
+use std::{fmt::Debug, mem::take, sync::Arc};
 
+use anyhow::Result;
 use auto_hash_map::AutoSet;
 use parking_lot::{Mutex, MutexGuard};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    get_invalidator, manager::next_state_id, mark_stateful, trace::TraceRawVcs, turbo_tasks,
+    Invalidator, TaskId,
+};
+
+/// Identifies a [State] by the task that created it and the order in which
+/// the task created its states. It stays the same when the task is executed
+/// again.
+#[derive(Hash, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct StateId {
+    pub task: TaskId,
+    pub index: u32,
+}
 
-use crate::{get_invalidator, mark_stateful, trace::TraceRawVcs, Invalidator};
+/// A [State] as seen by the backend, which stores it separately from the task
+/// that created it.
+pub trait PersistableState: Send + Sync {
+    /// Serializes the value of the state together with the tasks that depend
+    /// on it.
+    fn serialize_state(&self) -> Result<Vec<u8>>;
+}
 
+/// A value that can be changed from outside of the task graph.
+///
+/// The task that creates the state only serializes the [StateId], the value
+/// is stored by the backend. So changing the value or reading it from a new
+/// task doesn't affect the serialization of the creating task.
 pub struct State<T> {
-    inner: Mutex<StateInner<T>>,
+    id: StateId,
+    inner: Arc<Mutex<StateInner<T>>>,
 }
 
 struct StateInner<T> {
@@ -33,7 +60,7 @@ impl<T: TraceRawVcs> TraceRawVcs for State<T> {
     }
 }
 
-impl<T: Default> Default for State<T> {
+impl<T: Default + Serialize + Send + 'static> Default for State<T> {
     fn default() -> Self {
         // Need to be explicit to ensure marking as stateful.
         Self::new(Default::default())
@@ -47,19 +74,36 @@ impl<T> PartialEq for State<T> {
 }
 impl<T> Eq for State<T> {}
 
-impl<T: Serialize> Serialize for State<T> {
-    fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
-        // For this to work at all we need to do more. Changing the the state need to
-        // invalidate the serialization of the task that contains the state. So we
-        // probably need to store the state outside of the task to be able to serialize
-        // it independent from the creating task.
-        panic!("State serialization is not implemented yet");
+impl<T> Serialize for State<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.id.serialize(serializer)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for State<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = StateId::deserialize(deserializer)?;
+        let data = turbo_tasks().read_persisted_state(id).ok_or_else(|| {
+            serde::de::Error::custom(format!("the value of {id:?} hasn't been persisted"))
+        })?;
+        // The invalidators of the dependent tasks are stored too, so restored tasks
+        // are still invalidated when the state changes.
+        let (value, invalidators) =
+            serde_json::from_slice(&data).map_err(serde::de::Error::custom)?;
+        Ok(Self {
+            id,
+            inner: Arc::new(Mutex::new(StateInner {
+                value,
+                invalidators,
+            })),
+        })
     }
 }
 
-impl<'de, T: Deserialize<'de>> Deserialize<'de> for State<T> {
-    fn deserialize<D: Deserializer<'de>>(_deserializer: D) -> Result<Self, D::Error> {
-        panic!("State serialization is not implemented yet");
+impl<T: Serialize + Send> PersistableState for Mutex<StateInner<T>> {
+    fn serialize_state(&self) -> Result<Vec<u8>> {
+        let inner = self.lock();
+        Ok(serde_json::to_vec(&(&inner.value, &inner.invalidators))?)
     }
 }
 
@@ -72,15 +116,18 @@ impl<T> Drop for State<T> {
     }
 }
 
-impl<T> State<T> {
+impl<T: Serialize + Send + 'static> State<T> {
     pub fn new(value: T) -> Self {
         mark_stateful();
-        Self {
-            inner: Mutex::new(StateInner {
+        let state = Self {
+            id: next_state_id(),
+            inner: Arc::new(Mutex::new(StateInner {
                 value,
                 invalidators: AutoSet::new(),
-            }),
-        }
+            })),
+        };
+        state.invalidate_serialization();
+        state
     }
 
     /// Gets the current value of the state. The current task will be registered
@@ -89,7 +136,12 @@ impl<T> State<T> {
     pub fn get(&self) -> StateRef<'_, T> {
         let invalidator = get_invalidator();
         let mut inner = self.inner.lock();
-        inner.invalidators.insert(invalidator);
+        if inner.invalidators.insert(invalidator) {
+            // Only the stored state needs to be updated, not the creating task
+            drop(inner);
+            self.invalidate_serialization();
+            inner = self.inner.lock();
+        }
         StateRef { inner }
     }
 
@@ -101,6 +153,8 @@ impl<T> State<T> {
         for invalidator in take(&mut inner.invalidators) {
             invalidator.invalidate();
         }
+        drop(inner);
+        self.invalidate_serialization();
     }
 
     /// Updates the current state with the `update` function. The `update`
@@ -115,10 +169,17 @@ impl<T> State<T> {
         for invalidator in take(&mut inner.invalidators) {
             invalidator.invalidate();
         }
+        drop(inner);
+        self.invalidate_serialization();
+    }
+
+    fn invalidate_serialization(&self) {
+        let inner: Arc<dyn PersistableState> = self.inner.clone();
+        turbo_tasks().invalidate_state_serialization(self.id, Arc::downgrade(&inner));
     }
 }
 
-impl<T: PartialEq> State<T> {
+impl<T: PartialEq + Serialize + Send + 'static> State<T> {
     /// Update the current state when the `value` is different from the current
     /// value. `T` must implement [PartialEq] for this to work.
     pub fn set(&self, value: T) {
@@ -130,6 +191,8 @@ impl<T: PartialEq> State<T> {
         for invalidator in take(&mut inner.invalidators) {
             invalidator.invalidate();
         }
+        drop(inner);
+        self.invalidate_serialization();
     }
 }
 
@@ -417,6 +417,8 @@ impl ConsoleUi {
 #[turbo_tasks::value(transparent)]
 pub struct DisplayIssueState {
     pub has_fatal: bool,
+    /// Whether any of the issues is an error or worse.
+    pub has_errors: bool,
     pub has_issues: bool,
     pub has_new_issues: bool,
 }
@@ -460,6 +462,7 @@ impl ConsoleUiVc {
         let mut new_ids = this.seen.lock().unwrap().new_ids(source, issue_ids);
 
         let mut has_fatal = false;
+        let mut has_errors = false;
         let has_issues = !issues.is_empty();
         let has_new_issues = !new_ids.is_empty();
 
@@ -472,7 +475,8 @@ impl ConsoleUiVc {
             let context_path = make_relative_to_cwd(context, current_dir).await?;
             let category = &plain_issue.category;
             let title = &plain_issue.title;
-            has_fatal = severity == IssueSeverity::Fatal;
+            has_fatal |= severity == IssueSeverity::Fatal;
+            has_errors |= severity <= IssueSeverity::Error;
             let severity_map = grouped_issues
                 .entry(severity)
                 .or_insert_with(Default::default);
@@ -607,6 +611,7 @@ impl ConsoleUiVc {
 
         Ok(DisplayIssueState {
             has_fatal,
+            has_errors,
             has_issues,
             has_new_issues,
         }
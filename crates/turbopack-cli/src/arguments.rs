@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "cli")]
+use clap::{Args, Parser};
+use turbopack_cli_utils::issue::IssueSeverityCliOption;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "cli", derive(Parser))]
+#[cfg_attr(feature = "cli", clap(author, version, about, long_about = None))]
+pub enum Arguments {
+    /// Builds the given entries once for production and writes the result
+    /// into the output directory.
+    Build(BuildArguments),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "cli", derive(Args))]
+pub struct BuildArguments {
+    /// The entrypoints of the project, relative to `dir`.
+    #[cfg_attr(feature = "cli", clap(value_parser, required = true))]
+    pub entries: Vec<String>,
+
+    /// The directory of the application.
+    /// If no directory is provided, the current directory will be used.
+    #[cfg_attr(feature = "cli", clap(long, value_parser))]
+    pub dir: Option<PathBuf>,
+
+    /// The root directory of the project. Nothing outside of this directory can
+    /// be accessed. e. g. the monorepo root.
+    /// If no directory is provided, `dir` will be used.
+    #[cfg_attr(feature = "cli", clap(long, value_parser))]
+    pub root: Option<PathBuf>,
+
+    /// The directory the build output is written to, relative to `dir`.
+    #[cfg_attr(
+        feature = "cli",
+        clap(short, long, value_parser, default_value = "dist")
+    )]
+    pub output_directory: PathBuf,
+
+    /// The browserslist query the output should be compiled for.
+    #[cfg_attr(feature = "cli", clap(long, value_parser))]
+    pub browserslist: Option<String>,
+
+    /// Replaces an expression with code at compile time, e. g.
+    /// `--define __DEV__=false` or `--define 'process.env.API="/api"'`.
+    /// `process.env.NODE_ENV` is defined as `"production"` unless it's
+    /// defined explicitly.
+    #[cfg_attr(feature = "cli", clap(long, value_parser, value_name = "KEY=VALUE"))]
+    pub define: Vec<String>,
+
+    /// Don't minify the generated code.
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub no_minify: bool,
+
+    /// Don't remove unused exports.
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub no_tree_shaking: bool,
+
+    /// Don't concatenate modules into the scope of their importer.
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub no_scope_hoisting: bool,
+
+    /// Filter by issue severity.
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub log_level: Option<IssueSeverityCliOption>,
+
+    /// Show all log messages without limit.
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub show_all: bool,
+
+    /// Expand the log details.
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub log_detail: bool,
+}
@@ -0,0 +1,344 @@
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use indexmap::IndexMap;
+use turbo_tasks::{
+    primitives::StringsVc, util::FormatDuration, NothingVc, TransientInstance, TransientValue,
+    TryJoinIterExt, TurboTasks, Value,
+};
+use turbo_tasks_fs::{util::sys_to_unix, DiskFileSystemVc, FileSystemPathVc, FileSystemVc};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{
+    condition::ContextCondition,
+    ecmascript::{CompileTimeDefinesVc, EcmascriptModuleAssetVc},
+    emit_with_completion,
+    module_options::ModuleOptionsContext,
+    resolve_options_context::ResolveOptionsContext,
+    transition::TransitionsByNameVc,
+    ModuleAssetContextVc,
+};
+use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
+use turbopack_core::{
+    asset::AssetsVc,
+    chunk::{prod::ProdChunkingContextVc, ChunkVc, ChunkableAssetVc, ChunksVc},
+    context::AssetContextVc,
+    environment::{BrowserEnvironment, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment},
+    issue::IssueSeverity,
+    reference::all_assets,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    source_asset::SourceAssetVc,
+};
+
+use crate::{arguments::BuildArguments, register};
+
+const DEFAULT_BROWSERSLIST_QUERY: &str =
+    "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari versions, last 1 Edge versions";
+
+/// Resolved options of a single build.
+#[derive(Debug)]
+struct BuildOptions {
+    /// The root directory of the project filesystem.
+    root_dir: String,
+    /// The application directory, relative to `root_dir`.
+    project_dir: String,
+    /// The directory the output is written to.
+    output_dir: String,
+    entries: Vec<String>,
+    browserslist_query: String,
+    /// Expressions which are replaced with code at compile time.
+    defines: IndexMap<String, String>,
+    minify: bool,
+    tree_shaking: bool,
+    scope_hoisting: bool,
+}
+
+impl BuildOptions {
+    fn from_arguments(args: &BuildArguments) -> Result<Self> {
+        let dir = match &args.dir {
+            Some(dir) => canonicalize(dir)?,
+            None => current_dir()?,
+        };
+        let root = match &args.root {
+            Some(root) => canonicalize(root)?,
+            None => dir.clone(),
+        };
+        let project_dir = dir.strip_prefix(&root).map_err(|_| {
+            anyhow!(
+                "the directory {} must be inside of the root directory {}",
+                dir.display(),
+                root.display()
+            )
+        })?;
+        let output_dir = dir.join(&args.output_directory);
+        std::fs::create_dir_all(&output_dir).with_context(|| {
+            format!("failed to create output directory {}", output_dir.display())
+        })?;
+
+        let mut defines = IndexMap::new();
+        for define in args.define.iter() {
+            let (key, value) = define
+                .split_once('=')
+                .with_context(|| format!("define {define} must have the form KEY=VALUE"))?;
+            defines.insert(key.trim().to_string(), value.to_string());
+        }
+        defines
+            .entry("process.env.NODE_ENV".to_string())
+            .or_insert_with(|| "\"production\"".to_string());
+
+        Ok(BuildOptions {
+            root_dir: path_to_string(&root)?,
+            project_dir: sys_to_unix(&path_to_string(project_dir)?).into_owned(),
+            output_dir: path_to_string(&canonicalize(&output_dir)?)?,
+            entries: args.entries.clone(),
+            browserslist_query: args
+                .browserslist
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BROWSERSLIST_QUERY.to_string()),
+            defines,
+            minify: !args.no_minify,
+            tree_shaking: !args.no_tree_shaking,
+            scope_hoisting: !args.no_scope_hoisting,
+        })
+    }
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf> {
+    path.canonicalize()
+        .with_context(|| format!("failed to resolve directory {}", path.display()))
+}
+
+fn path_to_string(path: &Path) -> Result<String> {
+    path.to_str()
+        .map(|path| path.to_string())
+        .with_context(|| format!("path {} is not valid unicode", path.display()))
+}
+
+/// Runs a single production build of the given entries. Issues are printed to
+/// the console, and an error is returned when any of them is an error or worse.
+pub async fn build(args: &BuildArguments) -> Result<()> {
+    register();
+
+    let start = Instant::now();
+
+    let options = Arc::new(BuildOptions::from_arguments(args)?);
+    let console_ui = Arc::new(ConsoleUi::new(LogOptions {
+        current_dir: current_dir()?,
+        show_all: args.show_all,
+        log_detail: args.log_detail,
+        log_level: args
+            .log_level
+            .map_or_else(|| IssueSeverity::Warning, |l| l.0),
+    }));
+
+    let tt = TurboTasks::new(MemoryBackend::new());
+    let task = tt.spawn_root_task(move || {
+        let options = options.clone();
+        let console_ui = console_ui.clone();
+        Box::pin(async move {
+            // Issues are checked before anything is written, so a failed build
+            // doesn't leave partial output behind.
+            let chunks = compile_operation(options.clone().into());
+
+            let console_ui = (*console_ui).clone().cell();
+            let state = console_ui
+                .group_and_display_issues(TransientValue::new(chunks.into()))
+                .await?;
+            if state.has_errors {
+                bail!("build failed because of the errors above");
+            }
+
+            let output = emit_operation(options.clone().into(), chunks);
+            for (entry, chunk_path) in options.entries.iter().zip(output.await?.iter()) {
+                println!("{entry} -> {chunk_path}");
+            }
+            Ok(NothingVc::new().into())
+        })
+    });
+    tt.wait_task_completion(task, true).await?;
+
+    println!("done in {}", FormatDuration(start.elapsed()));
+    Ok(())
+}
+
+fn output_root(options: &BuildOptions) -> FileSystemPathVc {
+    let output_fs: FileSystemVc =
+        DiskFileSystemVc::new("output".to_string(), options.output_dir.clone()).into();
+    output_fs.root()
+}
+
+/// Builds the entry chunks of all entries, in the order of the entries, and
+/// computes the content of everything that would be emitted without writing
+/// it, so all issues of the build are reported.
+#[turbo_tasks::function]
+async fn compile_operation(options: TransientInstance<BuildOptions>) -> Result<ChunksVc> {
+    let project_fs: FileSystemVc =
+        DiskFileSystemVc::new("project".to_string(), options.root_dir.clone()).into();
+    let project_path = project_fs.root().join(&options.project_dir);
+    let output_root = output_root(&options);
+
+    let env = EnvironmentVc::new(
+        Value::new(ExecutionEnvironment::Browser(
+            BrowserEnvironment {
+                dom: true,
+                web_worker: false,
+                service_worker: false,
+                browserslist_query: options.browserslist_query.clone(),
+            }
+            .into(),
+        )),
+        Value::new(EnvironmentIntention::Client),
+    );
+    let defines = CompileTimeDefinesVc::cell(options.defines.clone());
+    let context = get_context(env, defines);
+    let modules = options
+        .entries
+        .iter()
+        .map(|entry| {
+            context.process(
+                SourceAssetVc::new(project_path.join(entry)).into(),
+                Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut chunking_context =
+        ProdChunkingContextVc::builder(output_root, output_root, output_root.join("assets"), env)
+            .minify(options.minify);
+    let entries = AssetsVc::cell(modules.clone());
+    if options.tree_shaking {
+        chunking_context = chunking_context.tree_shaking_entries(entries);
+    }
+    if options.scope_hoisting {
+        chunking_context = chunking_context.scope_hoisting_entries(entries);
+    }
+    let chunking_context = chunking_context.build();
+
+    let chunks = options
+        .entries
+        .iter()
+        .zip(modules)
+        .map(|(entry, module)| async move {
+            let chunk: ChunkVc =
+                if let Some(ecmascript) = EcmascriptModuleAssetVc::resolve_from(module).await? {
+                    ecmascript.as_evaluated_chunk(chunking_context, None)
+                } else if let Some(chunkable) = ChunkableAssetVc::resolve_from(module).await? {
+                    chunkable.as_chunk(chunking_context)
+                } else {
+                    bail!("entry {entry} is not chunkable, so it can't be built");
+                };
+            Ok(chunk)
+        })
+        .try_join()
+        .await?;
+
+    // An entry chunk references everything that is emitted with it, e.g. other
+    // chunks of its chunk group, source maps and static assets.
+    let output_dir = &*output_root.await?;
+    let assets = chunks
+        .iter()
+        .map(|chunk| all_assets(chunk.as_asset()))
+        .try_join()
+        .await?;
+    assets
+        .iter()
+        .flat_map(|assets| assets.iter())
+        .map(|asset| async move {
+            if asset.path().await?.is_inside(output_dir) {
+                asset.content().await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        })
+        .try_join()
+        .await?;
+
+    Ok(ChunksVc::cell(chunks))
+}
+
+/// Emits the chunks of [compile_operation]. Returns the path of each entry
+/// chunk relative to the output directory, in the order of the entries.
+#[turbo_tasks::function]
+async fn emit_operation(
+    options: TransientInstance<BuildOptions>,
+    chunks: ChunksVc,
+) -> Result<StringsVc> {
+    let output_root = output_root(&options);
+    let chunks = chunks.await?;
+
+    // Emitting an entry chunk also emits everything it references.
+    let emits = chunks
+        .iter()
+        .map(|chunk| emit_with_completion(chunk.as_asset(), output_root))
+        .collect::<Vec<_>>();
+    // Wait for all files to be emitted
+    for emit in emits {
+        emit.await?;
+    }
+
+    let output_root = &*output_root.await?;
+    let chunk_paths = chunks
+        .iter()
+        .map(|chunk| async move {
+            let path = chunk.as_asset().path().await?;
+            Ok(output_root
+                .get_path_to(&path)
+                .map(|path| path.to_string())
+                .unwrap_or_else(|| path.path.clone()))
+        })
+        .try_join()
+        .await?;
+
+    Ok(StringsVc::cell(chunk_paths))
+}
+
+#[turbo_tasks::function]
+fn get_context(env: EnvironmentVc, defines: CompileTimeDefinesVc) -> AssetContextVc {
+    ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        env,
+        ModuleOptionsContext {
+            enable_jsx: true,
+            enable_typescript_transform: true,
+            preset_env_versions: Some(env),
+            compile_time_defines: Some(defines),
+            rules: vec![(
+                ContextCondition::InDirectory("node_modules".to_string()),
+                ModuleOptionsContext {
+                    preset_env_versions: Some(env),
+                    compile_time_defines: Some(defines),
+                    ..Default::default()
+                }
+                .cell(),
+            )],
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext {
+            enable_typescript: true,
+            enable_react: true,
+            enable_node_modules: true,
+            custom_conditions: vec!["production".to_string()],
+            browser: true,
+            module: true,
+            rules: vec![(
+                ContextCondition::InDirectory("node_modules".to_string()),
+                ResolveOptionsContext {
+                    enable_node_modules: true,
+                    custom_conditions: vec!["production".to_string()],
+                    browser: true,
+                    module: true,
+                    ..Default::default()
+                }
+                .cell(),
+            )],
+            ..Default::default()
+        }
+        .cell(),
+    )
+    .into()
+}
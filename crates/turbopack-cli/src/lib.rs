@@ -0,0 +1,10 @@
+#![feature(min_specialization)]
+
+pub mod arguments;
+pub mod build;
+
+pub fn register() {
+    turbopack::register();
+    turbopack_cli_utils::register();
+    include!(concat!(env!("OUT_DIR"), "/register.rs"));
+}
@@ -0,0 +1,39 @@
+#[cfg(not(feature = "cli"))]
+use anyhow::bail;
+use anyhow::Result;
+#[cfg(feature = "cli")]
+use clap::Parser;
+
+#[global_allocator]
+static ALLOC: turbo_malloc::TurboMalloc = turbo_malloc::TurboMalloc;
+
+#[cfg(not(feature = "cli"))]
+fn main() -> Result<()> {
+    bail!("the turbopack binary can't be run without the `cli` feature enabled");
+}
+
+#[cfg(feature = "cli")]
+fn main() -> Result<()> {
+    use turbo_malloc::TurboMalloc;
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .on_thread_stop(|| {
+            TurboMalloc::thread_stop();
+        })
+        .build()
+        .unwrap()
+        .block_on(main_inner())
+}
+
+#[cfg(feature = "cli")]
+async fn main_inner() -> Result<()> {
+    use turbopack_cli::arguments::Arguments;
+
+    #[cfg(feature = "tokio_console")]
+    console_subscriber::init();
+
+    match Arguments::parse() {
+        Arguments::Build(args) => turbopack_cli::build::build(&args).await,
+    }
+}
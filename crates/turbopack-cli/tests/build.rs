@@ -0,0 +1,184 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Result};
+use tempfile::tempdir;
+use turbopack_cli::{arguments::BuildArguments, build::build};
+
+fn arguments(dir: &Path, output_directory: &Path) -> BuildArguments {
+    BuildArguments {
+        entries: vec!["index.js".to_string()],
+        dir: Some(dir.to_path_buf()),
+        root: None,
+        output_directory: output_directory.to_path_buf(),
+        browserslist: None,
+        define: vec![],
+        no_minify: true,
+        no_tree_shaking: false,
+        no_scope_hoisting: false,
+        log_level: None,
+        show_all: false,
+        log_detail: false,
+    }
+}
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Reads the contents of all JavaScript chunks in the output directory.
+fn read_chunks(output_directory: &Path) -> Result<Vec<String>> {
+    Ok(fs::read_dir(output_directory)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| path.extension().map_or(false, |ext| ext == "js"))
+        .map(fs::read_to_string)
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Returns the file name of the manifest chunk in the output directory, which
+/// lists the chunks of a dynamically imported chunk group.
+fn manifest_chunk_name(output_directory: &Path) -> Result<String> {
+    for entry in fs::read_dir(output_directory)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "js")
+            && fs::read_to_string(&path)?.contains("const chunks = [")
+        {
+            return Ok(path.file_name().unwrap().to_string_lossy().into_owned());
+        }
+    }
+    bail!("no manifest chunk was emitted")
+}
+
+#[tokio::test]
+async fn builds_entry_into_output_directory() -> Result<()> {
+    let output = tempdir()?;
+    build(&arguments(&fixture("basic"), output.path())).await?;
+
+    let chunks = read_chunks(output.path())?;
+    assert!(!chunks.is_empty(), "no chunks were emitted");
+    assert!(
+        chunks
+            .iter()
+            .any(|chunk| chunk.contains("hello from turbopack")),
+        "the imported module is not part of the output"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejects_directory_outside_of_root() -> Result<()> {
+    let output = tempdir()?;
+    let mut args = arguments(&fixture("basic"), output.path());
+    args.root = Some(fixture("basic").join("missing"));
+    assert!(build(&args).await.is_err());
+
+    let other_root = tempdir()?;
+    args.root = Some(other_root.path().to_path_buf());
+    let err = build(&args).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("must be inside of the root directory"),
+        "unexpected error: {err}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn fails_on_unresolvable_import() -> Result<()> {
+    let output = tempdir()?;
+    let err = build(&arguments(&fixture("unresolvable"), output.path()))
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("build failed because of the errors above"),
+        "unexpected error: {err}"
+    );
+    assert!(
+        fs::read_dir(output.path())?.next().is_none(),
+        "the failed build wrote into the output directory"
+    );
+
+    let result = Command::new(env!("CARGO_BIN_EXE_turbopack"))
+        .arg("build")
+        .arg("index.js")
+        .arg("--dir")
+        .arg(fixture("unresolvable"))
+        .arg("--output-directory")
+        .arg(output.path())
+        .output()?;
+    assert!(!result.status.success(), "the build didn't fail");
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(
+        !stdout.contains("index.js ->"),
+        "the entry chunk of a failed build is printed: {stdout}"
+    );
+    assert!(
+        fs::read_dir(output.path())?.next().is_none(),
+        "the failed build wrote into the output directory"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn replaces_defines_and_removes_dead_branches() -> Result<()> {
+    let output = tempdir()?;
+    let mut args = arguments(&fixture("defines"), output.path());
+    args.define = vec!["__GREETING__=\"hello from a define\"".to_string()];
+    build(&args).await?;
+
+    let chunks = read_chunks(output.path())?;
+    assert!(
+        chunks
+            .iter()
+            .any(|chunk| chunk.contains("hello from a define")),
+        "the define is not replaced"
+    );
+    assert!(
+        !chunks
+            .iter()
+            .any(|chunk| chunk.contains("only in development")),
+        "the module required in a dead branch is part of the output"
+    );
+
+    args.define = vec!["__GREETING__".to_string()];
+    let err = build(&args).await.unwrap_err();
+    assert!(
+        err.to_string().contains("must have the form KEY=VALUE"),
+        "unexpected error: {err}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn changes_manifest_path_with_imported_content() -> Result<()> {
+    let dir = tempdir()?;
+    for file in ["index.js", "lazy.js"] {
+        fs::copy(fixture("async").join(file), dir.path().join(file))?;
+    }
+
+    let output = tempdir()?;
+    build(&arguments(dir.path(), output.path())).await?;
+    let manifest = manifest_chunk_name(output.path())?;
+
+    fs::write(
+        dir.path().join("lazy.js"),
+        "export const message = \"hello from a changed lazy module\";\n",
+    )?;
+    let changed_output = tempdir()?;
+    build(&arguments(dir.path(), changed_output.path())).await?;
+    let changed_manifest = manifest_chunk_name(changed_output.path())?;
+
+    assert_ne!(
+        manifest, changed_manifest,
+        "the manifest chunk path doesn't change with the content of the imported module"
+    );
+    Ok(())
+}
@@ -0,0 +1,3 @@
+import("./lazy").then(({ message }) => {
+  console.log(message);
+});
@@ -0,0 +1 @@
+export const message = "hello from a lazy module";
@@ -0,0 +1 @@
+console.log("only in development");
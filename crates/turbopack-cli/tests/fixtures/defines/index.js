@@ -0,0 +1,5 @@
+if (process.env.NODE_ENV !== "production") {
+  require("./development");
+}
+
+console.log(__GREETING__);
@@ -0,0 +1,3 @@
+import { message } from "./missing";
+
+console.log(message);
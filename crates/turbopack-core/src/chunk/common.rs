@@ -0,0 +1,59 @@
+//! Logic shared between the development and the production chunking contexts.
+
+use anyhow::Result;
+use turbo_tasks_fs::FileSystemPathVc;
+
+use crate::asset::AssetVc;
+
+/// Converts a layer name to its stored form, where the empty layer is `None`.
+pub(super) fn layer_name(layer: &str) -> Option<String> {
+    (!layer.is_empty()).then(|| layer.to_string())
+}
+
+/// Returns the directory chunks with the given extension are placed in.
+pub(super) fn chunk_root_path(
+    chunk_root_path: FileSystemPathVc,
+    css_chunk_root_path: Option<FileSystemPathVc>,
+    layer: Option<&str>,
+    extension: &str,
+) -> FileSystemPathVc {
+    let mut root_path = chunk_root_path;
+    #[allow(clippy::single_match, reason = "future extensions")]
+    match extension {
+        ".css" => {
+            if let Some(path) = css_chunk_root_path {
+                root_path = path;
+            }
+        }
+        _ => {}
+    }
+    if let Some(layer) = layer {
+        root_path.join(layer)
+    } else {
+        root_path
+    }
+}
+
+/// Assets can be in the same chunk when `asset_b` is within the directory of
+/// `asset_a` and not inside of a `node_modules` directory.
+pub(super) async fn can_be_in_same_chunk(asset_a: AssetVc, asset_b: AssetVc) -> Result<bool> {
+    let parent_dir = asset_a.path().parent().await?;
+
+    let path = asset_b.path().await?;
+    if let Some(rel_path) = parent_dir.get_path_to(&path) {
+        if !rel_path.starts_with("node_modules/") && !rel_path.contains("/node_modules/") {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Returns the path of a static asset with the given content hash.
+pub(super) fn asset_path(
+    asset_root_path: FileSystemPathVc,
+    content_hash: &str,
+    extension: &str,
+) -> FileSystemPathVc {
+    asset_root_path.join(&format!("{content_hash}.{extension}"))
+}
@@ -6,7 +6,7 @@ use turbo_tasks::{
 use turbo_tasks_fs::FileSystemPathVc;
 use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
 
-use super::{ChunkingContext, ChunkingContextVc};
+use super::{common, ChunkingContext, ChunkingContextVc};
 use crate::{asset::AssetVc, environment::EnvironmentVc};
 
 pub struct DevChunkingContextBuilder {
@@ -20,7 +20,7 @@ impl DevChunkingContextBuilder {
     }
 
     pub fn layer(mut self, layer: &str) -> Self {
-        self.context.layer = (!layer.is_empty()).then(|| layer.to_string());
+        self.context.layer = common::layer_name(layer);
         self
     }
 
@@ -148,42 +148,25 @@ impl ChunkingContext for DevChunkingContext {
         if !name.ends_with(extension) {
             name += extension;
         }
-        let mut root_path = self.chunk_root_path;
-        #[allow(clippy::single_match, reason = "future extensions")]
-        match extension {
-            ".css" => {
-                if let Some(path) = self.css_chunk_root_path {
-                    root_path = path;
-                }
-            }
-            _ => {}
-        }
-        let root_path = if let Some(layer) = self.layer.as_deref() {
-            root_path.join(layer)
-        } else {
-            root_path
-        };
+        let root_path = common::chunk_root_path(
+            self.chunk_root_path,
+            self.css_chunk_root_path,
+            self.layer.as_deref(),
+            extension,
+        );
         Ok(root_path.join(&name))
     }
 
     #[turbo_tasks::function]
     async fn can_be_in_same_chunk(&self, asset_a: AssetVc, asset_b: AssetVc) -> Result<BoolVc> {
-        let parent_dir = asset_a.path().parent().await?;
-
-        let path = asset_b.path().await?;
-        if let Some(rel_path) = parent_dir.get_path_to(&path) {
-            if !rel_path.starts_with("node_modules/") && !rel_path.contains("/node_modules/") {
-                return Ok(BoolVc::cell(true));
-            }
-        }
-
-        Ok(BoolVc::cell(false))
+        Ok(BoolVc::cell(
+            common::can_be_in_same_chunk(asset_a, asset_b).await?,
+        ))
     }
 
     #[turbo_tasks::function]
     fn asset_path(&self, content_hash: &str, extension: &str) -> FileSystemPathVc {
-        self.asset_root_path
-            .join(&format!("{content_hash}.{extension}"))
+        common::asset_path(self.asset_root_path, content_hash, extension)
     }
 
     #[turbo_tasks::function]
@@ -199,7 +182,17 @@ impl ChunkingContext for DevChunkingContext {
     #[turbo_tasks::function]
     async fn with_layer(self_vc: DevChunkingContextVc, layer: &str) -> Result<ChunkingContextVc> {
         let mut context = self_vc.await?.clone_value();
-        context.layer = (!layer.is_empty()).then(|| layer.to_string());
+        context.layer = common::layer_name(layer);
+        Ok(DevChunkingContextVc::new(Value::new(context)).into())
+    }
+
+    #[turbo_tasks::function]
+    async fn with_environment(
+        self_vc: DevChunkingContextVc,
+        environment: EnvironmentVc,
+    ) -> Result<ChunkingContextVc> {
+        let mut context = self_vc.await?.clone_value();
+        context.environment = environment;
         Ok(DevChunkingContextVc::new(Value::new(context)).into())
     }
 }
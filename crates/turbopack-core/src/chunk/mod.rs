@@ -1,5 +1,7 @@
+mod common;
 pub mod dev;
 pub mod optimize;
+pub mod prod;
 
 use std::{collections::VecDeque, fmt::Debug};
 
@@ -62,11 +64,44 @@ pub trait ChunkingContext {
         BoolVc::cell(false)
     }
 
+    /// Whether chunk paths should include a hash of the chunk content, which
+    /// makes them suitable for long-term caching.
+    fn is_content_hashing_enabled(&self) -> BoolVc {
+        BoolVc::cell(false)
+    }
+
+    /// Whether chunk items should minify the code they generate.
+    fn is_minification_enabled(&self) -> BoolVc {
+        BoolVc::cell(false)
+    }
+
+    /// The entries of the module graph that is tree shaken. Exports which are
+    /// not used within the module graph of these entries are removed. Tree
+    /// shaking is disabled when there are no entries.
+    fn tree_shaking_entries(&self) -> AssetsVc {
+        AssetsVc::empty()
+    }
+
+    /// The entries of the module graph in which modules are hoisted into the
+    /// scope of their importer, when they are only imported statically by
+    /// modules of the same scope. Scope hoisting is disabled when there are no
+    /// entries.
+    fn scope_hoisting_entries(&self) -> AssetsVc {
+        AssetsVc::empty()
+    }
+
     fn layer(&self) -> StringVc {
         StringVc::cell("".to_string())
     }
 
     fn with_layer(&self, layer: &str) -> ChunkingContextVc;
+
+    /// A chunking context that creates chunks for the given environment, e.g.
+    /// the entry chunks of web workers. Chunking contexts which can't create
+    /// chunks for other environments return themselves.
+    fn with_environment(&self, _environment: EnvironmentVc) -> ChunkingContextVc {
+        *self
+    }
 }
 
 /// An [Asset] that can be converted into a [Chunk].
@@ -0,0 +1,196 @@
+use anyhow::Result;
+use turbo_tasks::{
+    primitives::{BoolVc, StringVc},
+    Value, ValueToString,
+};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+use super::{common, ChunkingContext, ChunkingContextVc};
+use crate::{
+    asset::{AssetVc, AssetsVc},
+    environment::EnvironmentVc,
+};
+
+pub struct ProdChunkingContextBuilder {
+    context: ProdChunkingContext,
+}
+
+impl ProdChunkingContextBuilder {
+    pub fn minify(mut self, minify: bool) -> Self {
+        self.context.minify = minify;
+        self
+    }
+
+    pub fn layer(mut self, layer: &str) -> Self {
+        self.context.layer = common::layer_name(layer);
+        self
+    }
+
+    pub fn css_chunk_root_path(mut self, path: FileSystemPathVc) -> Self {
+        self.context.css_chunk_root_path = Some(path);
+        self
+    }
+
+    /// Removes exports which are not used within the module graph of the
+    /// entries.
+    pub fn tree_shaking_entries(mut self, entries: AssetsVc) -> Self {
+        self.context.tree_shaking_entries = Some(entries);
+        self
+    }
+
+    /// Concatenates modules within the module graph of the entries into the
+    /// scope of their importer, when possible.
+    pub fn scope_hoisting_entries(mut self, entries: AssetsVc) -> Self {
+        self.context.scope_hoisting_entries = Some(entries);
+        self
+    }
+
+    pub fn build(self) -> ChunkingContextVc {
+        ProdChunkingContextVc::new(Value::new(self.context)).into()
+    }
+}
+
+/// A chunking context for production builds.
+/// Chunks are named after a hash of their identity and content, so unchanged
+/// chunks keep their filename between builds and can be cached forever.
+/// Chunk items are minified and no hot module replacement runtime is emitted.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Clone, Hash, PartialOrd, Ord)]
+pub struct ProdChunkingContext {
+    /// This path is used to compute the url to request chunks or assets from
+    output_root_path: FileSystemPathVc,
+    /// Chunks are placed at this path
+    chunk_root_path: FileSystemPathVc,
+    /// Css Chunks are placed at this path
+    css_chunk_root_path: Option<FileSystemPathVc>,
+    /// Static assets are placed at this path
+    asset_root_path: FileSystemPathVc,
+    /// Layer name within this context
+    layer: Option<String>,
+    /// Minify the generated code
+    minify: bool,
+    /// The entries of the module graph that is tree shaken
+    tree_shaking_entries: Option<AssetsVc>,
+    /// The entries of the module graph that is scope hoisted
+    scope_hoisting_entries: Option<AssetsVc>,
+    /// The environment chunks will be evaluated in.
+    environment: EnvironmentVc,
+}
+
+impl ProdChunkingContextVc {
+    pub fn builder(
+        output_root_path: FileSystemPathVc,
+        chunk_root_path: FileSystemPathVc,
+        asset_root_path: FileSystemPathVc,
+        environment: EnvironmentVc,
+    ) -> ProdChunkingContextBuilder {
+        ProdChunkingContextBuilder {
+            context: ProdChunkingContext {
+                output_root_path,
+                chunk_root_path,
+                css_chunk_root_path: None,
+                asset_root_path,
+                layer: None,
+                minify: true,
+                tree_shaking_entries: None,
+                scope_hoisting_entries: None,
+                environment,
+            },
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProdChunkingContextVc {
+    #[turbo_tasks::function]
+    fn new(this: Value<ProdChunkingContext>) -> Self {
+        this.into_value().cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkingContext for ProdChunkingContext {
+    #[turbo_tasks::function]
+    fn output_root(&self) -> FileSystemPathVc {
+        self.output_root_path
+    }
+
+    #[turbo_tasks::function]
+    fn environment(&self) -> EnvironmentVc {
+        self.environment
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_path(
+        &self,
+        path: FileSystemPathVc,
+        extension: &str,
+    ) -> Result<FileSystemPathVc> {
+        // Chunks include a hash of their content in `path` when content hashing is
+        // enabled, so hashing the full path covers both identity and content.
+        let hash = encode_hex(hash_xxh3_hash64(path.to_string().await?.as_bytes()));
+        let name = format!("{hash}{extension}");
+        let root_path = common::chunk_root_path(
+            self.chunk_root_path,
+            self.css_chunk_root_path,
+            self.layer.as_deref(),
+            extension,
+        );
+        Ok(root_path.join(&name))
+    }
+
+    #[turbo_tasks::function]
+    async fn can_be_in_same_chunk(&self, asset_a: AssetVc, asset_b: AssetVc) -> Result<BoolVc> {
+        Ok(BoolVc::cell(
+            common::can_be_in_same_chunk(asset_a, asset_b).await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn asset_path(&self, content_hash: &str, extension: &str) -> FileSystemPathVc {
+        common::asset_path(self.asset_root_path, content_hash, extension)
+    }
+
+    #[turbo_tasks::function]
+    fn is_content_hashing_enabled(&self) -> BoolVc {
+        BoolVc::cell(true)
+    }
+
+    #[turbo_tasks::function]
+    fn is_minification_enabled(&self) -> BoolVc {
+        BoolVc::cell(self.minify)
+    }
+
+    #[turbo_tasks::function]
+    fn tree_shaking_entries(&self) -> AssetsVc {
+        self.tree_shaking_entries.unwrap_or_else(AssetsVc::empty)
+    }
+
+    #[turbo_tasks::function]
+    fn scope_hoisting_entries(&self) -> AssetsVc {
+        self.scope_hoisting_entries.unwrap_or_else(AssetsVc::empty)
+    }
+
+    #[turbo_tasks::function]
+    fn layer(&self) -> StringVc {
+        StringVc::cell(self.layer.clone().unwrap_or_default())
+    }
+
+    #[turbo_tasks::function]
+    async fn with_layer(self_vc: ProdChunkingContextVc, layer: &str) -> Result<ChunkingContextVc> {
+        let mut context = self_vc.await?.clone_value();
+        context.layer = common::layer_name(layer);
+        Ok(ProdChunkingContextVc::new(Value::new(context)).into())
+    }
+
+    #[turbo_tasks::function]
+    async fn with_environment(
+        self_vc: ProdChunkingContextVc,
+        environment: EnvironmentVc,
+    ) -> Result<ChunkingContextVc> {
+        let mut context = self_vc.await?.clone_value();
+        context.environment = environment;
+        Ok(ProdChunkingContextVc::new(Value::new(context)).into())
+    }
+}
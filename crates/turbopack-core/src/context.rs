@@ -34,4 +34,7 @@ pub trait AssetContext {
         reference_type: Value<ReferenceType>,
     ) -> ResolveResultVc;
     fn with_transition(&self, transition: &str) -> AssetContextVc;
+    /// Returns a context which builds assets for another environment, e.g. for
+    /// a web worker created from a browser environment.
+    fn with_environment(&self, environment: EnvironmentVc) -> AssetContextVc;
 }
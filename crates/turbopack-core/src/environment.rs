@@ -72,6 +72,8 @@ pub enum ChunkLoading {
     NodeJs,
     /// <script> and <link> tags in the browser
     Dom,
+    /// importScripts in a web worker
+    WebWorker,
 }
 
 #[turbo_tasks::value]
@@ -250,10 +252,42 @@ impl EnvironmentVc {
             ExecutionEnvironment::NodeJsBuildTime(_)
             | ExecutionEnvironment::NodeJsLambda(_)
             | ExecutionEnvironment::EdgeFunction(_) => ChunkLoading::NodeJs.cell(),
-            ExecutionEnvironment::Browser(_) => ChunkLoading::Dom.cell(),
+            ExecutionEnvironment::Browser(browser_env) => {
+                let browser_env = browser_env.await?;
+                if !browser_env.dom && browser_env.web_worker {
+                    ChunkLoading::WebWorker.cell()
+                } else {
+                    ChunkLoading::Dom.cell()
+                }
+            }
             _ => ChunkLoading::None.cell(),
         })
     }
+
+    /// The environment a web worker created from this environment runs in.
+    /// Outside of the browser, workers run in the same kind of environment.
+    #[turbo_tasks::function]
+    pub async fn web_worker(self) -> Result<EnvironmentVc> {
+        let env = self.await?;
+        Ok(match env.execution {
+            ExecutionEnvironment::Browser(browser_env) => {
+                let browser_env = browser_env.await?;
+                EnvironmentVc::new(
+                    Value::new(ExecutionEnvironment::Browser(
+                        BrowserEnvironment {
+                            dom: false,
+                            web_worker: true,
+                            service_worker: false,
+                            browserslist_query: browser_env.browserslist_query.clone(),
+                        }
+                        .into(),
+                    )),
+                    Value::new(env.intention),
+                )
+            }
+            _ => self,
+        })
+    }
 }
 
 pub enum NodeEnvironmentType {
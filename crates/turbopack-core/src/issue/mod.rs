@@ -390,7 +390,7 @@ impl CapturedIssues {
     }
 }
 
-#[turbo_tasks::value]
+#[turbo_tasks::value(shared)]
 #[derive(Clone)]
 pub struct IssueSource {
     pub asset: AssetVc,
@@ -164,6 +164,28 @@ impl<T> AliasMap<T> {
         }
     }
 
+    /// Returns an iterator over all the aliases of the map.
+    ///
+    /// Beware: The items are *NOT* returned in the order defined by
+    /// [PATTERN_KEY_COMPARE].
+    ///
+    /// [PATTERN_KEY_COMPARE]: https://nodejs.org/api/esm.html#resolver-algorithm-specification
+    pub fn iter(&self) -> impl Iterator<Item = (AliasPattern, &T)> {
+        self.map.iter().flat_map(|(prefix, map)| {
+            let prefix = String::from_utf8(prefix).expect("invalid UTF-8 key in AliasMap");
+            map.iter().map(move |(key, template)| {
+                let pattern = match key {
+                    AliasKey::Exact => AliasPattern::Exact(prefix.clone()),
+                    AliasKey::Wildcard { suffix } => AliasPattern::Wildcard {
+                        prefix: prefix.clone(),
+                        suffix: suffix.clone(),
+                    },
+                };
+                (pattern, template)
+            })
+        })
+    }
+
     /// Inserts a new alias into the map.
     ///
     /// If the map did not have this alias already, `None` is returned.
@@ -704,6 +726,74 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_package_internal() {
+        // The patterns of an "imports" field
+        let mut map = AliasMap::new();
+        map.insert(AliasPattern::parse("#dep"), "./dep.js");
+        map.insert(AliasPattern::parse("#utils/*"), "./utils/*.js");
+        map.insert(AliasPattern::parse("#utils/internal/*"), "./internal/*.js");
+
+        assert_alias_matches!(map, "#dep", exact(&"./dep.js"));
+        assert_alias_matches!(map, "#de");
+        assert_alias_matches!(map, "dep");
+        assert_alias_matches!(map, "#utils/format", replaced_owned("./utils/format.js"));
+        assert_alias_matches!(
+            map,
+            "#utils/internal/format",
+            // The longer prefix should come first.
+            replaced_owned("./internal/format.js"),
+            replaced_owned("./utils/internal/format.js"),
+        );
+    }
+
+    #[test]
+    fn test_exports_subpaths() {
+        // The patterns of an "exports" field
+        let mut map = AliasMap::new();
+        map.insert(AliasPattern::parse("."), "./index.js");
+        map.insert(AliasPattern::parse("./feature"), "./feature.js");
+        map.insert(AliasPattern::parse("./*"), "./lib/*.js");
+        map.insert(AliasPattern::parse("./*.js"), "./lib/*.js");
+
+        assert_alias_matches!(map, ".", exact(&"./index.js"));
+        assert_alias_matches!(
+            map,
+            "./feature",
+            exact(&"./feature.js"),
+            replaced_owned("./lib/feature.js"),
+        );
+        assert_alias_matches!(
+            map,
+            "./utils/format.js",
+            // The longer suffix should come first.
+            replaced_owned("./lib/utils/format.js"),
+            replaced_owned("./lib/utils/format.js.js"),
+        );
+        assert_alias_matches!(map, "feature");
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut map = AliasMap::new();
+        map.insert(AliasPattern::parse("."), "./index.js");
+        map.insert(AliasPattern::parse("./*"), "./lib/*.js");
+        map.insert(AliasPattern::parse("./*.css"), "./styles/*.css");
+
+        let entries = map.iter().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 3);
+        for (pattern, template) in [
+            (AliasPattern::parse("."), "./index.js"),
+            (AliasPattern::parse("./*"), "./lib/*.js"),
+            (AliasPattern::parse("./*.css"), "./styles/*.css"),
+        ] {
+            assert!(
+                entries.contains(&(pattern.clone(), &template)),
+                "missing {pattern:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_wildcard_replace_only() {
         let mut map = AliasMap::new();
@@ -0,0 +1,115 @@
+use anyhow::{anyhow, Result};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+/// The decoded content of a `data:` URI.
+///
+/// See <https://developer.mozilla.org/en-US/docs/Web/HTTP/Basics_of_HTTP/Data_URIs>
+#[derive(Debug, PartialEq, Eq)]
+pub struct DataUri {
+    /// The MIME type without parameters, e.g. `text/javascript`.
+    pub mime_type: String,
+    pub content: Vec<u8>,
+}
+
+impl DataUri {
+    /// Parses the part of a `data:` URI after the protocol, i.e.
+    /// `[<mediatype>][;base64],<data>`.
+    pub fn parse(remainder: &str) -> Result<Self> {
+        let (media_type, data) = remainder
+            .split_once(',')
+            .ok_or_else(|| anyhow!("data URI is missing a \",\""))?;
+
+        let mut parameters = media_type.split(';');
+        let mime_type = parameters.next().unwrap_or_default().trim();
+        let is_base64 = parameters.any(|parameter| parameter.trim() == "base64");
+        let mime_type = if mime_type.is_empty() {
+            "text/plain".to_string()
+        } else {
+            mime_type.to_ascii_lowercase()
+        };
+
+        let data = urlencoding::decode_binary(data.as_bytes());
+        let content = if is_base64 {
+            let data = data
+                .iter()
+                .copied()
+                .filter(|c| !c.is_ascii_whitespace())
+                .collect::<Vec<_>>();
+            base64::decode(data).map_err(|e| anyhow!("invalid base64 in data URI: {}", e))?
+        } else {
+            data.into_owned()
+        };
+
+        Ok(DataUri { mime_type, content })
+    }
+
+    /// Returns a file name for the content, which has the extension that is
+    /// processed like the MIME type.
+    pub fn file_name(&self, remainder: &str) -> String {
+        let hash = encode_hex(hash_xxh3_hash64(remainder));
+        format!(
+            "data-uri-{}.{}",
+            hash,
+            extension_for_mime_type(&self.mime_type)
+        )
+    }
+}
+
+fn extension_for_mime_type(mime_type: &str) -> &'static str {
+    match mime_type {
+        "text/javascript" | "application/javascript" | "application/x-javascript" => "js",
+        "application/json" => "json",
+        "text/css" => "css",
+        "application/wasm" => "wasm",
+        "image/apng" => "apng",
+        "image/avif" => "avif",
+        "image/gif" => "gif",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/svg+xml" => "svg",
+        "image/webp" => "webp",
+        "font/woff2" => "woff2",
+        "text/plain" => "txt",
+        _ => "bin",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataUri;
+
+    #[test]
+    fn percent_encoded() {
+        assert_eq!(
+            DataUri::parse("text/javascript,export%20default%2042").unwrap(),
+            DataUri {
+                mime_type: "text/javascript".to_string(),
+                content: b"export default 42".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn base64() {
+        assert_eq!(
+            DataUri::parse("application/json;charset=utf-8;base64,eyJhIjogMX0=").unwrap(),
+            DataUri {
+                mime_type: "application/json".to_string(),
+                content: br#"{"a": 1}"#.to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn default_mime_type() {
+        let data_uri = DataUri::parse(",hello").unwrap();
+        assert_eq!(data_uri.mime_type, "text/plain");
+        assert!(data_uri.file_name(",hello").ends_with(".txt"));
+    }
+
+    #[test]
+    fn missing_comma() {
+        assert!(DataUri::parse("text/plain").is_err());
+    }
+}
@@ -317,4 +317,48 @@ impl ExportsField {
     pub fn lookup<'a>(&'a self, request: &'a str) -> AliasMapLookupIterator<'a, ExportsValue> {
         self.0.lookup(request)
     }
+
+    /// Returns an iterator over all subpath patterns of the "exports" field
+    /// and the values they map to.
+    pub fn iter(&self) -> impl Iterator<Item = (AliasPattern, &ExportsValue)> {
+        self.0.iter()
+    }
+}
+
+/// Content of an "imports" field in a package.json
+#[derive(PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportsField(AliasMap<ExportsValue>);
+
+impl TryFrom<&Value> for ImportsField {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        // The "imports" field must be an object with keys starting with "#".
+        // https://nodejs.org/api/packages.html#imports
+        let Value::Object(object) = value else {
+            bail!("\"imports\" field must be an object");
+        };
+
+        let mut map = AliasMap::new();
+        for (key, value) in object.iter() {
+            if !key.starts_with('#') || key == "#" || key.starts_with("#/") {
+                bail!(
+                    "invalid key \"{}\" in the \"imports\" field. Keys must start with \"#\" and \
+                     can't be \"#\" or start with \"#/\"",
+                    key
+                );
+            }
+            map.insert(AliasPattern::parse(key), value.try_into()?);
+        }
+        Ok(Self(map))
+    }
+}
+
+impl ImportsField {
+    /// Looks up a request string in the "imports" field. Returns an iterator of
+    /// matching requests. Usually only the first one is relevant, except
+    /// when conditions don't match or only partially match.
+    pub fn lookup<'a>(&'a self, request: &'a str) -> AliasMapLookupIterator<'a, ExportsValue> {
+        self.0.lookup(request)
+    }
 }
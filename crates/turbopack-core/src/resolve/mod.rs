@@ -16,11 +16,13 @@ use turbo_tasks::{
 };
 use turbo_tasks_fs::{
     util::{normalize_path, normalize_request},
-    FileJsonContent, FileJsonContentVc, FileSystemEntryType, FileSystemPathVc, RealPathResult,
+    File, FileJsonContent, FileJsonContentVc, FileSystemEntryType, FileSystemPathVc,
+    RealPathResult,
 };
 
 use self::{
-    exports::{ExportsField, ExportsValue},
+    data_uri::DataUri,
+    exports::{ExportsField, ExportsValue, ImportsField},
     options::{
         resolve_modules_options, ImportMapResult, ResolveInPackage, ResolveIntoPackage,
         ResolveModules, ResolveModulesOptionsVc, ResolveOptionsVc,
@@ -42,9 +44,11 @@ use crate::{
         pattern::{read_matches, Pattern, PatternMatch, PatternVc},
     },
     source_asset::SourceAssetVc,
+    virtual_asset::VirtualAssetVc,
 };
 
 mod alias_map;
+mod data_uri;
 mod exports;
 pub mod options;
 pub mod origin;
@@ -386,6 +390,45 @@ async fn exports_field(
     }
 }
 
+#[turbo_tasks::value(shared)]
+enum ImportsFieldResult {
+    Some(
+        #[turbo_tasks(debug_ignore, trace_ignore)] ImportsField,
+        FileSystemPathVc,
+    ),
+    None,
+}
+
+/// Looks up the "imports" field of the package.json closest to `context`.
+#[turbo_tasks::function]
+async fn imports_field(context: FileSystemPathVc) -> Result<ImportsFieldResultVc> {
+    let package_json_result = find_context_file(context, package_json()).await?;
+    let FindContextFileResult::Found(package_json_path, _refs) = &*package_json_result else {
+        return Ok(ImportsFieldResult::None.cell());
+    };
+
+    let read = package_json_path.read_json().await?;
+    let FileJsonContent::Content(package_json) = &*read else {
+        return Ok(ImportsFieldResult::None.cell());
+    };
+
+    let Some(imports) = package_json.get("imports") else {
+        return Ok(ImportsFieldResult::None.cell());
+    };
+    match imports.try_into() {
+        Ok(imports) => Ok(ImportsFieldResult::Some(imports, *package_json_path).cell()),
+        Err(err) => {
+            let issue: PackageJsonIssueVc = PackageJsonIssue {
+                path: *package_json_path,
+                error_message: err.to_string(),
+            }
+            .into();
+            issue.as_issue().emit();
+            Ok(ImportsFieldResult::None.cell())
+        }
+    }
+}
+
 #[turbo_tasks::function]
 pub fn package_json() -> StringsVc {
     StringsVc::cell(vec!["package.json".to_string()])
@@ -478,7 +521,22 @@ async fn find_package(
                     packages.push(package_dir.resolve().await?);
                 }
             }
-            ResolveModules::Registry(_, _) => todo!(),
+            ResolveModules::Registry(root, locked_versions) => {
+                let root_value = root.await?;
+                let context_value = context.await?;
+                let importer = root_value
+                    .get_path_to(&context_value)
+                    .and_then(registry_package_id);
+                let locked_versions = locked_versions.await?;
+                if let Some(version) =
+                    locked_versions.version(importer.as_deref(), &package_name)?
+                {
+                    let package_dir = root.join(&format!("{package_name}/{version}"));
+                    if dir_exists(package_dir, &mut references).await?.is_some() {
+                        packages.push(package_dir.resolve().await?);
+                    }
+                }
+            }
         }
     }
     Ok(FindPackageResultVc::cell(FindPackageResult {
@@ -487,6 +545,21 @@ async fn find_package(
     }))
 }
 
+/// Returns the `name@version` of the package a path inside of a registry
+/// filesystem belongs to. The registry is laid out as
+/// `@scope/module/version/<path-in-package>`.
+fn registry_package_id(path: &str) -> Option<String> {
+    let mut segments = path.split('/');
+    let first = segments.next()?;
+    let name = if first.starts_with('@') {
+        format!("{first}/{}", segments.next()?)
+    } else {
+        first.to_string()
+    };
+    let version = segments.next()?;
+    Some(format!("{name}@{version}"))
+}
+
 fn merge_results(results: Vec<ResolveResultVc>) -> ResolveResultVc {
     match results.len() {
         0 => ResolveResult::unresolveable().into(),
@@ -601,6 +674,12 @@ pub async fn resolve(
     }
 
     let request_value = request.await?;
+    if let Some(url_resolver) = options_value.url_resolver {
+        if let Some(url) = request_url(context, &request_value, url_resolver.root()).await? {
+            return Ok(url_resolver.resolve_url(&url));
+        }
+    }
+
     let result = match &*request_value {
         Request::Dynamic => ResolveResult::unresolveable().into(),
         Request::Alternatives { requests } => {
@@ -698,18 +777,13 @@ pub async fn resolve(
             ResolveResult::unresolveable().into()
         }
         Request::Empty => ResolveResult::unresolveable().into(),
-        Request::PackageInternal { path: _ } => {
-            let issue: ResolvingIssueVc = ResolvingIssue {
-                request_type: "package internal import: not implemented yet".to_string(),
-                request,
-                context,
-                resolve_options: options,
-                error_message: Some("package internal imports are not implemented yet".to_string()),
-            }
-            .into();
-            issue.as_issue().emit();
-            ResolveResult::unresolveable().into()
+        Request::PackageInternal { path } => {
+            resolve_package_internal_with_imports_field(context, request, options, path).await?
         }
+        Request::Uri {
+            protocol,
+            remainder,
+        } if protocol == "data:" => resolve_data_uri(context, request, options, remainder),
         Request::Uri {
             protocol,
             remainder,
@@ -748,6 +822,79 @@ pub async fn resolve(
     Ok(result)
 }
 
+/// Returns the url of an `http:` or `https:` request. This includes relative
+/// requests from assets that have been resolved from a url, which are placed
+/// in `url_root` as `<scheme>/<host>/<path>`.
+async fn request_url(
+    context: FileSystemPathVc,
+    request: &Request,
+    url_root: FileSystemPathVc,
+) -> Result<Option<String>> {
+    let path = match request {
+        Request::Uri {
+            protocol,
+            remainder,
+        } if protocol == "http:" || protocol == "https:" => {
+            return Ok(Some(format!("{protocol}{remainder}")));
+        }
+        Request::Relative {
+            path: Pattern::Constant(path),
+            ..
+        }
+        | Request::ServerRelative {
+            path: Pattern::Constant(path),
+        } => path,
+        _ => return Ok(None),
+    };
+    let url_root = url_root.await?;
+    let context = context.await?;
+    let Some(dir) = url_root.get_path_to(&context) else {
+        return Ok(None);
+    };
+    let mut segments = dir.splitn(3, '/');
+    let (Some(scheme), Some(host)) = (segments.next(), segments.next()) else {
+        return Ok(None);
+    };
+    let base = if path.starts_with('/') {
+        ""
+    } else {
+        segments.next().unwrap_or_default()
+    };
+    let Some(path) = normalize_path(&format!("{base}/{path}")) else {
+        return Ok(None);
+    };
+    Ok(Some(format!("{scheme}://{host}/{path}")))
+}
+
+/// Decodes a `data:` URI into a virtual asset next to the importing module,
+/// which is processed like a file of the MIME type.
+fn resolve_data_uri(
+    context: FileSystemPathVc,
+    request: RequestVc,
+    options: ResolveOptionsVc,
+    remainder: &str,
+) -> ResolveResultVc {
+    match DataUri::parse(remainder) {
+        Ok(data_uri) => {
+            let path = context.join(&data_uri.file_name(remainder));
+            let asset = VirtualAssetVc::new(path, File::from(data_uri.content).into());
+            ResolveResult::Single(asset.into(), Vec::new()).into()
+        }
+        Err(err) => {
+            let issue: ResolvingIssueVc = ResolvingIssue {
+                request_type: "data URI".to_string(),
+                request,
+                context,
+                resolve_options: options,
+                error_message: Some(err.to_string()),
+            }
+            .into();
+            issue.as_issue().emit();
+            ResolveResult::unresolveable().into()
+        }
+    }
+}
+
 async fn resolve_into_folder(
     package_path: FileSystemPathVc,
     package_json: FileJsonContentVc,
@@ -885,7 +1032,17 @@ async fn resolve_module_request(
                                     unspecified_conditions,
                                 )?);
                             } else {
-                                todo!("pattern into an exports field is not implemented yet");
+                                let mut request = path.clone();
+                                request.push_front(".".to_string().into());
+                                results.push(handle_exports_field_pattern(
+                                    *package_path,
+                                    package_json_path,
+                                    options,
+                                    exports_field,
+                                    &request,
+                                    conditions,
+                                    unspecified_conditions,
+                                )?);
                             }
                             // other options do not apply anymore when an exports
                             // field exist
@@ -1068,21 +1225,15 @@ async fn resolved(
     .into())
 }
 
-fn handle_exports_field(
-    package_path: FileSystemPathVc,
-    package_json: FileSystemPathVc,
-    options: ResolveOptionsVc,
-    exports_field: &ExportsField,
-    path: &str,
+/// Walks the values an "exports" or "imports" field maps a request to and
+/// returns the results that match the conditions, without duplicates.
+fn conditional_results<'a>(
+    values: &'a [Cow<'_, ExportsValue>],
     conditions: &BTreeMap<String, ConditionValue>,
     unspecified_conditions: &ConditionValue,
-) -> Result<ResolveResultVc> {
+) -> Vec<&'a str> {
     let mut results = Vec::new();
     let mut conditions_state = HashMap::new();
-    let values = exports_field
-        .lookup(path)
-        .map(AliasMatch::try_into_self)
-        .collect::<Result<Vec<Cow<'_, ExportsValue>>>>()?;
     for value in values.iter() {
         if value.add_results(
             conditions,
@@ -1093,10 +1244,25 @@ fn handle_exports_field(
             break;
         }
     }
-    {
-        let mut duplicates_set = HashSet::new();
-        results.retain(|item| duplicates_set.insert(*item));
-    }
+    let mut duplicates_set = HashSet::new();
+    results.retain(|item| duplicates_set.insert(*item));
+    results
+}
+
+fn handle_exports_field(
+    package_path: FileSystemPathVc,
+    package_json: FileSystemPathVc,
+    options: ResolveOptionsVc,
+    exports_field: &ExportsField,
+    path: &str,
+    conditions: &BTreeMap<String, ConditionValue>,
+    unspecified_conditions: &ConditionValue,
+) -> Result<ResolveResultVc> {
+    let values = exports_field
+        .lookup(path)
+        .map(AliasMatch::try_into_self)
+        .collect::<Result<Vec<Cow<'_, ExportsValue>>>>()?;
+    let results = conditional_results(&values, conditions, unspecified_conditions);
     let mut resolved_results = Vec::new();
     for path in results {
         if let Some(path) = normalize_path(path) {
@@ -1111,6 +1277,154 @@ fn handle_exports_field(
     ))
 }
 
+/// Resolves a dynamic request into a package with an "exports" field. Every
+/// subpath of the exports field the request could match is resolved, which
+/// gives all the assets the request could lead to.
+fn handle_exports_field_pattern(
+    package_path: FileSystemPathVc,
+    package_json: FileSystemPathVc,
+    options: ResolveOptionsVc,
+    exports_field: &ExportsField,
+    request: &Pattern,
+    conditions: &BTreeMap<String, ConditionValue>,
+    unspecified_conditions: &ConditionValue,
+) -> Result<ResolveResultVc> {
+    let mut resolved_results = Vec::new();
+    for (subpath, value) in exports_field.iter() {
+        let capture = match &subpath {
+            AliasPattern::Exact(subpath) => {
+                if !request.is_match(subpath) {
+                    continue;
+                }
+                None
+            }
+            AliasPattern::Wildcard { prefix, suffix } => {
+                if !request.could_match(prefix) {
+                    continue;
+                }
+                let Some(capture) = request.wildcard_capture(prefix, suffix) else {
+                    continue;
+                };
+                Some(capture)
+            }
+        };
+        let values = [Cow::Borrowed(value)];
+        for result in conditional_results(&values, conditions, unspecified_conditions) {
+            let request = match &capture {
+                Some(capture) if result.contains('*') => {
+                    if !result.starts_with("./") {
+                        continue;
+                    }
+                    let mut parts = Vec::new();
+                    for (i, part) in result.split('*').enumerate() {
+                        if i > 0 {
+                            parts.push(capture.clone());
+                        }
+                        parts.push(Pattern::Constant(part.to_string()));
+                    }
+                    let mut target = Pattern::Concatenation(parts);
+                    target.normalize();
+                    RequestVc::parse(Value::new(target))
+                }
+                _ => {
+                    let Some(path) = normalize_path(result) else {
+                        continue;
+                    };
+                    RequestVc::parse(Value::new(format!("./{}", path).into()))
+                }
+            };
+            resolved_results.push(resolve(package_path, request, options));
+        }
+    }
+    Ok(merge_results_with_references(
+        resolved_results,
+        vec![AffectingResolvingAssetReferenceVc::new(package_json).into()],
+    ))
+}
+
+/// Resolves a `#internal` request with the "imports" field of the closest
+/// package.json. The conditions of the first exports field configured in
+/// [ResolveIntoPackage] apply.
+async fn resolve_package_internal_with_imports_field(
+    context: FileSystemPathVc,
+    request: RequestVc,
+    resolve_options: ResolveOptionsVc,
+    pattern: &Pattern,
+) -> Result<ResolveResultVc> {
+    let unresolveable = |error_message: &str| -> ResolveResultVc {
+        let issue: ResolvingIssueVc = ResolvingIssue {
+            request_type: "package internal import".to_string(),
+            request,
+            context,
+            resolve_options,
+            error_message: Some(error_message.to_string()),
+        }
+        .into();
+        issue.as_issue().emit();
+        ResolveResult::unresolveable().into()
+    };
+
+    let Some(specifier) = pattern.clone().into_string() else {
+        return Ok(unresolveable(
+            "package internal imports with dynamic patterns are not implemented yet",
+        ));
+    };
+    let imports_field_result = imports_field(context).await?;
+    let ImportsFieldResult::Some(imports_field, package_json_path) = &*imports_field_result else {
+        return Ok(unresolveable(
+            "package internal imports need an \"imports\" field in the closest package.json",
+        ));
+    };
+
+    let options = resolve_options.await?;
+    let (conditions, unspecified_conditions) = options
+        .into_package
+        .iter()
+        .find_map(|into_package| match into_package {
+            ResolveIntoPackage::ExportsField {
+                conditions,
+                unspecified_conditions,
+                ..
+            } => Some((Cow::Borrowed(conditions), unspecified_conditions.clone())),
+            _ => None,
+        })
+        .unwrap_or((Cow::Owned(BTreeMap::new()), ConditionValue::Unset));
+
+    let values = imports_field
+        .lookup(&specifier)
+        .map(AliasMatch::try_into_self)
+        .collect::<Result<Vec<Cow<'_, ExportsValue>>>>()?;
+    let results = conditional_results(&values, &conditions, &unspecified_conditions);
+    let package_path = package_json_path.parent();
+    let mut resolved_results = Vec::new();
+    for path in results {
+        // Targets are either relative to the package or other packages
+        let request = if path.starts_with("./") {
+            let Some(path) = normalize_path(path) else {
+                continue;
+            };
+            RequestVc::parse(Value::new(format!("./{}", path).into()))
+        } else if path.starts_with('.') || path.starts_with('/') || path.starts_with('#') {
+            continue;
+        } else {
+            RequestVc::parse(Value::new(path.to_string().into()))
+        };
+        resolved_results.push(resolve(package_path, request, resolve_options));
+    }
+    if resolved_results.is_empty() {
+        return Ok(unresolveable(&format!(
+            "\"{}\" is not defined in the \"imports\" field of {}",
+            specifier,
+            package_json_path.to_string().await?
+        )));
+    }
+
+    Ok(merge_results_with_references(
+        resolved_results,
+        vec![AffectingResolvingAssetReferenceVc::new(*package_json_path).into()],
+    ))
+}
+
 #[turbo_tasks::value]
 pub struct AffectingResolvingAssetReference {
     path: FileSystemPathVc,
@@ -1179,3 +1493,22 @@ pub async fn handle_resolve_error(
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::registry_package_id;
+
+    #[test]
+    fn test_registry_package_id() {
+        assert_eq!(
+            registry_package_id("react/18.2.0/index.js").as_deref(),
+            Some("react@18.2.0")
+        );
+        assert_eq!(
+            registry_package_id("@scope/pkg/1.0.0/lib/index.js").as_deref(),
+            Some("@scope/pkg@1.0.0")
+        );
+        assert_eq!(registry_package_id("react").as_deref(), None);
+        assert_eq!(registry_package_id("@scope/pkg").as_deref(), None);
+    }
+}
@@ -1,6 +1,6 @@
 use std::{collections::BTreeMap, future::Future, pin::Pin};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{
     debug::ValueDebugFormat, primitives::StringVc, trace::TraceRawVcs, TryJoinIterExt, Value,
@@ -14,9 +14,40 @@ use super::{
 };
 use crate::resolve::parse::RequestVc;
 
+/// The package versions a lockfile pinned, used to find packages in a
+/// registry filesystem.
 #[turbo_tasks::value(shared)]
-#[derive(Hash, Debug)]
-pub struct LockedVersions {}
+#[derive(Hash, Debug, Default)]
+pub struct LockedVersions {
+    /// The versions of the dependencies of the project itself, keyed by
+    /// package name.
+    pub versions: BTreeMap<String, String>,
+    /// The versions of the dependencies of packages in the registry, keyed by
+    /// the `name@version` of the dependent package and then by package name.
+    /// Every package in the registry needs an entry, and a package can only
+    /// import the dependencies listed in it.
+    pub dependencies: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl LockedVersions {
+    /// Returns the locked version of `package_name`, imported either from
+    /// the registry package `importer` (`name@version`) or from the project
+    /// itself.
+    ///
+    /// Registry packages don't fall back to the versions of the project, as
+    /// that would silently pick versions the lockfile never pinned for them.
+    /// An importer that's not in the lockfile is an error.
+    pub fn version(&self, importer: Option<&str>, package_name: &str) -> Result<Option<&str>> {
+        let versions = match importer {
+            Some(importer) => self
+                .dependencies
+                .get(importer)
+                .ok_or_else(|| anyhow!("the registry package {importer} is not in the lockfile"))?,
+            None => &self.versions,
+        };
+        Ok(versions.get(package_name).map(|version| version.as_str()))
+    }
+}
 
 /// A location where to resolve modules.
 #[derive(
@@ -374,6 +405,8 @@ pub struct ResolveOptions {
     /// An import map to use when a request is otherwise unresolveable.
     pub fallback_import_map: Option<ImportMapVc>,
     pub resolved_map: Option<ResolvedMapVc>,
+    /// Resolves `http:` and `https:` requests. They are external when unset.
+    pub url_resolver: Option<UrlResolverVc>,
     pub placeholder_for_future_extensions: (),
 }
 
@@ -435,3 +468,59 @@ pub trait ImportMappingReplacement {
     fn replace(&self, capture: &str) -> ImportMappingVc;
     fn result(&self, request: RequestVc) -> ImportMapResultVc;
 }
+
+/// Resolves `http:` and `https:` requests to assets, e.g. by downloading them.
+#[turbo_tasks::value_trait]
+pub trait UrlResolver {
+    /// The directory that assets resolved from urls are placed in, as
+    /// `<scheme>/<host>/<path>`. Relative requests from these assets are
+    /// resolved against their url.
+    fn root(&self) -> FileSystemPathVc;
+    fn resolve_url(&self, url: &str) -> ResolveResultVc;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::LockedVersions;
+
+    fn locked_versions() -> LockedVersions {
+        LockedVersions {
+            versions: BTreeMap::from([
+                ("react".to_string(), "18.2.0".to_string()),
+                ("@scope/pkg".to_string(), "1.0.0".to_string()),
+            ]),
+            dependencies: BTreeMap::from([
+                (
+                    "@scope/pkg@1.0.0".to_string(),
+                    BTreeMap::from([("react".to_string(), "17.0.2".to_string())]),
+                ),
+                ("react@18.2.0".to_string(), BTreeMap::new()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn project_versions() {
+        let locked = locked_versions();
+        assert_eq!(locked.version(None, "react").unwrap(), Some("18.2.0"));
+        assert_eq!(locked.version(None, "@scope/pkg").unwrap(), Some("1.0.0"));
+        assert_eq!(locked.version(None, "missing").unwrap(), None);
+    }
+
+    #[test]
+    fn registry_package_versions() {
+        let locked = locked_versions();
+        assert_eq!(
+            locked.version(Some("@scope/pkg@1.0.0"), "react").unwrap(),
+            Some("17.0.2")
+        );
+        // Dependencies of the project aren't available to registry packages.
+        assert_eq!(
+            locked.version(Some("react@18.2.0"), "@scope/pkg").unwrap(),
+            None
+        );
+        assert!(locked.version(Some("react@17.0.2"), "react").is_err());
+    }
+}
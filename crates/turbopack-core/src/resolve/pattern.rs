@@ -412,6 +412,46 @@ impl Pattern {
         }
     }
 
+    /// Returns the pattern of the part of this pattern that the wildcard of a
+    /// `prefix*suffix` pattern captures, or a dynamic pattern when that can't
+    /// be determined. Returns `None` when this pattern ends with a constant
+    /// that can't end with `suffix`.
+    pub fn wildcard_capture(&self, prefix: &str, suffix: &str) -> Option<Pattern> {
+        let mut parts = match self {
+            Pattern::Concatenation(list) => list.clone(),
+            other => vec![other.clone()],
+        };
+        let constant = parts.len() == 1;
+        if !suffix.is_empty() {
+            match parts.last() {
+                Some(Pattern::Constant(last)) if last.ends_with(suffix) => {}
+                // A dynamic part before the constant could still provide the
+                // beginning of the suffix.
+                Some(Pattern::Constant(last)) if constant || !suffix.ends_with(last.as_str()) => {
+                    return None;
+                }
+                _ => return Some(Pattern::Dynamic),
+            }
+        }
+        match parts.first_mut() {
+            Some(Pattern::Constant(first)) if first.starts_with(prefix) => {
+                first.drain(..prefix.len());
+            }
+            _ => return Some(Pattern::Dynamic),
+        }
+        if !suffix.is_empty() {
+            match parts.last_mut() {
+                Some(Pattern::Constant(last)) if last.ends_with(suffix) => {
+                    last.truncate(last.len() - suffix.len());
+                }
+                _ => return Some(Pattern::Dynamic),
+            }
+        }
+        let mut capture = Pattern::Concatenation(parts);
+        capture.normalize();
+        Some(capture)
+    }
+
     pub fn next_constants<'a>(&'a self, value: &str) -> Option<Vec<(&'a str, bool)>> {
         if let Pattern::Alternatives(list) = self {
             let mut results = Vec::new();
@@ -1026,4 +1066,76 @@ mod tests {
     ) {
         assert_eq!(pat.next_constants(value), expected);
     }
+
+    #[test]
+    fn exports_subpath_match() {
+        // `import(`pkg/${name}`)` is looked up as `./${name}` in the "exports"
+        // field of the package.
+        let request =
+            Pattern::Concatenation(vec![Pattern::Constant("./".to_string()), Pattern::Dynamic]);
+        // Exact subpaths need to match completely.
+        assert!(request.is_match("./feature.js"));
+        assert!(!request.is_match("."));
+        // The prefixes of wildcard subpaths only need to be able to match.
+        assert!(request.could_match("./lib/"));
+
+        let request = Pattern::Concatenation(vec![
+            Pattern::Constant("./lib/".to_string()),
+            Pattern::Dynamic,
+        ]);
+        assert!(request.could_match("./"));
+        assert!(!request.could_match("./utils/"));
+        assert!(!request.is_match("./utils/feature.js"));
+    }
+
+    #[test]
+    fn wildcard_capture() {
+        let c = |value: &str| Pattern::Constant(value.to_string());
+        let d = Pattern::Dynamic;
+
+        assert_eq!(
+            c("./lib/feature.js").wildcard_capture("./lib/", ".js"),
+            Some(c("feature"))
+        );
+        assert_eq!(
+            Pattern::Concatenation(vec![c("./lib/"), d.clone(), c(".js")])
+                .wildcard_capture("./lib/", ".js"),
+            Some(d.clone())
+        );
+        assert_eq!(
+            Pattern::Concatenation(vec![c("./lib/feature/"), d.clone()])
+                .wildcard_capture("./lib/", ""),
+            Some(Pattern::Concatenation(vec![c("feature/"), d.clone()]))
+        );
+        assert_eq!(
+            Pattern::Concatenation(vec![c("./lib/"), d.clone()]).wildcard_capture("./", ""),
+            Some(Pattern::Concatenation(vec![c("lib/"), d.clone()]))
+        );
+
+        // The request could match, but the captured part is unknown.
+        assert_eq!(
+            Pattern::Concatenation(vec![c("./"), d.clone()]).wildcard_capture("./lib/", ""),
+            Some(d.clone())
+        );
+        assert_eq!(
+            Pattern::Concatenation(vec![c("./lib/"), d.clone()]).wildcard_capture("./lib/", ".js"),
+            Some(d.clone())
+        );
+        assert_eq!(
+            Pattern::Concatenation(vec![c("./lib/"), d.clone(), c("s")])
+                .wildcard_capture("./lib/", ".js"),
+            Some(d.clone())
+        );
+
+        // The request can't end with the suffix.
+        assert_eq!(
+            Pattern::Concatenation(vec![c("./locales/"), d, c(".json")])
+                .wildcard_capture("./", ".js"),
+            None
+        );
+        assert_eq!(
+            c("./lib/feature.json").wildcard_capture("./lib/", ".js"),
+            None
+        );
+    }
 }
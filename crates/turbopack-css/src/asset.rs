@@ -3,7 +3,7 @@ use swc_core::{
     common::{Globals, GLOBALS},
     css::{
         ast::{AtRule, AtRulePrelude, Rule},
-        codegen::{writer::basic::BasicCssWriter, CodeGenerator, Emit},
+        codegen::{writer::basic::BasicCssWriter, CodeGenerator, CodegenConfig, Emit},
         visit::{VisitMutWith, VisitMutWithPath},
     },
 };
@@ -242,9 +242,10 @@ impl CssChunkItem for ModuleChunkItem {
             let mut code_string = String::new();
             let mut srcmap = vec![];
 
+            let minify = *self.context.is_minification_enabled().await?;
             let mut code_gen = CodeGenerator::new(
                 BasicCssWriter::new(&mut code_string, Some(&mut srcmap), Default::default()),
-                Default::default(),
+                CodegenConfig { minify },
             );
 
             code_gen.emit(&stylesheet)?;
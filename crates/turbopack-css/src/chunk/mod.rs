@@ -286,6 +286,16 @@ impl Asset for CssChunk {
             needs_hash = true;
         }
 
+        // When content hashing is enabled, the generated code of all chunk items
+        // contributes to the hash, so the path changes whenever the content does.
+        if *this.context.is_content_hashing_enabled().await? {
+            let content = css_chunk_content(this.context, this.main_entries).await?;
+            for item in content.chunk_items.iter() {
+                hasher.write_ref(&item.content().await?.inner_code);
+            }
+            needs_hash = true;
+        }
+
         let hash = hasher.finish();
         let mut path = main_entry.path();
         if needs_hash {
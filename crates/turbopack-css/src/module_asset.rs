@@ -1,20 +1,23 @@
-use std::fmt::Write;
+use std::{fmt::Write, mem::take};
 
 use anyhow::Result;
+use indexmap::IndexSet;
 use swc_core::css::modules::CssClassName;
-use turbo_tasks::{primitives::StringVc, ValueToString, ValueToStringVc};
+use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{
         ChunkItem, ChunkItemVc, ChunkVc, ChunkableAsset, ChunkableAssetReference,
         ChunkableAssetReferenceVc, ChunkableAssetVc, ChunkingContextVc, ChunkingType,
-        ChunkingTypeOptionVc,
+        ChunkingTypeOptionVc, ModuleId,
     },
     context::AssetContextVc,
     reference::{AssetReference, AssetReferenceVc, AssetReferencesVc},
+    reference_type::CssReferenceSubType,
     resolve::{
         origin::{ResolveOrigin, ResolveOriginVc},
+        parse::RequestVc,
         ResolveResult, ResolveResultVc,
     },
 };
@@ -24,10 +27,12 @@ use turbopack_ecmascript::{
         EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
         EcmascriptChunkVc, EcmascriptExports, EcmascriptExportsVc,
     },
-    utils::stringify_str,
+    utils::{stringify_module_id, stringify_str},
 };
 
-use crate::{parse::ParseResult, transform::CssInputTransformsVc, CssModuleAssetVc};
+use crate::{
+    parse::ParseResult, references::css_resolve, transform::CssInputTransformsVc, CssModuleAssetVc,
+};
 
 #[turbo_tasks::value]
 #[derive(Clone)]
@@ -43,6 +48,34 @@ impl ModuleCssModuleAssetVc {
             inner: CssModuleAssetVc::new_module(source, context, transforms),
         })
     }
+
+    /// References to the other CSS modules that class names are composed
+    /// from, e. g. `composes: button from "./button.module.css"`.
+    #[turbo_tasks::function]
+    async fn compose_references(self) -> Result<AssetReferencesVc> {
+        let parsed = self.await?.inner.parse().await?;
+        let mut references = Vec::new();
+        if let ParseResult::Ok { exports, .. } = &*parsed {
+            let requests = exports
+                .values()
+                .flatten()
+                .filter_map(|class_name| match class_name {
+                    CssClassName::Import { from, .. } => Some(&**from),
+                    CssClassName::Local { .. } | CssClassName::Global { .. } => None,
+                })
+                .collect::<IndexSet<_>>();
+            for request in requests {
+                references.push(
+                    CssModuleComposeReferenceVc::new(self.into(), compose_request(request)).into(),
+                );
+            }
+        }
+        Ok(AssetReferencesVc::cell(references))
+    }
+}
+
+fn compose_request(from: &str) -> RequestVc {
+    RequestVc::parse(Value::new(from.to_string().into()))
 }
 
 #[turbo_tasks::value_impl]
@@ -58,8 +91,10 @@ impl Asset for ModuleCssModuleAsset {
     }
 
     #[turbo_tasks::function]
-    fn references(&self) -> AssetReferencesVc {
-        self.inner.references()
+    async fn references(self_vc: ModuleCssModuleAssetVc) -> Result<AssetReferencesVc> {
+        let mut references = self_vc.await?.inner.references().await?.clone_value();
+        references.extend(self_vc.compose_references().await?.iter().copied());
+        Ok(AssetReferencesVc::cell(references))
     }
 }
 
@@ -74,10 +109,13 @@ impl ChunkableAsset for ModuleCssModuleAsset {
 #[turbo_tasks::value_impl]
 impl EcmascriptChunkPlaceable for ModuleCssModuleAsset {
     #[turbo_tasks::function]
-    fn as_chunk_item(&self, context: ChunkingContextVc) -> EcmascriptChunkItemVc {
+    fn as_chunk_item(
+        self_vc: ModuleCssModuleAssetVc,
+        context: ChunkingContextVc,
+    ) -> EcmascriptChunkItemVc {
         ModuleChunkItem {
             context,
-            module: self.inner,
+            module: self_vc,
         }
         .cell()
         .into()
@@ -104,7 +142,7 @@ impl ResolveOrigin for ModuleCssModuleAsset {
 
 #[turbo_tasks::value]
 struct ModuleChunkItem {
-    module: CssModuleAssetVc,
+    module: ModuleCssModuleAssetVc,
     context: ChunkingContextVc,
 }
 
@@ -114,7 +152,7 @@ impl ValueToString for ModuleChunkItem {
     async fn to_string(&self) -> Result<StringVc> {
         Ok(StringVc::cell(format!(
             "{} (css module)",
-            self.module.await?.source.path().to_string().await?
+            self.module.path().to_string().await?
         )))
     }
 }
@@ -122,13 +160,15 @@ impl ValueToString for ModuleChunkItem {
 #[turbo_tasks::value_impl]
 impl ChunkItem for ModuleChunkItem {
     #[turbo_tasks::function]
-    fn references(&self) -> AssetReferencesVc {
-        AssetReferencesVc::cell(vec![CssProxyToCssAssetReference {
-            module: self.module,
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        let mut references = vec![CssProxyToCssAssetReference {
+            module: self.module.await?.inner,
             context: self.context,
         }
         .cell()
-        .into()])
+        .into()];
+        references.extend(self.module.compose_references().await?.iter().copied());
+        Ok(AssetReferencesVc::cell(references))
     }
 }
 
@@ -162,6 +202,56 @@ impl ChunkableAssetReference for CssProxyToCssAssetReference {
     }
 }
 
+/// A reference from a CSS module to another CSS module whose class names are
+/// composed via `composes: ... from "..."`.
+#[turbo_tasks::value]
+struct CssModuleComposeReference {
+    origin: ResolveOriginVc,
+    request: RequestVc,
+}
+
+#[turbo_tasks::value_impl]
+impl CssModuleComposeReferenceVc {
+    #[turbo_tasks::function]
+    fn new(origin: ResolveOriginVc, request: RequestVc) -> Self {
+        Self::cell(CssModuleComposeReference { origin, request })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for CssModuleComposeReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "compose {}",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for CssModuleComposeReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> ResolveResultVc {
+        css_resolve(
+            self.origin,
+            self.request,
+            Value::new(CssReferenceSubType::Compose),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAssetReference for CssModuleComposeReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self, _context: ChunkingContextVc) -> ChunkingTypeOptionVc {
+        // The composed module and its stylesheet need to be loaded together with
+        // the module that composes its class names. It's chunked like an ES
+        // import, so a module that is imported too is only chunked once.
+        ChunkingTypeOptionVc::cell(Some(ChunkingType::default()))
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl EcmascriptChunkItem for ModuleChunkItem {
     #[turbo_tasks::function]
@@ -171,24 +261,45 @@ impl EcmascriptChunkItem for ModuleChunkItem {
 
     #[turbo_tasks::function]
     async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
-        let parsed = self.module.parse().await?;
+        let parsed = self.module.await?.inner.parse().await?;
         Ok(match &*parsed {
             ParseResult::Ok { exports, .. } => {
                 let mut code = "__turbopack_export_value__({\n".to_string();
                 for (key, elements) in exports {
-                    let content = elements
-                        .iter()
-                        .map(|element| match element {
-                            CssClassName::Local { name } | CssClassName::Global { name } => &**name,
-                            CssClassName::Import { .. } => "TODO",
-                        })
-                        .collect::<Vec<_>>()
-                        .join(" ");
+                    // The class names keep their order, consecutive local names are
+                    // joined into a single string.
+                    let mut content = Vec::new();
+                    let mut names = Vec::new();
+                    for element in elements {
+                        match element {
+                            CssClassName::Local { name } | CssClassName::Global { name } => {
+                                names.push(&**name);
+                            }
+                            CssClassName::Import { name, from } => {
+                                // Unresolveable requests are already reported as issues by
+                                // the reference.
+                                let Some(id) = self.composed_module_id(from).await? else {
+                                    continue;
+                                };
+                                if !names.is_empty() {
+                                    content.push(stringify_str(&take(&mut names).join(" ")));
+                                }
+                                content.push(format!(
+                                    "__turbopack_require__({})[{}]",
+                                    stringify_module_id(&id),
+                                    stringify_str(name)
+                                ));
+                            }
+                        }
+                    }
+                    if !names.is_empty() || content.is_empty() {
+                        content.push(stringify_str(&names.join(" ")));
+                    }
                     writeln!(
                         code,
                         "  {}: {},",
                         stringify_str(key),
-                        stringify_str(&content)
+                        content.join(r#" + " " + "#)
                     )?;
                 }
                 code += "});\n";
@@ -211,3 +322,18 @@ impl EcmascriptChunkItem for ModuleChunkItem {
         .cell())
     }
 }
+
+impl ModuleChunkItem {
+    /// Returns the module id of the chunk item of the CSS module that a class
+    /// name is composed from.
+    async fn composed_module_id(&self, from: &str) -> Result<Option<ModuleId>> {
+        let reference = CssModuleComposeReferenceVc::new(self.module.into(), compose_request(from));
+        for asset in reference.resolve_reference().primary_assets().await?.iter() {
+            if let Some(placeable) = EcmascriptChunkPlaceableVc::resolve_from(*asset).await? {
+                let id = placeable.as_chunk_item(self.context).id().await?;
+                return Ok(Some(id.clone_value()));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -247,10 +247,13 @@ impl CodeGenerateable for ImportAssetReference {
             remainder,
         } = &*this.request.await?
         {
-            imports.push(CssImport::External(StringVc::cell(format!(
-                "{}{}",
-                protocol, remainder
-            ))))
+            // data URIs are inlined into the chunk
+            if protocol != "data:" {
+                imports.push(CssImport::External(StringVc::cell(format!(
+                    "{}{}",
+                    protocol, remainder
+                ))))
+            }
         }
 
         Ok(CodeGeneration {
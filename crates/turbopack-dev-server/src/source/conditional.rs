@@ -17,7 +17,7 @@ use crate::source::ContentSourcesVc;
 /// pages. Here HTML and "other assets" are in different content sources. So we
 /// use this source to only serve (and process) "other assets" when the HTML was
 /// served once.
-#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+#[turbo_tasks::value(eq = "manual", cell = "new")]
 pub struct ConditionalContentSource {
     activator: ContentSourceVc,
     action: ContentSourceVc,
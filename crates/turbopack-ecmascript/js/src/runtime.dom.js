@@ -33,5 +33,16 @@ const BACKEND = {
     });
   },
 
+  loadWasm(chunkPath, _from) {
+    return fetch(`/${chunkPath}`)
+      .then((response) => {
+        if (!response.ok) {
+          throw new Error(`${response.status} ${response.statusText}`);
+        }
+        return response.arrayBuffer();
+      })
+      .then((buffer) => WebAssembly.compile(buffer));
+  },
+
   restart: () => self.location.reload(),
 };
@@ -1,18 +1,5 @@
-/* eslint-disable @next/next/no-assign-module-variable */
-
-/** @typedef {import('../types').ChunkRegistration} ChunkRegistration */
-/** @typedef {import('../types').ModuleFactory} ModuleFactory */
-
-/** @typedef {import('../types').ChunkPath} ChunkPath */
-/** @typedef {import('../types').ModuleId} ModuleId */
-/** @typedef {import('../types').GetFirstModuleChunk} GetFirstModuleChunk */
-
-/** @typedef {import('../types').Module} Module */
-/** @typedef {import('../types').Exports} Exports */
-/** @typedef {import('../types').EsmInteropNamespace} EsmInteropNamespace */
-/** @typedef {import('../types').Runnable} Runnable */
-
-/** @typedef {import('../types').Runtime} Runtime */
+// This is the hot module replacement part of the development runtime. It is
+// appended to `runtime.base.js`.
 
 /** @typedef {import('../types').RefreshHelpers} RefreshHelpers */
 /** @typedef {import('../types/hot').Hot} Hot */
@@ -23,27 +10,8 @@
 /** @typedef {import('../types/protocol').EcmascriptChunkUpdate} EcmascriptChunkUpdate */
 /** @typedef {import('../types/protocol').HmrUpdateEntry} HmrUpdateEntry */
 
-/** @typedef {import('../types/runtime').Loader} Loader */
 /** @typedef {import('../types/runtime').ModuleEffect} ModuleEffect */
 
-/** @type {Array<Runnable>} */
-let runnable = [];
-/** @type {Object.<ModuleId, ModuleFactory>} */
-const moduleFactories = { __proto__: null };
-/** @type {Object.<ModuleId, Module>} */
-const moduleCache = { __proto__: null };
-/**
- * Contains the IDs of all chunks that have been loaded.
- *
- * @type {Set<ChunkPath>}
- */
-const loadedChunks = new Set();
-/**
- * Maps a chunk ID to the chunk's loader if the chunk is currently being loaded.
- *
- * @type {Map<ChunkPath, Loader>}
- */
-const chunkLoaders = new Map();
 /**
  * Maps module IDs to persisted data between executions of their hot module
  * implementation (`hot.data`).
@@ -63,219 +31,6 @@ const moduleHotState = new Map();
  * @type {Set<ModuleId>}
  */
 const runtimeModules = new Set();
-/**
- * Map from module ID to the chunks that contain this module.
- *
- * In HMR, we need to keep track of which modules are contained in which so
- * chunks. This is so we don't eagerly dispose of a module when it is removed
- * from chunk A, but still exists in chunk B.
- *
- * @type {Map<ModuleId, Set<ChunkPath>>}
- */
-const moduleChunksMap = new Map();
-const hOP = Object.prototype.hasOwnProperty;
-const _process =
-  typeof process !== "undefined"
-    ? process
-    : {
-        env: {},
-        // Some modules rely on `process.browser` to execute browser-specific code.
-        // NOTE: `process.browser` is specific to Webpack.
-        browser: true,
-      };
-
-const toStringTag = typeof Symbol !== "undefined" && Symbol.toStringTag;
-
-/**
- * @param {any} obj
- * @param {PropertyKey} name
- * @param {PropertyDescriptor & ThisType<any>} options
- */
-function defineProp(obj, name, options) {
-  if (!hOP.call(obj, name)) Object.defineProperty(obj, name, options);
-}
-
-/**
- * Adds the getters to the exports object
- *
- * @param {Exports} exports
- * @param {Record<string, () => any>} getters
- */
-function esm(exports, getters) {
-  defineProp(exports, "__esModule", { value: true });
-  if (toStringTag) defineProp(exports, toStringTag, { value: "Module" });
-  for (const key in getters) {
-    defineProp(exports, key, { get: getters[key], enumerable: true });
-  }
-}
-
-/**
- * Adds the getters to the exports object
- *
- * @param {Exports} exports
- * @param {Record<string, any>} props
- */
-function cjs(exports, props) {
-  for (const key in props) {
-    defineProp(exports, key, { get: () => props[key], enumerable: true });
-  }
-}
-
-/**
- * @param {Module} module
- * @param {any} value
- */
-function exportValue(module, value) {
-  module.exports = value;
-}
-
-/**
- * @param {Record<string, any>} obj
- * @param {string} key
- */
-function createGetter(obj, key) {
-  return () => obj[key];
-}
-
-/**
- * @param {Exports} raw
- * @param {EsmInteropNamespace} ns
- * @param {boolean} [allowExportDefault]
- */
-function interopEsm(raw, ns, allowExportDefault) {
-  /** @type {Object.<string, () => any>} */
-  const getters = { __proto__: null };
-  for (const key in raw) {
-    getters[key] = createGetter(raw, key);
-  }
-  if (!(allowExportDefault && "default" in getters)) {
-    getters["default"] = () => raw;
-  }
-  esm(ns, getters);
-}
-
-/**
- * @param {Module} sourceModule
- * @param {ModuleId} id
- * @param {boolean} allowExportDefault
- * @returns {EsmInteropNamespace}
- */
-function esmImport(sourceModule, id, allowExportDefault) {
-  const module = getOrInstantiateModuleFromParent(id, sourceModule);
-  const raw = module.exports;
-  if (raw.__esModule) return raw;
-  if (module.interopNamespace) return module.interopNamespace;
-  const ns = (module.interopNamespace = {});
-  interopEsm(raw, ns, allowExportDefault);
-  return ns;
-}
-
-/**
- * @param {Module} sourceModule
- * @param {ModuleId} id
- * @returns {Exports}
- */
-function commonJsRequire(sourceModule, id) {
-  return getOrInstantiateModuleFromParent(id, sourceModule).exports;
-}
-
-function externalRequire(id, esm) {
-  let raw;
-  try {
-    raw = require(id);
-  } catch (err) {
-    // TODO(alexkirsz) This can happen when a client-side module tries to load
-    // an external module we don't provide a shim for (e.g. querystring, url).
-    // For now, we fail semi-silently, but in the future this should be a
-    // compilation error.
-    throw new Error(`Failed to load external module ${id}: ${err}`);
-  }
-  if (!esm || raw.__esModule) {
-    return raw;
-  }
-  const ns = {};
-  interopEsm(raw, ns, true);
-  return ns;
-}
-
-/**
- * @param {ModuleId} from
- * @param {string} chunkPath
- * @returns {Promise<any> | undefined}
- */
-function loadChunk(from, chunkPath) {
-  if (loadedChunks.has(chunkPath)) {
-    return Promise.resolve();
-  }
-
-  const chunkLoader = getOrCreateChunkLoader(chunkPath, from);
-
-  return chunkLoader.promise;
-}
-
-/**
- * @param {string} chunkPath
- * @param {ModuleId} from
- * @returns {Loader}
- */
-function getOrCreateChunkLoader(chunkPath, from) {
-  let chunkLoader = chunkLoaders.get(chunkPath);
-  if (chunkLoader) {
-    return chunkLoader;
-  }
-
-  let resolve;
-  let reject;
-  const promise = new Promise((innerResolve, innerReject) => {
-    resolve = innerResolve;
-    reject = innerReject;
-  });
-
-  const onError = (error) => {
-    chunkLoaders.delete(chunkPath);
-    reject(
-      new Error(
-        `Failed to load chunk from ${chunkPath}${error ? `: ${error}` : ""}`
-      )
-    );
-  };
-
-  const onLoad = () => {
-    loadedChunks.add(chunkPath);
-    chunkLoaders.delete(chunkPath);
-    resolve();
-  };
-
-  chunkLoader = {
-    promise,
-    onLoad,
-  };
-  chunkLoaders.set(chunkPath, chunkLoader);
-
-  BACKEND.loadChunk(chunkPath, from).then(onLoad, onError);
-
-  return chunkLoader;
-}
-
-/**
- * @enum {number}
- */
-const SourceType = {
-  /**
-   * The module was instantiated because it was included in an evaluated chunk's
-   * runtime.
-   */
-  Runtime: 0,
-  /**
-   * The module was instantiated because a parent module imported it.
-   */
-  Parent: 1,
-  /**
-   * The module was instantiated because it was included in a chunk's hot module
-   * update.
-   */
-  Update: 2,
-};
 
 /**
  *
@@ -333,28 +88,10 @@ function instantiateModule(id, sourceType, sourceId) {
   }
 
   runModuleExecutionHooks(module, () => {
-    moduleFactory.call(module.exports, {
-      e: module.exports,
-      r: commonJsRequire.bind(null, module),
-      x: externalRequire,
-      i: esmImport.bind(null, module),
-      s: esm.bind(null, module.exports),
-      j: cjs.bind(null, module.exports),
-      v: exportValue.bind(null, module),
-      m: module,
-      c: moduleCache,
-      l: loadChunk.bind(null, id),
-      p: _process,
-      g: globalThis,
-      __dirname: module.id.replace(/(^|\/)[\/]+$/, ""),
-    });
+    runModuleFactory(moduleFactory, module);
   });
 
-  module.loaded = true;
-  if (module.interopNamespace) {
-    // in case of a circular dependency: cjs1 -> esm2 -> cjs1
-    interopEsm(module.exports, module.interopNamespace);
-  }
+  finishModuleInstantiation(module);
 
   return module;
 }
@@ -878,36 +615,6 @@ function createModuleHot(hotData) {
   return { hot, hotState };
 }
 
-/**
- * Adds a module to a chunk.
- *
- * @param {ModuleId} moduleId
- * @param {ChunkPath} chunkPath
- */
-function addModuleToChunk(moduleId, chunkPath) {
-  let moduleChunks = moduleChunksMap.get(moduleId);
-  if (!moduleChunks) {
-    moduleChunks = new Set([chunkPath]);
-    moduleChunksMap.set(moduleId, moduleChunks);
-  } else {
-    moduleChunks.add(chunkPath);
-  }
-}
-
-/**
- * Returns the first chunk that included a module.
- *
- * @type {GetFirstModuleChunk}
- */
-function getFirstModuleChunk(moduleId) {
-  const moduleChunkPaths = moduleChunksMap.get(moduleId);
-  if (moduleChunkPaths == null) {
-    return null;
-  }
-
-  return moduleChunkPaths.values().next().value;
-}
-
 /**
  * Removes a module from a chunk. Returns true there are no remaining chunks
  * including this module.
@@ -928,18 +635,6 @@ function removeModuleFromChunk(moduleId, chunkPath) {
   return true;
 }
 
-/**
- * Instantiates a runtime module.
- */
-/**
- *
- * @param {ModuleId} moduleId
- * @returns {Module}
- */
-function instantiateRuntimeModule(moduleId) {
-  return instantiateModule(moduleId, SourceType.Runtime);
-}
-
 /**
  * Subscribes to chunk updates from the update server and applies them.
  *
@@ -953,28 +648,6 @@ function subscribeToChunkUpdates(chunkPath) {
   ]);
 }
 
-function markChunkAsLoaded(chunkPath) {
-  const chunkLoader = chunkLoaders.get(chunkPath);
-  if (!chunkLoader) {
-    loadedChunks.add(chunkPath);
-
-    // This happens for all initial chunks that are loaded directly from
-    // the HTML.
-    return;
-  }
-
-  // Only chunks that are loaded via `loadChunk` will have a loader.
-  chunkLoader.onLoad();
-}
-
-/** @type {Runtime} */
-const runtime = {
-  loadedChunks,
-  modules: moduleFactories,
-  cache: moduleCache,
-  instantiateRuntimeModule,
-};
-
 /**
  * @param {ChunkRegistration} chunkRegistration
  */
@@ -23,6 +23,23 @@ const BACKEND = {
     });
   },
 
+  loadWasm(chunkPath, from) {
+    const fromPath = getFirstModuleChunk(from);
+    if (fromPath == null) {
+      return Promise.reject(
+        `Module ${from} that requested chunk ${chunkPath} has been removed`
+      );
+    }
+
+    const path = require("path");
+    const resolved = require.resolve(
+      "./" + path.relative(path.dirname(fromPath), chunkPath)
+    );
+    return require("fs")
+      .promises.readFile(resolved)
+      .then((buffer) => WebAssembly.compile(buffer));
+  },
+
   restart: () => {
     throw new Error("restart not implemented for the Node.js backend");
   },
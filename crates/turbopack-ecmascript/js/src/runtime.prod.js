@@ -0,0 +1,96 @@
+// This is the production part of the runtime, for chunks with content-hashed
+// paths. It is appended to `runtime.base.js` and has no hot module replacement
+// support.
+
+/**
+ *
+ * @param {ModuleId} id
+ * @param {SourceType} sourceType
+ * @param {ModuleId} [sourceId]
+ * @returns {Module}
+ */
+function instantiateModule(id, sourceType, sourceId) {
+  const moduleFactory = moduleFactories[id];
+  if (typeof moduleFactory !== "function") {
+    let instantiationReason;
+    switch (sourceType) {
+      case SourceType.Runtime:
+        instantiationReason = "as a runtime entry";
+        break;
+      case SourceType.Parent:
+        instantiationReason = `because it was required from module ${sourceId}`;
+        break;
+    }
+    throw new Error(
+      `Module ${id} was instantiated ${instantiationReason}, but the module factory is not available.`
+    );
+  }
+
+  /** @type {Module} */
+  const module = {
+    exports: {},
+    loaded: false,
+    id,
+    parents: [],
+    children: [],
+    interopNamespace: undefined,
+  };
+  moduleCache[id] = module;
+
+  if (sourceType === SourceType.Parent) {
+    module.parents.push(sourceId);
+
+    // No need to add this module as a child of the parent module here, this
+    // has already been taken care of in `getOrInstantiateModuleFromParent`.
+  }
+
+  runModuleFactory(moduleFactory, module);
+  finishModuleInstantiation(module);
+
+  return module;
+}
+
+/**
+ * Retrieves a module from the cache, or instantiate it if it is not cached.
+ *
+ * @param {ModuleId} id
+ * @param {Module} sourceModule
+ * @returns {Module}
+ */
+function getOrInstantiateModuleFromParent(id, sourceModule) {
+  const module = moduleCache[id];
+
+  if (sourceModule.children.indexOf(id) === -1) {
+    sourceModule.children.push(id);
+  }
+
+  if (module) {
+    if (module.parents.indexOf(sourceModule.id) === -1) {
+      module.parents.push(sourceModule.id);
+    }
+
+    return module;
+  }
+
+  return instantiateModule(id, SourceType.Parent, sourceModule.id);
+}
+
+/**
+ * @param {ChunkRegistration} chunkRegistration
+ */
+function registerChunk([chunkPath, chunkModules, ...run]) {
+  markChunkAsLoaded(chunkPath);
+  for (const [moduleId, moduleFactory] of Object.entries(chunkModules)) {
+    if (!moduleFactories[moduleId]) {
+      moduleFactories[moduleId] = moduleFactory;
+    }
+    addModuleToChunk(moduleId, chunkPath);
+  }
+  runnable.push(...run);
+  runnable = runnable.filter((r) => r(runtime));
+}
+
+globalThis.TURBOPACK.forEach(registerChunk);
+globalThis.TURBOPACK = {
+  push: registerChunk,
+};
@@ -0,0 +1,39 @@
+/** @typedef {import('../types/backend').RuntimeBackend} RuntimeBackend */
+
+/** @type {RuntimeBackend} */
+const BACKEND = {
+  loadChunk(chunkPath, _from) {
+    if (chunkPath.endsWith(".css")) {
+      // Workers have no document to apply styles to, so CSS chunks are marked
+      // as loaded instantly.
+      return Promise.resolve();
+    } else if (chunkPath.endsWith(".js")) {
+      // `importScripts` executes the chunk synchronously, which registers it
+      // with the runtime. It is deferred so that chunks which are loaded while
+      // the runtime is still initializing register with the runtime itself
+      // rather than with the list of pending chunks.
+      return Promise.resolve().then(() => {
+        importScripts(`/${chunkPath}`);
+      });
+    } else {
+      return Promise.reject(
+        new Error(`can't infer type of chunk from path ${chunkPath}`)
+      );
+    }
+  },
+
+  loadWasm(chunkPath, _from) {
+    return fetch(`/${chunkPath}`)
+      .then((response) => {
+        if (!response.ok) {
+          throw new Error(`${response.status} ${response.statusText}`);
+        }
+        return response.arrayBuffer();
+      })
+      .then((buffer) => WebAssembly.compile(buffer));
+  },
+
+  restart: () => {
+    throw new Error("restart not implemented for the web worker backend");
+  },
+};
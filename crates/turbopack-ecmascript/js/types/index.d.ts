@@ -46,6 +46,10 @@ type EsmExport = (exportGetters: Record<string, () => any>) => void;
 type ExportValue = (value: any) => void;
 
 type LoadChunk = (chunkPath: ChunkPath) => Promise<any> | undefined;
+type InstantiateWasm = (
+  chunkPath: ChunkPath,
+  importsObj: WebAssembly.Imports
+) => WebAssembly.Exports;
 
 interface TurbopackContext {
   e: Module["exports"];
@@ -56,6 +60,7 @@ interface TurbopackContext {
   m: Module;
   c: ModuleCache;
   l: LoadChunk;
+  w: InstantiateWasm;
   p: Partial<NodeJS.Process> & Pick<NodeJS.Process, "env">;
 }
 
@@ -69,6 +74,7 @@ type ModuleFactoryString = string;
 
 interface Runtime {
   loadedChunks: Set<ChunkPath>;
+  loadChunk: (from: ModuleId, chunkPath: ChunkPath) => Promise<any> | undefined;
   modules: Record<ModuleId, ModuleFactory>;
   cache: Record<string, Module>;
 
@@ -77,6 +83,7 @@ interface Runtime {
 
 interface RuntimeBackend {
   loadChunk: (chunkPath: ChunkPath, from: ModuleId) => Promise<void>;
+  loadWasm: (chunkPath: ChunkPath, from: ModuleId) => Promise<WebAssembly.Module>;
 
   restart: () => void;
 }
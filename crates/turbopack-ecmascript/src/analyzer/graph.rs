@@ -51,6 +51,19 @@ pub enum Effect {
         ast_path: Vec<AstParentKind>,
         span: Span,
     },
+    /// A `new URL(input, import.meta.url)` that is passed to a `Worker` or
+    /// `SharedWorker` constructor.
+    Worker {
+        input: JsValue,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+    },
+    /// An `import.meta.glob(patterns, options)` call.
+    ImportMetaGlob {
+        args: Vec<JsValue>,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+    },
 }
 
 impl Effect {
@@ -103,9 +116,23 @@ impl Effect {
                 input,
                 ast_path: _,
                 span: _,
+            }
+            | Effect::Worker {
+                input,
+                ast_path: _,
+                span: _,
             } => {
                 input.normalize();
             }
+            Effect::ImportMetaGlob {
+                args,
+                ast_path: _,
+                span: _,
+            } => {
+                for arg in args.iter_mut() {
+                    arg.normalize();
+                }
+            }
         }
     }
 }
@@ -427,6 +454,89 @@ impl EvalContext {
             _ => JsValue::Unknown(None, "unsupported expression"),
         }
     }
+
+    /// Evaluates the truthiness of a condition, when it's known at compile
+    /// time and evaluating it has no side effects. That's the case for e. g.
+    /// `"production" !== "production"` after compile time defines have been
+    /// replaced.
+    pub fn eval_condition(&self, e: &Expr) -> Option<bool> {
+        match unparen(e) {
+            Expr::Bin(BinExpr {
+                op: op!("&&"),
+                left,
+                right,
+                ..
+            }) => match self.eval_condition(left)? {
+                true => self.eval_condition(right),
+                false => Some(false),
+            },
+            Expr::Bin(BinExpr {
+                op: op!("||"),
+                left,
+                right,
+                ..
+            }) => match self.eval_condition(left)? {
+                true => Some(true),
+                false => self.eval_condition(right),
+            },
+            e => self.eval_constant(e).map(|value| value.is_truthy()),
+        }
+    }
+
+    /// Evaluates an expression without side effects to a constant.
+    fn eval_constant(&self, e: &Expr) -> Option<ConstantValue> {
+        match unparen(e) {
+            e @ (Expr::Lit(..) | Expr::Tpl(..)) => match self.eval(e) {
+                JsValue::Constant(value) => Some(value),
+                _ => None,
+            },
+            Expr::Ident(ident)
+                if &*ident.sym == "undefined" && is_unresolved(ident, self.unresolved_mark) =>
+            {
+                Some(ConstantValue::Undefined)
+            }
+            Expr::Unary(UnaryExpr {
+                op: op!("!"), arg, ..
+            }) => Some(if self.eval_condition(arg)? {
+                ConstantValue::False
+            } else {
+                ConstantValue::True
+            }),
+            Expr::Unary(UnaryExpr {
+                op: op!("typeof"),
+                arg,
+                ..
+            }) => Some(ConstantValue::StrWord(
+                self.eval_constant(arg)?.type_of().into(),
+            )),
+            Expr::Unary(UnaryExpr {
+                op: op!("void"),
+                arg,
+                ..
+            }) => {
+                self.eval_constant(arg)?;
+                Some(ConstantValue::Undefined)
+            }
+            Expr::Bin(BinExpr {
+                op, left, right, ..
+            }) => {
+                let left = self.eval_constant(left)?;
+                let right = self.eval_constant(right)?;
+                let equals = match op {
+                    op!("===") | op!("!==") => left.strict_equals(&right),
+                    op!("==") | op!("!=") => left.loose_equals(&right)?,
+                    _ => return None,
+                };
+                let negated = matches!(op, op!("!==") | op!("!="));
+                Some(if equals != negated {
+                    ConstantValue::True
+                } else {
+                    ConstantValue::False
+                })
+            }
+            _ => None,
+        }
+    }
 }
 
 struct Analyzer<'a> {
@@ -451,6 +561,32 @@ pub fn as_parent_path(ast_path: &AstNodePath<AstParentNodeRef<'_>>) -> Vec<AstPa
 }
 
 impl Analyzer<'_> {
+    /// Checks whether `new_expr` is the first argument of a `new Worker(...)`
+    /// or `new SharedWorker(...)` expression.
+    fn is_worker_constructor_argument(
+        &self,
+        new_expr: &NewExpr,
+        ast_path: &AstNodePath<AstParentNodeRef<'_>>,
+    ) -> bool {
+        // The path ends with the argument list entry of the worker constructor, the
+        // argument and the expression itself.
+        let Some(AstParentNodeRef::NewExpr(parent, NewExprField::Args)) =
+            ast_path.iter().rev().nth(2)
+        else {
+            return false;
+        };
+        let Expr::Ident(callee) = &*parent.callee else {
+            return false;
+        };
+        (&*callee.sym == "Worker" || &*callee.sym == "SharedWorker")
+            && is_unresolved(callee, self.eval_context.unresolved_mark)
+            && matches!(
+                parent.args.as_deref(),
+                Some([ExprOrSpread { spread: None, expr: box Expr::New(arg) }, ..])
+                    if std::ptr::eq(arg, new_expr)
+            )
+    }
+
     fn add_value(&mut self, id: Id, value: JsValue) {
         if let Some(prev) = self.data.values.get_mut(&id) {
             prev.add_alt(value);
@@ -694,6 +830,23 @@ impl Analyzer<'_> {
             }
             Callee::Expr(box expr) => {
                 if let Expr::Member(MemberExpr { obj, prop, .. }) = unparen(expr) {
+                    if let (
+                        Expr::MetaProp(MetaPropExpr {
+                            kind: MetaPropKind::ImportMeta,
+                            ..
+                        }),
+                        MemberProp::Ident(prop),
+                    ) = (&**obj, prop)
+                    {
+                        if &*prop.sym == "glob" {
+                            self.data.effects.push(Effect::ImportMetaGlob {
+                                args,
+                                ast_path: as_parent_path(ast_path),
+                                span: n.span(),
+                            });
+                            return;
+                        }
+                    }
                     let obj_value = self.eval_context.eval(obj);
                     let prop_value = match prop {
                         // TODO avoid clone
@@ -841,11 +994,24 @@ impl VisitAstPath for Analyzer<'_> {
                         }) = &*args[1].expr
                         {
                             if &*prop.sym == "url" {
-                                self.data.effects.push(Effect::Url {
-                                    input: self.eval_context.eval(&args[0].expr),
-                                    ast_path: as_parent_path(ast_path),
-                                    span: new_expr.span(),
-                                });
+                                let input = self.eval_context.eval(&args[0].expr);
+                                let span = new_expr.span();
+                                // new Worker(new URL("path", import.meta.url))
+                                self.data.effects.push(
+                                    if self.is_worker_constructor_argument(new_expr, ast_path) {
+                                        Effect::Worker {
+                                            input,
+                                            ast_path: as_parent_path(ast_path),
+                                            span,
+                                        }
+                                    } else {
+                                        Effect::Url {
+                                            input,
+                                            ast_path: as_parent_path(ast_path),
+                                            span,
+                                        }
+                                    },
+                                );
                             }
                         }
                     }
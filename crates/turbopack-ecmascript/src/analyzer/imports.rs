@@ -138,6 +138,21 @@ impl ImportMap {
         None
     }
 
+    /// Returns the symbols that are imported from the reference with the given
+    /// index, or `None` when the reference is imported as namespace.
+    pub fn imported_symbols(&self, i: usize) -> Option<Vec<&JsWord>> {
+        if self.namespace_imports.values().any(|&j| j == i) {
+            return None;
+        }
+        Some(
+            self.imports
+                .values()
+                .filter(|(j, _)| *j == i)
+                .map(|(_, symbol)| symbol)
+                .collect(),
+        )
+    }
+
     pub fn references(&self) -> impl Iterator<Item = (&JsWord, &ImportAnnotations)> {
         self.references.iter().map(|(m, a)| (m, a))
     }
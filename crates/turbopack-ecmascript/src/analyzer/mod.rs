@@ -9,7 +9,7 @@ use std::{
 };
 
 use indexmap::IndexSet;
-use num_bigint::BigInt;
+use num_bigint::{BigInt, Sign};
 use swc_core::{
     common::Mark,
     ecma::{
@@ -97,6 +97,57 @@ impl ConstantValue {
             _ => None,
         }
     }
+
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Undefined | Self::False | Self::Null => false,
+            Self::StrWord(s) => !s.is_empty(),
+            Self::StrAtom(s) => !s.is_empty(),
+            Self::Num(ConstantNumber(n)) => *n != 0.0 && !n.is_nan(),
+            Self::BigInt(n) => n.sign() != Sign::NoSign,
+            Self::True | Self::Regex(..) => true,
+        }
+    }
+
+    /// The result of `typeof` for the constant.
+    pub fn type_of(&self) -> &'static str {
+        match self {
+            Self::Undefined => "undefined",
+            Self::StrWord(..) | Self::StrAtom(..) => "string",
+            Self::Num(..) => "number",
+            Self::True | Self::False => "boolean",
+            Self::Null | Self::Regex(..) => "object",
+            Self::BigInt(..) => "bigint",
+        }
+    }
+
+    /// Compares two constants with `===`.
+    pub fn strict_equals(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Num(ConstantNumber(a)), Self::Num(ConstantNumber(b))) => a == b,
+            (Self::BigInt(a), Self::BigInt(b)) => a == b,
+            // Every evaluation of a regex literal creates a new object.
+            (Self::Regex(..), _) | (_, Self::Regex(..)) => false,
+            _ => match (self.as_str(), other.as_str()) {
+                (Some(a), Some(b)) => a == b,
+                (None, None) => self == other,
+                _ => false,
+            },
+        }
+    }
+
+    /// Compares two constants with `==`, when the result doesn't depend on
+    /// type coercion rules which aren't implemented.
+    pub fn loose_equals(&self, other: &Self) -> Option<bool> {
+        let nullish = |value: &Self| matches!(value, Self::Undefined | Self::Null);
+        if nullish(self) || nullish(other) {
+            Some(nullish(self) && nullish(other))
+        } else if self.type_of() == other.type_of() {
+            Some(self.strict_equals(other))
+        } else {
+            None
+        }
+    }
 }
 
 impl Default for ConstantValue {
@@ -912,6 +963,7 @@ impl JsValue {
                     ),
                     WellKnownFunctionKind::Require => ("require".to_string(), "The require method from CommonJS"),
                     WellKnownFunctionKind::RequireResolve => ("require.resolve".to_string(), "The require.resolve method from CommonJS"),
+                    WellKnownFunctionKind::RequireContext => ("require.context".to_string(), "The require.context method from webpack: https://webpack.js.org/guides/dependency-management/#requirecontext"),
                     WellKnownFunctionKind::Define => ("define".to_string(), "The define method from AMD"),
                     WellKnownFunctionKind::FsReadMethod(name) => (
                         format!("fs.{name}"),
@@ -1891,6 +1943,7 @@ pub enum WellKnownFunctionKind {
     Import,
     Require,
     RequireResolve,
+    RequireContext,
     Define,
     FsReadMethod(JsWord),
     PathToFileUrl,
@@ -1,9 +1,16 @@
-use std::io::Write as _;
+use std::{
+    collections::{HashSet, VecDeque},
+    io::Write as _,
+};
 
 use anyhow::{anyhow, bail, Result};
 use indexmap::IndexSet;
-use turbo_tasks::{primitives::StringVc, ValueToString, ValueToStringVc};
+use turbo_tasks::{
+    primitives::{StringVc, U64Vc},
+    ValueToString, ValueToStringVc,
+};
 use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks_hash::Xxh3Hash64Hasher;
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{
@@ -46,6 +53,13 @@ impl ManifestLoaderItemVc {
     pub fn new(context: ChunkingContextVc, manifest: ManifestChunkAssetVc) -> Self {
         Self::cell(ManifestLoaderItem { context, manifest })
     }
+
+    /// The content hash of the chunk group loaded by this item, see
+    /// [ManifestChunkAssetVc::content_hash].
+    #[turbo_tasks::function]
+    pub(crate) async fn manifest_content_hash(self) -> Result<U64Vc> {
+        Ok(self.await?.manifest.content_hash())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -160,11 +174,51 @@ impl ManifestChunkAssetVc {
     }
 
     #[turbo_tasks::function]
-    async fn chunks(self) -> Result<ChunksVc> {
+    pub(crate) async fn chunks(self) -> Result<ChunksVc> {
         let this = self.await?;
         let chunk_group = ChunkGroupVc::from_asset(this.asset, this.chunking_context);
         Ok(chunk_group.chunks())
     }
+
+    /// Hashes the generated code of the chunk group of the imported asset and
+    /// of all chunk groups it dynamically imports, transitively.
+    ///
+    /// Manifest loader items only contribute the identity of the manifest they
+    /// load, not its chunk path, so the hash never depends on a content hashed
+    /// chunk path. That allows to use it for the paths of chunks which
+    /// dynamically import each other, or themselves, without cycles.
+    #[turbo_tasks::function]
+    pub(crate) async fn content_hash(self) -> Result<U64Vc> {
+        let mut hasher = Xxh3Hash64Hasher::new();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([self]);
+        while let Some(manifest) = queue.pop_front() {
+            if !visited.insert(manifest) {
+                continue;
+            }
+            hasher.write_ref(&*manifest.path().to_string().await?);
+            for chunk in manifest.chunks().await?.iter() {
+                let Some(chunk) = EcmascriptChunkVc::resolve_from(chunk).await? else {
+                    // Other chunks, e.g. CSS chunks, can't import anything
+                    // dynamically, so their content hashed path covers them.
+                    hasher.write_ref(&*chunk.path().to_string().await?);
+                    continue;
+                };
+                for entry in chunk.content_entries().await?.iter() {
+                    if let Some(loader) =
+                        ManifestLoaderItemVc::resolve_from(entry.chunk_item).await?
+                    {
+                        let loaded = loader.await?.manifest.resolve().await?;
+                        hasher.write_ref(&*loaded.path().to_string().await?);
+                        queue.push_back(loaded);
+                    } else {
+                        hasher.write_value(entry.hash);
+                    }
+                }
+            }
+        }
+        Ok(U64Vc::cell(hasher.finish()))
+    }
 }
 
 #[turbo_tasks::value_impl]
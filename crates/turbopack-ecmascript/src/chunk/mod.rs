@@ -211,12 +211,19 @@ impl EcmascriptChunkEvaluateVc {
         let mut chunks_server_paths = Vec::new();
         let output_root = context.output_root().await?;
         for chunk in evaluate_chunks.iter() {
-            if let Some(ecma_chunk) = EcmascriptChunkVc::resolve_from(chunk).await? {
-                if ecma_chunk != origin_chunk {
-                    let chunk_path = &*chunk.path().await?;
-                    if let Some(chunk_server_path) = output_root.get_path_to(chunk_path) {
-                        chunks_server_paths.push(chunk_server_path.to_string());
-                    }
+            // WebAssembly chunks are not loaded by the page, so the runtime needs to
+            // load them before the entries can be evaluated. The path of the origin
+            // chunk depends on this content, so it must not be read here.
+            let is_dependency =
+                if let Some(ecma_chunk) = EcmascriptChunkVc::resolve_from(chunk).await? {
+                    ecma_chunk != origin_chunk
+                } else {
+                    chunk.path().await?.extension() == Some("wasm")
+                };
+            if is_dependency {
+                let chunk_path = &*chunk.path().await?;
+                if let Some(chunk_server_path) = output_root.get_path_to(chunk_path) {
+                    chunks_server_paths.push(chunk_server_path.to_string());
                 }
             }
         }
@@ -346,6 +353,9 @@ pub struct EcmascriptChunkContent {
     output_root: FileSystemPathVc,
     evaluate: Option<EcmascriptChunkContentEvaluateVc>,
     environment: EnvironmentVc,
+    /// Chunks with content-hashed paths can't be hot updated, as their path
+    /// changes with every update, so they get a runtime without HMR support.
+    hmr_runtime: bool,
 }
 
 #[turbo_tasks::value(transparent)]
@@ -450,12 +460,14 @@ impl EcmascriptChunkContentVc {
         let chunk_content = chunk_content.await?;
         let module_factories = chunk_content.chunk_items.to_entry_snapshot().await?;
         let output_root = context.output_root();
+        let hmr_runtime = !*context.is_content_hashing_enabled().await?;
         Ok(EcmascriptChunkContent {
             module_factories,
             chunk_path,
             output_root,
             evaluate,
             environment: context.environment(),
+            hmr_runtime,
         }
         .cell())
     }
@@ -527,6 +539,9 @@ async fn module_factory(content: EcmascriptChunkItemContentVc) -> Result<CodeVc>
     if content.options.exports {
         args.push("e: exports");
     }
+    if content.options.wasm {
+        args.push("w: __turbopack_wasm__");
+    }
     let mut code = CodeBuilder::default();
     let args = FormatIter(|| args.iter().copied().intersperse(", "));
     if content.options.this {
@@ -535,6 +550,11 @@ async fn module_factory(content: EcmascriptChunkItemContentVc) -> Result<CodeVc>
         write!(code, "(({{ {} }}) => (() => {{\n\n", args,)?;
     }
 
+    for (hoisted_code, source_map) in content.hoisted_code.iter() {
+        let source_map = source_map.map(|sm| sm.as_generate_source_map());
+        code.push_source(hoisted_code, source_map);
+        code += "\n";
+    }
     let source_map = content.source_map.map(|sm| sm.as_generate_source_map());
     code.push_source(&content.inner_code, source_map);
     if content.options.this {
@@ -596,14 +616,40 @@ impl EcmascriptChunkContentVc {
 
         if let Some(evaluate) = &this.evaluate {
             let evaluate = evaluate.await?;
-            let condition = evaluate
-                .chunks_server_paths
-                .await?
+            let chunks_server_paths = evaluate.chunks_server_paths.await?;
+            let condition = chunks_server_paths
                 .iter()
                 .map(|path| format!(" && loadedChunks.has({})", stringify_str(path)))
                 .collect::<Vec<_>>()
                 .join("");
             let entries_ids = &*evaluate.entry_modules_ids.await?;
+            // WebAssembly chunks don't register themselves like other chunks do. The
+            // runtime loads them on behalf of the entries and runs pending runnables
+            // again once they are compiled. Web workers have no HTML page that loads
+            // the chunks of the chunk group, so all of them are loaded that way.
+            let load_all_chunks = matches!(
+                *this.environment.chunk_loading().await?,
+                ChunkLoading::WebWorker
+            );
+            let mut chunk_loads = String::new();
+            if let Some(first_entry_id) = entries_ids.first() {
+                let first_entry_id = stringify_module_id(&*first_entry_id.await?);
+                for path in chunks_server_paths
+                    .iter()
+                    .filter(|path| load_all_chunks || path.ends_with(".wasm"))
+                {
+                    write!(
+                        chunk_loads,
+                        "\n    loadChunk({first_entry_id}, {});",
+                        stringify_str(path)
+                    )?;
+                }
+            }
+            let runtime_params = if chunk_loads.is_empty() {
+                "loadedChunks, instantiateRuntimeModule"
+            } else {
+                "loadedChunks, instantiateRuntimeModule, loadChunk"
+            };
             let entries_instantiations = entries_ids
                 .iter()
                 .map(|id| async move {
@@ -622,7 +668,7 @@ impl EcmascriptChunkContentVc {
             // all dependent chunks have been evaluated.
             write!(
                 code,
-                ", ({{ loadedChunks, instantiateRuntimeModule }}) => {{
+                ", ({{ {runtime_params} }}) => {{{chunk_loads}
     if(!(true{condition})) return true;
     {entries_instantiations}
 }}"
@@ -648,6 +694,7 @@ impl EcmascriptChunkContentVc {
                 ChunkLoading::None => return Err(anyhow!("unsupported environment")),
                 ChunkLoading::NodeJs => embed_file!("js/src/runtime.nodejs.js").await?,
                 ChunkLoading::Dom => embed_file!("js/src/runtime.dom.js").await?,
+                ChunkLoading::WebWorker => embed_file!("js/src/runtime.webworker.js").await?,
             };
 
             match &*specific_runtime_code {
@@ -655,13 +702,26 @@ impl EcmascriptChunkContentVc {
                 FileContent::Content(file) => code.push_source(file.content(), None),
             };
 
-            let shared_runtime_code = embed_file!("js/src/runtime.js").await?;
+            let shared_runtime_code = embed_file!("js/src/runtime.base.js").await?;
 
             match &*shared_runtime_code {
                 FileContent::NotFound => return Err(anyhow!("shared runtime code is not found")),
                 FileContent::Content(file) => code.push_source(file.content(), None),
             };
 
+            // Module instantiation and chunk registration differ depending on whether
+            // hot module replacement is supported.
+            let mode_runtime_code = if this.hmr_runtime {
+                embed_file!("js/src/runtime.js").await?
+            } else {
+                embed_file!("js/src/runtime.prod.js").await?
+            };
+
+            match &*mode_runtime_code {
+                FileContent::NotFound => return Err(anyhow!("mode runtime code is not found")),
+                FileContent::Content(file) => code.push_source(file.content(), None),
+            };
+
             code += indoc! { r#"
                 })();
             "# };
@@ -918,6 +978,16 @@ impl EcmascriptChunkVc {
         ))
     }
 
+    /// The generated code of all chunk items of the chunk.
+    #[turbo_tasks::function]
+    async fn content_entries(self) -> Result<EcmascriptChunkContentEntriesSnapshotVc> {
+        Ok(self
+            .chunk_content_result()
+            .await?
+            .chunk_items
+            .to_entry_snapshot())
+    }
+
     #[turbo_tasks::function]
     async fn chunk_items_count(self) -> Result<UsizeVc> {
         Ok(UsizeVc::cell(
@@ -978,6 +1048,41 @@ impl Asset for EcmascriptChunk {
             }
         }
         let main_entries = this.main_entries.await?;
+
+        // When content hashing is enabled, the generated code of all chunk items
+        // contributes to the hashed info, so the path changes whenever the content
+        // does. Manifest loader items contain the path of the manifest chunk they
+        // load, which would create cycles for chunk groups that import each other,
+        // so they contribute the content hash of the imported chunk group instead.
+        if *this.context.is_content_hashing_enabled().await? {
+            let mut manifest = None;
+            for entry in main_entries.iter() {
+                if let Some(entry) = ManifestChunkAssetVc::resolve_from(entry).await? {
+                    manifest = Some(entry);
+                }
+            }
+            if let Some(manifest) = manifest {
+                // Manifest chunks list the paths of the chunks they load, which are
+                // content hashed themselves.
+                for chunk in manifest.chunks().await?.iter() {
+                    hasher.write_ref(&*chunk.path().to_string().await?);
+                    need_hash = true;
+                }
+            } else {
+                let entries = self_vc.content_entries().await?;
+                for entry in entries.iter() {
+                    if let Some(loader) =
+                        ManifestLoaderItemVc::resolve_from(entry.chunk_item).await?
+                    {
+                        hasher.write_value(*loader.manifest_content_hash().await?);
+                    } else {
+                        hasher.write_value(entry.hash);
+                    }
+                    need_hash = true;
+                }
+            }
+        }
+
         // If there is only a single entry we can used that for the named info.
         // If there are multiple entries we hash them and use the common parent as named
         // info.
@@ -1167,6 +1272,9 @@ impl EcmascriptChunkPlaceablesVc {
 pub struct EcmascriptChunkItemContent {
     pub inner_code: Rope,
     pub source_map: Option<ParseResultSourceMapVc>,
+    /// The code of modules which are hoisted into the scope of this chunk
+    /// item, in execution order. It's placed before the `inner_code`.
+    pub hoisted_code: Vec<(Rope, Option<ParseResultSourceMapVc>)>,
     pub options: EcmascriptChunkItemOptions,
     pub placeholder_for_future_extensions: (),
 }
@@ -1176,6 +1284,9 @@ pub struct EcmascriptChunkItemOptions {
     pub module: bool,
     pub exports: bool,
     pub this: bool,
+    /// Whether the module needs `__turbopack_wasm__` to instantiate
+    /// WebAssembly modules.
+    pub wasm: bool,
     pub placeholder_for_future_extensions: (),
 }
 
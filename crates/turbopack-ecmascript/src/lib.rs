@@ -12,17 +12,20 @@ pub mod chunk_group_files_asset;
 pub mod code_gen;
 mod errors;
 pub mod magic_identifier;
+mod minify;
 pub(crate) mod parse;
 mod path_visitor;
 pub(crate) mod references;
 pub mod resolve;
+pub mod scope_hoist;
 pub(crate) mod special_cases;
 pub(crate) mod transform;
+pub mod tree_shake;
 pub mod typescript;
 pub mod utils;
 pub mod webpack;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, iter::once};
 
 use anyhow::Result;
 use chunk::{
@@ -36,11 +39,13 @@ use swc_core::{
     common::GLOBALS,
     ecma::{
         codegen::{text_writer::JsWriter, Emitter},
+        transforms::base::resolver,
         visit::{VisitMutWith, VisitMutWithPath},
     },
 };
 pub use transform::{
-    EcmascriptInputTransform, EcmascriptInputTransformsVc, NextJsPageExportFilter,
+    CompileTimeDefines, CompileTimeDefinesVc, EcmascriptInputTransform,
+    EcmascriptInputTransformsVc, NextJsPageExportFilter,
 };
 use turbo_tasks::{primitives::StringVc, TryJoinIterExt, Value, ValueToString, ValueToStringVc};
 use turbo_tasks_fs::FileSystemPathVc;
@@ -60,10 +65,22 @@ use self::chunk::{
     EcmascriptChunkItemContent, EcmascriptChunkItemContentVc, EcmascriptChunkItemOptions,
     EcmascriptExportsVc,
 };
-pub use self::references::AnalyzeEcmascriptModuleResultVc;
+pub use self::references::{
+    esm::{EsmExport, EsmExports, EsmExportsVc},
+    AnalyzeEcmascriptModuleResultVc,
+};
 use crate::{
     chunk::{EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc},
-    references::analyze_ecmascript_module,
+    code_gen::CodeGenerationVc,
+    references::{
+        analyze_ecmascript_module,
+        esm::{base::ReferencedAsset, EsmAssetReferenceVc, EsmBinding, EsmBindingVc},
+    },
+    scope_hoist::{
+        hoisted_index, module_hoisting, references_hoisted_module, resolve_hoisted_binding,
+        HoistedBinding, HoistedBindingsRenamer, HoistedScope, ModuleHoisting,
+    },
+    tree_shake::{module_usage, ModuleUsage},
 };
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
@@ -249,8 +266,32 @@ impl ValueToString for ModuleChunkItem {
 #[turbo_tasks::value_impl]
 impl ChunkItem for ModuleChunkItem {
     #[turbo_tasks::function]
-    fn references(&self) -> AssetReferencesVc {
-        self.module.references()
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        // The references of hoisted modules become references of the module
+        // they are hoisted into
+        let hoisted = match &*module_hoisting(self.context, self.module).await? {
+            ModuleHoisting::Root(hoisted) => hoisted.clone(),
+            _ => Vec::new(),
+        };
+        let mut references = Vec::new();
+        for module in once(self.module).chain(hoisted.iter().copied()) {
+            if let ModuleUsage::Omitted = &*module_usage(self.context, module.into()).await? {
+                continue;
+            }
+            for r in module.references().await?.iter() {
+                // Omitted modules must not end up in chunks, and hoisted
+                // modules are part of this chunk item
+                if let Some(esm_reference) = EsmAssetReferenceVc::resolve_from(r).await? {
+                    if *esm_reference.is_omitted(self.context).await?
+                        || references_hoisted_module(&hoisted, esm_reference).await?
+                    {
+                        continue;
+                    }
+                }
+                references.push(*r);
+            }
+        }
+        Ok(AssetReferencesVc::cell(references))
     }
 }
 
@@ -263,116 +304,248 @@ impl EcmascriptChunkItem for ModuleChunkItem {
 
     #[turbo_tasks::function]
     async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
-        let AnalyzeEcmascriptModuleResult {
-            references,
-            code_generation,
-            ..
-        } = &*self.module.analyze().await?;
         let context = self.context;
-        let mut code_gens = Vec::new();
-        for r in references.await?.iter() {
-            if let Some(code_gen) = CodeGenerateableVc::resolve_from(r).await? {
-                code_gens.push(code_gen.code_generation(context));
-            }
-        }
-        for c in code_generation.await?.iter() {
-            let c = c.resolve().await?;
-            code_gens.push(c.code_generation(context));
-        }
-        // need to keep that around to allow references into that
-        let code_gens = code_gens.into_iter().try_join().await?;
-        let code_gens = code_gens.iter().map(|cg| &**cg).collect::<Vec<_>>();
-        // TOOD use interval tree with references into "code_gens"
-        let mut visitors = Vec::new();
-        let mut root_visitors = Vec::new();
-        for code_gen in code_gens {
-            for (path, visitor) in code_gen.visitors.iter() {
-                if path.is_empty() {
-                    root_visitors.push(&**visitor);
-                } else {
-                    visitors.push((path, &**visitor));
+        Ok(match &*module_hoisting(context, self.module).await? {
+            ModuleHoisting::Root(hoisted) => {
+                let scope = HoistedScope::new(self.module, hoisted).await?;
+                let mut hoisted_code = Vec::new();
+                for (index, module) in hoisted.iter().enumerate() {
+                    if let ModuleUsage::Omitted = &*module_usage(context, (*module).into()).await? {
+                        continue;
+                    }
+                    let content =
+                        module_content(*module, context, ModuleScope::Hoisted(&scope, index + 1))
+                            .await?;
+                    hoisted_code.push((content.inner_code, content.source_map));
                 }
+                EcmascriptChunkItemContent {
+                    hoisted_code,
+                    ..module_content(self.module, context, ModuleScope::Root(&scope)).await?
+                }
+            }
+            ModuleHoisting::None | ModuleHoisting::Hoisted(_) => {
+                module_content(self.module, context, ModuleScope::Own).await?
             }
         }
+        .cell())
+    }
+}
 
-        let module = self.module.await?;
-        let parsed = parse(module.source, Value::new(module.ty), module.transforms).await?;
+#[derive(Clone, Copy)]
+enum ModuleScope<'a> {
+    /// The module is wrapped in its own module factory.
+    Own,
+    /// The module factory also contains the hoisted modules.
+    Root(&'a HoistedScope<'a>),
+    /// The module is hoisted into the module factory of another module, at
+    /// the index within the hoisted modules.
+    Hoisted(&'a HoistedScope<'a>, usize),
+}
 
-        if let ParseResult::Ok {
-            program,
-            source_map,
-            globals,
-            eval_context,
-            ..
-        } = &*parsed
+impl<'a> ModuleScope<'a> {
+    fn hoisted_modules(&self) -> Option<&'a HoistedScope<'a>> {
+        match *self {
+            ModuleScope::Own => None,
+            ModuleScope::Root(hoisted) | ModuleScope::Hoisted(hoisted, _) => Some(hoisted),
+        }
+    }
+}
+
+async fn module_content(
+    module_vc: EcmascriptModuleAssetVc,
+    context: ChunkingContextVc,
+    scope: ModuleScope<'_>,
+) -> Result<EcmascriptChunkItemContent> {
+    let AnalyzeEcmascriptModuleResult {
+        references,
+        code_generation,
+        ..
+    } = &*module_vc.analyze().await?;
+    let hoisted = scope.hoisted_modules();
+    let mut code_gens = Vec::new();
+    for r in references.await?.iter() {
+        // Hoisted modules don't need to be imported
+        if let (Some(hoisted), Some(esm_reference)) =
+            (hoisted, EsmAssetReferenceVc::resolve_from(r).await?)
         {
+            if references_hoisted_module(hoisted.modules, esm_reference).await? {
+                continue;
+            }
+        }
+        if let Some(code_gen) = CodeGenerateableVc::resolve_from(r).await? {
+            code_gens.push(code_gen.code_generation(context));
+        }
+    }
+    for c in code_generation.await?.iter() {
+        let c = c.resolve().await?;
+        // Unused exports are omitted, and the exports of hoisted modules are
+        // accessed through their bindings
+        if let Some(esm_exports) = EsmExportsVc::resolve_from(c).await? {
+            if !matches!(scope, ModuleScope::Hoisted(..)) {
+                let usage = module_usage(context, module_vc.into());
+                code_gens.push(esm_exports.code_generation_for_usage(context, usage));
+            }
+            continue;
+        }
+        if let Some(hoisted) = hoisted {
+            if let Some(code_gen) = hoisted_binding_code_generation(context, hoisted, c).await? {
+                code_gens.push(code_gen);
+                continue;
+            }
+        }
+        code_gens.push(c.code_generation(context));
+    }
+    // need to keep that around to allow references into that
+    let code_gens = code_gens.into_iter().try_join().await?;
+    let code_gens = code_gens.iter().map(|cg| &**cg).collect::<Vec<_>>();
+    // TOOD use interval tree with references into "code_gens"
+    let mut visitors = Vec::new();
+    let mut root_visitors = Vec::new();
+    for code_gen in code_gens {
+        for (path, visitor) in code_gen.visitors.iter() {
+            if path.is_empty() {
+                root_visitors.push(&**visitor);
+            } else {
+                visitors.push((path, &**visitor));
+            }
+        }
+    }
+
+    let module = module_vc.await?;
+    let parsed = parse(module.source, Value::new(module.ty), module.transforms).await?;
+    let minify = *context.is_minification_enabled().await?;
+
+    if let ParseResult::Ok {
+        program,
+        source_map,
+        globals,
+        eval_context,
+        top_level_mark,
+        ..
+    } = &*parsed
+    {
+        let program = GLOBALS.set(globals, || {
             let mut program = program.clone();
+            if !visitors.is_empty() {
+                program.visit_mut_with_path(
+                    &mut ApplyVisitors::new(visitors),
+                    &mut Default::default(),
+                );
+            }
+            for visitor in root_visitors {
+                program.visit_mut_with(&mut visitor.create());
+            }
+            let renamed = match scope {
+                ModuleScope::Hoisted(hoisted, index) => Some((hoisted, index)),
+                ModuleScope::Root(hoisted) if hoisted.renames_root_bindings() => Some((hoisted, 0)),
+                _ => None,
+            };
+            if let Some((hoisted, index)) = renamed {
+                // Resolve the declarations and references inserted by code
+                // generation, so they are renamed together with the
+                // declarations of the module
+                program.visit_mut_with(&mut resolver(
+                    eval_context.unresolved_mark,
+                    *top_level_mark,
+                    false,
+                ));
+                let mut renamer =
+                    HoistedBindingsRenamer::new(&program, *top_level_mark, hoisted, index);
+                program.visit_mut_with(&mut renamer);
+            }
+            if minify {
+                program = minify::minify(
+                    program,
+                    source_map,
+                    eval_context.unresolved_mark,
+                    *top_level_mark,
+                    eval_context.is_esm(),
+                    hoisted.is_some(),
+                );
+            }
+            program.visit_mut_with(&mut swc_core::ecma::transforms::base::hygiene::hygiene());
+            program.visit_mut_with(&mut swc_core::ecma::transforms::base::fixer::fixer(None));
+            program
+        });
 
-            GLOBALS.set(globals, || {
-                if !visitors.is_empty() {
-                    program.visit_mut_with_path(
-                        &mut ApplyVisitors::new(visitors),
-                        &mut Default::default(),
-                    );
-                }
-                for visitor in root_visitors {
-                    program.visit_mut_with(&mut visitor.create());
-                }
-                program.visit_mut_with(&mut swc_core::ecma::transforms::base::hygiene::hygiene());
-                program.visit_mut_with(&mut swc_core::ecma::transforms::base::fixer::fixer(None));
-            });
+        let mut bytes: Vec<u8> = vec![];
+        // TODO: Insert this as a sourceless segment so that sourcemaps aren't affected.
+        // = format!("/* {} */\n", module_vc.path().to_string().await?).into_bytes();
 
-            let mut bytes: Vec<u8> = vec![];
-            // TODO: Insert this as a sourceless segment so that sourcemaps aren't affected.
-            // = format!("/* {} */\n", self.module.path().to_string().await?).into_bytes();
+        let mut srcmap = vec![];
 
-            let mut srcmap = vec![];
+        let mut emitter = Emitter {
+            cfg: swc_core::ecma::codegen::Config {
+                minify,
+                ..Default::default()
+            },
+            cm: source_map.clone(),
+            comments: None,
+            wr: JsWriter::new(source_map.clone(), "\n", &mut bytes, Some(&mut srcmap)),
+        };
 
-            let mut emitter = Emitter {
-                cfg: swc_core::ecma::codegen::Config {
-                    ..Default::default()
-                },
-                cm: source_map.clone(),
-                comments: None,
-                wr: JsWriter::new(source_map.clone(), "\n", &mut bytes, Some(&mut srcmap)),
-            };
+        emitter.emit_program(&program)?;
 
-            emitter.emit_program(&program)?;
+        let srcmap = ParseResultSourceMap::new(source_map.clone(), srcmap).cell();
 
-            let srcmap = ParseResultSourceMap::new(source_map.clone(), srcmap).cell();
+        Ok(EcmascriptChunkItemContent {
+            inner_code: bytes.into(),
+            source_map: Some(srcmap),
+            options: if eval_context.is_esm() {
+                EcmascriptChunkItemOptions {
+                    ..Default::default()
+                }
+            } else {
+                EcmascriptChunkItemOptions {
+                    // These things are not available in ESM
+                    module: true,
+                    exports: true,
+                    this: true,
+                    ..Default::default()
+                }
+            },
+            ..Default::default()
+        })
+    } else {
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!(
+                "const e = new Error(\"Could not parse module '{path}'\");\ne.code = \
+                 'MODULE_UNPARSEABLE';\nthrow e;",
+                path = module_vc.path().to_string().await?
+            )
+            .into(),
+            ..Default::default()
+        })
+    }
+}
 
-            Ok(EcmascriptChunkItemContent {
-                inner_code: bytes.into(),
-                source_map: Some(srcmap),
-                options: if eval_context.is_esm() {
-                    EcmascriptChunkItemOptions {
-                        ..Default::default()
-                    }
-                } else {
-                    EcmascriptChunkItemOptions {
-                        // These things are not available in ESM
-                        module: true,
-                        exports: true,
-                        this: true,
-                        ..Default::default()
-                    }
-                },
-                ..Default::default()
-            }
-            .into())
-        } else {
-            Ok(EcmascriptChunkItemContent {
-                inner_code: format!(
-                    "const e = new Error(\"Could not parse module '{path}'\");\ne.code = \
-                     'MODULE_UNPARSEABLE';\nthrow e;",
-                    path = self.module.path().to_string().await?
-                )
-                .into(),
-                ..Default::default()
-            }
-            .into())
-        }
+/// Bindings to modules which are hoisted into the same scope are replaced by
+/// the bindings of the hoisted modules.
+async fn hoisted_binding_code_generation(
+    context: ChunkingContextVc,
+    hoisted: &HoistedScope<'_>,
+    code_gen: CodeGenerateableVc,
+) -> Result<Option<CodeGenerationVc>> {
+    let Some(binding) = EsmBindingVc::resolve_from(code_gen).await? else {
+        return Ok(None);
+    };
+    let EsmBinding {
+        reference, export, ..
+    } = &*binding.await?;
+    let ReferencedAsset::Some(target) = &*reference.get_referenced_asset().await? else {
+        return Ok(None);
+    };
+    if hoisted_index(hoisted.modules, *target).await?.is_none() {
+        return Ok(None);
     }
+    // Namespace imports are never hoisted
+    let hoisted_binding = match export {
+        Some(export) => resolve_hoisted_binding(context, hoisted, *target, export.clone()).await?,
+        None => HoistedBinding::Undefined,
+    };
+    Ok(Some(
+        binding.hoisted_code_generation(hoisted_binding.cell()),
+    ))
 }
 
 pub fn register() {
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use swc_core::{
+    common::{Mark, SourceMap},
+    ecma::{
+        ast::Program,
+        minifier::{
+            optimize,
+            option::{CompressOptions, ExtraOptions, MangleOptions, MinifyOptions},
+        },
+    },
+};
+
+/// Minifies the program of a single module.
+///
+/// Every module is wrapped in its own factory function by the chunk, so
+/// top-level bindings are local to the module and can be mangled and dropped
+/// like any other binding. That doesn't apply to modules which share the scope
+/// with hoisted modules, as their top-level bindings are referenced by the
+/// code of the other modules. Must be called with the `GLOBALS` the program
+/// was parsed with.
+pub(crate) fn minify(
+    program: Program,
+    source_map: &Arc<SourceMap>,
+    unresolved_mark: Mark,
+    top_level_mark: Mark,
+    is_esm: bool,
+    hoisted: bool,
+) -> Program {
+    optimize(
+        program,
+        source_map.clone(),
+        None,
+        None,
+        &MinifyOptions {
+            compress: Some(CompressOptions {
+                module: is_esm && !hoisted,
+                ..Default::default()
+            }),
+            mangle: Some(MangleOptions {
+                top_level: Some(!hoisted),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        &ExtraOptions {
+            unresolved_mark,
+            top_level_mark,
+        },
+    )
+}
@@ -50,6 +50,8 @@ pub enum ParseResult {
         globals: Globals,
         #[turbo_tasks(debug_ignore, trace_ignore)]
         source_map: Arc<SourceMap>,
+        #[turbo_tasks(debug_ignore, trace_ignore)]
+        top_level_mark: Mark,
     },
     Unparseable,
     NotFound,
@@ -287,6 +289,7 @@ async fn parse_content(
                 // borrowed
                 globals: Globals::new(),
                 source_map,
+                top_level_mark,
             })
         },
     )
@@ -0,0 +1,407 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Result;
+use regex::Regex;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{Expr, KeyValueProp, ObjectLit, Prop, PropName, PropOrSpread},
+    quote,
+};
+use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::{glob::GlobVc, DirectoryEntry, FileSystemPathVc};
+use turbopack_core::{
+    chunk::{
+        ChunkableAssetReference, ChunkableAssetReferenceVc, ChunkingContextVc, ChunkingType,
+        ChunkingTypeOptionVc, ModuleId,
+    },
+    reference::{AssetReference, AssetReferenceVc},
+    resolve::{
+        origin::ResolveOriginVc, parse::RequestVc, pattern::Pattern, ResolveResult, ResolveResultVc,
+    },
+};
+
+use super::pattern_mapping::{PatternMapping, PatternMappingVc, ResolveType};
+use crate::{
+    code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
+    create_visitor,
+    references::AstPathVc,
+    resolve::{cjs_resolve, esm_resolve},
+    utils::module_id_to_lit,
+};
+
+/// The files matched by a `require.context()` or `import.meta.glob()` call, by
+/// the key they are available with at runtime.
+#[turbo_tasks::value(transparent)]
+pub struct ContextMap(BTreeMap<String, FileSystemPathVc>);
+
+/// Reads the files of a directory that are matched by the filter of a
+/// `require.context(dir, recursive, filter)` call. The filter is matched
+/// against the path relative to the directory, e. g. `./sub/file.js`.
+#[turbo_tasks::function]
+pub async fn require_context_map(
+    dir: FileSystemPathVc,
+    recursive: bool,
+    filter: String,
+) -> Result<ContextMapVc> {
+    let filter = Regex::new(&filter)?;
+    let glob = GlobVc::new(if recursive { "**/*" } else { "*" });
+    let mut map = BTreeMap::new();
+    for (path, file) in read_glob_files(dir, glob).await? {
+        let key = format!("./{path}");
+        if filter.is_match(&key) {
+            map.insert(key, file);
+        }
+    }
+    Ok(ContextMapVc::cell(map))
+}
+
+/// Reads the files matched by the patterns of an `import.meta.glob(patterns)`
+/// call. Patterns are relative to the directory of the importing module, and
+/// patterns starting with `!` exclude files.
+#[turbo_tasks::function]
+pub async fn import_meta_glob_map(
+    origin_dir: FileSystemPathVc,
+    patterns: Vec<String>,
+) -> Result<ContextMapVc> {
+    let mut map = BTreeMap::new();
+    let mut excluded = HashSet::new();
+    for pattern in patterns.iter() {
+        let (pattern, exclude) = match pattern.strip_prefix('!') {
+            Some(pattern) => (pattern, true),
+            None => (pattern.as_str(), false),
+        };
+        // The leading segments without glob characters are the directory to
+        // read
+        let segments = pattern.split('/').collect::<Vec<_>>();
+        let glob_start = segments
+            .iter()
+            .position(|segment| segment.contains(&['*', '?', '[', '{'][..]))
+            .unwrap_or(segments.len() - 1);
+        let base = segments[..glob_start].join("/");
+        let glob = segments[glob_start..].join("/");
+        let Some(dir) = *origin_dir.try_join(&base).await? else {
+            continue;
+        };
+        for (path, file) in read_glob_files(dir, GlobVc::new(&glob)).await? {
+            let key = format!("{base}/{path}");
+            if exclude {
+                excluded.insert(key);
+            } else {
+                map.insert(key, file);
+            }
+        }
+    }
+    map.retain(|key, _| !excluded.contains(key));
+    Ok(ContextMapVc::cell(map))
+}
+
+async fn read_glob_files(
+    dir: FileSystemPathVc,
+    glob: GlobVc,
+) -> Result<Vec<(String, FileSystemPathVc)>> {
+    let mut files = Vec::new();
+    let mut queue = vec![dir.read_glob(glob, false)];
+    while let Some(result) = queue.pop() {
+        let result = result.await?;
+        for (path, entry) in result.results.iter() {
+            if let DirectoryEntry::File(file) = entry {
+                files.push((path.clone(), *file));
+            }
+        }
+        queue.extend(result.inner.values().copied());
+    }
+    Ok(files)
+}
+
+/// Returns a request for each file of the map, relative to the origin.
+async fn context_requests(
+    origin: ResolveOriginVc,
+    map: ContextMapVc,
+) -> Result<Vec<(String, RequestVc)>> {
+    let origin_dir = origin.origin_path().parent().await?;
+    let mut requests = Vec::new();
+    for (key, file) in map.await?.iter() {
+        if let Some(request) = origin_dir.get_relative_path_to(&*file.await?) {
+            requests.push((
+                key.clone(),
+                RequestVc::parse(Value::new(Pattern::Constant(request))),
+            ));
+        }
+    }
+    Ok(requests)
+}
+
+fn context_resolve(origin: ResolveOriginVc, request: RequestVc, esm: bool) -> ResolveResultVc {
+    if esm {
+        esm_resolve(origin, request)
+    } else {
+        cjs_resolve(origin, request)
+    }
+}
+
+#[turbo_tasks::function]
+async fn resolve_context(
+    origin: ResolveOriginVc,
+    map: ContextMapVc,
+    esm: bool,
+) -> Result<ResolveResultVc> {
+    let mut assets = HashMap::new();
+    let mut references = Vec::new();
+    for (key, request) in context_requests(origin, map).await? {
+        let result = context_resolve(origin, request, esm).await?;
+        references.extend(result.get_references().iter().copied());
+        match &*result {
+            ResolveResult::Single(asset, _) => {
+                assets.insert(key, *asset);
+            }
+            ResolveResult::Alternatives(alternatives, _) => {
+                if let Some(asset) = alternatives.first() {
+                    assets.insert(key, *asset);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(ResolveResult::Keyed(assets, references).cell())
+}
+
+/// Returns the module id of each file of the map which can be resolved.
+async fn context_module_ids(
+    origin: ResolveOriginVc,
+    map: ContextMapVc,
+    context: ChunkingContextVc,
+    esm: bool,
+    resolve_type: ResolveType,
+) -> Result<Vec<(String, ModuleId)>> {
+    let mut ids = Vec::new();
+    for (key, request) in context_requests(origin, map).await? {
+        let pm = PatternMappingVc::resolve_request(
+            request,
+            origin,
+            context,
+            context_resolve(origin, request, esm),
+            Value::new(resolve_type),
+        )
+        .await?;
+        if let PatternMapping::Single(id) = &*pm {
+            ids.push((key, id.clone()));
+        }
+    }
+    Ok(ids)
+}
+
+fn object_lit(props: impl IntoIterator<Item = (String, Expr)>) -> Expr {
+    Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props: props
+            .into_iter()
+            .map(|(key, value)| {
+                PropOrSpread::Prop(box Prop::KeyValue(KeyValueProp {
+                    key: PropName::Str(key.as_str().into()),
+                    value: box value,
+                }))
+            })
+            .collect(),
+    })
+}
+
+/// A `require.context(dir, recursive, filter)` call. It's replaced by a
+/// function which requires the files of the directory by their key. Only the
+/// default "sync" mode is supported.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct RequireContextAssetReference {
+    pub origin: ResolveOriginVc,
+    pub dir: FileSystemPathVc,
+    pub map: ContextMapVc,
+    pub path: AstPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl RequireContextAssetReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: ResolveOriginVc,
+        dir: FileSystemPathVc,
+        recursive: bool,
+        filter: String,
+        path: AstPathVc,
+    ) -> Self {
+        Self::cell(RequireContextAssetReference {
+            origin,
+            dir,
+            map: require_context_map(dir, recursive, filter),
+            path,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for RequireContextAssetReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> ResolveResultVc {
+        resolve_context(self.origin, self.map, false)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for RequireContextAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "require.context {}",
+            self.dir.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAssetReference for RequireContextAssetReference {}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for RequireContextAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(&self, context: ChunkingContextVc) -> Result<CodeGenerationVc> {
+        let ids = context_module_ids(self.origin, self.map, context, false, ResolveType::Cjs)
+            .await?
+            .into_iter()
+            .map(|(key, id)| (key, module_id_to_lit(&id)));
+        let map = object_lit(ids);
+
+        let path = &self.path.await?;
+        let visitor = create_visitor!(path, visit_mut_expr(expr: &mut Expr) {
+            *expr = quote!(
+                "(() => {
+                    const map = $map;
+                    const context = (key) => __turbopack_require__(context.resolve(key));
+                    context.keys = () => Object.keys(map);
+                    context.resolve = (key) => {
+                        if (!Object.prototype.hasOwnProperty.call(map, key)) {
+                            const e = new Error(\"Cannot find module '\" + key + \"'\");
+                            e.code = 'MODULE_NOT_FOUND';
+                            throw e;
+                        }
+                        return map[key];
+                    };
+                    return context;
+                })()" as Expr,
+                map: Expr = map.clone()
+            );
+        });
+
+        Ok(CodeGeneration {
+            visitors: vec![visitor],
+        }
+        .into())
+    }
+}
+
+/// An `import.meta.glob(patterns, { eager, import })` call. It's replaced by an
+/// object which contains the matched modules by their key, or functions which
+/// import them when the import is not eager.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct ImportMetaGlobAssetReference {
+    pub origin: ResolveOriginVc,
+    pub patterns: Vec<String>,
+    pub map: ContextMapVc,
+    pub eager: bool,
+    /// Only this export of the modules is used.
+    pub import: Option<String>,
+    pub path: AstPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaGlobAssetReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: ResolveOriginVc,
+        patterns: Vec<String>,
+        eager: bool,
+        import: Option<String>,
+        path: AstPathVc,
+    ) -> Self {
+        Self::cell(ImportMetaGlobAssetReference {
+            origin,
+            map: import_meta_glob_map(origin.origin_path().parent(), patterns.clone()),
+            patterns,
+            eager,
+            import,
+            path,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> ResolveResultVc {
+        resolve_context(self.origin, self.map, true)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> StringVc {
+        StringVc::cell(format!("import.meta.glob {}", self.patterns.join(", ")))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAssetReference for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self, _context: ChunkingContextVc) -> ChunkingTypeOptionVc {
+        ChunkingTypeOptionVc::cell(Some(if self.eager {
+            ChunkingType::default()
+        } else {
+            ChunkingType::SeparateAsync
+        }))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(&self, context: ChunkingContextVc) -> Result<CodeGenerationVc> {
+        let resolve_type = if self.eager {
+            ResolveType::Cjs
+        } else {
+            ResolveType::EsmAsync
+        };
+        let ids = context_module_ids(self.origin, self.map, context, true, resolve_type).await?;
+        let modules = ids.into_iter().map(|(key, id)| {
+            let id = module_id_to_lit(&id);
+            let value = match (self.eager, &self.import) {
+                (true, None) => quote!("__turbopack_import__($id)" as Expr, id: Expr = id),
+                (true, Some(import)) => quote!(
+                    "__turbopack_import__($id)[$import]" as Expr,
+                    id: Expr = id,
+                    import: Expr = import.clone().into()
+                ),
+                (false, None) => quote!(
+                    "() => __turbopack_require__($id)(__turbopack_import__)" as Expr,
+                    id: Expr = id
+                ),
+                (false, Some(import)) => quote!(
+                    "() => __turbopack_require__($id)(__turbopack_import__).then((m) => m[$import])"
+                        as Expr,
+                    id: Expr = id,
+                    import: Expr = import.clone().into()
+                ),
+            };
+            (key, value)
+        });
+        let modules = object_lit(modules);
+
+        let path = &self.path.await?;
+        let visitor = create_visitor!(path, visit_mut_expr(expr: &mut Expr) {
+            *expr = modules.clone();
+        });
+
+        Ok(CodeGeneration {
+            visitors: vec![visitor],
+        }
+        .into())
+    }
+}
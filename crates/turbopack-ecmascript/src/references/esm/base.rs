@@ -5,7 +5,10 @@ use swc_core::{
     ecma::ast::{Expr, ExprStmt, Ident, Lit, Module, ModuleItem, Program, Script, Stmt},
     quote,
 };
-use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks::{
+    primitives::{BoolVc, StringVc},
+    Value, ValueToString, ValueToStringVc,
+};
 use turbopack_core::{
     asset::Asset,
     chunk::{
@@ -25,6 +28,7 @@ use crate::{
     create_visitor, magic_identifier,
     references::util::{request_to_string, throw_module_not_found_expr},
     resolve::esm_resolve,
+    tree_shake::{module_usage, ModuleUsage},
 };
 
 #[turbo_tasks::value]
@@ -107,7 +111,7 @@ impl EsmAssetReference {
 #[turbo_tasks::value_impl]
 impl EsmAssetReferenceVc {
     #[turbo_tasks::function]
-    pub(super) async fn get_referenced_asset(self) -> Result<ReferencedAssetVc> {
+    pub(crate) async fn get_referenced_asset(self) -> Result<ReferencedAssetVc> {
         let this = self.await?;
         Ok(ReferencedAssetVc::from_resolve_result(
             esm_resolve(this.get_origin(), this.request),
@@ -115,6 +119,19 @@ impl EsmAssetReferenceVc {
         ))
     }
 
+    /// Returns true when the referenced module is omitted from the output,
+    /// because it's side effect free and none of its exports are used.
+    #[turbo_tasks::function]
+    pub(crate) async fn is_omitted(self, context: ChunkingContextVc) -> Result<BoolVc> {
+        Ok(BoolVc::cell(
+            if let ReferencedAsset::Some(asset) = &*self.get_referenced_asset().await? {
+                matches!(*module_usage(context, *asset).await?, ModuleUsage::Omitted)
+            } else {
+                false
+            },
+        ))
+    }
+
     #[turbo_tasks::function]
     pub fn new(
         origin: ResolveOriginVc,
@@ -177,6 +194,10 @@ impl CodeGenerateable for EsmAssetReference {
     ) -> Result<CodeGenerationVc> {
         let mut visitors = Vec::new();
 
+        if *self_vc.is_omitted(context).await? {
+            return Ok(CodeGeneration { visitors }.into());
+        }
+
         let chunking_type = self_vc.chunking_type(context).await?;
         let resolved = self_vc.resolve_reference().await?;
 
@@ -8,6 +8,7 @@ use swc_core::{
         },
         visit::fields::{ExprField, PropField},
     },
+    quote,
 };
 use turbopack_core::chunk::ChunkingContextVc;
 
@@ -16,6 +17,8 @@ use crate::{
     code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
     create_visitor,
     references::AstPathVc,
+    scope_hoist::{HoistedBinding, HoistedBindingVc},
+    utils::module_id_to_lit,
 };
 
 #[turbo_tasks::value(shared)]
@@ -51,68 +54,114 @@ impl CodeGenerateable for EsmBinding {
         _context: ChunkingContextVc,
     ) -> Result<CodeGenerationVc> {
         let this = self_vc.await?;
-        let mut visitors = Vec::new();
         let imported_module = this.reference.get_referenced_asset();
+        let imported_module = imported_module.await?.get_ident().await?;
 
-        fn make_expr(imported_module: &str, export: Option<&str>) -> Expr {
-            if let Some(export) = export {
-                Expr::Member(MemberExpr {
-                    span: DUMMY_SP,
-                    obj: box Expr::Ident(Ident::new(imported_module.into(), DUMMY_SP)),
-                    prop: MemberProp::Computed(ComputedPropName {
-                        span: DUMMY_SP,
-                        expr: box Expr::Lit(Lit::Str(Str {
-                            span: DUMMY_SP,
-                            value: export.into(),
-                            raw: None,
-                        })),
-                    }),
-                })
-            } else {
-                Expr::Ident(Ident::new(imported_module.into(), DUMMY_SP))
-            }
-        }
+        // If there's no identifier for the imported module, resolution failed
+        // and will insert code that throws before this expression is reached.
+        // Leave behind the original identifier.
+        let expr = imported_module
+            .as_deref()
+            .map(|ident| make_expr(ident, this.export.as_deref()));
 
-        let mut ast_path = this.ast_path.await?.clone_value();
-        let imported_module = imported_module.await?.get_ident().await?;
+        binding_code_generation(&this, expr).await
+    }
+}
 
-        loop {
-            match ast_path.last() {
-                Some(swc_core::ecma::visit::AstParentKind::Expr(ExprField::Ident)) => {
-                    ast_path.pop();
-                    visitors.push(
-                        create_visitor!(exact ast_path, visit_mut_expr(expr: &mut Expr) {
-                            if let Some(ident) = imported_module.as_deref() {
-                              *expr = make_expr(ident, this.export.as_deref());
-                            }
-                            // If there's no identifier for the imported module,
-                            // resolution failed and will insert code that throws
-                            // before this expression is reached. Leave behind the original identifier.
-                        }),
-                    );
-                    break;
+#[turbo_tasks::value_impl]
+impl EsmBindingVc {
+    /// Generates code for a binding to a module which is hoisted into the
+    /// same scope, or to a module which is imported by such a module.
+    #[turbo_tasks::function]
+    pub(crate) async fn hoisted_code_generation(
+        self,
+        binding: HoistedBindingVc,
+    ) -> Result<CodeGenerationVc> {
+        let this = self.await?;
+        let expr = match &*binding.await? {
+            HoistedBinding::Local(name) => Expr::Ident(Ident::new(name.as_str().into(), DUMMY_SP)),
+            HoistedBinding::Export(id, export) => {
+                let module = quote!(
+                    "__turbopack_import__($id)" as Expr,
+                    id: Expr = module_id_to_lit(id)
+                );
+                match export {
+                    Some(export) => export_expr(module, export),
+                    None => module,
                 }
-                Some(swc_core::ecma::visit::AstParentKind::Prop(PropField::Shorthand)) => {
-                    ast_path.pop();
-                    visitors.push(
-                        create_visitor!(exact ast_path, visit_mut_prop(prop: &mut Prop) {
-                            if let Prop::Shorthand(ident) = prop {
-                                // TODO: Merge with the above condition when https://rust-lang.github.io/rfcs/2497-if-let-chains.html lands.
-                                if let Some(imported_ident) = imported_module.as_deref() {
-                                    *prop = Prop::KeyValue(KeyValueProp { key: PropName::Ident(ident.clone()), value: box make_expr(imported_ident, this.export.as_deref())});
-                                }
+            }
+            HoistedBinding::Undefined => quote!("void 0" as Expr),
+        };
+        binding_code_generation(&this, Some(expr)).await
+    }
+}
+
+fn make_expr(imported_module: &str, export: Option<&str>) -> Expr {
+    let module = Expr::Ident(Ident::new(imported_module.into(), DUMMY_SP));
+    if let Some(export) = export {
+        export_expr(module, export)
+    } else {
+        module
+    }
+}
+
+fn export_expr(module: Expr, export: &str) -> Expr {
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: box module,
+        prop: MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: box Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: export.into(),
+                raw: None,
+            })),
+        }),
+    })
+}
+
+/// Replaces the binding with the expression, or leaves it untouched when
+/// there is no expression.
+async fn binding_code_generation(
+    binding: &EsmBinding,
+    expr: Option<Expr>,
+) -> Result<CodeGenerationVc> {
+    let mut visitors = Vec::new();
+    let mut ast_path = binding.ast_path.await?.clone_value();
+
+    loop {
+        match ast_path.last() {
+            Some(swc_core::ecma::visit::AstParentKind::Expr(ExprField::Ident)) => {
+                ast_path.pop();
+                visitors.push(
+                    create_visitor!(exact ast_path, visit_mut_expr(expr_node: &mut Expr) {
+                        if let Some(expr) = &expr {
+                            *expr_node = expr.clone();
+                        }
+                    }),
+                );
+                break;
+            }
+            Some(swc_core::ecma::visit::AstParentKind::Prop(PropField::Shorthand)) => {
+                ast_path.pop();
+                visitors.push(
+                    create_visitor!(exact ast_path, visit_mut_prop(prop: &mut Prop) {
+                        if let Prop::Shorthand(ident) = prop {
+                            // TODO: Merge with the above condition when https://rust-lang.github.io/rfcs/2497-if-let-chains.html lands.
+                            if let Some(expr) = &expr {
+                                *prop = Prop::KeyValue(KeyValueProp { key: PropName::Ident(ident.clone()), value: box expr.clone()});
                             }
-                        }),
-                    );
-                    break;
-                }
-                Some(_) => {
-                    ast_path.pop();
-                }
-                None => break,
+                        }
+                    }),
+                );
+                break;
             }
+            Some(_) => {
+                ast_path.pop();
+            }
+            None => break,
         }
-
-        Ok(CodeGeneration { visitors }.into())
     }
+
+    Ok(CodeGeneration { visitors }.into())
 }
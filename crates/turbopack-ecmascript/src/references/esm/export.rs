@@ -26,6 +26,7 @@ use crate::{
     code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
     create_visitor,
     references::esm::base::insert_hoisted_stmt,
+    tree_shake::{ModuleUsage, ModuleUsageVc, UsedExports},
 };
 
 #[derive(Clone, Hash, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
@@ -135,11 +136,23 @@ pub struct EsmExports {
 #[turbo_tasks::value_impl]
 impl CodeGenerateable for EsmExports {
     #[turbo_tasks::function]
-    async fn code_generation(
-        self_vc: EsmExportsVc,
-        _context: ChunkingContextVc,
+    fn code_generation(self_vc: EsmExportsVc, context: ChunkingContextVc) -> CodeGenerationVc {
+        self_vc.code_generation_for_usage(context, ModuleUsage::Included(UsedExports::All).cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EsmExportsVc {
+    /// Generates the getters of the exports. Exports which are not used by
+    /// any module are omitted.
+    #[turbo_tasks::function]
+    pub async fn code_generation_for_usage(
+        self,
+        context: ChunkingContextVc,
+        usage: ModuleUsageVc,
     ) -> Result<CodeGenerationVc> {
-        let this = self_vc.await?;
+        let this = self.await?;
+        let usage = usage.await?;
         let mut visitors = Vec::new();
 
         let mut all_exports: BTreeMap<Cow<str>, Cow<EsmExport>> = this
@@ -151,6 +164,9 @@ impl CodeGenerateable for EsmExports {
         let mut cjs_exports = Vec::<Box<Expr>>::new();
 
         for esm_ref in this.star_exports.iter() {
+            if *esm_ref.is_omitted(context).await? {
+                continue;
+            }
             if let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? {
                 let export_info = expand_star_exports(*asset).await?;
                 let export_names = &export_info.star_exports;
@@ -174,6 +190,9 @@ impl CodeGenerateable for EsmExports {
             }
         }
         for (exported, local) in all_exports.into_iter() {
+            if !usage.is_export_used(&exported) {
+                continue;
+            }
             let expr = match local.as_ref() {
                 EsmExport::Error => Some(quote!(
                     "(() => { throw new Error(\"Failed binding. See build errors!\"); })" as Expr,
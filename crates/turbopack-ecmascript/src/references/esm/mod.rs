@@ -6,13 +6,15 @@ pub(crate) mod meta;
 pub(crate) mod module_id;
 pub(crate) mod module_item;
 pub(crate) mod url;
+pub(crate) mod worker;
 
 pub use self::{
     base::{EsmAssetReference, EsmAssetReferenceVc},
     binding::{EsmBinding, EsmBindingVc},
     dynamic::{EsmAsyncAssetReference, EsmAsyncAssetReferenceVc},
-    export::{EsmExports, EsmExportsVc},
+    export::{EsmExport, EsmExports, EsmExportsVc},
     meta::{ImportMetaBinding, ImportMetaBindingVc, ImportMetaRef, ImportMetaRefVc},
     module_item::{EsmModuleItem, EsmModuleItemVc},
     url::{UrlAssetReference, UrlAssetReferenceVc},
+    worker::{WorkerAssetReference, WorkerAssetReferenceVc},
 };
@@ -113,38 +113,7 @@ impl CodeGenerateable for UrlAssetReference {
 
         let referenced_asset = self_vc.get_referenced_asset().await?;
 
-        // For rendering environments (CSR and SSR), we rewrite the `import.meta.url` to
-        // be a location.origin because it allows us to access files from the root of
-        // the dev server. It's important that this be rewritten for SSR as well, so
-        // that the client's hydration matches exactly.
-        //
-        // In a non-rendering env, the `import.meta.url` is already the correct `file://` URL
-        // to load files.
-        let rewrite = match &*this.rendering.await? {
-            Rendering::None => {
-                CodeGenerationIssue {
-                    severity: IssueSeverity::Error.into(),
-                    title: StringVc::cell(
-                        "new URL(…) not implemented for this environment".to_string(),
-                    ),
-                    message: StringVc::cell(
-                        "new URL(…) is only currently supported for rendering environments like \
-                         Client-Side or Server-Side Rendering."
-                            .to_string(),
-                    ),
-                    path: this.origin.origin_path(),
-                }
-                .cell()
-                .as_issue()
-                .emit();
-                None
-            }
-            Rendering::Client => Some(quote!("location.origin" as Expr)),
-            Rendering::Server(server_addr) => {
-                let location = server_addr.await?.to_string()?;
-                Some(location.into())
-            }
-        };
+        let rewrite = import_meta_url_replacement(this.rendering, this.origin).await?;
 
         let ast_path = this.ast_path.await?;
 
@@ -197,3 +166,43 @@ impl CodeGenerateable for UrlAssetReference {
         Ok(CodeGeneration { visitors }.into())
     }
 }
+
+/// The expression that replaces `import.meta.url` in the rewritten `new
+/// URL(…)` expression, or [None] if it should be kept as is.
+pub(super) async fn import_meta_url_replacement(
+    rendering: RenderingVc,
+    origin: ResolveOriginVc,
+) -> Result<Option<Expr>> {
+    // For rendering environments (CSR and SSR), we rewrite the `import.meta.url` to
+    // be a location.origin because it allows us to access files from the root of
+    // the dev server. It's important that this be rewritten for SSR as well, so
+    // that the client's hydration matches exactly.
+    //
+    // In a non-rendering env, the `import.meta.url` is already the correct `file://` URL
+    // to load files.
+    Ok(match &*rendering.await? {
+        Rendering::None => {
+            CodeGenerationIssue {
+                severity: IssueSeverity::Error.into(),
+                title: StringVc::cell(
+                    "new URL(…) not implemented for this environment".to_string(),
+                ),
+                message: StringVc::cell(
+                    "new URL(…) is only currently supported for rendering environments like \
+                     Client-Side or Server-Side Rendering."
+                        .to_string(),
+                ),
+                path: origin.origin_path(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+            None
+        }
+        Rendering::Client => Some(quote!("location.origin" as Expr)),
+        Rendering::Server(server_addr) => {
+            let location = server_addr.await?.to_string()?;
+            Some(location.into())
+        }
+    })
+}
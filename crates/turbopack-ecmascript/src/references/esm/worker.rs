@@ -0,0 +1,304 @@
+use anyhow::{bail, Result};
+use swc_core::{
+    ecma::ast::{Expr, ExprOrSpread, NewExpr},
+    quote,
+};
+use turbo_tasks::{primitives::StringVc, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbopack_core::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    chunk::{
+        ChunkItem, ChunkItemVc, ChunkReferenceVc, ChunkVc, ChunkableAsset, ChunkableAssetReference,
+        ChunkableAssetReferenceVc, ChunkableAssetVc, ChunkingContextVc,
+    },
+    environment::RenderingVc,
+    reference::{AssetReference, AssetReferenceVc, AssetReferencesVc, SingleAssetReferenceVc},
+    resolve::{
+        origin::{PlainResolveOriginVc, ResolveOriginVc},
+        parse::RequestVc,
+        ResolveResultVc,
+    },
+};
+
+use super::{
+    base::{ReferencedAsset, ReferencedAssetVc},
+    url::import_meta_url_replacement,
+};
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
+        EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
+        EcmascriptChunkVc, EcmascriptExports, EcmascriptExportsVc,
+    },
+    code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
+    create_visitor,
+    references::AstPathVc,
+    resolve::esm_resolve,
+    utils::{module_id_to_lit, stringify_str},
+    EcmascriptModuleAssetVc,
+};
+
+/// Worker Asset References are injected during code analysis when we find a
+/// (staticly analyzable) `new Worker(new URL("path", import.meta.url))` or
+/// `new SharedWorker(new URL("path", import.meta.url))`.
+///
+/// The worker module is the entry of a separate chunk group, which is built for
+/// a web worker environment. The `URL` constructor's arguments are rewritten
+/// to point to the entry chunk of that chunk group.
+#[turbo_tasks::value]
+pub struct WorkerAssetReference {
+    origin: ResolveOriginVc,
+    request: RequestVc,
+    rendering: RenderingVc,
+    ast_path: AstPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerAssetReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: ResolveOriginVc,
+        request: RequestVc,
+        rendering: RenderingVc,
+        ast_path: AstPathVc,
+    ) -> Self {
+        WorkerAssetReference {
+            origin,
+            request,
+            rendering,
+            ast_path,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn get_referenced_asset(self) -> Result<ReferencedAssetVc> {
+        let this = self.await?;
+        Ok(ReferencedAssetVc::from_resolve_result(
+            self.resolve_reference(),
+            this.request,
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<ResolveResultVc> {
+        // The worker module and its dependencies are built for the environment of
+        // the worker, not for the environment of the module that creates it.
+        let context = self.origin.context();
+        let worker_context = context.with_environment(context.environment().web_worker());
+        let worker_origin = PlainResolveOriginVc::new(worker_context, self.origin.origin_path());
+        let result = esm_resolve(worker_origin.into(), self.request).await?;
+        Ok(result
+            .map(
+                |asset| async move {
+                    Ok(
+                        if let Some(asset) = EcmascriptModuleAssetVc::resolve_from(asset).await? {
+                            WorkerLoaderAssetVc::new(asset).into()
+                        } else {
+                            asset
+                        },
+                    )
+                },
+                |reference| async move { Ok(reference) },
+            )
+            .await?
+            .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "new Worker(new URL({}))",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAssetReference for WorkerAssetReference {}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self_vc: WorkerAssetReferenceVc,
+        context: ChunkingContextVc,
+    ) -> Result<CodeGenerationVc> {
+        let this = self_vc.await?;
+        let mut visitors = vec![];
+
+        if let ReferencedAsset::Some(asset) = &*self_vc.get_referenced_asset().await? {
+            let rewrite = import_meta_url_replacement(this.rendering, this.origin).await?;
+            let ast_path = this.ast_path.await?;
+
+            // We rewrite the first `new URL()` argument to be a require() of the worker
+            // loader, which exports the path of the worker's entry chunk.
+            let id = asset.as_chunk_item(context).id().await?;
+
+            visitors.push(
+                create_visitor!(ast_path, visit_mut_expr(new_expr: &mut Expr) {
+                    if let Expr::New(NewExpr { args: Some(args), .. }) = new_expr {
+                        if let Some(ExprOrSpread { box expr, spread: None }) = args.get_mut(0) {
+                            *expr = quote!(
+                                "__turbopack_require__($id)" as Expr,
+                                id: Expr = module_id_to_lit(&id),
+                            );
+                        }
+
+                        if let Some(rewrite) = &rewrite {
+                            if let Some(ExprOrSpread { box expr, spread: None }) = args.get_mut(1) {
+                                *expr = rewrite.clone();
+                            }
+                        }
+                    }
+                }),
+            );
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
+
+/// An asset that exports the path of the entry chunk of a web worker. The
+/// worker module is placed into its own chunk group, which is built for a web
+/// worker environment.
+#[turbo_tasks::value]
+pub struct WorkerLoaderAsset {
+    asset: EcmascriptModuleAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerLoaderAssetVc {
+    #[turbo_tasks::function]
+    pub fn new(asset: EcmascriptModuleAssetVc) -> Self {
+        Self::cell(WorkerLoaderAsset { asset })
+    }
+
+    /// The entry chunk of the worker, which loads all other chunks of its chunk
+    /// group by itself.
+    #[turbo_tasks::function]
+    async fn worker_chunk(self, context: ChunkingContextVc) -> Result<ChunkVc> {
+        let worker_context = context.with_environment(context.environment().web_worker());
+        Ok(self.await?.asset.as_evaluated_chunk(worker_context, None))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WorkerLoaderAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.asset.path().join("worker-loader.js")
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<AssetContentVc> {
+        bail!(
+            "the worker loader of {} has no content of its own, it's only used as a chunk item",
+            self.asset.path().to_string().await?
+        )
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        Ok(AssetReferencesVc::cell(vec![SingleAssetReferenceVc::new(
+            self.asset.into(),
+            StringVc::cell(format!("worker {}", self.asset.path().to_string().await?)),
+        )
+        .into()]))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAsset for WorkerLoaderAsset {
+    #[turbo_tasks::function]
+    fn as_chunk(self_vc: WorkerLoaderAssetVc, context: ChunkingContextVc) -> ChunkVc {
+        EcmascriptChunkVc::new(context, self_vc.as_ecmascript_chunk_placeable()).into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for WorkerLoaderAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self_vc: WorkerLoaderAssetVc,
+        context: ChunkingContextVc,
+    ) -> EcmascriptChunkItemVc {
+        WorkerLoaderChunkItem {
+            context,
+            inner: self_vc,
+        }
+        .cell()
+        .into()
+    }
+
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> EcmascriptExportsVc {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct WorkerLoaderChunkItem {
+    context: ChunkingContextVc,
+    inner: WorkerLoaderAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "{}/worker-loader.js",
+            self.inner.await?.asset.path().to_string().await?
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for WorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> ChunkingContextVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
+        let output_root = self.context.output_root().await?;
+        let chunk_path = self.inner.worker_chunk(self.context).path().await?;
+        let Some(chunk_server_path) = output_root.get_path_to(&chunk_path) else {
+            bail!(
+                "worker chunk {} is not in output root {}",
+                chunk_path,
+                output_root
+            );
+        };
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!(
+                "__turbopack_export_value__({});\n",
+                stringify_str(&format!("/{chunk_server_path}"))
+            )
+            .into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for WorkerLoaderChunkItem {
+    #[turbo_tasks::function]
+    fn references(&self) -> AssetReferencesVc {
+        // The worker chunk is not loaded with the chunk group of the loader, but it
+        // still needs to be emitted.
+        AssetReferencesVc::cell(vec![ChunkReferenceVc::new(
+            self.inner.worker_chunk(self.context),
+        )
+        .into()])
+    }
+}
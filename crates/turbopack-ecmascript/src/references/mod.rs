@@ -1,5 +1,6 @@
 pub mod amd;
 pub mod cjs;
+pub mod context;
 pub mod esm;
 pub mod node;
 pub mod pattern_mapping;
@@ -50,9 +51,11 @@ use self::{
         AmdDefineWithDependenciesCodeGenVc,
     },
     cjs::CjsAssetReferenceVc,
+    context::{ImportMetaGlobAssetReferenceVc, RequireContextAssetReferenceVc},
     esm::{
         export::EsmExport, EsmAssetReferenceVc, EsmAsyncAssetReferenceVc, EsmExports,
         EsmModuleItemVc, ImportMetaBindingVc, ImportMetaRefVc, UrlAssetReferenceVc,
+        WorkerAssetReferenceVc,
     },
     node::{DirAssetReferenceVc, PackageJsonReferenceVc},
     raw::SourceAssetReferenceVc,
@@ -91,6 +94,7 @@ use crate::{
         },
         esm::{module_id::EsmModuleIdAssetReferenceVc, EsmBindingVc, EsmExportsVc},
     },
+    tree_shake::{ImportedExportsVc, UsedExports},
     typescript::resolve::tsconfig,
     EcmascriptInputTransformsVc,
 };
@@ -100,6 +104,7 @@ pub struct AnalyzeEcmascriptModuleResult {
     pub references: AssetReferencesVc,
     pub code_generation: CodeGenerateablesVc,
     pub exports: EcmascriptExportsVc,
+    pub imported_exports: ImportedExportsVc,
 }
 
 /// A temporary analysis result builder to pass around, to be turned into an
@@ -108,6 +113,7 @@ pub(crate) struct AnalyzeEcmascriptModuleResultBuilder {
     references: Vec<AssetReferenceVc>,
     code_gens: Vec<CodeGenerateableVc>,
     exports: EcmascriptExports,
+    imported_exports: Vec<(EsmAssetReferenceVc, UsedExports)>,
 }
 
 impl AnalyzeEcmascriptModuleResultBuilder {
@@ -116,6 +122,7 @@ impl AnalyzeEcmascriptModuleResultBuilder {
             references: Vec::new(),
             code_gens: Vec::new(),
             exports: EcmascriptExports::None,
+            imported_exports: Vec::new(),
         }
     }
 
@@ -140,6 +147,11 @@ impl AnalyzeEcmascriptModuleResultBuilder {
         self.exports = exports;
     }
 
+    /// Adds the exports that are imported through an ESM reference.
+    pub fn add_imported_exports(&mut self, reference: EsmAssetReferenceVc, used: UsedExports) {
+        self.imported_exports.push((reference, used));
+    }
+
     /// Builds the final analysis result. Resolves internal Vcs for performance
     /// in using them.
     pub async fn build(mut self) -> Result<AnalyzeEcmascriptModuleResultVc> {
@@ -149,11 +161,15 @@ impl AnalyzeEcmascriptModuleResultBuilder {
         for c in self.code_gens.iter_mut() {
             *c = c.resolve().await?;
         }
+        for (r, _) in self.imported_exports.iter_mut() {
+            *r = r.resolve().await?;
+        }
         Ok(AnalyzeEcmascriptModuleResultVc::cell(
             AnalyzeEcmascriptModuleResult {
                 references: AssetReferencesVc::cell(self.references),
                 code_generation: CodeGenerateablesVc::cell(self.code_gens),
                 exports: self.exports.into(),
+                imported_exports: ImportedExportsVc::cell(self.imported_exports),
             },
         ))
     }
@@ -291,11 +307,18 @@ pub(crate) async fn analyze_ecmascript_module(
                 );
                 import_references.push(r);
             }
-            for r in import_references.iter_mut() {
+            for (i, r) in import_references.iter_mut().enumerate() {
                 // Resolving these references here avoids many resolve wrapper tasks when
                 // passing that to other turbo tasks functions later.
                 *r = r.resolve().await?;
                 analysis.add_reference(*r);
+                let used = match eval_context.imports.imported_symbols(i) {
+                    Some(symbols) => {
+                        UsedExports::Names(symbols.into_iter().map(|s| s.to_string()).collect())
+                    }
+                    None => UsedExports::All,
+                };
+                analysis.add_imported_exports(*r, used);
             }
 
             let (
@@ -619,6 +642,17 @@ pub(crate) async fn analyze_ecmascript_module(
                         )
                     }
 
+                    JsValue::WellKnownFunction(WellKnownFunctionKind::RequireContext) => {
+                        analyze_require_context(
+                            analysis,
+                            origin,
+                            handler,
+                            span,
+                            ast_path,
+                            linked_args().await?,
+                        );
+                    }
+
                     JsValue::WellKnownFunction(WellKnownFunctionKind::FsReadMethod(name)) => {
                         let args = linked_args().await?;
                         if !args.is_empty() {
@@ -1204,6 +1238,45 @@ pub(crate) async fn analyze_ecmascript_module(
                             AstPathVc::cell(ast_path),
                         ));
                     }
+                    Effect::Worker {
+                        input,
+                        ast_path,
+                        span,
+                    } => {
+                        let pat = js_value_to_pattern(&input);
+                        if !pat.has_constant_parts() {
+                            handler.span_warn_with_code(
+                                span,
+                                &format!(
+                                    "new Worker(new URL({input}, import.meta.url)) is very dynamic"
+                                ),
+                                DiagnosticId::Lint(
+                                    errors::failed_to_analyse::ecmascript::NEW_WORKER.to_string(),
+                                ),
+                            )
+                        }
+                        analysis.add_reference(WorkerAssetReferenceVc::new(
+                            origin,
+                            RequestVc::parse(Value::new(pat)),
+                            environment.rendering(),
+                            AstPathVc::cell(ast_path),
+                        ));
+                    }
+                    Effect::ImportMetaGlob {
+                        args,
+                        ast_path,
+                        span,
+                    } => {
+                        let args = args.into_iter().map(&link_value).try_join().await?;
+                        analyze_import_meta_glob(
+                            &mut analysis,
+                            origin,
+                            &handler,
+                            span,
+                            &ast_path,
+                            args,
+                        );
+                    }
                 }
             }
         }
@@ -1213,6 +1286,151 @@ pub(crate) async fn analyze_ecmascript_module(
     analysis.build().await
 }
 
+fn analyze_require_context(
+    analysis: &mut AnalyzeEcmascriptModuleResultBuilder,
+    origin: ResolveOriginVc,
+    handler: &Handler,
+    span: Span,
+    ast_path: &[AstParentKind],
+    args: Vec<JsValue>,
+) {
+    // require.context(dir, recursive = true, filter = /^\.\/.*$/, mode = "sync")
+    let dir = args
+        .first()
+        .and_then(|dir| dir.as_str())
+        .filter(|dir| dir.starts_with("./") || dir.starts_with("../") || *dir == ".");
+    let recursive = match args.get(1) {
+        None => Some(true),
+        Some(JsValue::Constant(ConstantValue::True)) => Some(true),
+        Some(JsValue::Constant(ConstantValue::False)) => Some(false),
+        Some(_) => None,
+    };
+    let filter = match args.get(2) {
+        None => Some(r"^\./.*$".to_string()),
+        Some(JsValue::Constant(ConstantValue::Regex(exp, flags))) => {
+            Some(js_regex_to_rust(exp, flags)).filter(|filter| Regex::new(filter).is_ok())
+        }
+        Some(_) => None,
+    };
+    let mode = match args.get(3) {
+        None => Some("sync"),
+        Some(mode) => mode.as_str(),
+    };
+    // Only the "sync" mode is supported. The "eager", "weak", "lazy" and
+    // "lazy-once" modes return promises and load the modules on demand, which
+    // isn't implemented for context modules.
+    if let Some(mode) = mode.filter(|mode| *mode != "sync") {
+        handler.span_err_with_code(
+            span,
+            &format!(
+                "require.context() with the \"{mode}\" mode is not supported, only the \"sync\" \
+                 mode is"
+            ),
+            DiagnosticId::Error(errors::failed_to_analyse::ecmascript::REQUIRE_CONTEXT.to_string()),
+        );
+        return;
+    }
+    if let (Some(dir), Some(recursive), Some(filter), Some(_)) = (dir, recursive, filter, mode) {
+        analysis.add_reference(RequireContextAssetReferenceVc::new(
+            origin,
+            origin.origin_path().parent().join(dir),
+            recursive,
+            filter,
+            AstPathVc::cell(ast_path.to_vec()),
+        ));
+        return;
+    }
+    let (args, hints) = JsValue::explain_args(&args, 10, 2);
+    handler.span_err_with_code(
+        span,
+        &format!("require.context({args}) is not statically analyse-able{hints}",),
+        DiagnosticId::Error(errors::failed_to_analyse::ecmascript::REQUIRE_CONTEXT.to_string()),
+    )
+}
+
+/// Converts a JavaScript regular expression into the syntax of the regex
+/// crate. Only the flags which change what is matched are kept.
+fn js_regex_to_rust(exp: &str, flags: &str) -> String {
+    let flags = flags
+        .chars()
+        .filter(|flag| matches!(flag, 'i' | 'm' | 's'))
+        .collect::<String>();
+    // `/` has to be escaped in JavaScript, but the regex crate doesn't allow
+    // to escape it
+    let exp = exp.replace("\\/", "/");
+    if flags.is_empty() {
+        exp
+    } else {
+        format!("(?{flags}){exp}")
+    }
+}
+
+fn analyze_import_meta_glob(
+    analysis: &mut AnalyzeEcmascriptModuleResultBuilder,
+    origin: ResolveOriginVc,
+    handler: &Handler,
+    span: Span,
+    ast_path: &[AstParentKind],
+    args: Vec<JsValue>,
+) {
+    // import.meta.glob(patterns, { eager, import })
+    let patterns = match args.first() {
+        Some(JsValue::Array(_, patterns)) => patterns
+            .iter()
+            .map(|pattern| pattern.as_str().map(|pattern| pattern.to_string()))
+            .collect::<Option<Vec<_>>>(),
+        Some(pattern) => pattern.as_str().map(|pattern| vec![pattern.to_string()]),
+        None => None,
+    };
+    // Only patterns relative to the module are supported
+    let patterns = patterns.filter(|patterns| {
+        !patterns.is_empty()
+            && patterns.iter().all(|pattern| {
+                let pattern = pattern.strip_prefix('!').unwrap_or(pattern);
+                pattern.starts_with("./") || pattern.starts_with("../")
+            })
+    });
+    let mut eager = false;
+    let mut import = None;
+    let mut supported_options = true;
+    match args.get(1) {
+        None => {}
+        Some(JsValue::Object(_, parts)) => {
+            for part in parts {
+                match part {
+                    ObjectPart::KeyValue(key, value) => match (key.as_str(), value) {
+                        (Some("eager"), JsValue::Constant(ConstantValue::True)) => eager = true,
+                        (Some("eager"), JsValue::Constant(ConstantValue::False)) => eager = false,
+                        (Some("import"), value) if value.as_str().is_some() => {
+                            import = value.as_str().map(|import| import.to_string());
+                        }
+                        _ => supported_options = false,
+                    },
+                    ObjectPart::Spread(_) => supported_options = false,
+                }
+            }
+        }
+        Some(_) => supported_options = false,
+    }
+    if let (Some(patterns), true) = (patterns, supported_options) {
+        analysis.add_reference(ImportMetaGlobAssetReferenceVc::new(
+            origin,
+            patterns,
+            eager,
+            import,
+            AstPathVc::cell(ast_path.to_vec()),
+        ));
+        return;
+    }
+    // There is no `import.meta.glob` at runtime, so this is an error
+    let (args, hints) = JsValue::explain_args(&args, 10, 2);
+    handler.span_err_with_code(
+        span,
+        &format!("import.meta.glob({args}) is not statically analyse-able{hints}",),
+        DiagnosticId::Error(errors::failed_to_analyse::ecmascript::IMPORT_META_GLOB.to_string()),
+    )
+}
+
 fn analyze_amd_define(
     analysis: &mut AnalyzeEcmascriptModuleResultBuilder,
     origin: ResolveOriginVc,
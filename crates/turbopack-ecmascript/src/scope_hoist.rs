@@ -0,0 +1,692 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use indexmap::{IndexMap, IndexSet};
+use swc_core::{
+    common::{Mark, SyntaxContext, GLOBALS},
+    ecma::{
+        ast::{
+            BindingIdent, BreakStmt, ClassDecl, ContinueStmt, Expr, FnDecl, Ident, KeyValueProp,
+            LabeledStmt, MemberProp, Program, Prop, PropName, SuperProp,
+        },
+        atoms::JsWord,
+        visit::{Visit, VisitMut, VisitMutWith, VisitWith},
+    },
+};
+use turbo_tasks::{primitives::StringsVc, Value};
+use turbopack_core::{
+    asset::AssetsVc,
+    chunk::{ChunkingContextVc, ModuleId},
+};
+
+use crate::{
+    chunk::{EcmascriptChunkPlaceableVc, EcmascriptExports},
+    parse::{parse, ParseResult},
+    references::esm::{base::ReferencedAsset, export::EsmExport, EsmAssetReferenceVc},
+    tree_shake::UsedExports,
+    EcmascriptModuleAssetVc,
+};
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub enum ModuleHoisting {
+    /// The module is wrapped in its own module factory.
+    None,
+    /// The module factory of the module also contains the code of the modules
+    /// which are hoisted into its scope. They are listed in execution order
+    /// and placed before the code of the module, so they are always imported
+    /// before any module which is not hoisted.
+    Root(Vec<EcmascriptModuleAssetVc>),
+    /// The module is hoisted into the scope of another module and has no
+    /// module factory of its own.
+    Hoisted(EcmascriptModuleAssetVc),
+}
+
+/// Returns how a module is concatenated with other modules within the scope
+/// hoisted module graph of the chunking context.
+#[turbo_tasks::function]
+pub async fn module_hoisting(
+    context: ChunkingContextVc,
+    module: EcmascriptModuleAssetVc,
+) -> Result<ModuleHoistingVc> {
+    let entries = context.scope_hoisting_entries();
+    if entries.await?.is_empty() {
+        return Ok(ModuleHoisting::None.cell());
+    }
+    let graph = module_graph_hoisting(entries).await?;
+    Ok(graph
+        .get(&module)
+        .cloned()
+        .unwrap_or(ModuleHoisting::None)
+        .cell())
+}
+
+#[turbo_tasks::value(transparent, serialization = "none")]
+struct ModuleGraphHoisting(HashMap<EcmascriptModuleAssetVc, ModuleHoisting>);
+
+#[derive(Default)]
+struct ModuleNode {
+    /// All modules referenced by this module.
+    dependencies: IndexSet<EcmascriptModuleAssetVc>,
+    /// Modules which are imported by static ESM imports of single bindings,
+    /// in import order.
+    static_imports: IndexSet<EcmascriptModuleAssetVc>,
+    /// The targets of all ESM imports and re-exports in evaluation order.
+    /// `None` for targets which are not ecmascript modules.
+    esm_imports: Vec<Option<EcmascriptModuleAssetVc>>,
+    /// Modules which statically import this module.
+    importers: Vec<EcmascriptModuleAssetVc>,
+    /// The module is an entry, or it's referenced in other ways than by a
+    /// static import, e. g. via `import()`, `require()`, as namespace or by a
+    /// re-export.
+    referenced_dynamically: bool,
+    /// The module is an ESM module without `export *`, so its exports are
+    /// known statically.
+    static_exports: bool,
+}
+
+/// A module can be hoisted into the scope of another module when it's an ESM
+/// module which is only imported statically by modules of that scope and not
+/// part of an import cycle. All other modules are wrapped in their own module
+/// factory, which is the root of a scope.
+#[turbo_tasks::function]
+async fn module_graph_hoisting(entries: AssetsVc) -> Result<ModuleGraphHoistingVc> {
+    let mut modules: IndexMap<EcmascriptModuleAssetVc, ModuleNode> = IndexMap::new();
+    let mut visited = HashSet::new();
+    let mut queue = Vec::new();
+    for entry in entries.await?.iter() {
+        let entry = entry.resolve().await?;
+        if let Some(module) = EcmascriptModuleAssetVc::resolve_from(entry).await? {
+            modules.entry(module).or_default().referenced_dynamically = true;
+        }
+        if visited.insert(entry) {
+            queue.push(entry);
+        }
+    }
+
+    while let Some(asset) = queue.pop() {
+        let Some(module) = EcmascriptModuleAssetVc::resolve_from(asset).await? else {
+            for reference in asset.references().await?.iter() {
+                for target in reference.resolve_reference().primary_assets().await?.iter() {
+                    let target = target.resolve().await?;
+                    if let Some(target) = EcmascriptModuleAssetVc::resolve_from(target).await? {
+                        modules.entry(target).or_default().referenced_dynamically = true;
+                    }
+                    if visited.insert(target) {
+                        queue.push(target);
+                    }
+                }
+            }
+            continue;
+        };
+
+        let analysis = module.analyze().await?;
+        let imported_exports = analysis.imported_exports.await?;
+        // Re-exports are bound to the import of the re-exported module
+        let (static_exports, reexports) = match &*analysis.exports.await? {
+            EcmascriptExports::EsmExports(exports) => {
+                let exports = exports.await?;
+                let mut reexports = exports.star_exports.clone();
+                for export in exports.exports.values() {
+                    match export {
+                        EsmExport::ImportedBinding(reference, _)
+                        | EsmExport::ImportedNamespace(reference) => reexports.push(*reference),
+                        EsmExport::LocalBinding(_) | EsmExport::Error => {}
+                    }
+                }
+                (exports.star_exports.is_empty(), reexports)
+            }
+            _ => (false, Vec::new()),
+        };
+        modules.entry(module).or_default().static_exports = static_exports;
+
+        for reference in analysis.references.await?.iter() {
+            let esm_reference = EsmAssetReferenceVc::resolve_from(reference).await?;
+            let is_static = match esm_reference {
+                Some(esm_reference) => {
+                    esm_reference.await?.annotations.chunking_type().is_none()
+                        && !reexports.contains(&esm_reference)
+                        && imported_exports
+                            .iter()
+                            .any(|(r, used)| *r == esm_reference && *used != UsedExports::All)
+                }
+                None => false,
+            };
+            for target in reference.resolve_reference().primary_assets().await?.iter() {
+                let target = target.resolve().await?;
+                let target_module = EcmascriptModuleAssetVc::resolve_from(target).await?;
+                if esm_reference.is_some() {
+                    modules
+                        .entry(module)
+                        .or_default()
+                        .esm_imports
+                        .push(target_module);
+                }
+                if let Some(target) = target_module {
+                    let node = modules.entry(module).or_default();
+                    node.dependencies.insert(target);
+                    if is_static {
+                        if node.static_imports.insert(target) {
+                            modules.entry(target).or_default().importers.push(module);
+                        }
+                    } else {
+                        modules.entry(target).or_default().referenced_dynamically = true;
+                    }
+                }
+                if visited.insert(target) {
+                    queue.push(target);
+                }
+            }
+        }
+    }
+
+    let cyclic = modules_in_cycles(&modules);
+    let roots = loop {
+        let mut roots = HashMap::new();
+        for module in modules.keys() {
+            scope_root(*module, &modules, &cyclic, &mut roots);
+        }
+        // Hoisted modules are executed before the code of the module they are
+        // imported by, so a module can't be hoisted when it's imported after a
+        // module which is not hoisted into the same scope.
+        let mut unordered = Vec::new();
+        for (module, node) in modules.iter() {
+            let root = roots[module];
+            let mut ordered = true;
+            for import in node.esm_imports.iter() {
+                match import {
+                    Some(import) if *import != root && roots[import] == root => {
+                        if !ordered {
+                            unordered.push(*import);
+                        }
+                    }
+                    _ => ordered = false,
+                }
+            }
+        }
+        if unordered.is_empty() {
+            break roots;
+        }
+        for module in unordered {
+            modules[&module].referenced_dynamically = true;
+        }
+    };
+
+    let mut hoisting = HashMap::new();
+    for (module, node) in modules.iter() {
+        if roots[module] != *module {
+            continue;
+        }
+        let mut hoisted = IndexSet::new();
+        collect_hoisted(*module, node, &modules, &roots, &mut hoisted);
+        if hoisted.is_empty() {
+            continue;
+        }
+        for hoisted_module in hoisted.iter() {
+            hoisting.insert(*hoisted_module, ModuleHoisting::Hoisted(*module));
+        }
+        hoisting.insert(*module, ModuleHoisting::Root(hoisted.into_iter().collect()));
+    }
+
+    Ok(ModuleGraphHoistingVc::cell(hoisting))
+}
+
+/// Returns the module into whose scope the module is hoisted, or the module
+/// itself when it can't be hoisted.
+fn scope_root(
+    module: EcmascriptModuleAssetVc,
+    modules: &IndexMap<EcmascriptModuleAssetVc, ModuleNode>,
+    cyclic: &HashSet<EcmascriptModuleAssetVc>,
+    roots: &mut HashMap<EcmascriptModuleAssetVc, EcmascriptModuleAssetVc>,
+) -> EcmascriptModuleAssetVc {
+    if let Some(root) = roots.get(&module) {
+        return *root;
+    }
+    let node = &modules[&module];
+    let mut root = None;
+    if node.static_exports && !node.referenced_dynamically && !cyclic.contains(&module) {
+        // There are no cycles, so this terminates
+        for importer in node.importers.iter() {
+            let importer_root = scope_root(*importer, modules, cyclic, roots);
+            match root {
+                None => root = Some(importer_root),
+                Some(root) if root == importer_root => {}
+                Some(_) => {
+                    root = None;
+                    break;
+                }
+            }
+        }
+    }
+    let root = root.unwrap_or(module);
+    roots.insert(module, root);
+    root
+}
+
+/// Collects the modules hoisted into the scope of `root` in execution order,
+/// i. e. imports are placed before their importer.
+fn collect_hoisted(
+    root: EcmascriptModuleAssetVc,
+    node: &ModuleNode,
+    modules: &IndexMap<EcmascriptModuleAssetVc, ModuleNode>,
+    roots: &HashMap<EcmascriptModuleAssetVc, EcmascriptModuleAssetVc>,
+    hoisted: &mut IndexSet<EcmascriptModuleAssetVc>,
+) {
+    for import in node.static_imports.iter() {
+        if *import == root || roots[import] != root || hoisted.contains(import) {
+            continue;
+        }
+        collect_hoisted(root, &modules[import], modules, roots, hoisted);
+        hoisted.insert(*import);
+    }
+}
+
+/// Finds all modules which are part of a cycle, using Tarjan's strongly
+/// connected components algorithm.
+fn modules_in_cycles(
+    modules: &IndexMap<EcmascriptModuleAssetVc, ModuleNode>,
+) -> HashSet<EcmascriptModuleAssetVc> {
+    struct State {
+        index: usize,
+        low_link: usize,
+        on_stack: bool,
+    }
+
+    let mut cyclic = HashSet::new();
+    let mut states: HashMap<EcmascriptModuleAssetVc, State> = HashMap::new();
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+
+    for start in modules.keys() {
+        if states.contains_key(start) {
+            continue;
+        }
+        // (module, index of the next dependency to visit)
+        let mut work = vec![(*start, 0)];
+        while let Some((module, dependency_index)) = work.pop() {
+            if dependency_index == 0 {
+                states.insert(
+                    module,
+                    State {
+                        index: next_index,
+                        low_link: next_index,
+                        on_stack: true,
+                    },
+                );
+                next_index += 1;
+                stack.push(module);
+            }
+            let dependencies = &modules[&module].dependencies;
+            if let Some(dependency) = dependencies.get_index(dependency_index) {
+                work.push((module, dependency_index + 1));
+                match states.get(dependency) {
+                    None => work.push((*dependency, 0)),
+                    Some(state) if state.on_stack => {
+                        let index = state.index;
+                        let state = states.get_mut(&module).unwrap();
+                        state.low_link = state.low_link.min(index);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            // All dependencies are visited
+            let state = &states[&module];
+            let (index, low_link) = (state.index, state.low_link);
+            if let Some((parent, _)) = work.last() {
+                let parent = states.get_mut(parent).unwrap();
+                parent.low_link = parent.low_link.min(low_link);
+            }
+            if index == low_link {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    states.get_mut(&member).unwrap().on_stack = false;
+                    component.push(member);
+                    if member == module {
+                        break;
+                    }
+                }
+                let self_import = dependencies.contains(&module);
+                if component.len() > 1 || self_import {
+                    cyclic.extend(component);
+                }
+            }
+        }
+    }
+    cyclic
+}
+
+/// What a binding to an export of a hoisted module is replaced with.
+#[turbo_tasks::value(shared)]
+pub(crate) enum HoistedBinding {
+    /// A top-level binding of a module in the same scope.
+    Local(String),
+    /// An export of a module which is not hoisted into the same scope, or its
+    /// namespace object.
+    Export(ModuleId, Option<String>),
+    /// The export doesn't exist.
+    Undefined,
+}
+
+/// Follows re-exports of the modules hoisted into the same scope to find the
+/// binding of the export.
+pub(crate) async fn resolve_hoisted_binding(
+    context: ChunkingContextVc,
+    scope: &HoistedScope<'_>,
+    mut module: EcmascriptChunkPlaceableVc,
+    mut export: String,
+) -> Result<HoistedBinding> {
+    loop {
+        let Some(index) = hoisted_index(scope.modules, module).await? else {
+            let id = module.as_chunk_item(context).id().await?;
+            return Ok(HoistedBinding::Export(id.clone_value(), Some(export)));
+        };
+        let EcmascriptExports::EsmExports(exports) = &*module.get_exports().await? else {
+            return Ok(HoistedBinding::Undefined);
+        };
+        let exports = exports.await?;
+        match exports.exports.get(&export) {
+            Some(EsmExport::LocalBinding(name)) => {
+                return Ok(HoistedBinding::Local(scope.hoisted_name(name, index)));
+            }
+            Some(EsmExport::ImportedBinding(reference, name)) => {
+                let ReferencedAsset::Some(target) = &*reference.get_referenced_asset().await?
+                else {
+                    return Ok(HoistedBinding::Undefined);
+                };
+                module = *target;
+                export = name.clone();
+            }
+            Some(EsmExport::ImportedNamespace(reference)) => {
+                let ReferencedAsset::Some(target) = &*reference.get_referenced_asset().await?
+                else {
+                    return Ok(HoistedBinding::Undefined);
+                };
+                // Re-exported modules are never hoisted
+                let id = target.as_chunk_item(context).id().await?;
+                return Ok(HoistedBinding::Export(id.clone_value(), None));
+            }
+            Some(EsmExport::Error) | None => return Ok(HoistedBinding::Undefined),
+        }
+    }
+}
+
+/// Returns the index of the module within the hoisted modules of a scope,
+/// starting at 1. The module which is the root of the scope has index 0.
+pub(crate) async fn hoisted_index(
+    hoisted: &[EcmascriptModuleAssetVc],
+    module: EcmascriptChunkPlaceableVc,
+) -> Result<Option<usize>> {
+    let Some(module) = EcmascriptModuleAssetVc::resolve_from(module).await? else {
+        return Ok(None);
+    };
+    Ok(hoisted
+        .iter()
+        .position(|hoisted| *hoisted == module)
+        .map(|index| index + 1))
+}
+
+/// Returns true when the reference points to a module hoisted into the same
+/// scope.
+pub(crate) async fn references_hoisted_module(
+    hoisted: &[EcmascriptModuleAssetVc],
+    reference: EsmAssetReferenceVc,
+) -> Result<bool> {
+    Ok(match &*reference.get_referenced_asset().await? {
+        ReferencedAsset::Some(module) => hoisted_index(hoisted, *module).await?.is_some(),
+        _ => false,
+    })
+}
+
+/// The modules hoisted into the scope of a root module.
+pub(crate) struct HoistedScope<'a> {
+    /// The hoisted modules in execution order.
+    pub modules: &'a [EcmascriptModuleAssetVc],
+    /// The names which renamed bindings must not use: the top-level bindings
+    /// of the root module and the unresolved references of all modules in the
+    /// scope.
+    reserved_names: HashSet<String>,
+    /// The top-level bindings of the root module which would shadow unresolved
+    /// references of the hoisted modules, e. g. a `const document` in the root
+    /// module and a read of the global `document` in a hoisted module.
+    root_renames: HashMap<String, String>,
+}
+
+impl<'a> HoistedScope<'a> {
+    pub async fn new(
+        root: EcmascriptModuleAssetVc,
+        modules: &'a [EcmascriptModuleAssetVc],
+    ) -> Result<HoistedScope<'a>> {
+        let root_bindings = top_level_bindings(root).await?;
+        let mut hoisted_references = HashSet::new();
+        for module in modules {
+            hoisted_references.extend(unresolved_references(*module).await?.iter().cloned());
+        }
+
+        let mut reserved_names = hoisted_references.clone();
+        reserved_names.extend(root_bindings.iter().cloned());
+        reserved_names.extend(unresolved_references(root).await?.iter().cloned());
+        let mut scope = Self {
+            modules,
+            reserved_names,
+            root_renames: HashMap::new(),
+        };
+        scope.root_renames = root_bindings
+            .iter()
+            .filter(|name| hoisted_references.contains(*name))
+            .map(|name| (name.clone(), scope.hoisted_name(name, 0)))
+            .collect();
+        Ok(scope)
+    }
+
+    /// Returns the name of a top-level binding of the hoisted module at
+    /// `index`, or of the root module for index 0, which must not be a
+    /// reserved name.
+    fn hoisted_name(&self, name: &str, index: usize) -> String {
+        let mut hoisted_name = format!("{name}$${index}");
+        while self.reserved_names.contains(&hoisted_name) {
+            hoisted_name.push('$');
+        }
+        hoisted_name
+    }
+
+    /// Returns true when bindings of the root module need to be renamed.
+    pub fn renames_root_bindings(&self) -> bool {
+        !self.root_renames.is_empty()
+    }
+}
+
+#[turbo_tasks::function]
+async fn top_level_bindings(module: EcmascriptModuleAssetVc) -> Result<StringsVc> {
+    let module = module.await?;
+    let parsed = parse(module.source, Value::new(module.ty), module.transforms).await?;
+    let ParseResult::Ok {
+        program,
+        globals,
+        top_level_mark,
+        ..
+    } = &*parsed
+    else {
+        return Ok(StringsVc::cell(Vec::new()));
+    };
+    let names = GLOBALS.set(globals, || top_level_decls(program, *top_level_mark));
+    Ok(StringsVc::cell(
+        names.into_iter().map(|name| name.to_string()).collect(),
+    ))
+}
+
+#[turbo_tasks::function]
+async fn unresolved_references(module: EcmascriptModuleAssetVc) -> Result<StringsVc> {
+    let module = module.await?;
+    let parsed = parse(module.source, Value::new(module.ty), module.transforms).await?;
+    let ParseResult::Ok {
+        program,
+        globals,
+        eval_context,
+        ..
+    } = &*parsed
+    else {
+        return Ok(StringsVc::cell(Vec::new()));
+    };
+    let names = GLOBALS.set(globals, || {
+        let mut collector = UnresolvedReferencesCollector {
+            unresolved_ctxt: SyntaxContext::empty().apply_mark(eval_context.unresolved_mark),
+            names: HashSet::new(),
+        };
+        program.visit_with(&mut collector);
+        collector.names
+    });
+    Ok(StringsVc::cell(
+        names.into_iter().map(|name| name.to_string()).collect(),
+    ))
+}
+
+/// Must be called with the `GLOBALS` the program was parsed with.
+fn top_level_decls(program: &Program, top_level_mark: Mark) -> HashSet<JsWord> {
+    let mut collector = TopLevelDeclsCollector {
+        top_level_ctxt: SyntaxContext::empty().apply_mark(top_level_mark),
+        names: HashSet::new(),
+    };
+    program.visit_with(&mut collector);
+    collector.names
+}
+
+/// Renames the top-level bindings of a module that is hoisted into the scope
+/// of another module, so they don't conflict with the bindings of the other
+/// modules in that scope. Bindings of the root module are only renamed when
+/// they would shadow unresolved references of the hoisted modules.
+pub(crate) struct HoistedBindingsRenamer {
+    renames: HashMap<JsWord, JsWord>,
+    top_level_ctxt: SyntaxContext,
+}
+
+impl HoistedBindingsRenamer {
+    /// Must be called with the `GLOBALS` the program was parsed with. The root
+    /// module has index 0.
+    pub fn new(
+        program: &Program,
+        top_level_mark: Mark,
+        scope: &HoistedScope<'_>,
+        index: usize,
+    ) -> Self {
+        let renames = if index == 0 {
+            scope
+                .root_renames
+                .iter()
+                .map(|(name, hoisted_name)| (name.as_str().into(), hoisted_name.as_str().into()))
+                .collect()
+        } else {
+            top_level_decls(program, top_level_mark)
+                .into_iter()
+                .map(|name| {
+                    let hoisted_name = scope.hoisted_name(&name, index).into();
+                    (name, hoisted_name)
+                })
+                .collect()
+        };
+        Self {
+            renames,
+            top_level_ctxt: SyntaxContext::empty().apply_mark(top_level_mark),
+        }
+    }
+
+    fn rename(&self, ident: &Ident) -> Option<Ident> {
+        if ident.span.ctxt != self.top_level_ctxt {
+            return None;
+        }
+        let hoisted_name = self.renames.get(&ident.sym)?;
+        let mut ident = ident.clone();
+        ident.sym = hoisted_name.clone();
+        Some(ident)
+    }
+}
+
+impl VisitMut for HoistedBindingsRenamer {
+    fn visit_mut_ident(&mut self, ident: &mut Ident) {
+        if let Some(renamed) = self.rename(ident) {
+            *ident = renamed;
+        }
+    }
+
+    fn visit_mut_prop(&mut self, prop: &mut Prop) {
+        if let Prop::Shorthand(ident) = prop {
+            if let Some(renamed) = self.rename(ident) {
+                *prop = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(ident.clone()),
+                    value: box Expr::Ident(renamed),
+                });
+            }
+            return;
+        }
+        prop.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_prop_name(&mut self, name: &mut PropName) {
+        if let PropName::Computed(computed) = name {
+            computed.visit_mut_with(self);
+        }
+    }
+
+    fn visit_mut_member_prop(&mut self, prop: &mut MemberProp) {
+        if let MemberProp::Computed(computed) = prop {
+            computed.visit_mut_with(self);
+        }
+    }
+
+    fn visit_mut_super_prop(&mut self, prop: &mut SuperProp) {
+        if let SuperProp::Computed(computed) = prop {
+            computed.visit_mut_with(self);
+        }
+    }
+
+    fn visit_mut_labeled_stmt(&mut self, stmt: &mut LabeledStmt) {
+        stmt.body.visit_mut_with(self);
+    }
+
+    fn visit_mut_break_stmt(&mut self, _: &mut BreakStmt) {}
+
+    fn visit_mut_continue_stmt(&mut self, _: &mut ContinueStmt) {}
+}
+
+struct UnresolvedReferencesCollector {
+    unresolved_ctxt: SyntaxContext,
+    names: HashSet<JsWord>,
+}
+
+impl Visit for UnresolvedReferencesCollector {
+    fn visit_ident(&mut self, ident: &Ident) {
+        if ident.span.ctxt == self.unresolved_ctxt {
+            self.names.insert(ident.sym.clone());
+        }
+    }
+}
+
+struct TopLevelDeclsCollector {
+    top_level_ctxt: SyntaxContext,
+    names: HashSet<JsWord>,
+}
+
+impl TopLevelDeclsCollector {
+    fn add(&mut self, ident: &Ident) {
+        if ident.span.ctxt == self.top_level_ctxt {
+            self.names.insert(ident.sym.clone());
+        }
+    }
+}
+
+impl Visit for TopLevelDeclsCollector {
+    fn visit_binding_ident(&mut self, ident: &BindingIdent) {
+        self.add(&ident.id);
+    }
+
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        self.add(&decl.ident);
+        decl.visit_children_with(self);
+    }
+
+    fn visit_class_decl(&mut self, decl: &ClassDecl) {
+        self.add(&decl.ident);
+        decl.visit_children_with(self);
+    }
+}
@@ -0,0 +1,594 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use swc_core::{
+    common::{input::StringInput, util::take::Take, FileName, Mark, SourceMap, Span, DUMMY_SP},
+    ecma::{
+        ast::{
+            op, ArrowExpr, AssignPatProp, BinExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, Class,
+            ComputedPropName, CondExpr, Decl, EmptyStmt, EsVersion, Expr, ExprStmt, FnDecl,
+            Function, Ident, IfStmt, Lit, MemberExpr, MemberProp, ParenExpr, Pat, PatOrExpr,
+            Program, Stmt, UnaryExpr, UpdateExpr, VarDecl, VarDeclKind, VarDeclarator,
+        },
+        atoms::JsWord,
+        parser::{lexer::Lexer, Parser, Syntax},
+        transforms::base::resolver,
+        visit::{Visit, VisitMut, VisitMutWith, VisitWith},
+    },
+};
+
+use crate::analyzer::graph::EvalContext;
+
+/// Expressions which are replaced with code at compile time, like webpack's
+/// `DefinePlugin`.
+///
+/// Keys are the replaced expressions, e. g. `__DEV__`, `process.env.NODE_ENV`
+/// or `typeof window`. Values are JavaScript code, so strings need to be
+/// quoted, e. g. `"\"production\""`.
+#[turbo_tasks::value(transparent)]
+pub struct CompileTimeDefines(#[turbo_tasks(trace_ignore)] pub IndexMap<String, String>);
+
+#[turbo_tasks::value_impl]
+impl CompileTimeDefinesVc {
+    /// Parses the values of the defines, so they are only parsed once for all
+    /// modules.
+    #[turbo_tasks::function]
+    pub async fn parse(self) -> Result<ParsedCompileTimeDefinesVc> {
+        Ok(ParsedCompileTimeDefines::new(&*self.await?)?.cell())
+    }
+}
+
+/// The parsed values of [CompileTimeDefines].
+#[turbo_tasks::value(shared, serialization = "none", eq = "manual")]
+pub struct ParsedCompileTimeDefines {
+    /// The values by their key, without spans.
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    values: HashMap<String, Expr>,
+    /// The identifiers the defined expressions start with.
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    roots: HashSet<JsWord>,
+}
+
+impl PartialEq for ParsedCompileTimeDefines {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+impl ParsedCompileTimeDefines {
+    pub fn new(defines: &IndexMap<String, String>) -> Result<Self> {
+        let source_map = SourceMap::default();
+        let mut roots = HashSet::new();
+        let mut values = HashMap::new();
+        for (key, value) in defines {
+            let key = key.trim();
+            let path = key.strip_prefix("typeof ").unwrap_or(key).trim_start();
+            roots.insert(path.split('.').next().unwrap_or_default().into());
+            let mut value = parse_define_value(&source_map, key, value)?;
+            // The spans refer to the source map of the define, not to the one
+            // of the module the value is inserted into
+            value.visit_mut_with(&mut DropSpans);
+            values.insert(key.to_string(), value);
+        }
+        Ok(ParsedCompileTimeDefines { values, roots })
+    }
+}
+
+/// Replaces the compile time defines in the program and removes the branches
+/// which become statically dead because of that, so that references in them
+/// aren't followed.
+pub fn apply_compile_time_defines(
+    program: &mut Program,
+    defines: &ParsedCompileTimeDefines,
+    unresolved_mark: Mark,
+    top_level_mark: Mark,
+) {
+    let values = defines
+        .values
+        .iter()
+        .map(|(key, value)| {
+            let mut value = value.clone();
+            value.visit_mut_with(&mut resolver(unresolved_mark, top_level_mark, false));
+            (key.clone(), value)
+        })
+        .collect();
+
+    program.visit_mut_with(&mut CompileTimeDefiner {
+        defines: values,
+        roots: defines.roots.clone(),
+        unresolved_mark,
+    });
+
+    let eval_context = EvalContext::new(program, unresolved_mark);
+    let strict = match program {
+        Program::Module(_) => true,
+        Program::Script(script) => has_use_strict(&script.body),
+    };
+    program.visit_mut_with(&mut DeadBranchRemover {
+        eval_context: &eval_context,
+        strict,
+    });
+}
+
+fn parse_define_value(source_map: &SourceMap, key: &str, value: &str) -> Result<Expr> {
+    let fm =
+        source_map.new_source_file(FileName::Custom(format!("define {key}")), value.to_string());
+    let lexer = Lexer::new(
+        Syntax::Es(Default::default()),
+        EsVersion::latest(),
+        StringInput::from(&*fm),
+        None,
+    );
+    let mut parser = Parser::new_from(lexer);
+    let expr = parser.parse_expr().map_err(|err| {
+        anyhow!(
+            "invalid value for compile time define {key}: {}",
+            err.kind().msg()
+        )
+    })?;
+    if let Some(err) = parser.take_errors().into_iter().next() {
+        return Err(anyhow!(
+            "invalid value for compile time define {key}: {}",
+            err.kind().msg()
+        ));
+    }
+
+    // The value is inserted into arbitrary expression positions, so anything
+    // but simple expressions needs to keep its parentheses.
+    Ok(match *expr {
+        expr @ (Expr::Lit(..) | Expr::Ident(..) | Expr::Member(..) | Expr::Paren(..)) => expr,
+        expr => Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: box expr,
+        }),
+    })
+}
+
+struct DropSpans;
+
+impl VisitMut for DropSpans {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        *span = DUMMY_SP;
+    }
+}
+
+struct CompileTimeDefiner {
+    defines: HashMap<String, Expr>,
+    /// The identifiers the defined expressions start with, to avoid building
+    /// keys for unrelated expressions.
+    roots: HashSet<JsWord>,
+    unresolved_mark: Mark,
+}
+
+impl CompileTimeDefiner {
+    /// Returns the key of the expression, e. g. `process.env.NODE_ENV`, when it
+    /// could be a compile time define.
+    fn key(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Ident(ident) => (ident.span.ctxt.outer() == self.unresolved_mark
+                && self.roots.contains(&ident.sym))
+            .then(|| ident.sym.to_string()),
+            Expr::Member(MemberExpr { obj, prop, .. }) => {
+                let prop = match prop {
+                    MemberProp::Ident(prop) => &prop.sym,
+                    MemberProp::Computed(ComputedPropName {
+                        expr: box Expr::Lit(Lit::Str(prop)),
+                        ..
+                    }) => &prop.value,
+                    _ => return None,
+                };
+                Some(format!("{}.{}", self.key(obj)?, prop))
+            }
+            Expr::Unary(UnaryExpr {
+                op: op!("typeof"),
+                arg,
+                ..
+            }) => Some(format!("typeof {}", self.key(arg)?)),
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for CompileTimeDefiner {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Some(value) = self.key(expr).and_then(|key| self.defines.get(&key)) {
+            *expr = value.clone();
+            return;
+        }
+        expr.visit_mut_children_with(self);
+    }
+
+    // Assignment targets must stay assignable.
+    fn visit_mut_pat_or_expr(&mut self, _: &mut PatOrExpr) {}
+
+    fn visit_mut_update_expr(&mut self, _: &mut UpdateExpr) {}
+}
+
+/// Removes the branches of `if` statements, conditional and logical
+/// expressions whose conditions are known at compile time.
+struct DeadBranchRemover<'a> {
+    eval_context: &'a EvalContext,
+    /// Whether the visited code is strict mode code.
+    strict: bool,
+}
+
+impl DeadBranchRemover<'_> {
+    fn visit_mut_with_strict<T: VisitMutWith<Self>>(&mut self, node: &mut T, strict: bool) {
+        let outer = self.strict;
+        self.strict |= strict;
+        node.visit_mut_children_with(self);
+        self.strict = outer;
+    }
+}
+
+impl VisitMut for DeadBranchRemover<'_> {
+    fn visit_mut_function(&mut self, function: &mut Function) {
+        let strict = function
+            .body
+            .as_ref()
+            .map_or(false, |body| has_use_strict(&body.stmts));
+        self.visit_mut_with_strict(function, strict);
+    }
+
+    fn visit_mut_arrow_expr(&mut self, arrow: &mut ArrowExpr) {
+        let strict = match &arrow.body {
+            BlockStmtOrExpr::BlockStmt(body) => has_use_strict(&body.stmts),
+            BlockStmtOrExpr::Expr(_) => false,
+        };
+        self.visit_mut_with_strict(arrow, strict);
+    }
+
+    fn visit_mut_class(&mut self, class: &mut Class) {
+        self.visit_mut_with_strict(class, true);
+    }
+
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.visit_mut_children_with(self);
+
+        let Stmt::If(IfStmt { span, test, .. }) = &*stmt else {
+            return;
+        };
+        let span = *span;
+        let Some(value) = self.eval_context.eval_condition(test) else {
+            return;
+        };
+        let Stmt::If(IfStmt { cons, alt, .. }) = stmt.take() else {
+            unreachable!();
+        };
+        let (live, dead) = if value {
+            (Some(*cons), alt)
+        } else {
+            (alt.map(|alt| *alt), Some(cons))
+        };
+
+        let mut stmts = Vec::new();
+        // `var` declarations are hoisted out of the removed branch, and so are
+        // function declarations in sloppy mode.
+        let mut collector = HoistedDeclsCollector {
+            strict: self.strict,
+            in_var_decl: false,
+            idents: Vec::new(),
+        };
+        if let Some(dead) = dead {
+            (*dead).visit_with(&mut collector);
+        }
+        let vars = collector.idents;
+        if !vars.is_empty() {
+            stmts.push(Stmt::Decl(Decl::Var(box VarDecl {
+                span: DUMMY_SP,
+                kind: VarDeclKind::Var,
+                declare: false,
+                decls: vars
+                    .into_iter()
+                    .map(|ident| VarDeclarator {
+                        span: DUMMY_SP,
+                        name: Pat::Ident(ident.into()),
+                        init: None,
+                        definite: false,
+                    })
+                    .collect(),
+            })));
+        }
+        stmts.extend(live);
+
+        *stmt = match stmts.len() {
+            0 => Stmt::Empty(EmptyStmt { span }),
+            1 => stmts.remove(0),
+            _ => Stmt::Block(BlockStmt { span, stmts }),
+        };
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        expr.visit_mut_children_with(self);
+
+        let live = match expr {
+            Expr::Cond(CondExpr {
+                test, cons, alt, ..
+            }) => match self.eval_context.eval_condition(test) {
+                Some(true) => cons,
+                Some(false) => alt,
+                None => return,
+            },
+            Expr::Bin(BinExpr {
+                op: op!("&&"),
+                left,
+                right,
+                ..
+            }) => match self.eval_context.eval_condition(left) {
+                Some(true) => right,
+                Some(false) => left,
+                None => return,
+            },
+            Expr::Bin(BinExpr {
+                op: op!("||"),
+                left,
+                right,
+                ..
+            }) => match self.eval_context.eval_condition(left) {
+                Some(true) => left,
+                Some(false) => right,
+                None => return,
+            },
+            _ => return,
+        };
+        *expr = (**live).take();
+    }
+}
+
+/// Collects the declarations of a removed branch which are declared in the
+/// enclosing function scope, i. e. `var` declarations and, in sloppy mode,
+/// function declarations within blocks.
+struct HoistedDeclsCollector {
+    strict: bool,
+    in_var_decl: bool,
+    idents: Vec<Ident>,
+}
+
+impl Visit for HoistedDeclsCollector {
+    fn visit_var_decl(&mut self, decl: &VarDecl) {
+        if decl.kind != VarDeclKind::Var {
+            return;
+        }
+        for declarator in decl.decls.iter() {
+            self.in_var_decl = true;
+            declarator.name.visit_with(self);
+            self.in_var_decl = false;
+        }
+    }
+
+    fn visit_binding_ident(&mut self, ident: &BindingIdent) {
+        if self.in_var_decl {
+            self.idents.push(ident.id.clone());
+        }
+    }
+
+    fn visit_assign_pat_prop(&mut self, prop: &AssignPatProp) {
+        if self.in_var_decl {
+            self.idents
+                .push(Ident::new(prop.key.sym.clone(), prop.key.span));
+        }
+    }
+
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        if !self.strict {
+            self.idents.push(decl.ident.clone());
+        }
+    }
+
+    fn visit_class(&mut self, _: &Class) {}
+
+    // Statements within expressions are in the scope of another function, and
+    // default values of patterns don't declare anything
+    fn visit_expr(&mut self, _: &Expr) {}
+}
+
+/// Returns true when the directives of the statements contain "use strict".
+fn has_use_strict(stmts: &[Stmt]) -> bool {
+    stmts
+        .iter()
+        .map_while(|stmt| match stmt {
+            Stmt::Expr(ExprStmt {
+                expr: box Expr::Lit(Lit::Str(directive)),
+                ..
+            }) => Some(directive),
+            _ => None,
+        })
+        .any(|directive| &*directive.value == "use strict")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use indexmap::IndexMap;
+    use swc_core::{
+        common::{FileName, Mark, SourceMap},
+        ecma::{
+            ast::{EsVersion, Program},
+            codegen::{text_writer::JsWriter, Emitter},
+            parser::{parse_file_as_module, parse_file_as_script},
+            transforms::base::resolver,
+            visit::VisitMutWith,
+        },
+        testing::run_test,
+    };
+
+    use super::{apply_compile_time_defines, ParsedCompileTimeDefines};
+
+    fn parse(cm: &Arc<SourceMap>, code: &str, module: bool) -> Program {
+        let fm = cm.new_source_file(FileName::Anon, code.into());
+        if module {
+            Program::Module(
+                parse_file_as_module(
+                    &fm,
+                    Default::default(),
+                    EsVersion::latest(),
+                    None,
+                    &mut vec![],
+                )
+                .unwrap(),
+            )
+        } else {
+            Program::Script(
+                parse_file_as_script(
+                    &fm,
+                    Default::default(),
+                    EsVersion::latest(),
+                    None,
+                    &mut vec![],
+                )
+                .unwrap(),
+            )
+        }
+    }
+
+    fn to_js(program: &Program, cm: &Arc<SourceMap>) -> String {
+        let mut bytes = Vec::new();
+        let mut emitter = Emitter {
+            cfg: swc_core::ecma::codegen::Config {
+                minify: true,
+                ..Default::default()
+            },
+            cm: cm.clone(),
+            comments: None,
+            wr: JsWriter::new(cm.clone(), "\n", &mut bytes, None),
+        };
+
+        emitter.emit_program(program).unwrap();
+
+        String::from_utf8(bytes).unwrap()
+    }
+
+    /// Applies the defines to the code and compares it with the expected
+    /// code, both printed by the same emitter.
+    fn assert_defined(defines: &[(&str, &str)], module: bool, code: &str, expected: &str) {
+        run_test(false, |cm, _handler| {
+            let defines = defines
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<IndexMap<_, _>>();
+            let defines = ParsedCompileTimeDefines::new(&defines).unwrap();
+
+            let mut program = parse(&cm, code, module);
+            let unresolved_mark = Mark::new();
+            let top_level_mark = Mark::new();
+            program.visit_mut_with(&mut resolver(unresolved_mark, top_level_mark, false));
+            apply_compile_time_defines(&mut program, &defines, unresolved_mark, top_level_mark);
+
+            assert_eq!(
+                to_js(&program, &cm),
+                to_js(&parse(&cm, expected, module), &cm),
+                "defining {code}"
+            );
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    const NODE_ENV: &[(&str, &str)] = &[("process.env.NODE_ENV", "\"production\"")];
+
+    #[test]
+    fn replaces_defines() {
+        assert_defined(
+            NODE_ENV,
+            true,
+            "console.log(process.env.NODE_ENV, process.env['NODE_ENV'], process.env.OTHER);",
+            "console.log(\"production\", \"production\", process.env.OTHER);",
+        );
+        // Assignment targets and local bindings are kept
+        assert_defined(
+            NODE_ENV,
+            true,
+            "process.env.NODE_ENV = 'x'; function f(process) { return process.env.NODE_ENV; }",
+            "process.env.NODE_ENV = 'x'; function f(process) { return process.env.NODE_ENV; }",
+        );
+        assert_defined(
+            &[("typeof window", "\"undefined\""), ("__VALUE__", "1 + 2")],
+            true,
+            "console.log(typeof window, __VALUE__ * 3);",
+            "console.log(\"undefined\", (1 + 2) * 3);",
+        );
+    }
+
+    #[test]
+    fn equality() {
+        assert_defined(
+            NODE_ENV,
+            true,
+            "if (process.env.NODE_ENV === 'production') { a(); } else { b(); }",
+            "{ a(); }",
+        );
+        assert_defined(
+            NODE_ENV,
+            true,
+            "if (process.env.NODE_ENV !== 'production') { a(); } else { b(); }",
+            "{ b(); }",
+        );
+        assert_defined(
+            &[("__VALUE__", "undefined")],
+            true,
+            "if (__VALUE__ == null) a(); if (__VALUE__ === null) b();",
+            "a(); ;",
+        );
+        // The result of `==` between different types depends on coercion, so
+        // the branch is kept
+        assert_defined(
+            &[("__VALUE__", "1")],
+            true,
+            "if (__VALUE__ == '1') a();",
+            "if (1 == '1') a();",
+        );
+    }
+
+    #[test]
+    fn truthiness() {
+        assert_defined(
+            &[("__DEV__", "false")],
+            true,
+            "__DEV__ && a(); __DEV__ || b(); __DEV__ ? c() : d();",
+            "false; b(); d();",
+        );
+        assert_defined(
+            &[("__NUM__", "0"), ("__STR__", "'str'")],
+            true,
+            "if (__NUM__) a(); if (!__NUM__ && __STR__) b(); if (typeof __STR__ === 'string') c();",
+            "; b(); c();",
+        );
+        // Unknown values are kept
+        assert_defined(
+            &[("__DEV__", "true")],
+            true,
+            "if (__DEV__ && unknown) a(); if (unknown && __DEV__) b(); unknown ? c() : d();",
+            "if (unknown) a(); if (unknown && true) b(); unknown ? c() : d();",
+        );
+    }
+
+    #[test]
+    fn dead_branches() {
+        let dev = &[("__DEV__", "false")];
+        assert_defined(
+            dev,
+            true,
+            "if (__DEV__) { var a = 1, { b, c: [d] } = {}; let e = 2; } else f();",
+            "{ var a, b, d; f(); }",
+        );
+        // Function declarations in blocks are block scoped in strict mode
+        assert_defined(dev, true, "if (__DEV__) { function f() {} }", ";");
+        assert_defined(
+            dev,
+            false,
+            "'use strict'; if (__DEV__) { function f() {} }",
+            "'use strict'; ;",
+        );
+        // In sloppy mode they are declared in the enclosing function scope
+        assert_defined(dev, false, "if (__DEV__) { function f() {} }", "var f;");
+        assert_defined(
+            dev,
+            false,
+            "function g() { 'use strict'; if (__DEV__) { function f() {} } } if (__DEV__) { \
+             h(function i() {}); }",
+            "function g() { 'use strict'; ; } ;",
+        );
+    }
+}
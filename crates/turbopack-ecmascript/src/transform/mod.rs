@@ -1,3 +1,4 @@
+pub mod define;
 mod server_to_client_proxy;
 
 use std::{path::Path, sync::Arc};
@@ -27,7 +28,11 @@ use turbo_tasks::{
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::environment::EnvironmentVc;
 
-use self::server_to_client_proxy::{create_proxy_module, is_client_module};
+pub use self::define::{CompileTimeDefines, CompileTimeDefinesVc};
+use self::{
+    define::apply_compile_time_defines,
+    server_to_client_proxy::{create_proxy_module, is_client_module},
+};
 
 #[derive(
     Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize, TraceRawVcs,
@@ -55,6 +60,9 @@ pub enum EcmascriptInputTransform {
     ClientDirective(StringVc),
     CommonJs,
     Custom,
+    /// Replaces expressions with code at compile time and removes the
+    /// branches which become dead because of that.
+    Define(CompileTimeDefinesVc),
     Emotion,
     /// This enables a Next.js transform which will eliminate some exports
     /// from a page file, as well as any imports exclusively used by these
@@ -152,6 +160,14 @@ impl EcmascriptInputTransform {
                     Some(comments.clone()),
                 ));
             }
+            EcmascriptInputTransform::Define(defines) => {
+                apply_compile_time_defines(
+                    program,
+                    &*defines.parse().await?,
+                    unresolved_mark,
+                    top_level_mark,
+                );
+            }
             EcmascriptInputTransform::Emotion => {
                 let p = std::mem::replace(program, Program::Module(Module::dummy()));
                 *program = p.fold_with(&mut swc_emotion::emotion(
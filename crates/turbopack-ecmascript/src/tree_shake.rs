@@ -0,0 +1,299 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::BoolVc, trace::TraceRawVcs};
+use turbo_tasks_fs::{glob::Glob, FileJsonContent, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetVc, AssetsVc},
+    chunk::ChunkingContextVc,
+    resolve::{find_context_file, package_json, FindContextFileResult},
+};
+
+use crate::{
+    chunk::{EcmascriptChunkPlaceableVc, EcmascriptExports},
+    references::esm::{base::ReferencedAsset, export::EsmExport, EsmAssetReferenceVc},
+    EcmascriptModuleAssetVc,
+};
+
+/// The exports of a module that are used within a module graph.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub enum UsedExports {
+    /// All exports are used, e. g. because the module is imported as namespace
+    /// or required via CommonJS.
+    All,
+    /// Only the named exports are used.
+    Names(BTreeSet<String>),
+}
+
+impl UsedExports {
+    fn none() -> Self {
+        UsedExports::Names(BTreeSet::new())
+    }
+
+    pub fn is_used(&self, name: &str) -> bool {
+        match self {
+            UsedExports::All => true,
+            UsedExports::Names(names) => names.contains(name),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            UsedExports::All => false,
+            UsedExports::Names(names) => names.is_empty(),
+        }
+    }
+
+    /// Adds the used exports of `other`. Returns true when exports were added.
+    fn extend(&mut self, other: &UsedExports) -> bool {
+        match (&mut *self, other) {
+            (UsedExports::All, _) => false,
+            (_, UsedExports::All) => {
+                *self = UsedExports::All;
+                true
+            }
+            (UsedExports::Names(names), UsedExports::Names(other)) => {
+                let len = names.len();
+                names.extend(other.iter().cloned());
+                names.len() != len
+            }
+        }
+    }
+}
+
+/// The exports that a module imports through each of its ESM references.
+/// Re-exports are not included, they are part of the module's
+/// [EcmascriptExports].
+#[turbo_tasks::value(transparent)]
+pub struct ImportedExports(Vec<(EsmAssetReferenceVc, UsedExports)>);
+
+#[turbo_tasks::value(shared)]
+pub enum ModuleUsage {
+    /// The module is part of the output. Exports which are not used can be
+    /// omitted.
+    Included(UsedExports),
+    /// The module is side effect free and none of its exports are used, so it
+    /// is omitted from the output.
+    Omitted,
+}
+
+impl ModuleUsage {
+    pub fn is_export_used(&self, name: &str) -> bool {
+        match self {
+            ModuleUsage::Included(used) => used.is_used(name),
+            ModuleUsage::Omitted => false,
+        }
+    }
+}
+
+/// Returns how a module is used within the tree shaken module graph of the
+/// chunking context. All exports are used when tree shaking is disabled.
+#[turbo_tasks::function]
+pub async fn module_usage(
+    context: ChunkingContextVc,
+    module: EcmascriptChunkPlaceableVc,
+) -> Result<ModuleUsageVc> {
+    let entries = context.tree_shaking_entries();
+    if entries.await?.is_empty() {
+        return Ok(ModuleUsage::Included(UsedExports::All).cell());
+    }
+    let graph = module_graph_used_exports(entries).await?;
+    Ok(match graph.get(&module) {
+        // Modules which are not reachable from the entries might be reachable
+        // in other ways, so nothing is removed from them.
+        None => ModuleUsage::Included(UsedExports::All),
+        Some(used) if used.is_empty() && *is_side_effect_free(module.path()).await? => {
+            ModuleUsage::Omitted
+        }
+        Some(used) => ModuleUsage::Included(used.clone()),
+    }
+    .cell())
+}
+
+#[turbo_tasks::value(transparent, serialization = "none")]
+struct ModuleGraphUsedExports(HashMap<EcmascriptChunkPlaceableVc, UsedExports>);
+
+/// Computes the used exports of all modules reachable from the entries. The
+/// used exports only grow, so the analysis is repeated for a module until
+/// they don't change anymore. Side effect free modules without used exports
+/// don't contribute any imports.
+#[turbo_tasks::function]
+async fn module_graph_used_exports(entries: AssetsVc) -> Result<ModuleGraphUsedExportsVc> {
+    let mut graph = UsedExportsGraph::default();
+    for entry in entries.await?.iter() {
+        graph.add_usage(*entry, &UsedExports::All).await?;
+    }
+
+    loop {
+        if let Some(asset) = graph.asset_queue.pop() {
+            for reference in asset.references().await?.iter() {
+                for asset in reference.resolve_reference().primary_assets().await?.iter() {
+                    graph.add_usage(*asset, &UsedExports::All).await?;
+                }
+            }
+        } else if let Some(module) = graph.module_queue.pop() {
+            graph.visit_module(module).await?;
+        } else {
+            break;
+        }
+    }
+
+    Ok(ModuleGraphUsedExportsVc::cell(graph.modules))
+}
+
+#[derive(Default)]
+struct UsedExportsGraph {
+    modules: HashMap<EcmascriptChunkPlaceableVc, UsedExports>,
+    /// Modules whose used exports changed since they were visited.
+    module_queue: Vec<EcmascriptChunkPlaceableVc>,
+    /// Assets which are not ecmascript, e. g. stylesheets. All exports of the
+    /// modules they reference are used.
+    visited_assets: HashSet<AssetVc>,
+    asset_queue: Vec<AssetVc>,
+}
+
+impl UsedExportsGraph {
+    async fn add_usage(&mut self, asset: AssetVc, used: &UsedExports) -> Result<()> {
+        let Some(module) = EcmascriptChunkPlaceableVc::resolve_from(asset).await? else {
+            let asset = asset.resolve().await?;
+            if self.visited_assets.insert(asset) {
+                self.asset_queue.push(asset);
+            }
+            return Ok(());
+        };
+        let changed = match self.modules.get_mut(&module) {
+            Some(current) => current.extend(used),
+            None => {
+                self.modules.insert(module, used.clone());
+                true
+            }
+        };
+        if changed {
+            self.module_queue.push(module);
+        }
+        Ok(())
+    }
+
+    async fn add_esm_usage(
+        &mut self,
+        reference: EsmAssetReferenceVc,
+        used: &UsedExports,
+    ) -> Result<()> {
+        if let ReferencedAsset::Some(module) = &*reference.get_referenced_asset().await? {
+            self.add_usage((*module).into(), used).await?;
+        }
+        Ok(())
+    }
+
+    async fn visit_module(&mut self, module: EcmascriptChunkPlaceableVc) -> Result<()> {
+        let used = self.modules[&module].clone();
+        if used.is_empty() && *is_side_effect_free(module.path()).await? {
+            return Ok(());
+        }
+
+        let imported_exports = match EcmascriptModuleAssetVc::resolve_from(module).await? {
+            Some(module) => Some(module.analyze().await?.imported_exports.await?),
+            None => None,
+        };
+        for reference in module.references().await?.iter() {
+            let esm_reference = EsmAssetReferenceVc::resolve_from(reference).await?;
+            let imported = esm_reference.and_then(|esm_reference| {
+                imported_exports.as_ref().map(|imported_exports| {
+                    imported_exports
+                        .iter()
+                        .find(|(r, _)| *r == esm_reference)
+                        .map_or_else(UsedExports::none, |(_, used)| used.clone())
+                })
+            });
+            let imported = imported.unwrap_or(UsedExports::All);
+            for asset in reference.resolve_reference().primary_assets().await?.iter() {
+                self.add_usage(*asset, &imported).await?;
+            }
+        }
+
+        // Re-exported bindings are used when the re-exporting module's export
+        // is used.
+        if let EcmascriptExports::EsmExports(exports) = &*module.get_exports().await? {
+            let exports = exports.await?;
+            for (name, export) in exports.exports.iter() {
+                if !used.is_used(name) {
+                    continue;
+                }
+                match export {
+                    EsmExport::ImportedBinding(reference, imported) => {
+                        let imported = UsedExports::Names(BTreeSet::from([imported.clone()]));
+                        self.add_esm_usage(*reference, &imported).await?;
+                    }
+                    EsmExport::ImportedNamespace(reference) => {
+                        self.add_esm_usage(*reference, &UsedExports::All).await?;
+                    }
+                    EsmExport::LocalBinding(_) | EsmExport::Error => {}
+                }
+            }
+            let star_used = match &used {
+                UsedExports::All => UsedExports::All,
+                UsedExports::Names(names) => UsedExports::Names(
+                    names
+                        .iter()
+                        .filter(|name| {
+                            *name != "default" && !exports.exports.contains_key(name.as_str())
+                        })
+                        .cloned()
+                        .collect(),
+                ),
+            };
+            for reference in exports.star_exports.iter() {
+                self.add_esm_usage(*reference, &star_used).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns true when the `sideEffects` field of the package.json of the
+/// module marks it as side effect free.
+#[turbo_tasks::function]
+async fn is_side_effect_free(path: FileSystemPathVc) -> Result<BoolVc> {
+    let FindContextFileResult::Found(package_json_path, _) =
+        &*find_context_file(path.parent(), package_json()).await?
+    else {
+        return Ok(BoolVc::cell(false));
+    };
+    let FileJsonContent::Content(package_json) = &*package_json_path.read_json().await? else {
+        return Ok(BoolVc::cell(false));
+    };
+    let package_dir = package_json_path.parent().await?;
+    let Some(path) = package_dir.get_path_to(&*path.await?).map(|path| path.to_string()) else {
+        return Ok(BoolVc::cell(false));
+    };
+    Ok(BoolVc::cell(match &package_json["sideEffects"] {
+        serde_json::Value::Bool(side_effects) => !side_effects,
+        serde_json::Value::Array(patterns) => {
+            let mut side_effect_free = true;
+            for pattern in patterns {
+                let Some(pattern) = pattern.as_str() else {
+                    side_effect_free = false;
+                    break;
+                };
+                // Like in webpack, patterns without a slash match the file name
+                // in any directory.
+                let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+                let pattern = if pattern.contains('/') {
+                    pattern.to_string()
+                } else {
+                    format!("**/{pattern}")
+                };
+                match Glob::parse(&pattern) {
+                    Ok(glob) if !glob.execute(&path) => {}
+                    _ => {
+                        side_effect_free = false;
+                        break;
+                    }
+                }
+            }
+            side_effect_free
+        }
+        _ => false,
+    }))
+}
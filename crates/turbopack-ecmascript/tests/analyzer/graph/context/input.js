@@ -0,0 +1,16 @@
+const pages = require.context("./pages", true, /\.js$/);
+const flat = require.context("./locales", false);
+const dir = "./dynamic";
+const dynamic = require.context(dir + "/nested");
+
+// Only the "sync" mode is supported.
+const lazy = require.context("./pages", true, /\.js$/, "lazy");
+
+const modules = import.meta.glob("./modules/*.js");
+const eager = import.meta.glob(["./modules/*.js", "!./modules/ignored.js"], {
+  eager: true,
+  import: "default",
+});
+
+// The pattern is not known statically.
+const unknown = import.meta.glob(pages.keys()[0]);
@@ -0,0 +1,12 @@
+const worker = new Worker(new URL("./worker.js", import.meta.url));
+const shared = new SharedWorker(new URL("./shared-worker.js", import.meta.url), {
+  type: "module",
+});
+
+// Not a worker, so this is an `Effect::Url`.
+const url = new URL("./asset.txt", import.meta.url);
+
+// `Worker` is shadowed by a local binding, so this is an `Effect::Url` too.
+function local(Worker) {
+  return new Worker(new URL("./local.js", import.meta.url));
+}
@@ -1,19 +1,25 @@
 use anyhow::Result;
 use turbo_tasks::primitives::StringVc;
 use turbo_tasks_fs::FileSystemPathVc;
-use turbopack_core::issue::{Issue, IssueVc};
+use turbopack_core::issue::{Issue, IssueVc, OptionIssueSourceVc};
 
 #[turbo_tasks::value(shared)]
 pub struct JsonIssue {
     pub path: FileSystemPathVc,
+    /// The name of the [crate::DataFormat] the file failed to parse as.
+    pub format_name: StringVc,
     pub error_message: StringVc,
+    pub source: OptionIssueSourceVc,
 }
 
 #[turbo_tasks::value_impl]
 impl Issue for JsonIssue {
     #[turbo_tasks::function]
-    fn title(&self) -> StringVc {
-        StringVc::cell("Error parsing JSON file".to_string())
+    async fn title(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "Error parsing {} file",
+            self.format_name.await?
+        )))
     }
 
     #[turbo_tasks::function]
@@ -31,6 +37,8 @@ impl Issue for JsonIssue {
         self.error_message
     }
 
-    // TODO provide source location when we extract remove JSON from FS crate.
-    // fn source(&self) -> OptionIssueSourceVc {}
+    #[turbo_tasks::function]
+    fn source(&self) -> OptionIssueSourceVc {
+        self.source
+    }
 }
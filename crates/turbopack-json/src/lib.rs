@@ -4,6 +4,9 @@
 //!
 //! When imported from ES modules, they produce a module that exports the
 //! JSON value as an object.
+//!
+//! Other data formats which map onto JSON values, like YAML or TOML, reuse
+//! the [JsonModuleAsset] by implementing [DataFormat].
 
 #![feature(min_specialization)]
 
@@ -12,27 +15,132 @@ pub mod issue;
 use anyhow::Result;
 use issue::{JsonIssue, JsonIssueVc};
 use turbo_tasks::{primitives::StringVc, ValueToString, ValueToStringVc};
-use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
 use turbopack_core::{
-    asset::{Asset, AssetContentVc, AssetVc},
+    asset::{Asset, AssetContent, AssetContentVc, AssetVc},
     chunk::{ChunkItem, ChunkItemVc, ChunkVc, ChunkableAsset, ChunkableAssetVc, ChunkingContextVc},
+    issue::{IssueSource, OptionIssueSourceVc},
     reference::AssetReferencesVc,
+    source_pos::SourcePos,
 };
 use turbopack_ecmascript::chunk::{
     EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
     EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc, EcmascriptChunkVc,
+    EcmascriptExports, EcmascriptExportsVc,
 };
 
+/// A data format whose sources are parsed to a JSON value, which is exported
+/// from the module.
+#[turbo_tasks::value_trait]
+pub trait DataFormat {
+    /// The name of the format, e. g. `JSON`.
+    fn name(&self) -> StringVc;
+
+    /// Parses the source to a JSON value.
+    fn parse(&self, source: AssetVc) -> ParsedJsonVc;
+}
+
+/// The result of parsing a source with a [DataFormat].
+#[turbo_tasks::value(shared)]
+pub enum ParsedJson {
+    /// The stringified JSON value.
+    Ok(String),
+    Err {
+        message: String,
+        source: OptionIssueSourceVc,
+    },
+}
+
+impl ParsedJson {
+    /// Creates an error result, pointing at `pos` in the source when it's
+    /// known.
+    pub fn error(message: String, source: AssetVc, pos: Option<SourcePos>) -> Self {
+        ParsedJson::Err {
+            message,
+            source: OptionIssueSourceVc::cell(pos.map(|pos| {
+                IssueSource {
+                    asset: source,
+                    start: pos,
+                    end: pos,
+                }
+                .cell()
+            })),
+        }
+    }
+}
+
+/// Reads the content of a source as a string, to be parsed by a
+/// [DataFormat]. When the source has no content a [ParsedJson::Err] is
+/// returned instead.
+pub async fn read_source(source: AssetVc) -> Result<Result<String, ParsedJson>> {
+    let AssetContent::File(file) = &*source.content().await? else {
+        return Ok(Err(ParsedJson::error("asset is not a file".to_string(), source, None)));
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        return Ok(Err(ParsedJson::error("file not found".to_string(), source, None)));
+    };
+    Ok(Ok(file.content().to_str()?.into_owned()))
+}
+
+#[turbo_tasks::value]
+pub struct JsonFormat;
+
+#[turbo_tasks::value_impl]
+impl JsonFormatVc {
+    #[turbo_tasks::function]
+    pub fn new() -> Self {
+        Self::cell(JsonFormat)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl DataFormat for JsonFormat {
+    #[turbo_tasks::function]
+    fn name(&self) -> StringVc {
+        StringVc::cell("JSON".to_string())
+    }
+
+    #[turbo_tasks::function]
+    async fn parse(&self, source: AssetVc) -> Result<ParsedJsonVc> {
+        let content = match read_source(source).await? {
+            Ok(content) => content,
+            Err(error) => return Ok(error.cell()),
+        };
+        // We parse to JSON and then stringify again to ensure that the
+        // JSON is valid.
+        Ok(match serde_json::from_str::<serde_json::Value>(&content) {
+            Ok(value) => ParsedJson::Ok(serde_json::to_string(&value)?),
+            Err(error) => {
+                // serde_json locations are 1-indexed
+                let pos = SourcePos {
+                    line: error.line().saturating_sub(1),
+                    column: error.column().saturating_sub(1),
+                };
+                ParsedJson::error(error.to_string(), source, Some(pos))
+            }
+        }
+        .cell())
+    }
+}
+
 #[turbo_tasks::value]
 pub struct JsonModuleAsset {
     source: AssetVc,
+    format: DataFormatVc,
 }
 
 #[turbo_tasks::value_impl]
 impl JsonModuleAssetVc {
     #[turbo_tasks::function]
     pub fn new(source: AssetVc) -> Self {
-        Self::cell(JsonModuleAsset { source })
+        Self::new_with_format(source, JsonFormatVc::new().into())
+    }
+
+    /// Creates a module which exports the JSON value `format` parses the
+    /// source to.
+    #[turbo_tasks::function]
+    pub fn new_with_format(source: AssetVc, format: DataFormatVc) -> Self {
+        Self::cell(JsonModuleAsset { source, format })
     }
 }
 
@@ -70,6 +178,11 @@ impl EcmascriptChunkPlaceable for JsonModuleAsset {
         })
         .into()
     }
+
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> EcmascriptExportsVc {
+        EcmascriptExports::Value.into()
+    }
 }
 
 #[turbo_tasks::value]
@@ -82,9 +195,11 @@ struct JsonChunkItem {
 impl ValueToString for JsonChunkItem {
     #[turbo_tasks::function]
     async fn to_string(&self) -> Result<StringVc> {
+        let module = self.module.await?;
         Ok(StringVc::cell(format!(
-            "{} (json)",
-            self.module.await?.source.path().to_string().await?
+            "{} ({})",
+            module.source.path().to_string().await?,
+            module.format.name().await?.to_lowercase()
         )))
     }
 }
@@ -106,22 +221,23 @@ impl EcmascriptChunkItem for JsonChunkItem {
 
     #[turbo_tasks::function]
     async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
-        // We parse to JSON and then stringify again to ensure that the
-        // JSON is valid.
-        let inner_code = match self.module.path().read_json().to_string().await {
-            Ok(content) => {
+        let module = self.module.await?;
+        let inner_code = match &*module.format.parse(module.source).await? {
+            ParsedJson::Ok(content) => {
                 let js_str_content = serde_json::to_string(content.as_str())?;
                 format!("__turbopack_export_value__(JSON.parse({js_str_content}));",)
             }
-            Err(error) => {
-                let error_message = format!("{:?}", error.to_string());
+            ParsedJson::Err { message, source } => {
                 let js_error_message = serde_json::to_string(&format!(
-                    "An error occurred while importing a JSON module: {}",
-                    &error_message
+                    "An error occurred while importing a {} module: {}",
+                    module.format.name().await?,
+                    message
                 ))?;
                 let issue: JsonIssueVc = JsonIssue {
                     path: self.module.path(),
-                    error_message: StringVc::cell(error_message),
+                    format_name: module.format.name(),
+                    error_message: StringVc::cell(message.clone()),
+                    source: *source,
                 }
                 .into();
                 issue.as_issue().emit();
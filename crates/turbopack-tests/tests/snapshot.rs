@@ -28,8 +28,8 @@ use turbopack::{
     ModuleAssetContextVc,
 };
 use turbopack_core::{
-    asset::{AssetContent, AssetContentVc, AssetVc},
-    chunk::{dev::DevChunkingContextVc, ChunkableAssetVc},
+    asset::{AssetContent, AssetContentVc, AssetVc, AssetsVc},
+    chunk::{dev::DevChunkingContextVc, prod::ProdChunkingContextVc, ChunkableAssetVc},
     context::AssetContextVc,
     environment::{BrowserEnvironment, EnvironmentIntention, EnvironmentVc, ExecutionEnvironment},
     issue::IssueVc,
@@ -66,6 +66,18 @@ struct SnapshotOptions {
     browserslist: String,
     #[serde(default = "default_entry")]
     entry: String,
+    /// Chunks the entry with the production chunking context instead of the
+    /// development one.
+    #[serde(default)]
+    production: bool,
+    /// Removes unused exports and side effect free modules within the module
+    /// graph of the entry. Only used for production.
+    #[serde(default)]
+    tree_shaking: bool,
+    /// Hoists statically imported modules into the scope of their importer.
+    /// Only used for production.
+    #[serde(default)]
+    scope_hoisting: bool,
 }
 
 impl Default for SnapshotOptions {
@@ -73,6 +85,9 @@ impl Default for SnapshotOptions {
         SnapshotOptions {
             browserslist: default_browserslist(),
             entry: default_entry(),
+            production: false,
+            tree_shaking: false,
+            scope_hoisting: false,
         }
     }
 }
@@ -194,11 +209,36 @@ async fn run_test(resource: String) -> Result<FileSystemPathVc> {
     )
     .into();
 
+    let modules = entry_paths
+        .into_iter()
+        .map(SourceAssetVc::new)
+        .map(|p| {
+            context.process(
+                p.into(),
+                Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+            )
+        })
+        .collect::<Vec<_>>();
+
     let chunk_root_path = path.join("output");
     let static_root_path = path.join("static");
-    let chunking_context =
+    let chunking_context = if options.production {
+        // Minification is disabled to keep the snapshots readable.
+        let mut builder =
+            ProdChunkingContextVc::builder(path, chunk_root_path, static_root_path, env)
+                .minify(false);
+        let entries = AssetsVc::cell(modules.clone());
+        if options.tree_shaking {
+            builder = builder.tree_shaking_entries(entries);
+        }
+        if options.scope_hoisting {
+            builder = builder.scope_hoisting_entries(entries);
+        }
+        builder.build()
+    } else {
         DevChunkingContextVc::builder(project_root, path, chunk_root_path, static_root_path, env)
-            .build();
+            .build()
+    };
 
     let expected_paths = expected(chunk_root_path)
         .await?
@@ -206,14 +246,8 @@ async fn run_test(resource: String) -> Result<FileSystemPathVc> {
         .copied()
         .collect();
 
-    let modules = entry_paths.into_iter().map(SourceAssetVc::new).map(|p| {
-        context.process(
-            p.into(),
-            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
-        )
-    });
-
     let chunks = modules
+        .into_iter()
         .map(|module| async move {
             if let Some(ecmascript) = EcmascriptModuleAssetVc::resolve_from(module).await? {
                 // TODO: Load runtime entries from snapshots
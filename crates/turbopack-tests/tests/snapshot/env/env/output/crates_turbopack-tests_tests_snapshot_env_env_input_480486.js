@@ -57,10 +57,26 @@ const BACKEND = {
     });
   },
 
+  loadWasm(chunkPath, _from) {
+    return fetch(`/${chunkPath}`)
+      .then((response) => {
+        if (!response.ok) {
+          throw new Error(`${response.status} ${response.statusText}`);
+        }
+        return response.arrayBuffer();
+      })
+      .then((buffer) => WebAssembly.compile(buffer));
+  },
+
   restart: () => self.location.reload(),
 };
 /* eslint-disable @next/next/no-assign-module-variable */
 
+// This is the runtime code shared between development and production chunks.
+// It is followed by either `runtime.js` (with hot module replacement) or
+// `runtime.prod.js`, which implement module instantiation and chunk
+// registration.
+
 /** @typedef {import('../types').ChunkRegistration} ChunkRegistration */
 /** @typedef {import('../types').ModuleFactory} ModuleFactory */
 
@@ -75,17 +91,7 @@ const BACKEND = {
 
 /** @typedef {import('../types').Runtime} Runtime */
 
-/** @typedef {import('../types').RefreshHelpers} RefreshHelpers */
-/** @typedef {import('../types/hot').Hot} Hot */
-/** @typedef {import('../types/hot').HotData} HotData */
-/** @typedef {import('../types/hot').AcceptCallback} AcceptCallback */
-/** @typedef {import('../types/hot').AcceptErrorHandler} AcceptErrorHandler */
-/** @typedef {import('../types/hot').HotState} HotState */
-/** @typedef {import('../types/protocol').EcmascriptChunkUpdate} EcmascriptChunkUpdate */
-/** @typedef {import('../types/protocol').HmrUpdateEntry} HmrUpdateEntry */
-
 /** @typedef {import('../types/runtime').Loader} Loader */
-/** @typedef {import('../types/runtime').ModuleEffect} ModuleEffect */
 
 /** @type {Array<Runnable>} */
 let runnable = [];
@@ -106,30 +112,18 @@ const loadedChunks = new Set();
  */
 const chunkLoaders = new Map();
 /**
- * Maps module IDs to persisted data between executions of their hot module
- * implementation (`hot.data`).
- *
- * @type {Map<ModuleId, HotData>}
- */
-const moduleHotData = new Map();
-/**
- * Maps module instances to their hot module state.
- *
- * @type {Map<Module, HotState>}
- */
-const moduleHotState = new Map();
-/**
- * Module IDs that are instantiated as part of the runtime of a chunk.
+ * Maps the paths of loaded WebAssembly chunks to their compiled modules.
  *
- * @type {Set<ModuleId>}
+ * @type {Map<ChunkPath, WebAssembly.Module>}
  */
-const runtimeModules = new Set();
+const compiledWasmModules = new Map();
 /**
  * Map from module ID to the chunks that contain this module.
  *
- * In HMR, we need to keep track of which modules are contained in which so
- * chunks. This is so we don't eagerly dispose of a module when it is removed
- * from chunk A, but still exists in chunk B.
+ * The Node.js backend uses this to resolve chunks relative to the chunk that
+ * requested them. In HMR, we also need to keep track of which modules are
+ * contained in which chunks, so we don't eagerly dispose of a module when it
+ * is removed from chunk A, but still exists in chunk B.
  *
  * @type {Map<ModuleId, Set<ChunkPath>>}
  */
@@ -313,11 +307,36 @@ function getOrCreateChunkLoader(chunkPath, from) {
   };
   chunkLoaders.set(chunkPath, chunkLoader);
 
-  BACKEND.loadChunk(chunkPath, from).then(onLoad, onError);
+  if (chunkPath.endsWith(".wasm")) {
+    // WebAssembly chunks do not register themselves. They are marked as loaded
+    // as soon as they are compiled, which might unblock pending runnables.
+    BACKEND.loadWasm(chunkPath, from).then((wasmModule) => {
+      compiledWasmModules.set(chunkPath, wasmModule);
+      onLoad();
+      runnable = runnable.filter((r) => r(runtime));
+    }, onError);
+  } else {
+    BACKEND.loadChunk(chunkPath, from).then(onLoad, onError);
+  }
 
   return chunkLoader;
 }
 
+/**
+ * Instantiates a WebAssembly module from a chunk that has already been loaded.
+ *
+ * @param {ChunkPath} chunkPath
+ * @param {WebAssembly.Imports} importsObj
+ * @returns {WebAssembly.Exports}
+ */
+function instantiateWasm(chunkPath, importsObj) {
+  const wasmModule = compiledWasmModules.get(chunkPath);
+  if (wasmModule == null) {
+    throw new Error(`WebAssembly chunk ${chunkPath} has not been loaded`);
+  }
+  return new WebAssembly.Instance(wasmModule, importsObj).exports;
+}
+
 /**
  * @enum {number}
  */
@@ -338,6 +357,142 @@ const SourceType = {
   Update: 2,
 };
 
+/**
+ * Runs the factory of a module, which populates the exports of the module.
+ *
+ * @param {ModuleFactory} moduleFactory
+ * @param {Module} module
+ */
+function runModuleFactory(moduleFactory, module) {
+  moduleFactory.call(module.exports, {
+    e: module.exports,
+    r: commonJsRequire.bind(null, module),
+    x: externalRequire,
+    i: esmImport.bind(null, module),
+    s: esm.bind(null, module.exports),
+    j: cjs.bind(null, module.exports),
+    v: exportValue.bind(null, module),
+    m: module,
+    c: moduleCache,
+    l: loadChunk.bind(null, module.id),
+    w: instantiateWasm,
+    p: _process,
+    g: globalThis,
+    __dirname: module.id.replace(/(^|\/)[\/]+$/, ""),
+  });
+}
+
+/**
+ * Marks a module as loaded after its factory has been run.
+ *
+ * @param {Module} module
+ */
+function finishModuleInstantiation(module) {
+  module.loaded = true;
+  if (module.interopNamespace) {
+    // in case of a circular dependency: cjs1 -> esm2 -> cjs1
+    interopEsm(module.exports, module.interopNamespace);
+  }
+}
+
+/**
+ * Adds a module to a chunk.
+ *
+ * @param {ModuleId} moduleId
+ * @param {ChunkPath} chunkPath
+ */
+function addModuleToChunk(moduleId, chunkPath) {
+  let moduleChunks = moduleChunksMap.get(moduleId);
+  if (!moduleChunks) {
+    moduleChunks = new Set([chunkPath]);
+    moduleChunksMap.set(moduleId, moduleChunks);
+  } else {
+    moduleChunks.add(chunkPath);
+  }
+}
+
+/**
+ * Returns the first chunk that included a module.
+ *
+ * @type {GetFirstModuleChunk}
+ */
+function getFirstModuleChunk(moduleId) {
+  const moduleChunkPaths = moduleChunksMap.get(moduleId);
+  if (moduleChunkPaths == null) {
+    return null;
+  }
+
+  return moduleChunkPaths.values().next().value;
+}
+
+/**
+ * Instantiates a runtime module.
+ */
+/**
+ *
+ * @param {ModuleId} moduleId
+ * @returns {Module}
+ */
+function instantiateRuntimeModule(moduleId) {
+  return instantiateModule(moduleId, SourceType.Runtime);
+}
+
+function markChunkAsLoaded(chunkPath) {
+  const chunkLoader = chunkLoaders.get(chunkPath);
+  if (!chunkLoader) {
+    loadedChunks.add(chunkPath);
+
+    // This happens for all initial chunks that are loaded directly from
+    // the HTML.
+    return;
+  }
+
+  // Only chunks that are loaded via `loadChunk` will have a loader.
+  chunkLoader.onLoad();
+}
+
+/** @type {Runtime} */
+const runtime = {
+  loadedChunks,
+  loadChunk,
+  modules: moduleFactories,
+  cache: moduleCache,
+  instantiateRuntimeModule,
+};
+// This is the hot module replacement part of the development runtime. It is
+// appended to `runtime.base.js`.
+
+/** @typedef {import('../types').RefreshHelpers} RefreshHelpers */
+/** @typedef {import('../types/hot').Hot} Hot */
+/** @typedef {import('../types/hot').HotData} HotData */
+/** @typedef {import('../types/hot').AcceptCallback} AcceptCallback */
+/** @typedef {import('../types/hot').AcceptErrorHandler} AcceptErrorHandler */
+/** @typedef {import('../types/hot').HotState} HotState */
+/** @typedef {import('../types/protocol').EcmascriptChunkUpdate} EcmascriptChunkUpdate */
+/** @typedef {import('../types/protocol').HmrUpdateEntry} HmrUpdateEntry */
+
+/** @typedef {import('../types/runtime').ModuleEffect} ModuleEffect */
+
+/**
+ * Maps module IDs to persisted data between executions of their hot module
+ * implementation (`hot.data`).
+ *
+ * @type {Map<ModuleId, HotData>}
+ */
+const moduleHotData = new Map();
+/**
+ * Maps module instances to their hot module state.
+ *
+ * @type {Map<Module, HotState>}
+ */
+const moduleHotState = new Map();
+/**
+ * Module IDs that are instantiated as part of the runtime of a chunk.
+ *
+ * @type {Set<ModuleId>}
+ */
+const runtimeModules = new Set();
+
 /**
  *
  * @param {ModuleId} id
@@ -394,28 +549,10 @@ function instantiateModule(id, sourceType, sourceId) {
   }
 
   runModuleExecutionHooks(module, () => {
-    moduleFactory.call(module.exports, {
-      e: module.exports,
-      r: commonJsRequire.bind(null, module),
-      x: externalRequire,
-      i: esmImport.bind(null, module),
-      s: esm.bind(null, module.exports),
-      j: cjs.bind(null, module.exports),
-      v: exportValue.bind(null, module),
-      m: module,
-      c: moduleCache,
-      l: loadChunk.bind(null, id),
-      p: _process,
-      g: globalThis,
-      __dirname: module.id.replace(/(^|\/)[\/]+$/, ""),
-    });
+    runModuleFactory(moduleFactory, module);
   });
 
-  module.loaded = true;
-  if (module.interopNamespace) {
-    // in case of a circular dependency: cjs1 -> esm2 -> cjs1
-    interopEsm(module.exports, module.interopNamespace);
-  }
+  finishModuleInstantiation(module);
 
   return module;
 }
@@ -939,36 +1076,6 @@ function createModuleHot(hotData) {
   return { hot, hotState };
 }
 
-/**
- * Adds a module to a chunk.
- *
- * @param {ModuleId} moduleId
- * @param {ChunkPath} chunkPath
- */
-function addModuleToChunk(moduleId, chunkPath) {
-  let moduleChunks = moduleChunksMap.get(moduleId);
-  if (!moduleChunks) {
-    moduleChunks = new Set([chunkPath]);
-    moduleChunksMap.set(moduleId, moduleChunks);
-  } else {
-    moduleChunks.add(chunkPath);
-  }
-}
-
-/**
- * Returns the first chunk that included a module.
- *
- * @type {GetFirstModuleChunk}
- */
-function getFirstModuleChunk(moduleId) {
-  const moduleChunkPaths = moduleChunksMap.get(moduleId);
-  if (moduleChunkPaths == null) {
-    return null;
-  }
-
-  return moduleChunkPaths.values().next().value;
-}
-
 /**
  * Removes a module from a chunk. Returns true there are no remaining chunks
  * including this module.
@@ -989,18 +1096,6 @@ function removeModuleFromChunk(moduleId, chunkPath) {
   return true;
 }
 
-/**
- * Instantiates a runtime module.
- */
-/**
- *
- * @param {ModuleId} moduleId
- * @returns {Module}
- */
-function instantiateRuntimeModule(moduleId) {
-  return instantiateModule(moduleId, SourceType.Runtime);
-}
-
 /**
  * Subscribes to chunk updates from the update server and applies them.
  *
@@ -1014,28 +1109,6 @@ function subscribeToChunkUpdates(chunkPath) {
   ]);
 }
 
-function markChunkAsLoaded(chunkPath) {
-  const chunkLoader = chunkLoaders.get(chunkPath);
-  if (!chunkLoader) {
-    loadedChunks.add(chunkPath);
-
-    // This happens for all initial chunks that are loaded directly from
-    // the HTML.
-    return;
-  }
-
-  // Only chunks that are loaded via `loadChunk` will have a loader.
-  chunkLoader.onLoad();
-}
-
-/** @type {Runtime} */
-const runtime = {
-  loadedChunks,
-  modules: moduleFactories,
-  cache: moduleCache,
-  instantiateRuntimeModule,
-};
-
 /**
  * @param {ChunkRegistration} chunkRegistration
  */
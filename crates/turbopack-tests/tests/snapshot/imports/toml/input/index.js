@@ -0,0 +1,4 @@
+import config from "./config.toml";
+console.log(config.package.name);
+import invalid from "./invalid.toml";
+console.log(invalid["this-is"]);
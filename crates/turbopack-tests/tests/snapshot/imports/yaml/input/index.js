@@ -0,0 +1,4 @@
+import config from "./config.yaml";
+console.log(config.name);
+import invalid from "./invalid.yaml";
+console.log(invalid["this-is"]);
@@ -0,0 +1,12 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/64baded11e115b09.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/production/async_chunk/input/import.js/manifest-chunk.js": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+
+const chunks = [
+    "output/696a8f50ee8822a7.js",
+    "output/d7097cf26f29057d.js",
+];
+
+__turbopack_export_value__(Promise.all(chunks.map(__turbopack_load__)));
+})()),
+}]);
\ No newline at end of file
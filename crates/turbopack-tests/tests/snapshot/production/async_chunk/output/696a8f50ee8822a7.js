@@ -0,0 +1,14 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/696a8f50ee8822a7.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/production/async_chunk/input/import.js (ecmascript)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$production$2f$async_chunk$2f$input$2f$node_modules$2f$foo$2f$index$2e$js__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/production/async_chunk/input/node_modules/foo/index.js (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+__TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$production$2f$async_chunk$2f$input$2f$node_modules$2f$foo$2f$index$2e$js__["foo"](true);
+
+})()),
+}]);
+
+
+//# sourceMappingURL=696a8f50ee8822a7.js.map
\ No newline at end of file
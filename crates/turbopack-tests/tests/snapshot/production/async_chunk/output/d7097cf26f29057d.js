@@ -0,0 +1,16 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/d7097cf26f29057d.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/production/async_chunk/input/node_modules/foo/index.js (ecmascript)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+
+__turbopack_esm__({
+    "foo": ()=>foo
+});
+function foo(value) {
+    console.assert(value);
+}
+
+})()),
+}]);
+
+
+//# sourceMappingURL=d7097cf26f29057d.js.map
\ No newline at end of file
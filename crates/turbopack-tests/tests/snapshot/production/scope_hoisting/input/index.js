@@ -0,0 +1,4 @@
+import { greet } from "./greet.js";
+import { name } from "./name.js";
+
+console.log(greet(name));
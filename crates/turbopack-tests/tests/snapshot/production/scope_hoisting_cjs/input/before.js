@@ -0,0 +1 @@
+export const before = "before";
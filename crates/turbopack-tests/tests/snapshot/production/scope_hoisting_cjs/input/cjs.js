@@ -0,0 +1,2 @@
+globalThis.order = ["cjs"];
+module.exports = "cjs";
@@ -0,0 +1,7 @@
+// `cjs.js` has to be evaluated before `after.js`, so `after.js` can't be
+// hoisted in front of it.
+import { before } from "./before.js";
+import cjs from "./cjs.js";
+import { after } from "./after.js";
+
+console.log(before, cjs, after);
@@ -0,0 +1,11 @@
+import { shared } from "./shared.js";
+
+const value = "a";
+function helper() {
+  return value;
+}
+function shadowed(value) {
+  return [value, shared];
+}
+
+export { value, helper, shadowed };
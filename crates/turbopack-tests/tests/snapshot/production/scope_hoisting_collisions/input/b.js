@@ -0,0 +1,6 @@
+import { shared } from "./shared.js";
+
+export const value = "b";
+export function helper() {
+  return [value, shared];
+}
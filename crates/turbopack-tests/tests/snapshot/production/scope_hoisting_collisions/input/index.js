@@ -0,0 +1,9 @@
+import { value as a, shadowed } from "./a.js";
+import { value as b } from "./b.js";
+
+const value = "index";
+// The name the binding of a.js would be renamed to
+const value$$1 = "index 1";
+
+console.log(value, value$$1, a, b, shadowed("shadowed"));
+import("./shared.js");
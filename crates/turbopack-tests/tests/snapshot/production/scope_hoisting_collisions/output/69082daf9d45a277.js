@@ -0,0 +1,14 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/69082daf9d45a277.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/production/scope_hoisting_collisions/input/shared.js (ecmascript)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+
+__turbopack_esm__({
+    "shared": ()=>shared
+});
+const shared = "shared";
+
+})()),
+}]);
+
+
+//# sourceMappingURL=69082daf9d45a277.js.map
\ No newline at end of file
@@ -0,0 +1,11 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/e65c7afcd4d78f2f.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/production/scope_hoisting_collisions/input/shared.js/manifest-chunk.js": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+
+const chunks = [
+    "output/69082daf9d45a277.js",
+];
+
+__turbopack_export_value__(Promise.all(chunks.map(__turbopack_load__)));
+})()),
+}]);
\ No newline at end of file
@@ -0,0 +1,7 @@
+import { title } from "./title.js";
+
+// Hoisted modules run before this code, so their reads of the global
+// `document` must not be bound to this declaration
+const document = { title: "local" };
+
+console.log(title, document.title);
@@ -0,0 +1 @@
+export const title = typeof document === "undefined" ? "none" : document.title;
@@ -0,0 +1,9 @@
+import { b } from "./b.js";
+
+export function a() {
+  return b();
+}
+
+export function unusedA() {
+  return "unused";
+}
@@ -0,0 +1,8 @@
+import { c } from "./c.js";
+
+export function b() {
+  return c;
+}
+
+// `d` is never used, so it's removed from both modules.
+export { d } from "./c.js";
@@ -0,0 +1,4 @@
+import { used } from "lib";
+import "lib/src/effect.js";
+
+console.log(used);
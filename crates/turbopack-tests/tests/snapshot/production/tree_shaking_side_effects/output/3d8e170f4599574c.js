@@ -0,0 +1,26 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/3d8e170f4599574c.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/production/tree_shaking_side_effects/input/node_modules/lib/index.js (ecmascript)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+
+__turbopack_esm__({
+    "used": ()=>__TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$production$2f$tree_shaking_side_effects$2f$input$2f$node_modules$2f$lib$2f$src$2f$used$2e$js__["used"]
+});
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$production$2f$tree_shaking_side_effects$2f$input$2f$node_modules$2f$lib$2f$src$2f$used$2e$js__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/production/tree_shaking_side_effects/input/node_modules/lib/src/used.js (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+;
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/production/tree_shaking_side_effects/input/node_modules/lib/src/used.js (ecmascript)": (({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_cjs__, p: process, g: global, __dirname }) => (() => {
+
+__turbopack_esm__({
+    "used": ()=>used
+});
+const used = "used";
+const alsoUnused = "also unused";
+
+})()),
+}]);
+
+
+//# sourceMappingURL=3d8e170f4599574c.js.map
\ No newline at end of file
@@ -0,0 +1,11 @@
+(self.TURBOPACK = self.TURBOPACK || []).push(["output/b234e2ffcba270f6.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/production/tree_shaking_side_effects/input/node_modules/lib/src/effect.js (ecmascript)": (function({ r: __turbopack_require__, x: __turbopack_external_require__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_cjs__, p: process, g: global, __dirname, m: module, e: exports }) { !function() {
+
+globalThis.effect = true;
+
+}.call(this) }),
+}]);
+
+
+//# sourceMappingURL=b234e2ffcba270f6.js.map
\ No newline at end of file
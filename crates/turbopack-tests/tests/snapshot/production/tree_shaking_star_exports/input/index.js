@@ -0,0 +1,3 @@
+import { one, three } from "./reexports.js";
+
+console.log(one, three);
@@ -0,0 +1,3 @@
+// `three` is shadowed by the local export of the re-exporting module.
+export const three = 3;
+export const four = 4;
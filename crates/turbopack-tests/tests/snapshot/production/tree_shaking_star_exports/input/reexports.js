@@ -0,0 +1,3 @@
+export * from "./numbers.js";
+export * from "./more-numbers.js";
+export const three = "three (local)";
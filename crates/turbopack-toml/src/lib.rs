@@ -0,0 +1,84 @@
+//! TOML asset support for turbopack.
+//!
+//! TOML assets are parsed to ensure they contain valid TOML.
+//!
+//! When imported from ES modules, they produce a module that exports the
+//! TOML value as an object.
+
+#![feature(min_specialization)]
+
+use anyhow::Result;
+use turbo_tasks::primitives::StringVc;
+use turbopack_core::{asset::AssetVc, source_pos::SourcePos};
+use turbopack_json::{
+    read_source, DataFormat, DataFormatVc, JsonModuleAssetVc, ParsedJson, ParsedJsonVc,
+};
+
+#[turbo_tasks::value]
+pub struct TomlFormat;
+
+#[turbo_tasks::value_impl]
+impl TomlFormatVc {
+    #[turbo_tasks::function]
+    pub fn new() -> Self {
+        Self::cell(TomlFormat)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl DataFormat for TomlFormat {
+    #[turbo_tasks::function]
+    fn name(&self) -> StringVc {
+        StringVc::cell("TOML".to_string())
+    }
+
+    #[turbo_tasks::function]
+    async fn parse(&self, source: AssetVc) -> Result<ParsedJsonVc> {
+        let content = match read_source(source).await? {
+            Ok(content) => content,
+            Err(error) => return Ok(error.cell()),
+        };
+        Ok(match toml::from_str(&content) {
+            Ok(value) => ParsedJson::Ok(serde_json::to_string(&toml_to_json(value))?),
+            Err(error) => {
+                let pos = error
+                    .line_col()
+                    .map(|(line, column)| SourcePos { line, column });
+                ParsedJson::error(error.to_string(), source, pos)
+            }
+        }
+        .cell())
+    }
+}
+
+/// TOML values map onto JSON values, except for datetimes, which are exported
+/// as their RFC 3339 string representation.
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(s) => serde_json::Value::String(s),
+        toml::Value::Integer(i) => i.into(),
+        toml::Value::Float(f) => f.into(),
+        toml::Value::Boolean(b) => b.into(),
+        toml::Value::Datetime(datetime) => serde_json::Value::String(datetime.to_string()),
+        toml::Value::Array(array) => array.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => serde_json::Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Creates a module which exports the value of the TOML source.
+pub fn toml_module_asset(source: AssetVc) -> JsonModuleAssetVc {
+    JsonModuleAssetVc::new_with_format(source, TomlFormatVc::new().into())
+}
+
+pub fn register() {
+    turbo_tasks::register();
+    turbo_tasks_fs::register();
+    turbopack_core::register();
+    turbopack_json::register();
+    include!(concat!(env!("OUT_DIR"), "/register.rs"));
+}
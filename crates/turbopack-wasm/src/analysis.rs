@@ -0,0 +1,55 @@
+use std::io::Read;
+
+use anyhow::{bail, Context, Result};
+use turbo_tasks::ValueToString;
+use turbo_tasks_fs::FileContent;
+use turbopack_core::asset::{AssetContent, AssetVc};
+use wasmparser::{Parser, Payload};
+
+/// The imports and exports of a WebAssembly module.
+#[turbo_tasks::value]
+pub struct WebAssemblyAnalysis {
+    /// The modules the WebAssembly module imports from, in order of their
+    /// first appearance.
+    pub imports: Vec<String>,
+    pub exports: Vec<String>,
+}
+
+/// Reads the import and export sections of a WebAssembly binary.
+#[turbo_tasks::function]
+pub async fn analyze(source: AssetVc) -> Result<WebAssemblyAnalysisVc> {
+    let path = source.path().to_string().await?;
+    let content = source.content().await?;
+    let AssetContent::File(file) = &*content else {
+        bail!("WebAssembly module {path} is not a file");
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        bail!("WebAssembly module {path} not found");
+    };
+    let mut bytes = Vec::new();
+    file.content().read().read_to_end(&mut bytes)?;
+
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+    let context = || format!("failed to parse WebAssembly module {path}");
+    for payload in Parser::new(0).parse_all(&bytes) {
+        match payload.with_context(context)? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.with_context(context)?;
+                    if !imports.iter().any(|module| module == import.module) {
+                        imports.push(import.module.to_string());
+                    }
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    exports.push(export.with_context(context)?.field.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(WebAssemblyAnalysis { imports, exports }.cell())
+}
@@ -0,0 +1,353 @@
+//! WebAssembly support for turbopack.
+//!
+//! WebAssembly modules are emitted as separate `.wasm` chunks, which the
+//! runtime compiles while loading the chunk group.
+//!
+//! When imported from ES modules, they produce a module that instantiates the
+//! WebAssembly module with its imports resolved as ES modules, and exports the
+//! exports of the instance.
+
+#![feature(min_specialization)]
+
+pub mod analysis;
+
+use std::fmt::Write;
+
+use analysis::analyze;
+use anyhow::{anyhow, Result};
+use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+use turbopack_core::{
+    asset::{Asset, AssetContent, AssetContentVc, AssetVc},
+    chunk::{
+        Chunk, ChunkItem, ChunkItemVc, ChunkReferenceVc, ChunkVc, ChunkableAsset,
+        ChunkableAssetReference, ChunkableAssetReferenceVc, ChunkableAssetVc, ChunkingContextVc,
+    },
+    context::AssetContextVc,
+    reference::{AssetReference, AssetReferenceVc, AssetReferencesVc},
+    resolve::{
+        origin::{PlainResolveOriginVc, ResolveOriginVc},
+        parse::RequestVc,
+        ResolveResultVc,
+    },
+};
+use turbopack_ecmascript::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
+        EcmascriptChunkItemOptions, EcmascriptChunkItemVc, EcmascriptChunkPlaceable,
+        EcmascriptChunkPlaceableVc, EcmascriptChunkVc, EcmascriptExports, EcmascriptExportsVc,
+    },
+    resolve::esm_resolve,
+    utils::{stringify_module_id, stringify_str},
+    EsmExport, EsmExports,
+};
+
+#[turbo_tasks::value]
+#[derive(Clone)]
+pub struct WebAssemblyModuleAsset {
+    pub source: AssetVc,
+    pub context: AssetContextVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WebAssemblyModuleAssetVc {
+    #[turbo_tasks::function]
+    pub fn new(source: AssetVc, context: AssetContextVc) -> Self {
+        Self::cell(WebAssemblyModuleAsset { source, context })
+    }
+
+    #[turbo_tasks::function]
+    async fn wasm_chunk(
+        self_vc: WebAssemblyModuleAssetVc,
+        context: ChunkingContextVc,
+    ) -> Result<WebAssemblyChunkVc> {
+        Ok(WebAssemblyChunkVc::cell(WebAssemblyChunk {
+            context,
+            source: self_vc.await?.source,
+        }))
+    }
+
+    /// The references to the modules the WebAssembly module imports from, in
+    /// the order of [WebAssemblyAnalysis::imports].
+    ///
+    /// [WebAssemblyAnalysis::imports]: analysis::WebAssemblyAnalysis::imports
+    #[turbo_tasks::function]
+    async fn import_references(self) -> Result<WebAssemblyImportReferencesVc> {
+        let this = self.await?;
+        let origin: ResolveOriginVc =
+            PlainResolveOriginVc::new(this.context, this.source.path()).into();
+        let references = analyze(this.source)
+            .await?
+            .imports
+            .iter()
+            .map(|module| {
+                WebAssemblyImportReferenceVc::new(
+                    origin,
+                    RequestVc::parse(Value::new(module.to_string().into())),
+                )
+            })
+            .collect();
+        Ok(WebAssemblyImportReferencesVc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WebAssemblyModuleAsset {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.source.path()
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        self.source.content()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self_vc: WebAssemblyModuleAssetVc) -> Result<AssetReferencesVc> {
+        Ok(AssetReferencesVc::cell(
+            self_vc
+                .import_references()
+                .await?
+                .iter()
+                .map(|reference| reference.as_asset_reference())
+                .collect(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAsset for WebAssemblyModuleAsset {
+    #[turbo_tasks::function]
+    fn as_chunk(self_vc: WebAssemblyModuleAssetVc, context: ChunkingContextVc) -> ChunkVc {
+        EcmascriptChunkVc::new(context, self_vc.as_ecmascript_chunk_placeable()).into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for WebAssemblyModuleAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self_vc: WebAssemblyModuleAssetVc,
+        context: ChunkingContextVc,
+    ) -> EcmascriptChunkItemVc {
+        ModuleChunkItemVc::cell(ModuleChunkItem {
+            module: self_vc,
+            context,
+            wasm_chunk: self_vc.wasm_chunk(context),
+        })
+        .into()
+    }
+
+    #[turbo_tasks::function]
+    async fn get_exports(&self) -> Result<EcmascriptExportsVc> {
+        let exports = analyze(self.source)
+            .await?
+            .exports
+            .iter()
+            .map(|export| (export.clone(), EsmExport::LocalBinding(export.clone())))
+            .collect();
+        Ok(EcmascriptExports::EsmExports(
+            EsmExports {
+                exports,
+                star_exports: Vec::new(),
+            }
+            .cell(),
+        )
+        .cell())
+    }
+}
+
+/// A reference from a WebAssembly module to a module it imports from. The
+/// imported module is resolved like an ES module import.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct WebAssemblyImportReference {
+    pub origin: ResolveOriginVc,
+    pub request: RequestVc,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct WebAssemblyImportReferences(Vec<WebAssemblyImportReferenceVc>);
+
+#[turbo_tasks::value_impl]
+impl WebAssemblyImportReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(origin: ResolveOriginVc, request: RequestVc) -> Self {
+        Self::cell(WebAssemblyImportReference { origin, request })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for WebAssemblyImportReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> ResolveResultVc {
+        esm_resolve(self.origin, self.request)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WebAssemblyImportReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "wasm import {}",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableAssetReference for WebAssemblyImportReference {}
+
+/// The WebAssembly binary, emitted as a chunk next to the chunks of the module
+/// that imports it.
+#[turbo_tasks::value]
+struct WebAssemblyChunk {
+    context: ChunkingContextVc,
+    source: AssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Chunk for WebAssemblyChunk {}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WebAssemblyChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "wasm chunk {}",
+            self.source.path().to_string().await?
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WebAssemblyChunk {
+    #[turbo_tasks::function]
+    async fn path(&self) -> Result<FileSystemPathVc> {
+        let mut path = self.source.path();
+        if *self.context.is_content_hashing_enabled().await? {
+            let content = self.source.content();
+            let content_hash = if let AssetContent::File(file) = &*content.await? {
+                if let FileContent::Content(file) = &*file.await? {
+                    turbo_tasks_hash::hash_xxh3_hash64(file.content())
+                } else {
+                    return Err(anyhow!("WebAssemblyChunk::path: not found"));
+                }
+            } else {
+                return Err(anyhow!("WebAssemblyChunk::path: unsupported file content"));
+            };
+            path = path.append_to_stem(&format!(".{}", turbo_tasks_hash::encode_hex(content_hash)));
+        }
+        Ok(self.context.chunk_path(path, ".wasm"))
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value]
+struct ModuleChunkItem {
+    module: WebAssemblyModuleAssetVc,
+    context: ChunkingContextVc,
+    wasm_chunk: WebAssemblyChunkVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ModuleChunkItem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "{} (wasm)",
+            self.module.await?.source.path().to_string().await?
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for ModuleChunkItem {
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        let mut references = self.module.references().await?.clone_value();
+        // The binary needs to be loaded before the module can be instantiated, so
+        // it's part of the chunk group.
+        references.push(ChunkReferenceVc::new_parallel(self.wasm_chunk.into()).into());
+        Ok(AssetReferencesVc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for ModuleChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> ChunkingContextVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
+        let this = self.module.await?;
+        let analysis = analyze(this.source).await?;
+        let import_references = self.module.import_references().await?;
+
+        let mut imports = String::new();
+        for (module, reference) in analysis.imports.iter().zip(import_references.iter()) {
+            let assets = reference.resolve_reference().primary_assets().await?;
+            let placeable = match assets.first() {
+                Some(asset) => EcmascriptChunkPlaceableVc::resolve_from(asset).await?,
+                None => None,
+            };
+            // Unresolvable imports have already been reported as issues.
+            // Instantiating the module will fail with a LinkError.
+            if let Some(placeable) = placeable {
+                let id = placeable.as_chunk_item(self.context).id().await?;
+                writeln!(
+                    imports,
+                    "    {}: __turbopack_import__({}),",
+                    stringify_str(module),
+                    stringify_module_id(&id)
+                )?;
+            }
+        }
+
+        let output_root = self.context.output_root().await?;
+        let chunk_path = self.wasm_chunk.path().await?;
+        let chunk_path = output_root.get_path_to(&chunk_path).ok_or_else(|| {
+            anyhow!(
+                "WebAssembly chunk {} is not inside of the output root",
+                chunk_path
+            )
+        })?;
+
+        let mut getters = String::new();
+        for export in analysis.exports.iter() {
+            let export = stringify_str(export);
+            writeln!(getters, "    {export}: () => exports[{export}],")?;
+        }
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!(
+                "const exports = __turbopack_wasm__({chunk_path}, \
+                 {{\n{imports}}});\n__turbopack_esm__({{\n{getters}}});\n",
+                chunk_path = stringify_str(chunk_path),
+            )
+            .into(),
+            options: EcmascriptChunkItemOptions {
+                wasm: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+pub fn register() {
+    turbo_tasks::register();
+    turbo_tasks_fs::register();
+    turbopack_core::register();
+    turbopack_ecmascript::register();
+    include!(concat!(env!("OUT_DIR"), "/register.rs"));
+}
@@ -0,0 +1,67 @@
+//! YAML asset support for turbopack.
+//!
+//! YAML assets are parsed to ensure they contain valid YAML.
+//!
+//! When imported from ES modules, they produce a module that exports the
+//! YAML value as an object.
+
+#![feature(min_specialization)]
+
+use anyhow::Result;
+use turbo_tasks::primitives::StringVc;
+use turbopack_core::{asset::AssetVc, source_pos::SourcePos};
+use turbopack_json::{
+    read_source, DataFormat, DataFormatVc, JsonModuleAssetVc, ParsedJson, ParsedJsonVc,
+};
+
+#[turbo_tasks::value]
+pub struct YamlFormat;
+
+#[turbo_tasks::value_impl]
+impl YamlFormatVc {
+    #[turbo_tasks::function]
+    pub fn new() -> Self {
+        Self::cell(YamlFormat)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl DataFormat for YamlFormat {
+    #[turbo_tasks::function]
+    fn name(&self) -> StringVc {
+        StringVc::cell("YAML".to_string())
+    }
+
+    #[turbo_tasks::function]
+    async fn parse(&self, source: AssetVc) -> Result<ParsedJsonVc> {
+        let content = match read_source(source).await? {
+            Ok(content) => content,
+            Err(error) => return Ok(error.cell()),
+        };
+        Ok(match serde_yaml::from_str::<serde_json::Value>(&content) {
+            Ok(value) => ParsedJson::Ok(serde_json::to_string(&value)?),
+            Err(error) => {
+                // serde_yaml locations are 1-indexed
+                let pos = error.location().map(|location| SourcePos {
+                    line: location.line().saturating_sub(1),
+                    column: location.column().saturating_sub(1),
+                });
+                ParsedJson::error(error.to_string(), source, pos)
+            }
+        }
+        .cell())
+    }
+}
+
+/// Creates a module which exports the value of the YAML source.
+pub fn yaml_module_asset(source: AssetVc) -> JsonModuleAssetVc {
+    JsonModuleAssetVc::new_with_format(source, YamlFormatVc::new().into())
+}
+
+pub fn register() {
+    turbo_tasks::register();
+    turbo_tasks_fs::register();
+    turbopack_core::register();
+    turbopack_json::register();
+    include!(concat!(env!("OUT_DIR"), "/register.rs"));
+}
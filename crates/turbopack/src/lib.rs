@@ -60,6 +60,9 @@ pub use turbopack_ecmascript as ecmascript;
 use turbopack_json::JsonModuleAssetVc;
 use turbopack_mdx::MdxModuleAssetVc;
 use turbopack_static::StaticModuleAssetVc;
+use turbopack_toml::toml_module_asset;
+use turbopack_wasm::WebAssemblyModuleAssetVc;
+use turbopack_yaml::yaml_module_asset;
 
 use self::{
     resolve_options_context::ResolveOptionsContextVc,
@@ -142,6 +145,8 @@ async fn apply_module_type(
         )
         .into(),
         ModuleType::Json => JsonModuleAssetVc::new(source).into(),
+        ModuleType::Yaml => yaml_module_asset(source).into(),
+        ModuleType::Toml => toml_module_asset(source).into(),
         ModuleType::Raw => source,
         ModuleType::Css(transforms) => {
             CssModuleAssetVc::new(source, context.into(), *transforms).into()
@@ -150,10 +155,11 @@ async fn apply_module_type(
             ModuleCssModuleAssetVc::new(source, context.into(), *transforms).into()
         }
         ModuleType::Static => StaticModuleAssetVc::new(source, context.into()).into(),
+        ModuleType::WebAssembly => WebAssemblyModuleAssetVc::new(source, context.into()).into(),
         ModuleType::Mdx(transforms) => {
             MdxModuleAssetVc::new(source, context.into(), *transforms).into()
         }
-        ModuleType::Custom(_) => todo!(),
+        ModuleType::Custom(custom) => custom.create_module(source, context.into()),
     })
 }
 
@@ -228,9 +234,6 @@ async fn module(
                             }
                         };
                     }
-                    ModuleRuleEffect::Custom => {
-                        todo!("Custom module rule effects are not yet supported");
-                    }
                 }
             }
         }
@@ -438,6 +441,19 @@ impl AssetContext for ModuleAssetContext {
             },
         )
     }
+
+    #[turbo_tasks::function]
+    fn with_environment(&self, environment: EnvironmentVc) -> AssetContextVc {
+        ModuleAssetContext {
+            transitions: self.transitions,
+            environment,
+            module_options_context: self.module_options_context,
+            resolve_options_context: self.resolve_options_context,
+            transition: self.transition,
+        }
+        .cell()
+        .into()
+    }
 }
 
 #[turbo_tasks::function]
@@ -619,5 +635,8 @@ pub fn register() {
     turbopack_mdx::register();
     turbopack_json::register();
     turbopack_static::register();
+    turbopack_toml::register();
+    turbopack_wasm::register();
+    turbopack_yaml::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
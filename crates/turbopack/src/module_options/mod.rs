@@ -59,6 +59,7 @@ impl ModuleOptionsVc {
             ref enable_postcss_transform,
             ref enable_webpack_loaders,
             preset_env_versions,
+            compile_time_defines,
             ref custom_ecmascript_app_transforms,
             ref custom_ecmascript_transforms,
             ref custom_rules,
@@ -74,6 +75,13 @@ impl ModuleOptionsVc {
                 }
             }
         }
+        // Defines are replaced before all other transforms, except for the
+        // TypeScript transform, so that other transforms see the replaced values.
+        let define_transforms = compile_time_defines
+            .map(EcmascriptInputTransform::Define)
+            .into_iter()
+            .collect::<Vec<_>>();
+
         let mut transforms = custom_ecmascript_app_transforms.clone();
         transforms.extend(custom_ecmascript_transforms.iter().cloned());
 
@@ -98,17 +106,28 @@ impl ModuleOptionsVc {
             transforms.push(EcmascriptInputTransform::PresetEnv(env));
         }
 
-        let app_transforms = EcmascriptInputTransformsVc::cell(transforms);
-        let vendor_transforms =
-            EcmascriptInputTransformsVc::cell(custom_ecmascript_transforms.clone());
+        let app_transforms = EcmascriptInputTransformsVc::cell(
+            define_transforms
+                .iter()
+                .chain(transforms.iter())
+                .cloned()
+                .collect(),
+        );
+        let vendor_transforms = EcmascriptInputTransformsVc::cell(
+            define_transforms
+                .iter()
+                .chain(custom_ecmascript_transforms.iter())
+                .cloned()
+                .collect(),
+        );
         let ts_app_transforms = if enable_typescript_transform {
             let mut base_transforms = vec![EcmascriptInputTransform::TypeScript];
+            base_transforms.extend(define_transforms.iter().cloned());
             base_transforms.extend(custom_ecmascript_transforms.iter().cloned());
             EcmascriptInputTransformsVc::cell(
                 base_transforms
-                    .iter()
-                    .cloned()
-                    .chain(app_transforms.await?.iter().cloned())
+                    .into_iter()
+                    .chain(transforms.iter().cloned())
                     .collect(),
             )
         } else {
@@ -129,6 +148,17 @@ impl ModuleOptionsVc {
                 ModuleRuleCondition::ResourcePathEndsWith(".json".to_string()),
                 vec![ModuleRuleEffect::ModuleType(ModuleType::Json)],
             ),
+            ModuleRule::new(
+                ModuleRuleCondition::any(vec![
+                    ModuleRuleCondition::ResourcePathEndsWith(".yaml".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".yml".to_string()),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Yaml)],
+            ),
+            ModuleRule::new(
+                ModuleRuleCondition::ResourcePathEndsWith(".toml".to_string()),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Toml)],
+            ),
             ModuleRule::new(
                 ModuleRuleCondition::ResourcePathEndsWith(".css".to_string()),
                 [
@@ -219,6 +249,10 @@ impl ModuleOptionsVc {
                 ]),
                 vec![ModuleRuleEffect::ModuleType(ModuleType::Static)],
             ),
+            ModuleRule::new(
+                ModuleRuleCondition::ResourcePathEndsWith(".wasm".to_string()),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::WebAssembly)],
+            ),
             ModuleRule::new(
                 ModuleRuleCondition::ResourcePathHasNoExtension,
                 vec![ModuleRuleEffect::ModuleType(ModuleType::Ecmascript(
@@ -2,7 +2,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{primitives::StringsVc, trace::TraceRawVcs};
 use turbopack_core::{environment::EnvironmentVc, resolve::options::ImportMappingVc};
-use turbopack_ecmascript::EcmascriptInputTransform;
+use turbopack_ecmascript::{CompileTimeDefinesVc, EcmascriptInputTransform};
 use turbopack_node::execution_context::ExecutionContextVc;
 
 use super::ModuleRule;
@@ -56,6 +56,9 @@ pub struct ModuleOptionsContext {
     pub enable_typescript_transform: bool,
     pub enable_mdx: bool,
     pub preset_env_versions: Option<EnvironmentVc>,
+    /// Expressions which are replaced with code at compile time, in app and
+    /// vendor code.
+    pub compile_time_defines: Option<CompileTimeDefinesVc>,
     pub custom_ecmascript_app_transforms: Vec<EcmascriptInputTransform>,
     pub custom_ecmascript_transforms: Vec<EcmascriptInputTransform>,
     /// Custom rules to be applied after all default rules.
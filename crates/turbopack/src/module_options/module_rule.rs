@@ -2,7 +2,10 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::trace::TraceRawVcs;
 use turbo_tasks_fs::FileSystemPath;
-use turbopack_core::{reference_type::ReferenceType, source_transform::SourceTransformsVc};
+use turbopack_core::{
+    asset::AssetVc, context::AssetContextVc, reference_type::ReferenceType,
+    source_transform::SourceTransformsVc,
+};
 use turbopack_css::CssInputTransformsVc;
 use turbopack_ecmascript::EcmascriptInputTransformsVc;
 
@@ -34,7 +37,6 @@ pub enum ModuleRuleEffect {
     ModuleType(ModuleType),
     AddEcmascriptTransforms(EcmascriptInputTransformsVc),
     SourceTransforms(SourceTransformsVc),
-    Custom,
 }
 
 #[turbo_tasks::value(serialization = "auto_for_input", shared)]
@@ -45,11 +47,24 @@ pub enum ModuleType {
     TypescriptWithTypes(EcmascriptInputTransformsVc),
     TypescriptDeclaration(EcmascriptInputTransformsVc),
     Json,
+    Yaml,
+    Toml,
     Raw,
     Mdx(EcmascriptInputTransformsVc),
     Css(CssInputTransformsVc),
     CssModule(CssInputTransformsVc),
     Static,
-    // TODO allow custom function when we support function pointers
-    Custom(u8),
+    WebAssembly,
+    Custom(CustomModuleTypeVc),
+}
+
+/// A module type provided by an embedder. It turns a source asset into a
+/// module, which provides its own references and chunk items.
+///
+/// Custom module types are selected by adding a rule with a
+/// [ModuleRuleEffect::ModuleType] effect of [ModuleType::Custom] to the
+/// `custom_rules` of the module options context.
+#[turbo_tasks::value_trait]
+pub trait CustomModuleType {
+    fn create_module(&self, source: AssetVc, context: AssetContextVc) -> AssetVc;
 }
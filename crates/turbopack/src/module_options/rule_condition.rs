@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{primitives::Regex, trace::TraceRawVcs};
-use turbo_tasks_fs::{FileSystemPath, FileSystemPathReadRef};
+use turbo_tasks_fs::{glob::GlobReadRef, FileSystemPath, FileSystemPathReadRef};
 use turbopack_core::reference_type::ReferenceType;
 
 #[derive(Debug, Clone, Serialize, Deserialize, TraceRawVcs, PartialEq, Eq)]
@@ -14,7 +14,15 @@ pub enum ModuleRuleCondition {
     ResourcePathEndsWith(String),
     ResourcePathInDirectory(String),
     ResourcePathInExactDirectory(FileSystemPathReadRef),
+    /// The resource path, relative to the root of its filesystem, matches the
+    /// regex.
     ResourcePathRegex(#[turbo_tasks(trace_ignore)] Regex),
+    /// The resource path, relative to `base`, matches the glob. Resources
+    /// outside of `base` never match.
+    ResourcePathGlob {
+        base: FileSystemPathReadRef,
+        glob: GlobReadRef,
+    },
 }
 
 impl ModuleRuleCondition {
@@ -64,7 +72,10 @@ impl ModuleRuleCondition {
             ModuleRuleCondition::ReferenceType(condition_ty) => {
                 condition_ty.includes(reference_type)
             }
-            _ => todo!("not implemented yet"),
+            ModuleRuleCondition::ResourcePathRegex(regex) => regex.is_match(&path.path),
+            ModuleRuleCondition::ResourcePathGlob { base, glob } => base
+                .get_path_to(path)
+                .map_or(false, |rel_path| glob.execute(rel_path)),
         }
     }
 }
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use turbopack_core::{
     environment::EnvironmentVc,
-    resolve::options::{ImportMapVc, ResolvedMapVc},
+    resolve::options::{ImportMapVc, ResolvedMapVc, UrlResolverVc},
 };
 
 use crate::condition::ContextCondition;
@@ -37,6 +37,8 @@ pub struct ResolveOptionsContext {
     pub fallback_import_map: Option<ImportMapVc>,
     /// An additional resolved map to use after modules have been resolved.
     pub resolved_map: Option<ResolvedMapVc>,
+    /// Resolves `http:` and `https:` imports, which are external otherwise.
+    pub url_resolver: Option<UrlResolverVc>,
     /// A list of rules to use a different resolve option context for certain
     /// context paths. The first matching is used.
     pub rules: Vec<(ContextCondition, ResolveOptionsContextVc)>,
@@ -0,0 +1,111 @@
+#![cfg(test)]
+#![feature(min_specialization)]
+
+use std::collections::HashMap;
+
+use turbo_tasks::Value;
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystemPathVc, FileSystemVc};
+use turbo_tasks_testing::{register, run};
+use turbopack::{
+    ecmascript::EcmascriptModuleAssetVc,
+    module_options::{
+        CustomModuleType, CustomModuleTypeVc, ModuleOptionsContext, ModuleRule,
+        ModuleRuleCondition, ModuleRuleEffect, ModuleType,
+    },
+    resolve_options_context::ResolveOptionsContext,
+    transition::TransitionsByNameVc,
+    ModuleAssetContextVc,
+};
+use turbopack_core::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    context::AssetContextVc,
+    environment::{EnvironmentIntention, EnvironmentVc, ExecutionEnvironment, NodeJsEnvironment},
+    reference_type::ReferenceType,
+    source_asset::SourceAssetVc,
+};
+
+register!();
+
+/// A module type which wraps `.raw` files into a [RawModule].
+#[turbo_tasks::value]
+struct RawModuleType;
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for RawModuleType {
+    #[turbo_tasks::function]
+    fn create_module(&self, source: AssetVc, _context: AssetContextVc) -> AssetVc {
+        RawModule { source }.cell().into()
+    }
+}
+
+#[turbo_tasks::value]
+struct RawModule {
+    source: AssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for RawModule {
+    #[turbo_tasks::function]
+    fn path(&self) -> FileSystemPathVc {
+        self.source.path()
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        self.source.content()
+    }
+}
+
+fn context() -> AssetContextVc {
+    let custom_module_type: CustomModuleTypeVc = RawModuleType.cell().into();
+    ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        EnvironmentVc::new(
+            Value::new(ExecutionEnvironment::NodeJsLambda(
+                NodeJsEnvironment::default().into(),
+            )),
+            Value::new(EnvironmentIntention::ServerRendering),
+        ),
+        ModuleOptionsContext {
+            custom_rules: vec![ModuleRule::new(
+                ModuleRuleCondition::ResourcePathEndsWith(".raw".to_string()),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+                    custom_module_type,
+                ))],
+            )],
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext::default().cell(),
+    )
+    .into()
+}
+
+#[tokio::test]
+async fn custom_module_type_creates_module() {
+    // The module type of this test implements a trait of turbopack, so turbopack
+    // needs to be registered before the test registers its own types
+    turbopack::register();
+    run! {
+        let fs: FileSystemVc =
+            DiskFileSystemVc::new("project".to_string(), "/project".to_string()).into();
+        let context = context();
+        let process = |path: &str| {
+            context.process(
+                SourceAssetVc::new(fs.root().join(path)).into(),
+                Value::new(ReferenceType::Undefined),
+            )
+        };
+
+        let module = process("data.raw");
+        let raw_module = RawModuleVc::resolve_from(module)
+            .await?
+            .expect("the custom module type should be used for .raw files");
+        assert_eq!(raw_module.path().await?.path, "data.raw");
+
+        // Other files keep their default module types.
+        let module = process("index.js");
+        assert!(RawModuleVc::resolve_from(module).await?.is_none());
+        assert!(EcmascriptModuleAssetVc::resolve_from(module).await?.is_some());
+    }
+}
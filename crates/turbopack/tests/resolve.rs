@@ -0,0 +1,119 @@
+#![cfg(test)]
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystemPathVc, FileSystemVc};
+use turbo_tasks_testing::{register, run};
+use turbopack_core::resolve::{
+    options::{
+        ConditionValue, ResolveIntoPackage, ResolveModules, ResolveOptions, ResolveOptionsVc,
+    },
+    parse::RequestVc,
+    resolve,
+};
+
+register!();
+
+fn fixture_root(name: &str) -> FileSystemPathVc {
+    let fs: FileSystemVc = DiskFileSystemVc::new(
+        name.to_string(),
+        format!("{}/tests/resolve/{}", env!("CARGO_MANIFEST_DIR"), name),
+    )
+    .into();
+    fs.root()
+}
+
+fn resolve_options(root: FileSystemPathVc) -> ResolveOptionsVc {
+    ResolveOptions {
+        extensions: vec![".js".to_string()],
+        modules: vec![ResolveModules::Nested(
+            root,
+            vec!["node_modules".to_string()],
+        )],
+        into_package: vec![
+            ResolveIntoPackage::ExportsField {
+                field: "exports".to_string(),
+                conditions: BTreeMap::from([("browser".to_string(), ConditionValue::Set)]),
+                unspecified_conditions: ConditionValue::Unset,
+            },
+            ResolveIntoPackage::MainField("main".to_string()),
+            ResolveIntoPackage::Default("index".to_string()),
+        ],
+        ..Default::default()
+    }
+    .cell()
+}
+
+/// Resolves `request` from the `src` directory of the fixture and returns the
+/// paths of the resolved assets.
+async fn resolve_paths(fixture: &str, request: &str) -> Result<Vec<String>> {
+    let root = fixture_root(fixture);
+    let result = resolve(
+        root.join("src"),
+        RequestVc::parse_string(request.to_string()),
+        resolve_options(root),
+    );
+    let mut paths = Vec::new();
+    for asset in result.primary_assets().await?.iter() {
+        paths.push(asset.path().await?.path.clone());
+    }
+    Ok(paths)
+}
+
+#[tokio::test]
+async fn internal_import_exact() {
+    run! {
+        turbopack::register();
+
+        assert_eq!(resolve_paths("internal-imports", "#dep").await?, vec!["src/dep.js"]);
+    }
+}
+
+#[tokio::test]
+async fn internal_import_conditions() {
+    run! {
+        turbopack::register();
+
+        assert_eq!(
+            resolve_paths("internal-imports", "#conditional").await?,
+            vec!["src/browser.js"]
+        );
+    }
+}
+
+#[tokio::test]
+async fn internal_import_wildcard() {
+    run! {
+        turbopack::register();
+
+        assert_eq!(
+            resolve_paths("internal-imports", "#utils/format").await?,
+            vec!["src/utils/format.js"]
+        );
+    }
+}
+
+#[tokio::test]
+async fn internal_import_of_package() {
+    run! {
+        turbopack::register();
+
+        assert_eq!(
+            resolve_paths("internal-imports", "#pkg").await?,
+            vec!["node_modules/pkg/main.js"]
+        );
+    }
+}
+
+#[tokio::test]
+async fn internal_import_unresolveable() {
+    run! {
+        turbopack::register();
+
+        // Not defined in the "imports" field
+        assert!(resolve_paths("internal-imports", "#missing").await?.is_empty());
+        // Targets must stay inside of the package
+        assert!(resolve_paths("internal-imports", "#outside").await?.is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(test)]
+
+use turbo_tasks::primitives::Regex;
+use turbo_tasks_fs::{glob::GlobVc, DiskFileSystemVc, FileSystemPathVc, FileSystemVc};
+use turbo_tasks_testing::{register, run};
+use turbopack::module_options::ModuleRuleCondition;
+use turbopack_core::reference_type::ReferenceType;
+
+register!();
+
+fn project_root() -> FileSystemPathVc {
+    let fs: FileSystemVc =
+        DiskFileSystemVc::new("project".to_string(), "/project".to_string()).into();
+    fs.root()
+}
+
+#[tokio::test]
+async fn resource_path_regex() {
+    run! {
+        turbopack::register();
+
+        let root = project_root();
+        let condition =
+            ModuleRuleCondition::ResourcePathRegex(Regex(regex::Regex::new(r"^src/.*\.svg$")?));
+
+        let matches = |path: &str| {
+            let condition = condition.clone();
+            let path = root.join(path);
+            async move {
+                anyhow::Ok(condition.matches(&*path.await?, &ReferenceType::Undefined))
+            }
+        };
+        assert!(matches("src/icon.svg").await?);
+        assert!(matches("src/nested/icon.svg").await?);
+        // The regex is matched against the path relative to the filesystem root.
+        assert!(!matches("lib/src/icon.svg").await?);
+        assert!(!matches("src/icon.svg.js").await?);
+    }
+}
+
+#[tokio::test]
+async fn resource_path_glob() {
+    run! {
+        turbopack::register();
+
+        let root = project_root();
+        let condition = ModuleRuleCondition::ResourcePathGlob {
+            base: root.join("src").await?,
+            glob: GlobVc::new("**/*.{yml,yaml}").await?,
+        };
+
+        let matches = |path: &str| {
+            let condition = condition.clone();
+            let path = root.join(path);
+            async move {
+                anyhow::Ok(condition.matches(&*path.await?, &ReferenceType::Undefined))
+            }
+        };
+        assert!(matches("src/config.yml").await?);
+        assert!(matches("src/nested/config.yaml").await?);
+        assert!(!matches("src/config.json").await?);
+        // The glob is relative to `base`, so resources outside of it never match.
+        assert!(!matches("config.yml").await?);
+        assert!(!matches("lib/src/config.yml").await?);
+    }
+}
@@ -5,16 +5,16 @@ use clap::{ArgAction, CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{generate, Shell};
 use dunce::canonicalize as fs_canonicalize;
 use log::error;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    commands::bin,
+    commands::{bin, prune, run as run_command},
     get_version,
     shim::{RepoMode, RepoState},
     Payload,
 };
 
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, ValueEnum)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
 pub enum OutputLogsMode {
     #[serde(rename = "full")]
     Full,
@@ -391,9 +391,30 @@ pub fn run(repo_state: Option<RepoState>) -> Result<Payload> {
         | Command::Link { .. }
         | Command::Logout { .. }
         | Command::Unlink { .. }
-        | Command::Daemon { .. }
-        | Command::Prune { .. }
-        | Command::Run(_) => Ok(Payload::Go(Box::new(clap_args))),
+        | Command::Daemon { .. } => Ok(Payload::Go(Box::new(clap_args))),
+        Command::Run(args) if run_command::can_run_natively(args) => {
+            let repo_root = match &clap_args.cwd {
+                Some(cwd) => cwd.clone(),
+                None => env::current_dir()?,
+            };
+            run_command::run(&repo_root, args)?;
+
+            Ok(Payload::Rust(Ok(0)))
+        }
+        Command::Run(_) => Ok(Payload::Go(Box::new(clap_args))),
+        Command::Prune {
+            scope,
+            docker,
+            output_dir,
+        } => {
+            let repo_root = match &clap_args.cwd {
+                Some(cwd) => cwd.clone(),
+                None => env::current_dir()?,
+            };
+            prune::run(&repo_root, scope, *docker, output_dir)?;
+
+            Ok(Payload::Rust(Ok(0)))
+        }
         Command::Completion { shell } => {
             generate(*shell, &mut Args::command(), "turbo", &mut io::stdout());
 
@@ -1 +1,3 @@
 pub(crate) mod bin;
+pub(crate) mod prune;
+pub(crate) mod run;
@@ -0,0 +1,371 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+
+use crate::{
+    lockfile::{self, Lockfile, Package},
+    package_graph::{PackageGraph, WorkspaceInfo},
+    package_json::PackageJson,
+};
+
+/// Creates a subset of the monorepo in `output_dir` that only contains the
+/// workspaces in `scope` and the workspaces they depend on, together with a
+/// pruned lockfile. Without a lockfile that can be parsed, the workspaces are
+/// pruned and the lockfile is left out.
+///
+/// With `docker`, the output is split into `full`, which contains the pruned
+/// monorepo, and `json`, which only contains the package.json files, to allow
+/// installing dependencies in a separate docker layer.
+pub fn run(repo_root: &Path, scope: &[String], docker: bool, output_dir: &str) -> Result<()> {
+    if scope.is_empty() {
+        return Err(anyhow!("at least one target must be specified"));
+    }
+
+    let root_package_json_path = repo_root.join("package.json");
+    let root_package_json = PackageJson::load(&root_package_json_path)
+        .map_err(|e| anyhow!("failed to read package.json: {}", e))?;
+    let graph = PackageGraph::build(repo_root, root_package_json)
+        .map_err(|e| anyhow!("could not construct graph: {}", e))?;
+    let package_manager = graph.package_manager;
+
+    let out_dir = repo_root.join(output_dir);
+    let full_dir = if docker {
+        out_dir.join("full")
+    } else {
+        out_dir.clone()
+    };
+    debug!("scope: {}", scope.join(", "));
+    debug!("docker: {}", docker);
+    debug!("out dir: {}", out_dir.display());
+
+    for workspace in scope {
+        let info = graph
+            .workspaces
+            .get(workspace)
+            .ok_or_else(|| anyhow!("invalid scope: package {} not found", workspace))?;
+        debug!("target: {} in {}", workspace, info.dir.display());
+        debug!("external deps: {:?}", info.unresolved_external_dependencies);
+        debug!("internal deps: {:?}", info.internal_dependencies);
+    }
+
+    package_manager.check_can_prune(repo_root)?;
+    let lockfile = graph.lockfile.as_deref();
+    if lockfile.is_none() {
+        warn!(
+            "{} couldn't be parsed, the pruned monorepo won't have a lockfile",
+            package_manager.lockfile_name()
+        );
+    }
+
+    println!(
+        "Generating pruned monorepo for {} in {}",
+        scope.join(", "),
+        out_dir.display()
+    );
+
+    fs::create_dir_all(&full_dir)
+        .map_err(|e| anyhow!("could not create output directory: {}", e))?;
+    if let Some(workspace_config) = package_manager.workspace_configuration_path() {
+        let workspace_config_path = repo_root.join(workspace_config);
+        if workspace_config_path.exists() {
+            let mut targets = vec![
+                out_dir.join(workspace_config),
+                full_dir.join(workspace_config),
+            ];
+            if docker {
+                targets.push(out_dir.join("json").join(workspace_config));
+            }
+            for target in targets {
+                copy_file(&workspace_config_path, &target)
+                    .map_err(|e| anyhow!("could not copy {}: {}", workspace_config, e))?;
+            }
+        }
+    }
+
+    let targets = graph
+        .internal_dependencies(scope)
+        .map_err(|e| anyhow!("could not traverse the dependency graph: {}", e))?;
+    debug!("targets: {:?}", targets);
+
+    let mut workspaces = Vec::new();
+    for target in &targets {
+        let info = &graph.workspaces[target];
+        workspaces.push(lockfile::to_unix_path(&info.dir));
+
+        let target_dir = full_dir.join(&info.dir);
+        copy_dir(&repo_root.join(&info.dir), &target_dir).map_err(|e| {
+            anyhow!(
+                "failed to copy {} into {}: {}",
+                target,
+                target_dir.display(),
+                e
+            )
+        })?;
+        if docker {
+            let json_path = out_dir.join("json").join(&info.package_json_path);
+            copy_file(&repo_root.join(&info.package_json_path), &json_path).map_err(|e| {
+                anyhow!(
+                    "failed to copy {} into {}: {}",
+                    target,
+                    json_path.display(),
+                    e
+                )
+            })?;
+        }
+
+        println!(" - Added {target}");
+    }
+    debug!("new workspaces: {:?}", workspaces);
+
+    let pruned_lockfile = lockfile
+        .map(|lockfile| prune_lockfile(&graph, lockfile, &targets, &workspaces))
+        .transpose()?;
+    if let Some(pruned_lockfile) = &pruned_lockfile {
+        fs::write(
+            out_dir.join(package_manager.lockfile_name()),
+            pruned_lockfile
+                .encode()
+                .map_err(|e| anyhow!("Failed to encode pruned lockfile: {}", e))?,
+        )
+        .map_err(|e| anyhow!("Failed to write pruned lockfile: {}", e))?;
+    }
+
+    for file in [".gitignore", "turbo.json"] {
+        let path = repo_root.join(file);
+        if path.exists() {
+            copy_file(&path, &full_dir.join(file))
+                .map_err(|e| anyhow!("failed to copy root {}: {}", file, e))?;
+        }
+    }
+
+    let new_package_json_path = full_dir.join("package.json");
+    let pruned_patches = match (lockfile, &pruned_lockfile) {
+        (Some(lockfile), Some(pruned_lockfile)) if !lockfile.patches().is_empty() => {
+            Some(pruned_lockfile.patches())
+        }
+        _ => None,
+    };
+    if let Some(patches) = pruned_patches {
+        // The package.json can't reference patches of packages that were pruned
+        let mut package_json = graph.root.package_json.raw_json.clone();
+        package_manager
+            .prune_patched_packages(&mut package_json, &patches)
+            .map_err(|e| {
+                anyhow!(
+                    "Unable to prune patches section of {}: {}",
+                    root_package_json_path.display(),
+                    e
+                )
+            })?;
+        let mut content = serde_json::to_string_pretty(&package_json)?;
+        content.push('\n');
+        fs::write(&new_package_json_path, content)?;
+        fs::set_permissions(
+            &new_package_json_path,
+            fs::metadata(&root_package_json_path)?.permissions(),
+        )?;
+
+        for patch in patches {
+            copy_file(&repo_root.join(&patch), &full_dir.join(&patch))
+                .map_err(|e| anyhow!("Failed copying patch file {}: {}", patch, e))?;
+        }
+    } else {
+        copy_file(&root_package_json_path, &new_package_json_path)
+            .map_err(|e| anyhow!("failed to copy root package.json: {}", e))?;
+    }
+
+    if docker {
+        // Copy the package.json from the full directory to get the pruned version
+        copy_file(
+            &new_package_json_path,
+            &out_dir.join("json").join("package.json"),
+        )
+        .map_err(|e| anyhow!("failed to copy root package.json: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Creates a lockfile with the workspaces in `targets`, the root package and
+/// their external dependencies.
+fn prune_lockfile(
+    graph: &PackageGraph,
+    lockfile: &dyn Lockfile,
+    targets: &BTreeSet<String>,
+    workspaces: &[String],
+) -> Result<Box<dyn Lockfile>> {
+    let mut lockfile_packages = transitive_dependencies(&graph.root)?.clone();
+    for target in targets {
+        lockfile_packages.extend(
+            transitive_dependencies(&graph.workspaces[target])?
+                .iter()
+                .cloned(),
+        );
+    }
+
+    let mut lockfile_keys = lockfile_packages
+        .into_iter()
+        .map(|package| package.key)
+        .collect::<Vec<_>>();
+    lockfile_keys.sort();
+    lockfile
+        .subgraph(workspaces, &lockfile_keys)
+        .map_err(|e| anyhow!("Failed creating pruned lockfile: {}", e))
+}
+
+fn transitive_dependencies(info: &WorkspaceInfo) -> Result<&HashSet<Package>> {
+    info.transitive_dependencies.as_ref().ok_or_else(|| {
+        anyhow!(
+            "could not resolve the external dependencies of {}",
+            info.package_json_path.display()
+        )
+    })
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(from, to)?;
+    Ok(())
+}
+
+/// Copies a workspace directory. Installed dependencies are skipped, as well as
+/// symlinked directories.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    fs::set_permissions(to, fs::metadata(from)?.permissions())?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if entry.file_name() != "node_modules" {
+                copy_dir(&path, &target)?;
+            }
+        } else if !file_type.is_symlink() || path.is_file() {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Copies the fixture monorepo, as pruning writes into the repository.
+    fn fixture() -> Result<TempDir> {
+        let repo_root = tempfile::tempdir()?;
+        copy_dir(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/npm-monorepo"),
+            repo_root.path(),
+        )?;
+        Ok(repo_root)
+    }
+
+    fn read_json(path: &Path) -> Result<Value> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    fn workspace_names(repo_root: &Path) -> Result<Vec<String>> {
+        let root_package_json = PackageJson::load(&repo_root.join("package.json"))?;
+        let graph = PackageGraph::build(repo_root, root_package_json)?;
+        Ok(graph.workspaces.into_keys().collect())
+    }
+
+    #[test]
+    fn test_prune() -> Result<()> {
+        let repo_root = fixture()?;
+        run(repo_root.path(), &["web".to_string()], false, "out")?;
+        let out_dir = repo_root.path().join("out");
+
+        assert_eq!(
+            fs::read_to_string(out_dir.join("package.json"))?,
+            fs::read_to_string(repo_root.path().join("package.json"))?
+        );
+        assert!(out_dir.join("turbo.json").exists());
+        assert!(out_dir.join("apps/web/src/index.js").exists());
+        assert!(out_dir.join("packages/ui/src/index.js").exists());
+        assert!(!out_dir.join("apps/docs").exists());
+        assert_eq!(workspace_names(&out_dir)?, vec!["ui", "web"]);
+
+        let original = read_json(&repo_root.path().join("package-lock.json"))?;
+        let pruned = read_json(&out_dir.join("package-lock.json"))?;
+        let packages = pruned["packages"].as_object().unwrap();
+        assert_eq!(
+            packages.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec![
+                "",
+                "apps/web",
+                "node_modules/js-tokens",
+                "node_modules/loose-envify",
+                "node_modules/prettier",
+                "node_modules/react",
+                "node_modules/ui",
+                "node_modules/web",
+                "packages/ui",
+            ]
+        );
+        for (key, entry) in packages {
+            assert_eq!(entry, &original["packages"][key]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_docker() -> Result<()> {
+        let repo_root = fixture()?;
+        run(repo_root.path(), &["docs".to_string()], true, "out")?;
+        let out_dir = repo_root.path().join("out");
+
+        assert!(out_dir.join("package-lock.json").exists());
+        assert!(out_dir.join("full/apps/docs/package.json").exists());
+        assert!(out_dir.join("full/packages/ui/src/index.js").exists());
+        assert!(!out_dir.join("full/apps/web").exists());
+        assert_eq!(workspace_names(&out_dir.join("full"))?, vec!["docs", "ui"]);
+
+        assert!(out_dir.join("json/package.json").exists());
+        assert!(out_dir.join("json/apps/docs/package.json").exists());
+        assert!(out_dir.join("json/packages/ui/package.json").exists());
+        assert!(!out_dir.join("json/packages/ui/src").exists());
+        assert_eq!(workspace_names(&out_dir.join("json"))?, vec!["docs", "ui"]);
+
+        let pruned = read_json(&out_dir.join("package-lock.json"))?;
+        let packages = pruned["packages"].as_object().unwrap();
+        assert!(packages.contains_key("node_modules/lodash"));
+        assert!(!packages.contains_key("node_modules/react"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_without_lockfile() -> Result<()> {
+        let repo_root = fixture()?;
+        fs::remove_file(repo_root.path().join("package-lock.json"))?;
+        run(repo_root.path(), &["web".to_string()], false, "out")?;
+        let out_dir = repo_root.path().join("out");
+
+        assert!(!out_dir.join("package-lock.json").exists());
+        assert!(out_dir.join("package.json").exists());
+        assert_eq!(workspace_names(&out_dir)?, vec!["ui", "web"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_unknown_scope() -> Result<()> {
+        let repo_root = fixture()?;
+        let error = run(repo_root.path(), &["api".to_string()], false, "out").unwrap_err();
+        assert_eq!(error.to_string(), "invalid scope: package api not found");
+        Ok(())
+    }
+}
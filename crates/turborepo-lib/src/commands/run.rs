@@ -0,0 +1,348 @@
+use std::{collections::BTreeSet, path::Path};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::{
+    cli::{DryRunMode, RunArgs},
+    package_graph::PackageGraph,
+    package_json::PackageJson,
+    scope::{LegacyFilter, Resolver},
+    task_graph::{package_and_task, TaskGraph, TaskGraphOptions},
+    turbo_json::{pipeline_has_task, task_definition, TurboJson},
+};
+
+const MISSING_COMMAND: &str = "<NONEXISTENT>";
+
+/// The packages and tasks a run would execute, printed by `--dry=json`.
+///
+/// The task hashes and cache states that Go prints as `hash` and `cacheState`
+/// are left out until task hashing is ported.
+#[derive(Debug, Serialize)]
+struct DryRunSummary {
+    packages: Vec<String>,
+    tasks: Vec<TaskSummary>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskSummary {
+    task_id: String,
+    task: String,
+    package: String,
+    command: String,
+    outputs: Vec<String>,
+    excluded_outputs: Vec<String>,
+    log_file: String,
+    directory: String,
+    dependencies: Vec<String>,
+    dependents: Vec<String>,
+}
+
+/// The dry run summary of a single package repository, which omits the
+/// package names.
+#[derive(Debug, Serialize)]
+struct SinglePackageDryRunSummary {
+    tasks: Vec<SinglePackageTaskSummary>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SinglePackageTaskSummary {
+    task: String,
+    command: String,
+    outputs: Vec<String>,
+    excluded_outputs: Vec<String>,
+    log_file: String,
+    dependencies: Vec<String>,
+    dependents: Vec<String>,
+}
+
+impl From<TaskSummary> for SinglePackageTaskSummary {
+    fn from(summary: TaskSummary) -> Self {
+        let strip_package = |task_id: String| package_and_task(&task_id).1.to_string();
+        SinglePackageTaskSummary {
+            task: summary.task,
+            command: summary.command,
+            outputs: summary.outputs,
+            excluded_outputs: summary.excluded_outputs,
+            log_file: summary.log_file,
+            dependencies: summary
+                .dependencies
+                .into_iter()
+                .map(strip_package)
+                .collect(),
+            dependents: summary.dependents.into_iter().map(strip_package).collect(),
+        }
+    }
+}
+
+/// Checks if a run only needs the package and task graphs, which are built in
+/// Rust. Executing tasks, text dry runs and filtering by changed packages are
+/// still handled by Go.
+pub fn can_run_natively(args: &RunArgs) -> bool {
+    // Graph files are rendered with graphviz, which is left to Go
+    let only_inspects_graph = match args.graph.as_deref() {
+        Some(graph) => graph.is_empty(),
+        None => matches!(args.dry_run, Some(DryRunMode::Json)),
+    };
+    let filters_by_changes =
+        args.since.is_some() || args.filter.iter().any(|filter| filter.contains('['));
+    only_inspects_graph && !filters_by_changes
+}
+
+/// Builds the task graph of a run and prints it, either as a dot graph for
+/// `--graph` or as JSON for `--dry=json`.
+pub fn run(repo_root: &Path, args: &RunArgs) -> Result<()> {
+    let output = render(repo_root, args)?;
+    if args.graph.is_some() {
+        println!();
+        print!("{output}");
+    } else {
+        println!("{output}");
+    }
+    Ok(())
+}
+
+fn render(repo_root: &Path, args: &RunArgs) -> Result<String> {
+    if args.tasks.is_empty() {
+        return Err(anyhow!("at least one task must be specified"));
+    }
+
+    let root_package_json = PackageJson::load(&repo_root.join("package.json"))
+        .map_err(|e| anyhow!("failed to read package.json: {}", e))?;
+    let turbo_json = TurboJson::load(repo_root, &root_package_json, args.single_package)?;
+    let package_graph = if args.single_package {
+        PackageGraph::build_single_package(repo_root, root_package_json)
+    } else {
+        PackageGraph::build(repo_root, root_package_json)
+    }
+    .map_err(|e| anyhow!("could not construct graph: {}", e))?;
+
+    for task in &args.tasks {
+        if !pipeline_has_task(&turbo_json.pipeline, task) {
+            return Err(anyhow!("task `{}` not found", task));
+        }
+    }
+
+    let packages = packages_in_scope(&package_graph, args)?;
+    let task_graph = TaskGraph::build(
+        &package_graph,
+        &turbo_json.pipeline,
+        TaskGraphOptions {
+            packages: &packages,
+            tasks: &args.tasks,
+            tasks_only: args.only,
+        },
+    )?;
+
+    if args.graph.is_some() {
+        return Ok(task_graph.to_dot());
+    }
+    dry_run_json(
+        &package_graph,
+        &turbo_json,
+        &task_graph,
+        packages,
+        args.single_package,
+    )
+}
+
+/// Returns the packages selected by `--filter`, or by the legacy `--scope`
+/// options. All packages are selected if there are no filters.
+fn packages_in_scope(package_graph: &PackageGraph, args: &RunArgs) -> Result<BTreeSet<String>> {
+    let legacy_filter = LegacyFilter {
+        include_dependencies: args.include_dependencies,
+        skip_dependents: args.no_deps,
+        entrypoints: &args.scope,
+        since: args.since.as_deref(),
+    };
+    let mut patterns = args.filter.clone();
+    patterns.extend(legacy_filter.as_filter_patterns());
+
+    if patterns.is_empty() {
+        return Ok(package_graph
+            .workspace_names()
+            .map(|name| name.to_string())
+            .collect());
+    }
+    Resolver {
+        graph: package_graph,
+    }
+    .packages_from_patterns(&patterns)
+}
+
+/// Renders the summary printed by `--dry=json`.
+fn dry_run_json(
+    package_graph: &PackageGraph,
+    turbo_json: &TurboJson,
+    task_graph: &TaskGraph,
+    packages: BTreeSet<String>,
+    single_package: bool,
+) -> Result<String> {
+    let tasks = task_graph
+        .sorted_tasks()
+        .into_iter()
+        .map(|task_id| task_summary(package_graph, turbo_json, task_graph, task_id))
+        .collect::<Result<Vec<_>>>()?;
+    let json = if single_package {
+        serde_json::to_string_pretty(&SinglePackageDryRunSummary {
+            tasks: tasks
+                .into_iter()
+                .map(SinglePackageTaskSummary::from)
+                .collect(),
+        })?
+    } else {
+        serde_json::to_string_pretty(&DryRunSummary {
+            packages: packages.into_iter().collect(),
+            tasks,
+        })?
+    };
+    Ok(json)
+}
+
+fn task_summary(
+    package_graph: &PackageGraph,
+    turbo_json: &TurboJson,
+    task_graph: &TaskGraph,
+    task_id: &str,
+) -> Result<TaskSummary> {
+    let (package, task) = package_and_task(task_id);
+    let workspace = package_graph
+        .workspace(package)
+        .ok_or_else(|| anyhow!("Cannot find package {}", package))?;
+    let definition = task_definition(&turbo_json.pipeline, task_id)
+        .ok_or_else(|| anyhow!("Cannot find task definition for {}", task_id))?;
+
+    let command = workspace
+        .package_json
+        .scripts
+        .get(task)
+        .cloned()
+        .unwrap_or_else(|| MISSING_COMMAND.to_string());
+    let directory = match workspace.dir.to_str() {
+        Some("") => ".".to_string(),
+        _ => workspace.dir.to_string_lossy().to_string(),
+    };
+    let log_file = workspace
+        .dir
+        .join(".turbo")
+        .join(format!("turbo-{task}.log"));
+
+    Ok(TaskSummary {
+        task_id: task_id.to_string(),
+        task: task.to_string(),
+        package: package.to_string(),
+        command,
+        outputs: definition.outputs.inclusions.clone(),
+        excluded_outputs: definition.outputs.exclusions.clone(),
+        log_file: log_file.to_string_lossy().to_string(),
+        directory,
+        dependencies: task_graph
+            .transitive_dependencies(task_id)
+            .into_iter()
+            .map(|task| task.to_string())
+            .collect(),
+        dependents: task_graph
+            .transitive_dependents(task_id)
+            .into_iter()
+            .map(|task| task.to_string())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> &'static Path {
+        Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/npm-monorepo"
+        ))
+    }
+
+    #[test]
+    fn test_can_run_natively() {
+        let graph = RunArgs {
+            graph: Some(String::new()),
+            ..RunArgs::default()
+        };
+        assert!(can_run_natively(&graph));
+        assert!(!can_run_natively(&RunArgs {
+            graph: Some("graph.svg".to_string()),
+            ..RunArgs::default()
+        }));
+        assert!(can_run_natively(&RunArgs {
+            dry_run: Some(DryRunMode::Json),
+            ..RunArgs::default()
+        }));
+        assert!(!can_run_natively(&RunArgs {
+            dry_run: Some(DryRunMode::Text),
+            ..RunArgs::default()
+        }));
+        assert!(!can_run_natively(&RunArgs {
+            since: Some("main".to_string()),
+            ..graph
+        }));
+    }
+
+    #[test]
+    fn test_dry_run_json() -> Result<()> {
+        let json = render(
+            fixture(),
+            &RunArgs {
+                tasks: vec!["build".to_string()],
+                dry_run: Some(DryRunMode::Json),
+                ..RunArgs::default()
+            },
+        )?;
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json)?,
+            serde_json::json!({
+                "packages": ["//", "docs", "ui", "web"],
+                "tasks": [
+                    {
+                        "taskId": "ui#build",
+                        "task": "build",
+                        "package": "ui",
+                        "command": "echo 'building ui'",
+                        "outputs": ["dist/**"],
+                        "excludedOutputs": [],
+                        "logFile": "packages/ui/.turbo/turbo-build.log",
+                        "directory": "packages/ui",
+                        "dependencies": [],
+                        "dependents": ["docs#build", "web#build"],
+                    },
+                    {
+                        "taskId": "docs#build",
+                        "task": "build",
+                        "package": "docs",
+                        "command": "echo 'building docs'",
+                        "outputs": ["dist/**"],
+                        "excludedOutputs": [],
+                        "logFile": "apps/docs/.turbo/turbo-build.log",
+                        "directory": "apps/docs",
+                        "dependencies": ["ui#build"],
+                        "dependents": [],
+                    },
+                    {
+                        "taskId": "web#build",
+                        "task": "build",
+                        "package": "web",
+                        "command": "echo 'building web'",
+                        "outputs": ["dist/**"],
+                        "excludedOutputs": [],
+                        "logFile": "apps/web/.turbo/turbo-build.log",
+                        "directory": "apps/web",
+                        "dependencies": ["ui#build"],
+                        "dependents": [],
+                    },
+                ],
+            })
+        );
+        Ok(())
+    }
+}
@@ -1,7 +1,13 @@
 mod cli;
 mod commands;
+mod lockfile;
+mod package_graph;
+mod package_json;
 mod package_manager;
+mod scope;
 mod shim;
+mod task_graph;
+mod turbo_json;
 
 use anyhow::Result;
 use log::error;
@@ -0,0 +1,788 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use anyhow::{anyhow, Result};
+use semver::VersionReq;
+use serde::Deserialize;
+
+use super::{Lockfile, Package};
+
+const METADATA_KEY: &str = "__metadata";
+
+const HEADER: &str = "# This file is generated by running \"yarn install\" inside your \
+                      project.\n# Manual changes might be lost - proceed with caution!\n";
+
+/// An entry of a `yarn.lock` written by yarn 2 or newer.
+///
+/// See https://github.com/yarnpkg/berry/blob/master/packages/yarnpkg-core/sources/Manifest.ts
+/// for the full definition, only a subset of it is written to the lockfile.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BerryPackage {
+    #[serde(default)]
+    version: String,
+    language_name: Option<String>,
+
+    dependencies: Option<BTreeMap<String, String>>,
+    peer_dependencies: Option<BTreeMap<String, String>>,
+
+    dependencies_meta: Option<BTreeMap<String, DependencyMeta>>,
+    peer_dependencies_meta: Option<BTreeMap<String, DependencyMeta>>,
+
+    bin: Option<BTreeMap<String, String>>,
+
+    link_type: Option<String>,
+    #[serde(default)]
+    resolution: String,
+    checksum: Option<String>,
+    conditions: Option<String>,
+
+    // Only used for the metadata entry
+    cache_key: Option<String>,
+}
+
+impl BerryPackage {
+    /// Returns all descriptors that the dependencies of this entry could be
+    /// written as.
+    fn possible_descriptors(&self) -> impl Iterator<Item = Descriptor> + '_ {
+        self.dependencies
+            .iter()
+            .flatten()
+            .flat_map(|(name, version)| berry_possible_keys(name, version))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct DependencyMeta {
+    built: Option<bool>,
+    optional: Option<bool>,
+    unplugged: Option<bool>,
+}
+
+/// The contents of a `yarn.lock` written by yarn 2 or newer.
+#[derive(Debug, Clone)]
+pub struct BerryLockfile {
+    packages: HashMap<Locator, BerryPackage>,
+    version: u32,
+    cache_key: Option<String>,
+    /// Maps descriptors (`lodash@npm:^4.17.21`) to the locators they resolved
+    /// to (`lodash@npm:4.17.21`).
+    descriptors: HashMap<Descriptor, Locator>,
+    /// Maps locators of packages to the locators of their patched versions.
+    patches: HashMap<Locator, Locator>,
+    /// Descriptors that are only used by package extensions.
+    package_extensions: HashSet<Descriptor>,
+    has_crlf: bool,
+}
+
+impl BerryLockfile {
+    pub fn load(content: &[u8]) -> Result<Self> {
+        let has_crlf = content.ends_with(b"\r\n");
+        let mut packages: BTreeMap<String, BerryPackage> = serde_yaml::from_slice(content)
+            .map_err(|e| anyhow!("could not unmarshal lockfile: {}", e))?;
+
+        let metadata = packages
+            .remove(METADATA_KEY)
+            .ok_or_else(|| anyhow!("No __metadata entry found when decoding yarn.lock"))?;
+        let version: u32 = metadata
+            .version
+            .parse()
+            .map_err(|e| anyhow!("yarn lockfile version isn't valid integer: {}", e))?;
+
+        let mut locator_to_package = HashMap::new();
+        let mut descriptor_to_locator = HashMap::new();
+        let mut patches = HashMap::new();
+
+        for (key, mut data) in packages {
+            let locator = Locator::parse(&data.resolution)
+                .map_err(|e| anyhow!("unable to parse entry: {}", e))?;
+
+            if locator.is_patch() {
+                // A patch has the same identifier and version as the package it patches
+                let original = Locator {
+                    ident: locator.ident.clone(),
+                    reference: format!("npm:{}", data.version),
+                };
+                patches.insert(original, locator.clone());
+            }
+
+            // Only the metadata entry has a cache key
+            data.cache_key = None;
+
+            // All descriptors that resolve to the same locator are grouped into one key
+            for entry in key.split(',').map(str::trim) {
+                let mut descriptor =
+                    Descriptor::parse(entry).map_err(|e| anyhow!("Bad entry key found: {}", e))?;
+
+                // Before lockfile version 6 descriptors could be missing the npm protocol.
+                // Tags such as `latest` are still written without it.
+                if version <= 6 && descriptor.range != "*" && is_semver_range(&descriptor.range) {
+                    descriptor.range = format!("npm:{}", descriptor.range);
+                }
+
+                descriptor_to_locator.insert(descriptor, locator.clone());
+            }
+
+            locator_to_package.insert(locator, data);
+        }
+
+        // Descriptors that aren't used by any entry were added by package extensions
+        let mut package_extensions = descriptor_to_locator
+            .keys()
+            .filter(|descriptor| descriptor.protocol() == Some("npm"))
+            .cloned()
+            .collect::<HashSet<_>>();
+        for entry in locator_to_package.values() {
+            for descriptor in entry.possible_descriptors() {
+                package_extensions.remove(&descriptor);
+            }
+        }
+
+        Ok(BerryLockfile {
+            packages: locator_to_package,
+            version,
+            cache_key: metadata.cache_key,
+            descriptors: descriptor_to_locator,
+            patches,
+            package_extensions,
+            has_crlf,
+        })
+    }
+
+    /// Inverts the map of descriptors to locators.
+    fn locator_to_descriptors(&self) -> HashMap<&Locator, HashSet<&Descriptor>> {
+        let mut reverse_lookup: HashMap<&Locator, HashSet<&Descriptor>> = HashMap::new();
+        for (descriptor, locator) in &self.descriptors {
+            reverse_lookup
+                .entry(locator)
+                .or_default()
+                .insert(descriptor);
+        }
+        reverse_lookup
+    }
+}
+
+impl Lockfile for BerryLockfile {
+    fn resolve_package(
+        &self,
+        _workspace_path: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<Package>> {
+        for descriptor in berry_possible_keys(name, version) {
+            if let Some(locator) = self.descriptors.get(&descriptor) {
+                let entry = self
+                    .packages
+                    .get(locator)
+                    .ok_or_else(|| anyhow!("Unable to find entry for {}", locator))?;
+                return Ok(Some(Package::new(
+                    locator.to_string(),
+                    entry.version.clone(),
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn all_dependencies(&self, key: &str) -> Result<Option<HashMap<String, String>>> {
+        let locator = Locator::parse(key)?;
+        let Some(entry) = self.packages.get(&locator) else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            entry
+                .dependencies
+                .iter()
+                .flatten()
+                .map(|(name, version)| (name.clone(), version.clone()))
+                .collect(),
+        ))
+    }
+
+    fn subgraph(
+        &self,
+        workspace_packages: &[String],
+        packages: &[String],
+    ) -> Result<Box<dyn Lockfile>> {
+        let mut pruned_packages = HashMap::new();
+        let mut pruned_descriptors = HashMap::new();
+        let mut patches = HashMap::new();
+        let reverse_lookup = self.locator_to_descriptors();
+
+        // Add the entries of the root workspace and the pruned workspaces
+        for (locator, entry) in &self.packages {
+            if locator.reference == "workspace:." {
+                pruned_packages.insert(locator.clone(), entry.clone());
+                pruned_descriptors.insert(locator.as_descriptor(), locator.clone());
+                for descriptor in reverse_lookup.get(locator).into_iter().flatten() {
+                    pruned_descriptors.insert((*descriptor).clone(), locator.clone());
+                }
+            }
+        }
+        for workspace in workspace_packages {
+            let expected_reference = format!("workspace:{workspace}");
+            for (locator, entry) in &self.packages {
+                if locator.reference == expected_reference {
+                    pruned_packages.insert(locator.clone(), entry.clone());
+                    pruned_descriptors.insert(locator.as_descriptor(), locator.clone());
+                }
+            }
+        }
+
+        for key in packages {
+            let locator = Locator::parse(key)?;
+            if let Some(entry) = self.packages.get(&locator) {
+                pruned_packages.insert(locator.clone(), entry.clone());
+            }
+            // Patched versions of packages are part of the subgraph as well
+            if let Some(patch_locator) = self.patches.get(&locator) {
+                let patch_entry = self
+                    .packages
+                    .get(patch_locator)
+                    .ok_or_else(|| anyhow!("Unable to find entry for {}", patch_locator))?;
+                pruned_packages.insert(patch_locator.clone(), patch_entry.clone());
+                patches.insert(locator, patch_locator.clone());
+            }
+        }
+
+        for entry in pruned_packages.values() {
+            for descriptor in entry.possible_descriptors() {
+                if let Some(locator) = self.descriptors.get(&descriptor) {
+                    pruned_descriptors.insert(descriptor, locator.clone());
+                }
+            }
+        }
+
+        // Patch descriptors are kept if the descriptor of the package they patch is
+        // part of the subgraph
+        for (primary_locator, patch_locator) in &patches {
+            let primary_descriptors = reverse_lookup.get(primary_locator);
+            for patch in reverse_lookup.get(patch_locator).into_iter().flatten() {
+                let Some(primary_range) = patch.primary_version() else {
+                    continue;
+                };
+                let primary_descriptor = Descriptor {
+                    ident: patch.ident.clone(),
+                    range: primary_range,
+                };
+                if !primary_descriptors.map_or(false, |descriptors| {
+                    descriptors.contains(&primary_descriptor)
+                }) {
+                    return Err(anyhow!(
+                        "Unable to find primary descriptor {}",
+                        primary_descriptor
+                    ));
+                }
+                if pruned_descriptors.contains_key(&primary_descriptor) {
+                    pruned_descriptors.insert((*patch).clone(), patch_locator.clone());
+                }
+            }
+        }
+
+        // Add the descriptors used by package extensions
+        for descriptor in &self.package_extensions {
+            if let Some(locator) = self.descriptors.get(descriptor) {
+                if pruned_packages.contains_key(locator) {
+                    pruned_descriptors.insert(descriptor.clone(), locator.clone());
+                }
+            }
+        }
+
+        // yarn only writes a cache key if there are entries with a checksum
+        let cache_key = if pruned_packages
+            .values()
+            .any(|entry| entry.checksum.is_some())
+        {
+            self.cache_key.clone()
+        } else {
+            None
+        };
+
+        Ok(Box::new(BerryLockfile {
+            packages: pruned_packages,
+            version: self.version,
+            cache_key,
+            descriptors: pruned_descriptors,
+            patches,
+            package_extensions: self.package_extensions.clone(),
+            has_crlf: self.has_crlf,
+        }))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut entries = Vec::new();
+        for (locator, descriptors) in self.locator_to_descriptors() {
+            let mut descriptors = descriptors
+                .into_iter()
+                .map(|descriptor| descriptor.to_string())
+                .collect::<Vec<_>>();
+            descriptors.sort();
+
+            let entry = self
+                .packages
+                .get(locator)
+                .ok_or_else(|| anyhow!("Unable to find entry for {}", locator))?;
+            entries.push((descriptors.join(", "), stringify_entry(entry)));
+        }
+        entries.sort();
+
+        let mut metadata = vec![format!("  version: {}", self.version)];
+        if let Some(cache_key) = &self.cache_key {
+            metadata.push(format!("  cacheKey: {}", wrap_string(cache_key)));
+        }
+
+        let mut content = HEADER.to_string();
+        content.push_str(&format!("\n{}:\n{}\n", METADATA_KEY, metadata.join("\n")));
+        for (key, value) in entries {
+            let key = wrap_string(&key);
+            // Long keys need to be written as explicit keys
+            let key = if key.len() > 1024 {
+                format!("? {key}\n:")
+            } else {
+                format!("{key}:")
+            };
+            content.push_str(&format!("\n{key}\n{value}\n"));
+        }
+
+        if self.has_crlf {
+            content = content.replace('\n', "\r\n");
+        }
+        Ok(content.into_bytes())
+    }
+
+    fn patches(&self) -> Vec<String> {
+        self.patches
+            .values()
+            .filter_map(|locator| locator.patch_path())
+            .filter(|path| !path.starts_with('~') && !is_builtin(path))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Ident {
+    /// The scope of the package without the leading `@`.
+    scope: Option<String>,
+    name: String,
+}
+
+impl Ident {
+    /// Parses a string starting with an identifier. Returns the identifier and
+    /// the rest of the string after the `@` following it, if there is one.
+    fn parse(input: &str) -> Result<(Self, Option<&str>)> {
+        let (scope, rest) = match input.strip_prefix('@') {
+            Some(scoped) => {
+                let (scope, rest) = scoped
+                    .split_once('/')
+                    .ok_or_else(|| anyhow!("{} is missing a package name", input))?;
+                (Some(scope.to_string()), rest)
+            }
+            None => (None, input),
+        };
+        let (name, rest) = match rest.split_once('@') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (rest, None),
+        };
+        if name.is_empty() || name.contains('/') || scope.as_deref() == Some("") {
+            return Err(anyhow!("{} doesn't start with a valid package name", input));
+        }
+
+        Ok((
+            Ident {
+                scope,
+                name: name.to_string(),
+            },
+            rest,
+        ))
+    }
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.scope {
+            Some(scope) => write!(f, "@{}/{}", scope, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// A resolved package, e.g. `lodash@npm:4.17.21`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Locator {
+    ident: Ident,
+    /// The resolved version, prefixed with the protocol.
+    reference: String,
+}
+
+impl Locator {
+    fn parse(input: &str) -> Result<Self> {
+        match Ident::parse(input) {
+            Ok((ident, Some(reference))) if !reference.is_empty() => Ok(Locator {
+                ident,
+                reference: reference.to_string(),
+            }),
+            _ => Err(anyhow!("{} is not a valid locator string", input)),
+        }
+    }
+
+    fn as_descriptor(&self) -> Descriptor {
+        Descriptor {
+            ident: self.ident.clone(),
+            range: self.reference.clone(),
+        }
+    }
+
+    fn is_patch(&self) -> bool {
+        self.reference.starts_with("patch:")
+    }
+
+    /// Returns the path of the patch file if this is a patched package.
+    fn patch_path(&self) -> Option<String> {
+        if !self.is_patch() {
+            return None;
+        }
+        let patch_file_index = self.reference.find('#')?;
+        let param_index = self.reference.rfind("::")?;
+        let path = self.reference.get(patch_file_index + 1..param_index)?;
+        Some(path.trim_start_matches("./").to_string())
+    }
+}
+
+impl fmt::Display for Locator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.ident, self.reference)
+    }
+}
+
+/// A dependency as it is requested, e.g. `lodash@npm:^4.17.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Descriptor {
+    ident: Ident,
+    /// The requested version range, which can be prefixed with the protocol,
+    /// e.g. `npm:`, `workspace:` or `patch:`.
+    range: String,
+}
+
+impl Descriptor {
+    fn parse(input: &str) -> Result<Self> {
+        let (ident, range) = Ident::parse(input)
+            .map_err(|_| anyhow!("{} is not a valid descriptor string", input))?;
+        Ok(Descriptor {
+            ident,
+            range: range.unwrap_or_default().to_string(),
+        })
+    }
+
+    /// If this descriptor is for a patch, returns the range of the descriptor
+    /// it patches.
+    fn primary_version(&self) -> Option<String> {
+        let patch = self.range.strip_prefix("patch:")?;
+        let patch_file_index = patch.find('#')?;
+        // Skip the `@` of a scope
+        let version_index = patch.get(1..)?.find('@')? + 1;
+        // The `:` following the protocol is encoded as `%3A` in the patch string
+        let version = patch
+            .get(version_index + 1..patch_file_index)?
+            .replacen("%3A", ":", 1);
+        if version.starts_with("npm:") {
+            Some(version)
+        } else {
+            Some(format!("npm:{version}"))
+        }
+    }
+
+    fn protocol(&self) -> Option<&str> {
+        match self.range.find(':') {
+            Some(index) if index > 0 => Some(&self.range[..index]),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.ident, self.range)
+    }
+}
+
+/// Returns the descriptors that a dependency could be written as.
+fn berry_possible_keys(name: &str, version: &str) -> Vec<Descriptor> {
+    ["", "npm:", "file:", "workspace:", "yarn:"]
+        .iter()
+        .filter_map(|protocol| Descriptor::parse(&format!("{name}@{protocol}{version}")).ok())
+        .collect()
+}
+
+fn is_semver_range(range: &str) -> bool {
+    range.split("||").all(|alternative| {
+        let mut comparators = alternative.split_whitespace().peekable();
+        comparators.peek().is_some()
+            && comparators.all(|comparator| VersionReq::parse(comparator).is_ok())
+    })
+}
+
+fn is_builtin(path: &str) -> bool {
+    path.starts_with("builtin<") && path.ends_with('>')
+}
+
+fn stringify_entry(entry: &BerryPackage) -> String {
+    let non_empty = |value: &str| {
+        Some(value)
+            .filter(|value| !value.is_empty())
+            .map(wrap_string)
+    };
+    let fields = [
+        ("version", non_empty(entry.version.as_str())),
+        ("resolution", non_empty(entry.resolution.as_str())),
+        ("dependencies", stringify_deps(&entry.dependencies)),
+        ("peerDependencies", stringify_deps(&entry.peer_dependencies)),
+        (
+            "dependenciesMeta",
+            stringify_deps_meta(&entry.dependencies_meta),
+        ),
+        (
+            "peerDependenciesMeta",
+            stringify_deps_meta(&entry.peer_dependencies_meta),
+        ),
+        ("bin", stringify_deps(&entry.bin)),
+        ("checksum", entry.checksum.as_deref().map(wrap_string)),
+        ("conditions", entry.conditions.as_deref().map(wrap_string)),
+        (
+            "languageName",
+            entry.language_name.as_deref().map(wrap_string),
+        ),
+        ("linkType", entry.link_type.as_deref().map(wrap_string)),
+    ];
+
+    fields
+        .into_iter()
+        .filter_map(|(field, value)| {
+            let value = value?;
+            // Nested blocks start on their own line
+            Some(if value.starts_with('\n') {
+                format!("  {field}:{value}")
+            } else {
+                format!("  {field}: {value}")
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the lines of a map of dependencies, each starting with a newline.
+fn stringify_deps(deps: &Option<BTreeMap<String, String>>) -> Option<String> {
+    let lines = deps
+        .as_ref()?
+        .iter()
+        .map(|(name, version)| format!("\n    {}: {}", wrap_string(name), wrap_string(version)))
+        .collect::<String>();
+    Some(lines).filter(|lines| !lines.is_empty())
+}
+
+fn stringify_deps_meta(meta: &Option<BTreeMap<String, DependencyMeta>>) -> Option<String> {
+    let mut lines = String::new();
+    for (name, meta) in meta.as_ref()? {
+        let fields = [
+            ("built", meta.built),
+            ("optional", meta.optional),
+            ("unplugged", meta.unplugged),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| Some(format!("\n      {}: {}", field, value?)))
+        .collect::<String>();
+        if !fields.is_empty() {
+            lines.push_str(&format!("\n    {}:{}", wrap_string(name), fields));
+        }
+    }
+    Some(lines).filter(|lines| !lines.is_empty())
+}
+
+/// Quotes a string unless it can be written as a plain YAML scalar.
+fn wrap_string(value: &str) -> String {
+    let mut chars = value.chars();
+    let is_simple = match chars.next() {
+        Some(first) => {
+            let rest = chars.as_str();
+            !"-?:,][{}#&*!|>'\"%@` \t\r\n".contains(first)
+                && !rest.contains(|c| ",][{}:#\r\n".contains(c))
+                && !rest.ends_with([' ', '\t'])
+        }
+        None => false,
+    };
+    if is_simple {
+        value.to_string()
+    } else {
+        serde_json::to_string(value).expect("strings are always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] = include_bytes!("../../../../cli/internal/lockfile/testdata/berry.lock");
+    const MINIMAL_FIXTURE: &[u8] =
+        include_bytes!("../../../../cli/internal/lockfile/testdata/minimal-berry.lock");
+
+    #[test]
+    fn test_decoding() -> Result<()> {
+        let lockfile = BerryLockfile::load(FIXTURE)?;
+        assert_eq!(lockfile.version, 6);
+        assert_eq!(lockfile.cache_key.as_deref(), Some("8c0"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_package() -> Result<()> {
+        let lockfile = BerryLockfile::load(FIXTURE)?;
+        let cases = [
+            (
+                "js-tokens",
+                "^3.0.0 || ^4.0.0",
+                Some(("js-tokens@npm:4.0.0", "4.0.0")),
+            ),
+            (
+                "js-tokens",
+                "^4.0.0",
+                Some(("js-tokens@npm:4.0.0", "4.0.0")),
+            ),
+            ("@babel/code-frame", "^7.12.11", None),
+            (
+                "eslint-config-custom",
+                "*",
+                Some((
+                    "eslint-config-custom@workspace:packages/eslint-config-custom",
+                    "0.0.0-use.local",
+                )),
+            ),
+        ];
+        for (name, version, expected) in cases {
+            assert_eq!(
+                lockfile.resolve_package("some-pkg", name, version)?,
+                expected.map(|(key, version)| Package::new(key, version)),
+                "{name}@{version}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_dependencies() -> Result<()> {
+        let lockfile = BerryLockfile::load(FIXTURE)?;
+        let package = lockfile
+            .resolve_package("some-pkg", "react-dom", "18.2.0")?
+            .unwrap();
+        let deps = lockfile.all_dependencies(&package.key)?.unwrap();
+        assert_eq!(deps.len(), 2);
+        for (name, version) in deps {
+            assert!(
+                lockfile
+                    .resolve_package("some-pkg", &name, &version)?
+                    .is_some(),
+                "{name}@{version}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_patches() -> Result<()> {
+        let lockfile = BerryLockfile::load(FIXTURE)?;
+        let locator = Locator::parse("resolve@npm:2.0.0-next.4")?;
+        let patch = &lockfile.packages[&lockfile.patches[&locator]];
+        assert_eq!(patch.version, "2.0.0-next.4");
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_extensions() -> Result<()> {
+        let lockfile = BerryLockfile::load(FIXTURE)?;
+        let expected = ["@babel/types@npm:^7.8.3", "lodash@npm:4.17.21"]
+            .into_iter()
+            .map(Descriptor::parse)
+            .collect::<Result<HashSet<_>>>()?;
+        assert_eq!(lockfile.package_extensions, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let lockfile = BerryLockfile::load(FIXTURE)?;
+        assert_eq!(
+            String::from_utf8(lockfile.encode()?)?,
+            String::from_utf8(FIXTURE.to_vec())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_patch_path() -> Result<()> {
+        let cases = [
+            (
+                "lodash@patch:lodash@npm%3A4.17.21#./.yarn/patches/lodash-npm-4.17.21-6382451519.\
+                 patch::version=4.17.21&hash=2c6e9e&locator=berry-patch%40workspace%3A.",
+                Some(".yarn/patches/lodash-npm-4.17.21-6382451519.patch"),
+            ),
+            ("lodash@npm:4.17.21", None),
+            (
+                "resolve@patch:resolve@npm%3A2.0.0-next.4#~builtin<compat/resolve>::version=2.0.\
+                 0-next.4&hash=07638b",
+                Some("~builtin<compat/resolve>"),
+            ),
+        ];
+        for (locator, expected) in cases {
+            assert_eq!(
+                Locator::parse(locator)?.patch_path().as_deref(),
+                expected,
+                "{locator}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_patch_primary_version() -> Result<()> {
+        let cases = [
+            (
+                "lodash@patch:lodash@npm%3A4.17.21#./.yarn/patches/lodash-npm-4.17.21-6382451519.\
+                 patch::locator=berry-patch%40workspace%3A.",
+                Some("npm:4.17.21"),
+            ),
+            (
+                "typescript@patch:typescript@^4.5.2#~builtin<compat/typescript>",
+                Some("npm:^4.5.2"),
+            ),
+            ("react@npm:18.2.0", None),
+        ];
+        for (descriptor, expected) in cases {
+            assert_eq!(
+                Descriptor::parse(descriptor)?.primary_version().as_deref(),
+                expected,
+                "{descriptor}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_subgraph_keeps_used_descriptors() -> Result<()> {
+        let lockfile = BerryLockfile::load(MINIMAL_FIXTURE)?;
+        let lodash = vec!["lodash@npm:4.17.21".to_string()];
+        let encode_subgraph = |workspaces: &[&str]| -> Result<String> {
+            let workspaces = workspaces.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+            let pruned = lockfile.subgraph(&workspaces, &lodash)?;
+            Ok(String::from_utf8(pruned.encode()?)?)
+        };
+
+        let lockfile_a = encode_subgraph(&["packages/a", "packages/c"])?;
+        assert!(lockfile_a.contains("\n\"lodash@npm:^4.17.0\":\n"));
+
+        let lockfile_b = encode_subgraph(&["packages/b", "packages/c"])?;
+        assert!(lockfile_b.contains("\n\"lodash@npm:^3.0.0 || ^4.0.0\":\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+mod berry;
+mod npm;
+mod pnpm;
+mod yarn;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::Hasher,
+};
+
+use anyhow::{anyhow, Result};
+pub use berry::BerryLockfile;
+pub use npm::NpmLockfile;
+pub use pnpm::PnpmLockfile;
+pub use yarn::YarnLockfile;
+
+/// An entry of a lockfile that a dependency resolved to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Package {
+    /// The key of the entry in the lockfile.
+    pub key: String,
+    /// The version that the entry resolved to.
+    pub version: String,
+}
+
+impl Package {
+    pub fn new(key: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            version: version.into(),
+        }
+    }
+}
+
+/// A lockfile of one of the supported package managers.
+///
+/// Workspace paths are always relative to the repository root and use `/` as
+/// separator. The root workspace is the empty path.
+pub trait Lockfile {
+    /// Resolves the dependency `name` with the version range `version` that
+    /// the workspace at `workspace_path` depends on. Returns `None` if the
+    /// dependency isn't part of the lockfile.
+    fn resolve_package(
+        &self,
+        workspace_path: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<Package>>;
+
+    /// Returns all dependencies (including dev, optional and peer
+    /// dependencies) of the lockfile entry `key` mapped to their version
+    /// ranges. Returns `None` if there is no entry for `key`.
+    fn all_dependencies(&self, key: &str) -> Result<Option<HashMap<String, String>>>;
+
+    /// Creates a lockfile which only contains the given workspaces and the
+    /// lockfile entries with the given keys.
+    fn subgraph(
+        &self,
+        workspace_packages: &[String],
+        packages: &[String],
+    ) -> Result<Box<dyn Lockfile>>;
+
+    /// Serializes the lockfile into the format the package manager writes.
+    fn encode(&self) -> Result<Vec<u8>>;
+
+    /// Returns the paths of the patch files used by the lockfile, relative to
+    /// the repository root.
+    fn patches(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Resolves all external dependencies of the workspace at `workspace_path`,
+/// including their dependencies, to lockfile entries.
+pub fn transitive_closure(
+    lockfile: &dyn Lockfile,
+    workspace_path: &str,
+    unresolved_deps: &BTreeMap<String, String>,
+) -> Result<HashSet<Package>> {
+    let mut closure = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut queue = unresolved_deps
+        .iter()
+        .map(|(name, version)| (name.clone(), version.clone()))
+        .collect::<Vec<_>>();
+
+    while let Some((name, version)) = queue.pop() {
+        let Some(package) = lockfile.resolve_package(workspace_path, &name, &version)? else {
+            continue;
+        };
+        if !seen.insert(package.key.clone()) {
+            continue;
+        }
+
+        let dependencies = lockfile
+            .all_dependencies(&package.key)?
+            .ok_or_else(|| anyhow!("Unable to find entry for {}", package.key))?;
+        queue.extend(dependencies);
+        closure.insert(package);
+    }
+
+    Ok(closure)
+}
+
+/// Hashes a set of resolved external dependencies. The hash only depends on
+/// the keys and versions of the packages.
+///
+/// This matches `populateTopologicGraphForPackageJSON` in the Go
+/// implementation, which hashes the sorted `key@version` strings formatted
+/// with `%v`, i.e. `[key@version key@version]`, with xxHash64.
+pub fn external_deps_hash(packages: &HashSet<Package>) -> String {
+    let mut deps = packages
+        .iter()
+        .map(|package| format!("{}@{}", package.key, package.version))
+        .collect::<Vec<_>>();
+    deps.sort();
+
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    hasher.write(format!("[{}]", deps.join(" ")).as_bytes());
+    format!("{:016x}", hasher.finish())
+}
+
+/// Converts a relative path into the form used for workspace paths in
+/// lockfiles.
+pub fn to_unix_path(path: &std::path::Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_transitive_closures(
+        lockfile: &dyn Lockfile,
+        workspaces: &HashMap<String, BTreeMap<String, String>>,
+    ) -> Result<HashMap<String, HashSet<Package>>> {
+        workspaces
+            .iter()
+            .map(|(workspace_path, unresolved_deps)| {
+                let closure = transitive_closure(lockfile, workspace_path, unresolved_deps)?;
+                Ok((workspace_path.clone(), closure))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transitive_closures() -> Result<()> {
+        let lockfile = NpmLockfile::load(include_bytes!(
+            "../../../../cli/internal/lockfile/testdata/npm-lock.json"
+        ))?;
+        let workspaces = HashMap::from([
+            (
+                "apps/web".to_string(),
+                BTreeMap::from([("lodash".to_string(), "^4.17.21".to_string())]),
+            ),
+            (
+                "apps/docs".to_string(),
+                BTreeMap::from([("lodash".to_string(), "^3.10.1".to_string())]),
+            ),
+        ]);
+
+        let closures = all_transitive_closures(&lockfile, &workspaces)?;
+        assert_eq!(
+            closures["apps/web"],
+            HashSet::from([Package::new("apps/web/node_modules/lodash", "4.17.21")])
+        );
+        assert_eq!(
+            closures["apps/docs"],
+            HashSet::from([Package::new("node_modules/lodash", "3.10.1")])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_deps_hash_is_order_independent() {
+        let a = Package::new("node_modules/a", "1.0.0");
+        let b = Package::new("node_modules/b", "2.0.0");
+        assert_eq!(
+            external_deps_hash(&HashSet::from([a.clone(), b.clone()])),
+            external_deps_hash(&HashSet::from([b, a.clone()]))
+        );
+        assert_ne!(
+            external_deps_hash(&HashSet::from([a.clone()])),
+            external_deps_hash(&HashSet::from([Package::new("node_modules/a", "1.0.1")]))
+        );
+        assert_eq!(external_deps_hash(&HashSet::from([a])).len(), 16);
+    }
+
+    #[test]
+    fn test_external_deps_hash_matches_go() {
+        // Computed with `fs.HashObject([]string{"node_modules/a@1.0.0",
+        // "node_modules/b@2.0.0"})`.
+        assert_eq!(
+            external_deps_hash(&HashSet::from([
+                Package::new("node_modules/b", "2.0.0"),
+                Package::new("node_modules/a", "1.0.0"),
+            ])),
+            "d0dc205c3bfa32a5"
+        );
+        // `fs.HashObject([]string{})`
+        assert_eq!(external_deps_hash(&HashSet::new()), "ccab0b28617f1f56");
+    }
+}
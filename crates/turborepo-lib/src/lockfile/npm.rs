@@ -0,0 +1,376 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Lockfile, Package};
+
+/// The contents of a `package-lock.json`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpmLockfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default)]
+    lockfile_version: u32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    requires: bool,
+    /// Keys are paths to the directory of the package, which can be nested
+    /// in `node_modules`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    packages: BTreeMap<String, NpmPackage>,
+    /// Legacy information for npm 5 and 6.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    dependencies: BTreeMap<String, Value>,
+}
+
+/// An entry of `packages`. The fields are declared in the order npm writes
+/// them, so that encoding a lockfile keeps it as close to the original as
+/// possible.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NpmPackage {
+    /// Only used for the root package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resolved: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    integrity: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    link: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    dev: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    optional: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    dev_optional: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    in_bundle: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    has_install_script: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    has_shrinkwrap: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    extraneous: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    dependencies: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    dev_dependencies: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    peer_dependencies: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    peer_dependencies_meta: BTreeMap<String, Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    optional_dependencies: BTreeMap<String, String>,
+    /// Fields we don't use, but need to propagate, e.g. `bin`, `engines` or
+    /// `workspaces`.
+    #[serde(flatten)]
+    other: BTreeMap<String, Value>,
+}
+
+impl NpmLockfile {
+    pub fn load(content: &[u8]) -> Result<Self> {
+        let lockfile: NpmLockfile = serde_json::from_slice(content)?;
+
+        // Lockfile versions 0 and 1 are written by npm 6 and older
+        let ancient_lockfile = lockfile.lockfile_version <= 1
+            || (!lockfile.dependencies.is_empty() && lockfile.packages.is_empty());
+        if ancient_lockfile {
+            return Err(anyhow!(
+                "Support for lockfiles without a 'packages' field isn't implemented yet"
+            ));
+        }
+
+        Ok(lockfile)
+    }
+
+    fn get_version(&self, key: &str) -> Option<String> {
+        self.packages
+            .get(key)
+            .map(|entry| entry.version.clone().unwrap_or_default())
+    }
+}
+
+impl Lockfile for NpmLockfile {
+    fn resolve_package(
+        &self,
+        workspace_path: &str,
+        name: &str,
+        _version: &str,
+    ) -> Result<Option<Package>> {
+        if !self.packages.contains_key(workspace_path) {
+            return Err(anyhow!(
+                "No package found in lockfile for '{}'",
+                workspace_path
+            ));
+        }
+
+        // `all_dependencies` returns lockfile keys instead of names to avoid
+        // choosing the wrong version of a transitive dependency.
+        if let Some(version) = self.get_version(name) {
+            return Ok(Some(Package::new(name, version)));
+        }
+
+        // Otherwise the dependency comes from the package.json of a workspace,
+        // which either uses a nested version of the package or the hoisted one.
+        let nested_path = format!("{workspace_path}/node_modules/{name}");
+        let hoisted_path = format!("node_modules/{name}");
+        Ok([nested_path, hoisted_path].into_iter().find_map(|key| {
+            self.get_version(&key)
+                .map(|version| Package::new(key, version))
+        }))
+    }
+
+    fn all_dependencies(&self, key: &str) -> Result<Option<HashMap<String, String>>> {
+        let Some(entry) = self.packages.get(key) else {
+            return Ok(None);
+        };
+
+        let mut deps = HashMap::new();
+        for name in entry
+            .dependencies
+            .keys()
+            .chain(entry.dev_dependencies.keys())
+            .chain(entry.optional_dependencies.keys())
+            .chain(entry.peer_dependencies.keys())
+        {
+            if let Some((dep_key, version)) = possible_npm_deps(key, name)
+                .into_iter()
+                .find_map(|dep_key| self.get_version(&dep_key).map(|v| (dep_key, v)))
+            {
+                deps.insert(dep_key, version);
+            }
+        }
+
+        Ok(Some(deps))
+    }
+
+    fn subgraph(
+        &self,
+        workspace_packages: &[String],
+        packages: &[String],
+    ) -> Result<Box<dyn Lockfile>> {
+        let mut pruned_packages = BTreeMap::new();
+        for key in packages {
+            let entry = self
+                .packages
+                .get(key)
+                .ok_or_else(|| anyhow!("No lockfile entry found for {}", key))?;
+            pruned_packages.insert(key.clone(), entry.clone());
+        }
+        if let Some(root_entry) = self.packages.get("") {
+            pruned_packages.insert("".to_string(), root_entry.clone());
+        }
+        for workspace in workspace_packages {
+            let entry = self
+                .packages
+                .get(workspace)
+                .ok_or_else(|| anyhow!("No lockfile entry found for {}", workspace))?;
+            pruned_packages.insert(workspace.clone(), entry.clone());
+
+            // Each workspace has a link in node_modules pointing back to it, which is
+            // needed for dependency resolution.
+            if let Some((key, entry)) = self
+                .packages
+                .iter()
+                .find(|(_, entry)| entry.resolved.as_deref() == Some(workspace.as_str()))
+            {
+                pruned_packages.insert(key.clone(), entry.clone());
+            }
+        }
+
+        Ok(Box::new(NpmLockfile {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            // The legacy `dependencies` of npm 6 and older aren't pruned, so they are
+            // left out. npm 7 and newer only read `packages`, so the version is kept.
+            lockfile_version: self.lockfile_version,
+            requires: self.requires,
+            packages: pruned_packages,
+            dependencies: BTreeMap::new(),
+        }))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut content = serde_json::to_vec_pretty(self)?;
+        content.push(b'\n');
+        Ok(content)
+    }
+}
+
+/// Returns the keys that a dependency `dep` of the package at `key` could
+/// have, from the most nested to the hoisted one.
+fn possible_npm_deps(key: &str, dep: &str) -> Vec<String> {
+    let mut possible_deps = vec![format!("{key}/node_modules/{dep}")];
+
+    let mut curr = key;
+    while !curr.is_empty() {
+        let next = npm_path_parent(curr);
+        possible_deps.push(format!("{next}node_modules/{dep}"));
+        curr = next;
+    }
+
+    possible_deps
+}
+
+fn npm_path_parent(key: &str) -> &str {
+    match key.rfind("node_modules/") {
+        Some(index) => &key[..index],
+        None => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] =
+        include_bytes!("../../../../cli/internal/lockfile/testdata/npm-lock.json");
+
+    #[test]
+    fn test_npm_path_parent() {
+        assert_eq!(npm_path_parent("apps/docs"), "");
+        assert_eq!(npm_path_parent("apps/docs/node_modules/foo"), "apps/docs/");
+        assert_eq!(npm_path_parent("node_modules/foo"), "");
+        assert_eq!(
+            npm_path_parent("node_modules/foo/node_modules/bar"),
+            "node_modules/foo/"
+        );
+    }
+
+    #[test]
+    fn test_possible_npm_deps() {
+        assert_eq!(
+            possible_npm_deps("node_modules/foo/node_modules/bar", "baz"),
+            vec![
+                "node_modules/foo/node_modules/bar/node_modules/baz",
+                "node_modules/foo/node_modules/baz",
+                "node_modules/baz",
+            ]
+        );
+        assert_eq!(
+            possible_npm_deps("apps/docs/node_modules/foo", "baz"),
+            vec![
+                "apps/docs/node_modules/foo/node_modules/baz",
+                "apps/docs/node_modules/baz",
+                "node_modules/baz",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolves_alternate_workspace_format() -> Result<()> {
+        let lockfile = NpmLockfile::load(include_bytes!(
+            "../../../../cli/internal/lockfile/testdata/npm-lock-workspace-variation.json"
+        ))?;
+        assert_eq!(
+            lockfile.name.as_deref(),
+            Some("npm-prune-workspace-variation")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_package() -> Result<()> {
+        let lockfile = NpmLockfile::load(FIXTURE)?;
+        let cases = [
+            ("", "turbo", "node_modules/turbo", "1.5.5"),
+            (
+                "apps/web",
+                "lodash",
+                "apps/web/node_modules/lodash",
+                "4.17.21",
+            ),
+            ("apps/docs", "lodash", "node_modules/lodash", "3.10.1"),
+            (
+                "apps/docs",
+                "node_modules/@babel/generator/node_modules/@jridgewell/gen-mapping",
+                "node_modules/@babel/generator/node_modules/@jridgewell/gen-mapping",
+                "0.3.2",
+            ),
+        ];
+        for (workspace, name, key, version) in cases {
+            assert_eq!(
+                lockfile.resolve_package(workspace, name, "")?,
+                Some(Package::new(key, version))
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_dependencies() -> Result<()> {
+        let lockfile = NpmLockfile::load(FIXTURE)?;
+        let mut deps = lockfile
+            .all_dependencies("node_modules/table/node_modules/ajv")?
+            .unwrap()
+            .into_keys()
+            .collect::<Vec<_>>();
+        deps.sort();
+        assert_eq!(
+            deps,
+            vec![
+                "node_modules/fast-deep-equal",
+                "node_modules/require-from-string",
+                "node_modules/table/node_modules/json-schema-traverse",
+                "node_modules/uri-js",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_subgraph_keeps_lockfile_version() -> Result<()> {
+        let lockfile = NpmLockfile::load(FIXTURE)?;
+        assert_eq!(lockfile.lockfile_version, 2);
+
+        let subgraph = lockfile.subgraph(
+            &["apps/docs".to_string()],
+            &["node_modules/lodash".to_string()],
+        )?;
+        let pruned = NpmLockfile::load(&subgraph.encode()?)?;
+        assert_eq!(pruned.lockfile_version, 2);
+        assert!(pruned.packages.contains_key("node_modules/lodash"));
+        assert!(pruned.dependencies.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_keeps_field_order() -> Result<()> {
+        let lockfile = NpmLockfile::load(FIXTURE)?;
+        let encoded = String::from_utf8(lockfile.encode()?)?;
+        let expected = r#""node_modules/eslint-config-next": {
+      "version": "12.3.1",
+      "resolved": "https://registry.npmjs.org/eslint-config-next/-/eslint-config-next-12.3.1.tgz",
+      "integrity": "sha512-EN/xwKPU6jz1G0Qi6Bd/BqMnHLyRAL0VsaQaWA7F3KkjAgZHi4f1uL1JKGWNxdQpHTW/sdGONBd0bzxUka/DJg==",
+      "dependencies": {
+        "@next/eslint-plugin-next": "12.3.1",
+        "@rushstack/eslint-patch": "^1.1.3",
+        "@typescript-eslint/parser": "^5.21.0",
+        "eslint-import-resolver-node": "^0.3.6",
+        "eslint-import-resolver-typescript": "^2.7.1",
+        "eslint-plugin-import": "^2.26.0",
+        "eslint-plugin-jsx-a11y": "^6.5.1",
+        "eslint-plugin-react": "^7.31.7",
+        "eslint-plugin-react-hooks": "^4.5.0"
+      },
+      "peerDependencies": {
+        "eslint": "^7.23.0 || ^8.0.0",
+        "typescript": ">=3.3.1"
+      },
+      "peerDependenciesMeta": {
+        "typescript": {
+          "optional": true
+        }
+      }
+    },"#;
+        assert!(encoded.contains(expected));
+        Ok(())
+    }
+}
@@ -0,0 +1,453 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use super::{Lockfile, Package};
+
+const SUPPORTED_VERSIONS: &[f64] = &[5.3, 5.4];
+
+/// The contents of a `pnpm-lock.yaml`.
+///
+/// See https://github.com/pnpm/pnpm/blob/main/packages/lockfile-types/src/index.ts
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnpmLockfile {
+    lockfile_version: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    never_built_dependencies: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    only_built_dependencies: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    overrides: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    package_extensions_checksum: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    patched_dependencies: Option<BTreeMap<String, PatchFile>>,
+    #[serde(default)]
+    importers: BTreeMap<String, ProjectSnapshot>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    packages: BTreeMap<String, PackageSnapshot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time: Option<BTreeMap<String, String>>,
+}
+
+/// The dependencies of a workspace, listed in the `importers` section.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectSnapshot {
+    #[serde(default)]
+    specifiers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dependencies: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    optional_dependencies: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dev_dependencies: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dependencies_meta: Option<BTreeMap<String, DependenciesMeta>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    publish_directory: Option<String>,
+}
+
+impl ProjectSnapshot {
+    /// Finds the resolved version of `dependency` in any of the dependency
+    /// fields.
+    fn find_resolution(&self, dependency: &str) -> Option<&str> {
+        [
+            &self.dependencies,
+            &self.dev_dependencies,
+            &self.optional_dependencies,
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|deps| deps.get(dependency))
+        .map(|resolution| resolution.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DependenciesMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    injected: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    node: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    patch: Option<String>,
+}
+
+/// An entry of the `packages` section.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageSnapshot {
+    resolution: PackageResolution,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+
+    // Only needed for packages that aren't from the npm registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    engines: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cpu: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    os: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    libc: Option<Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deprecated: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    has_bin: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prepare: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    requires_build: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bundled_dependencies: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    peer_dependencies: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    peer_dependencies_meta: Option<BTreeMap<String, Value>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dependencies: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    optional_dependencies: Option<BTreeMap<String, String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transitive_peer_dependencies: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dev: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    optional: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    patched: Option<bool>,
+}
+
+/// The different ways a package can be resolved, e.g. from the registry, a
+/// tarball, a local directory or a git repository.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageResolution {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    type_field: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    integrity: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tarball: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    directory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit: Option<String>,
+}
+
+/// A patch applied to a package.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PatchFile {
+    path: String,
+    hash: String,
+}
+
+impl PnpmLockfile {
+    pub fn load(content: &[u8]) -> Result<Self> {
+        let lockfile: PnpmLockfile = serde_yaml::from_slice(content)
+            .map_err(|e| anyhow!("could not unmarshal lockfile: {}", e))?;
+        lockfile.check_version()?;
+
+        Ok(lockfile)
+    }
+
+    fn check_version(&self) -> Result<()> {
+        if SUPPORTED_VERSIONS.contains(&self.lockfile_version) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Unable to generate pnpm-lock.yaml with lockfileVersion: {}. Supported lockfile \
+                 versions are {:?}",
+                self.lockfile_version,
+                SUPPORTED_VERSIONS
+            ))
+        }
+    }
+
+    /// Finds the version a specifier of a workspace dependency resolved to.
+    fn resolve_specifier(
+        &self,
+        workspace_path: &str,
+        name: &str,
+        specifier: &str,
+    ) -> Result<Option<String>> {
+        // Dependencies of lockfile entries are already resolved versions
+        if self
+            .packages
+            .contains_key(&format_pnpm_key(name, specifier))
+        {
+            return Ok(Some(specifier.to_string()));
+        }
+
+        // pnpm names the root workspace "."
+        let importer_key = if workspace_path.is_empty() {
+            "."
+        } else {
+            workspace_path
+        };
+        let importer = self
+            .importers
+            .get(importer_key)
+            .ok_or_else(|| anyhow!("no workspace '{}' found in lockfile", workspace_path))?;
+
+        if importer.specifiers.get(name).map(|s| s.as_str()) != Some(specifier) {
+            return Ok(None);
+        }
+        importer
+            .find_resolution(name)
+            .map(|version| Some(version.to_string()))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unable to find resolved version for {}@{} in {}",
+                    name,
+                    specifier,
+                    workspace_path
+                )
+            })
+    }
+
+    /// Keeps only the patches of packages that are part of `packages`.
+    fn prune_patches(
+        &self,
+        packages: &BTreeMap<String, PackageSnapshot>,
+    ) -> Option<BTreeMap<String, PatchFile>> {
+        let patches = self.patched_dependencies.as_ref()?;
+        Some(
+            patches
+                .iter()
+                .filter(|(dependency, patch)| {
+                    // The key of a patched package is `/name/version_hash`, optionally
+                    // followed by the resolved peer dependencies.
+                    let Some((name, version)) = dependency.rsplit_once('@') else {
+                        return false;
+                    };
+                    let prefix = format!("{}_{}", format_pnpm_key(name, version), patch.hash);
+                    packages.keys().any(|key| key.starts_with(&prefix))
+                })
+                .map(|(dependency, patch)| (dependency.clone(), patch.clone()))
+                .collect(),
+        )
+    }
+}
+
+impl Lockfile for PnpmLockfile {
+    fn resolve_package(
+        &self,
+        workspace_path: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<Package>> {
+        let Some(resolved_version) = self.resolve_specifier(workspace_path, name, version)? else {
+            return Ok(None);
+        };
+        let key = format_pnpm_key(name, &resolved_version);
+        Ok(self.packages.get(&key).map(|entry| {
+            let version = entry.version.clone().unwrap_or(resolved_version);
+            Package::new(key, version)
+        }))
+    }
+
+    fn all_dependencies(&self, key: &str) -> Result<Option<HashMap<String, String>>> {
+        let Some(entry) = self.packages.get(key) else {
+            return Ok(None);
+        };
+
+        // Peer dependencies appear resolved in the dependencies
+        Ok(Some(
+            [&entry.dependencies, &entry.optional_dependencies]
+                .into_iter()
+                .flatten()
+                .flat_map(|deps| deps.iter())
+                .map(|(name, version)| (name.clone(), version.clone()))
+                .collect(),
+        ))
+    }
+
+    fn subgraph(
+        &self,
+        workspace_packages: &[String],
+        packages: &[String],
+    ) -> Result<Box<dyn Lockfile>> {
+        let mut pruned_packages = BTreeMap::new();
+        for key in packages {
+            let entry = self
+                .packages
+                .get(key)
+                .ok_or_else(|| anyhow!("Unable to find lockfile entry for {}", key))?;
+            pruned_packages.insert(key.clone(), entry.clone());
+        }
+
+        let mut importers = BTreeMap::new();
+        if let Some(root) = self.importers.get(".") {
+            importers.insert(".".to_string(), root.clone());
+        }
+        for workspace in workspace_packages {
+            let importer = self.importers.get(workspace).ok_or_else(|| {
+                anyhow!(
+                    "Unable to find import entry for workspace package {}",
+                    workspace
+                )
+            })?;
+            importers.insert(workspace.clone(), importer.clone());
+        }
+
+        // Injected dependencies are installed as a copy, so they need their entry
+        for importer in importers.values() {
+            let Some(dependencies_meta) = &importer.dependencies_meta else {
+                continue;
+            };
+            for (dependency, meta) in dependencies_meta {
+                if meta.injected != Some(true) {
+                    continue;
+                }
+                let resolution = importer.find_resolution(dependency).ok_or_else(|| {
+                    anyhow!(
+                        "Unable to find {} other than reference in dependenciesMeta",
+                        dependency
+                    )
+                })?;
+                let entry = self
+                    .packages
+                    .get(resolution)
+                    .ok_or_else(|| anyhow!("Unable to find package entry for {}", resolution))?;
+                pruned_packages.insert(resolution.to_string(), entry.clone());
+            }
+        }
+
+        Ok(Box::new(PnpmLockfile {
+            lockfile_version: self.lockfile_version,
+            never_built_dependencies: self.never_built_dependencies.clone(),
+            only_built_dependencies: self.only_built_dependencies.clone(),
+            overrides: self.overrides.clone(),
+            package_extensions_checksum: self.package_extensions_checksum.clone(),
+            patched_dependencies: self.prune_patches(&pruned_packages),
+            importers,
+            packages: pruned_packages,
+            time: None,
+        }))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        self.check_version()?;
+
+        let content = serde_yaml::to_string(self)
+            .map_err(|e| anyhow!("unable to encode pnpm lockfile: {}", e))?;
+        // pnpm doesn't write a document start marker
+        let content = content.strip_prefix("---\n").unwrap_or(&content);
+        Ok(content.as_bytes().to_vec())
+    }
+
+    fn patches(&self) -> Vec<String> {
+        self.patched_dependencies
+            .iter()
+            .flat_map(|patches| patches.values())
+            .map(|patch| patch.path.clone())
+            .collect()
+    }
+}
+
+fn format_pnpm_key(name: &str, version: &str) -> String {
+    format!("/{name}/{version}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNPM6: &[u8] =
+        include_bytes!("../../../../cli/internal/lockfile/testdata/pnpm6-workspace.yaml");
+    const PNPM7: &[u8] =
+        include_bytes!("../../../../cli/internal/lockfile/testdata/pnpm7-workspace.yaml");
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        for fixture in [PNPM6, PNPM7] {
+            let encoded = PnpmLockfile::load(fixture)?.encode()?;
+            let reencoded = PnpmLockfile::load(&encoded)?.encode()?;
+            assert_eq!(encoded, reencoded);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_specifier_resolution() -> Result<()> {
+        let lockfile = PnpmLockfile::load(PNPM7)?;
+        let cases = [
+            (
+                "apps/docs",
+                "next",
+                "12.2.5",
+                Some("12.2.5_ir3quccc6i62x6qn6jjhyjjiey"),
+            ),
+            (
+                "apps/web",
+                "next",
+                "12.2.5",
+                Some("12.2.5_ir3quccc6i62x6qn6jjhyjjiey"),
+            ),
+            ("apps/web", "typescript", "^4.5.3", Some("4.8.3")),
+            ("apps/web", "lodash", "bad-tag", None),
+            (
+                "apps/web",
+                "lodash",
+                "^4.17.21",
+                Some("4.17.21_ehchni3mpmovsvjxesffg2i5a4"),
+            ),
+            ("", "turbo", "latest", Some("1.4.6")),
+        ];
+        for (workspace, name, specifier, expected) in cases {
+            assert_eq!(
+                lockfile
+                    .resolve_specifier(workspace, name, specifier)?
+                    .as_deref(),
+                expected,
+                "{name}@{specifier}"
+            );
+        }
+
+        let error = lockfile
+            .resolve_specifier("apps/bad_workspace", "turbo", "latest")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "no workspace 'apps/bad_workspace' found in lockfile"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_subgraph_injected_packages() -> Result<()> {
+        let lockfile = PnpmLockfile::load(PNPM7)?;
+        let pruned = lockfile.subgraph(&["apps/docs".to_string()], &[])?;
+        let pruned = PnpmLockfile::load(&pruned.encode()?)?;
+        assert!(pruned.packages.contains_key("file:packages/ui"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_unquoted_url() -> Result<()> {
+        let resolution: PackageResolution =
+            serde_yaml::from_str("{integrity: sha512-deadbeef, tarball: path/to/tarball?foo=bar}")?;
+        assert_eq!(
+            resolution.tarball.as_deref(),
+            Some("path/to/tarball?foo=bar")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,359 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+};
+
+use anyhow::{anyhow, Result};
+
+use super::{Lockfile, Package};
+
+const HEADER: &str =
+    "# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.\n# yarn lockfile v1\n\n\n";
+
+/// The contents of a `yarn.lock` written by yarn 1.
+#[derive(Debug, Clone, Default)]
+pub struct YarnLockfile {
+    /// Entries are keyed by each of the descriptors that resolved to them,
+    /// e.g. `lodash@^4.17.21`.
+    entries: BTreeMap<String, YarnEntry>,
+    has_crlf: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct YarnEntry {
+    /// The fields of the entry, either strings or maps of strings, e.g. the
+    /// `dependencies`.
+    fields: BTreeMap<String, YarnValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum YarnValue {
+    String(String),
+    Map(BTreeMap<String, String>),
+}
+
+impl YarnEntry {
+    fn string(&self, field: &str) -> Option<&str> {
+        match self.fields.get(field) {
+            Some(YarnValue::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn map(&self, field: &str) -> Option<&BTreeMap<String, String>> {
+        match self.fields.get(field) {
+            Some(YarnValue::Map(map)) => Some(map),
+            _ => None,
+        }
+    }
+
+    fn version(&self) -> &str {
+        self.string("version").unwrap_or_default()
+    }
+}
+
+impl YarnLockfile {
+    pub fn load(content: &[u8]) -> Result<Self> {
+        let content = std::str::from_utf8(content)
+            .map_err(|e| anyhow!("Unable to decode yarn.lock: {}", e))?;
+        let has_crlf = match content.find('\n') {
+            Some(index) => content[..index].ends_with('\r'),
+            None => false,
+        };
+        let entries = parse(content).map_err(|e| anyhow!("Unable to decode yarn.lock: {}", e))?;
+
+        Ok(YarnLockfile { entries, has_crlf })
+    }
+}
+
+impl Lockfile for YarnLockfile {
+    fn resolve_package(
+        &self,
+        _workspace_path: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<Package>> {
+        Ok(["", "npm:", "file:", "workspace:", "yarn:"]
+            .iter()
+            .map(|protocol| format!("{name}@{protocol}{version}"))
+            .find_map(|key| {
+                let version = self.entries.get(&key)?.version().to_string();
+                Some(Package::new(key, version))
+            }))
+    }
+
+    fn all_dependencies(&self, key: &str) -> Result<Option<HashMap<String, String>>> {
+        let Some(entry) = self.entries.get(key) else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            ["dependencies", "optionalDependencies"]
+                .iter()
+                .filter_map(|field| entry.map(field))
+                .flat_map(|deps| deps.iter())
+                .map(|(name, version)| (name.clone(), version.clone()))
+                .collect(),
+        ))
+    }
+
+    fn subgraph(
+        &self,
+        _workspace_packages: &[String],
+        packages: &[String],
+    ) -> Result<Box<dyn Lockfile>> {
+        let entries = packages
+            .iter()
+            .filter_map(|key| Some((key.clone(), self.entries.get(key)?.clone())))
+            .collect();
+
+        Ok(Box::new(YarnLockfile {
+            entries,
+            has_crlf: self.has_crlf,
+        }))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut content = HEADER.to_string();
+
+        // Descriptors that resolve to the same entry share it
+        let mut grouped: Vec<(Vec<&str>, &YarnEntry)> = Vec::new();
+        for (key, entry) in &self.entries {
+            match grouped.iter_mut().find(|(_, existing)| *existing == entry) {
+                Some((keys, _)) => keys.push(key),
+                None => grouped.push((vec![key.as_str()], entry)),
+            }
+        }
+
+        let blocks = grouped
+            .into_iter()
+            .map(|(keys, entry)| {
+                let key = keys.into_iter().map(maybe_wrap).collect::<Vec<_>>();
+                format!("{}:\n{}\n", key.join(", "), stringify_entry(entry))
+            })
+            .collect::<Vec<_>>();
+        content.push_str(&blocks.join("\n"));
+
+        if self.has_crlf {
+            content = content.replace('\n', "\r\n");
+        }
+        Ok(content.into_bytes())
+    }
+}
+
+/// Orders fields like yarn does, with the important fields first and the
+/// rest in alphabetical order.
+fn compare_fields(a: &str, b: &str) -> Ordering {
+    fn priority(field: &str) -> usize {
+        [
+            "name",
+            "version",
+            "uid",
+            "resolved",
+            "integrity",
+            "registry",
+            "dependencies",
+        ]
+        .iter()
+        .position(|f| *f == field)
+        .unwrap_or(usize::MAX)
+    }
+    priority(a).cmp(&priority(b)).then_with(|| a.cmp(b))
+}
+
+fn stringify_entry(entry: &YarnEntry) -> String {
+    let mut fields = entry.fields.iter().collect::<Vec<_>>();
+    fields.sort_by(|(a, _), (b, _)| compare_fields(a, b));
+
+    fields
+        .into_iter()
+        .map(|(field, value)| match value {
+            YarnValue::String(value) => format!("  {} {}", maybe_wrap(field), maybe_wrap(value)),
+            YarnValue::Map(map) => {
+                let mut lines = vec![format!("  {}:", maybe_wrap(field))];
+                lines.extend(
+                    map.iter().map(|(key, value)| {
+                        format!("    {} {}", maybe_wrap(key), maybe_wrap(value))
+                    }),
+                );
+                lines.join("\n")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Quotes a string if yarn would quote it.
+fn maybe_wrap(value: &str) -> String {
+    let should_wrap = value.starts_with("true")
+        || value.starts_with("false")
+        || value
+            .chars()
+            .any(|c| matches!(c, ':' | '\\' | '"' | ',' | '[' | ']') || c.is_whitespace())
+        || !value.starts_with(|c: char| c.is_ascii_alphabetic());
+    if should_wrap {
+        serde_json::to_string(value).expect("strings are always serializable")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Parses the contents of a yarn 1 lockfile into a map of descriptors to
+/// entries.
+fn parse(content: &str) -> Result<BTreeMap<String, YarnEntry>> {
+    let mut entries = BTreeMap::new();
+    let mut current: Option<(Vec<String>, YarnEntry)> = None;
+    let mut current_map: Option<String> = None;
+
+    let mut finish = |current: Option<(Vec<String>, YarnEntry)>| {
+        if let Some((keys, entry)) = current {
+            for key in keys {
+                entries.insert(key, entry.clone());
+            }
+        }
+    };
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        let error = || anyhow!("unexpected content on line {}: {}", index + 1, line);
+
+        match indent {
+            0 => {
+                let keys = trimmed.strip_suffix(':').ok_or_else(error)?;
+                finish(current.take());
+                current = Some((parse_keys(keys)?, YarnEntry::default()));
+                current_map = None;
+            }
+            2 => {
+                let (_, entry) = current.as_mut().ok_or_else(error)?;
+                if let Some(field) = trimmed.strip_suffix(':') {
+                    let field = parse_string(field)?;
+                    entry
+                        .fields
+                        .insert(field.clone(), YarnValue::Map(BTreeMap::new()));
+                    current_map = Some(field);
+                } else {
+                    let (field, value) = parse_pair(trimmed)?.ok_or_else(error)?;
+                    entry.fields.insert(field, YarnValue::String(value));
+                    current_map = None;
+                }
+            }
+            4 => {
+                let (_, entry) = current.as_mut().ok_or_else(error)?;
+                let field = current_map.as_ref().ok_or_else(error)?;
+                let (key, value) = parse_pair(trimmed)?.ok_or_else(error)?;
+                if let Some(YarnValue::Map(map)) = entry.fields.get_mut(field) {
+                    map.insert(key, value);
+                }
+            }
+            _ => return Err(error()),
+        }
+    }
+    finish(current);
+
+    Ok(entries)
+}
+
+/// Parses a comma separated list of (possibly quoted) descriptors.
+fn parse_keys(keys: &str) -> Result<Vec<String>> {
+    let mut result = Vec::new();
+    let mut rest = keys.trim();
+    while !rest.is_empty() {
+        let (key, remainder) = split_token(rest)?;
+        result.push(key);
+        rest = remainder.trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    Ok(result)
+}
+
+/// Parses a `key value` line.
+fn parse_pair(line: &str) -> Result<Option<(String, String)>> {
+    let (key, rest) = split_token(line)?;
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some((key, parse_string(rest)?)))
+}
+
+fn parse_string(value: &str) -> Result<String> {
+    let (value, rest) = split_token(value.trim())?;
+    if !rest.trim().is_empty() {
+        return Err(anyhow!("unexpected content after {}", value));
+    }
+    Ok(value)
+}
+
+/// Splits off the first token of `input`, which is either a quoted string or
+/// ends at the next space or comma.
+fn split_token(input: &str) -> Result<(String, &str)> {
+    if input.starts_with('"') {
+        let mut escaped = false;
+        for (index, c) in input.char_indices().skip(1) {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => {
+                    let token = serde_json::from_str(&input[..=index])?;
+                    return Ok((token, &input[index + 1..]));
+                }
+                _ => escaped = false,
+            }
+        }
+        Err(anyhow!("unterminated string: {}", input))
+    } else {
+        let end = input.find([' ', ',']).unwrap_or(input.len());
+        Ok((input[..end].to_string(), &input[end..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] = include_bytes!("../../../../cli/internal/lockfile/testdata/yarn.lock");
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let lockfile = YarnLockfile::load(FIXTURE)?;
+        assert_eq!(
+            String::from_utf8(lockfile.encode()?)?,
+            String::from_utf8(FIXTURE.to_vec())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_splitting() -> Result<()> {
+        let lockfile = YarnLockfile::load(FIXTURE)?;
+        for key in [
+            "@babel/types@^7.18.10",
+            "@babel/types@^7.18.6",
+            "@babel/types@^7.19.0",
+        ] {
+            assert!(lockfile.entries.contains_key(key), "missing {key}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_subgraph_shares_entries() -> Result<()> {
+        let lockfile = YarnLockfile::load(FIXTURE)?;
+        let pruned = lockfile.subgraph(
+            &[],
+            &[
+                "@babel/types@^7.18.10".to_string(),
+                "@babel/types@^7.19.0".to_string(),
+            ],
+        )?;
+        let encoded = String::from_utf8(pruned.encode()?)?;
+        assert!(encoded.starts_with(HEADER));
+        assert!(encoded.contains("\"@babel/types@^7.18.10\", \"@babel/types@^7.19.0\":\n"));
+        Ok(())
+    }
+}
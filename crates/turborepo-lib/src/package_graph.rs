@@ -0,0 +1,412 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use semver::{Version, VersionReq};
+
+use crate::{
+    lockfile::{self, Lockfile, Package},
+    package_json::PackageJson,
+    package_manager::PackageManager,
+    task_graph::ROOT_PKG_NAME,
+};
+
+/// A workspace of the repository, or the repository root.
+#[derive(Debug)]
+pub struct WorkspaceInfo {
+    pub package_json: PackageJson,
+    /// The path of the package.json, relative to the repository root.
+    pub package_json_path: PathBuf,
+    /// The directory of the workspace, relative to the repository root.
+    pub dir: PathBuf,
+    /// The names of the workspaces this workspace depends on.
+    pub internal_dependencies: BTreeSet<String>,
+    /// The dependencies that aren't workspaces, mapped to their version
+    /// ranges.
+    pub unresolved_external_dependencies: BTreeMap<String, String>,
+    /// The lockfile entries of the external dependencies, including their
+    /// dependencies. `None` if there is no lockfile or the dependencies
+    /// couldn't be resolved.
+    pub transitive_dependencies: Option<HashSet<Package>>,
+    /// The hash of `transitive_dependencies`, used to invalidate tasks when
+    /// external dependencies change.
+    pub external_deps_hash: Option<String>,
+}
+
+/// The workspaces of a repository and the dependencies between them.
+pub struct PackageGraph {
+    pub package_manager: PackageManager,
+    pub lockfile: Option<Box<dyn Lockfile>>,
+    pub root: WorkspaceInfo,
+    pub workspaces: BTreeMap<String, WorkspaceInfo>,
+}
+
+impl PackageGraph {
+    pub fn build(repo_root: &Path, root_package_json: PackageJson) -> Result<Self> {
+        let package_manager = PackageManager::detect(repo_root, &root_package_json)?;
+        let lockfile = match package_manager.read_lockfile(repo_root) {
+            Ok(lockfile) => Some(lockfile),
+            Err(e) => {
+                warn!("could not read lockfile: {}", e);
+                None
+            }
+        };
+
+        let mut workspaces: BTreeMap<String, WorkspaceInfo> = BTreeMap::new();
+        for package_json_path in package_manager
+            .get_package_jsons(repo_root)
+            .map_err(|e| anyhow!("workspace configuration error: {}", e))?
+        {
+            let package_json = PackageJson::load(&package_json_path)?;
+            let package_json_path = package_json_path
+                .strip_prefix(repo_root)
+                .map_err(|_| {
+                    anyhow!(
+                        "{} is outside of the repository",
+                        package_json_path.display()
+                    )
+                })?
+                .to_path_buf();
+            let dir = package_json_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            if let Some(existing) = workspaces.get(&package_json.name) {
+                return Err(anyhow!(
+                    "Failed to add workspace \"{}\" from {}, it already exists at {}",
+                    package_json.name,
+                    dir.display(),
+                    existing.dir.display()
+                ));
+            }
+            workspaces.insert(
+                package_json.name.clone(),
+                WorkspaceInfo {
+                    package_json,
+                    package_json_path,
+                    dir,
+                    internal_dependencies: BTreeSet::new(),
+                    unresolved_external_dependencies: BTreeMap::new(),
+                    transitive_dependencies: None,
+                    external_deps_hash: None,
+                },
+            );
+        }
+
+        // Split the dependencies of each workspace into internal and external ones
+        let versions = workspaces
+            .iter()
+            .map(|(name, info)| (name.clone(), info.package_json.version.clone()))
+            .collect::<BTreeMap<_, _>>();
+        for info in workspaces.values_mut() {
+            populate_dependencies(info, &versions, repo_root, lockfile.as_deref());
+        }
+
+        let mut root = WorkspaceInfo {
+            package_json: root_package_json,
+            package_json_path: PathBuf::from("package.json"),
+            dir: PathBuf::new(),
+            internal_dependencies: BTreeSet::new(),
+            unresolved_external_dependencies: BTreeMap::new(),
+            transitive_dependencies: None,
+            external_deps_hash: None,
+        };
+        populate_dependencies(&mut root, &versions, repo_root, lockfile.as_deref());
+
+        Ok(PackageGraph {
+            package_manager,
+            lockfile,
+            root,
+            workspaces,
+        })
+    }
+
+    /// Builds the graph of a repository without workspaces, which only
+    /// contains the root package.
+    pub fn build_single_package(repo_root: &Path, root_package_json: PackageJson) -> Result<Self> {
+        let package_manager = PackageManager::detect(repo_root, &root_package_json)?;
+        let lockfile = package_manager.read_lockfile(repo_root).ok();
+        let mut root = WorkspaceInfo {
+            package_json: root_package_json,
+            package_json_path: PathBuf::from("package.json"),
+            dir: PathBuf::new(),
+            internal_dependencies: BTreeSet::new(),
+            unresolved_external_dependencies: BTreeMap::new(),
+            transitive_dependencies: None,
+            external_deps_hash: None,
+        };
+        populate_dependencies(&mut root, &BTreeMap::new(), repo_root, lockfile.as_deref());
+
+        Ok(PackageGraph {
+            package_manager,
+            lockfile,
+            root,
+            workspaces: BTreeMap::new(),
+        })
+    }
+
+    /// Returns the workspace with the given name, or the root package for
+    /// `//`.
+    pub fn workspace(&self, name: &str) -> Option<&WorkspaceInfo> {
+        if name == ROOT_PKG_NAME {
+            Some(&self.root)
+        } else {
+            self.workspaces.get(name)
+        }
+    }
+
+    /// Returns the names of all workspaces, including `//` for the root
+    /// package.
+    pub fn workspace_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(ROOT_PKG_NAME).chain(self.workspaces.keys().map(String::as_str))
+    }
+
+    /// Returns the workspaces that `name` directly depends on.
+    pub fn dependencies(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.workspace(name)
+            .into_iter()
+            .flat_map(|info| info.internal_dependencies.iter().map(String::as_str))
+    }
+
+    /// Returns the workspaces that `name` depends on, directly or
+    /// transitively.
+    pub fn transitive_dependencies(&self, name: &str) -> BTreeSet<&str> {
+        let mut dependencies = BTreeSet::new();
+        let mut stack = self.dependencies(name).collect::<Vec<_>>();
+        while let Some(dependency) = stack.pop() {
+            if dependencies.insert(dependency) {
+                stack.extend(self.dependencies(dependency));
+            }
+        }
+        dependencies
+    }
+
+    /// Returns the workspaces that depend on `name`, directly or
+    /// transitively.
+    pub fn transitive_dependents(&self, name: &str) -> BTreeSet<&str> {
+        let mut dependents = BTreeSet::new();
+        let mut stack = vec![name];
+        while let Some(current) = stack.pop() {
+            for workspace in self.workspace_names() {
+                let depends_on_current = self.dependencies(workspace).any(|dep| dep == current);
+                if depends_on_current && dependents.insert(workspace) {
+                    stack.push(workspace);
+                }
+            }
+        }
+        dependents
+    }
+
+    /// Returns the given workspaces and all workspaces they depend on,
+    /// directly or transitively.
+    pub fn internal_dependencies(&self, scopes: &[String]) -> Result<BTreeSet<String>> {
+        let mut dependencies = BTreeSet::new();
+        let mut queue = scopes.to_vec();
+        while let Some(name) = queue.pop() {
+            if dependencies.contains(&name) {
+                continue;
+            }
+            let info = self
+                .workspaces
+                .get(&name)
+                .ok_or_else(|| anyhow!("invalid scope: package {} not found", name))?;
+            queue.extend(info.internal_dependencies.iter().cloned());
+            dependencies.insert(name);
+        }
+        Ok(dependencies)
+    }
+}
+
+fn populate_dependencies(
+    info: &mut WorkspaceInfo,
+    versions: &BTreeMap<String, String>,
+    repo_root: &Path,
+    lockfile: Option<&dyn Lockfile>,
+) {
+    let workspace_dir = repo_root.join(&info.dir);
+    for (name, version) in info.package_json.all_dependencies() {
+        let is_internal = versions.get(&name).map_or(false, |package_version| {
+            is_workspace_reference(package_version, &version, &workspace_dir, repo_root)
+        });
+        if is_internal {
+            info.internal_dependencies.insert(name);
+        } else {
+            info.unresolved_external_dependencies.insert(name, version);
+        }
+    }
+
+    if let Some(lockfile) = lockfile {
+        let workspace_path = lockfile::to_unix_path(&info.dir);
+        match lockfile::transitive_closure(
+            lockfile,
+            &workspace_path,
+            &info.unresolved_external_dependencies,
+        ) {
+            Ok(closure) => {
+                info.external_deps_hash = Some(lockfile::external_deps_hash(&closure));
+                info.transitive_dependencies = Some(closure);
+            }
+            Err(e) => warn!(
+                "could not resolve dependencies of {}: {}",
+                info.package_json.name, e
+            ),
+        }
+    }
+}
+
+/// Checks if the dependency version range `dependency_version` of the
+/// workspace in `cwd` refers to the workspace with the version
+/// `package_version`.
+fn is_workspace_reference(
+    package_version: &str,
+    dependency_version: &str,
+    cwd: &Path,
+    repo_root: &Path,
+) -> bool {
+    let (protocol, version) = match dependency_version.split_once(':') {
+        Some((protocol, version)) => (Some(protocol), version),
+        None => (None, dependency_version),
+    };
+
+    match protocol {
+        // Workspaces with multiple versions of the same package aren't supported,
+        // so the range doesn't need to be checked
+        Some("workspace") => return true,
+        Some("file") | Some("link") => {
+            let path = cwd.join(version);
+            return match dunce::canonicalize(path) {
+                Ok(path) => dunce::canonicalize(repo_root)
+                    .map_or(true, |repo_root| path.starts_with(repo_root)),
+                // Default to internal if the path can't be resolved
+                Err(_) => true,
+            };
+        }
+        // The npm protocol still uses the workspace if the version matches,
+        // other protocols (e.g. `github:`) are external
+        Some("npm") | None => {}
+        Some(_) => return false,
+    }
+    if version == "*" {
+        return true;
+    }
+
+    match (VersionReq::parse(version), Version::parse(package_version)) {
+        (Ok(range), Ok(package_version)) => range.matches(&package_version),
+        // For backwards compatibility, versions that can't be parsed are treated
+        // as internal references
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture_graph() -> Result<PackageGraph> {
+        let repo_root = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/npm-monorepo");
+        let root_package_json = PackageJson::load(&repo_root.join("package.json"))?;
+        PackageGraph::build(&repo_root, root_package_json)
+    }
+
+    fn lockfile_keys(info: &WorkspaceInfo) -> BTreeSet<&str> {
+        info.transitive_dependencies
+            .iter()
+            .flatten()
+            .map(|package| package.key.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_build() -> Result<()> {
+        let graph = fixture_graph()?;
+        assert_eq!(graph.package_manager, PackageManager::Npm);
+        assert_eq!(
+            graph.workspaces.keys().collect::<Vec<_>>(),
+            vec!["docs", "ui", "web"]
+        );
+
+        let web = &graph.workspaces["web"];
+        assert_eq!(web.dir, Path::new("apps/web"));
+        assert_eq!(web.package_json_path, Path::new("apps/web/package.json"));
+        assert_eq!(
+            web.internal_dependencies,
+            BTreeSet::from(["ui".to_string()])
+        );
+        assert_eq!(
+            web.unresolved_external_dependencies,
+            BTreeMap::from([("react".to_string(), "^18.2.0".to_string())])
+        );
+        assert_eq!(
+            lockfile_keys(web),
+            BTreeSet::from([
+                "node_modules/js-tokens",
+                "node_modules/loose-envify",
+                "node_modules/react"
+            ])
+        );
+        assert!(web.external_deps_hash.is_some());
+
+        assert_eq!(
+            lockfile_keys(&graph.workspaces["ui"]),
+            BTreeSet::<&str>::new()
+        );
+        assert_eq!(
+            lockfile_keys(&graph.root),
+            BTreeSet::from(["node_modules/prettier"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_internal_dependencies() -> Result<()> {
+        let graph = fixture_graph()?;
+        assert_eq!(
+            graph.internal_dependencies(&["web".to_string()])?,
+            BTreeSet::from(["ui".to_string(), "web".to_string()])
+        );
+        assert_eq!(
+            graph.internal_dependencies(&["ui".to_string()])?,
+            BTreeSet::from(["ui".to_string()])
+        );
+        assert!(graph.internal_dependencies(&["api".to_string()]).is_err());
+
+        assert_eq!(graph.transitive_dependencies("web"), BTreeSet::from(["ui"]));
+        assert_eq!(
+            graph.transitive_dependents("ui"),
+            BTreeSet::from(["docs", "web"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_workspace_reference() {
+        let cwd = Path::new("/repo/apps/web");
+        let repo_root = Path::new("/repo");
+        assert!(is_workspace_reference(
+            "1.0.0",
+            "workspace:*",
+            cwd,
+            repo_root
+        ));
+        assert!(is_workspace_reference("1.0.0", "*", cwd, repo_root));
+        assert!(is_workspace_reference("1.2.0", "^1.0.0", cwd, repo_root));
+        assert!(is_workspace_reference(
+            "1.2.0",
+            "npm:^1.0.0",
+            cwd,
+            repo_root
+        ));
+        assert!(!is_workspace_reference("2.0.0", "^1.0.0", cwd, repo_root));
+        assert!(!is_workspace_reference(
+            "1.0.0",
+            "github:vercel/turbo",
+            cwd,
+            repo_root
+        ));
+    }
+}
@@ -0,0 +1,61 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+/// The fields of a `package.json` that turbo cares about.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageJson {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    pub package_manager: Option<String>,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    pub dev_dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    pub optional_dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+    /// The deprecated `turbo` key, which was used before `turbo.json`.
+    #[serde(rename = "turbo")]
+    pub legacy_turbo_config: Option<serde_json::Value>,
+    /// The complete contents of the file, used when the file needs to be
+    /// rewritten.
+    #[serde(skip)]
+    pub raw_json: serde_json::Map<String, serde_json::Value>,
+}
+
+impl PackageJson {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("could not read {}: {}", path.display(), e))?;
+        let mut package_json: PackageJson = serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("could not parse {}: {}", path.display(), e))?;
+        package_json.raw_json = serde_json::from_str(&contents)?;
+
+        Ok(package_json)
+    }
+
+    /// Returns the dependencies of all kinds that are installed for the
+    /// package. If a dependency is listed in multiple fields, `dependencies`
+    /// takes precedence over `optionalDependencies`, which takes precedence
+    /// over `devDependencies`.
+    pub fn all_dependencies(&self) -> BTreeMap<String, String> {
+        let mut dependencies = self.dev_dependencies.clone();
+        dependencies.extend(
+            self.optional_dependencies
+                .iter()
+                .map(|(name, version)| (name.clone(), version.clone())),
+        );
+        dependencies.extend(
+            self.dependencies
+                .iter()
+                .map(|(name, version)| (name.clone(), version.clone())),
+        );
+        dependencies
+    }
+}
@@ -4,8 +4,14 @@ use std::{
 };
 
 use anyhow::{anyhow, Result};
+use semver::Version;
 use serde::Deserialize;
 
+use crate::{
+    lockfile::{BerryLockfile, Lockfile, NpmLockfile, PnpmLockfile, YarnLockfile},
+    package_json::PackageJson,
+};
+
 #[derive(Debug, Deserialize)]
 struct PnpmWorkspaces {
     pub packages: Vec<String>,
@@ -41,26 +47,230 @@ impl From<Workspaces> for Vec<String> {
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YarnRc {
+    node_linker: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PackageManager {
-    #[allow(dead_code)]
     Berry,
     Npm,
     Pnpm,
-    #[allow(dead_code)]
     Pnpm6,
-    #[allow(dead_code)]
     Yarn,
 }
 
 #[derive(Debug)]
 pub struct Globs {
-    #[allow(dead_code)]
     inclusions: Vec<PathBuf>,
-    #[allow(dead_code)]
     exclusions: Vec<PathBuf>,
 }
 
 impl PackageManager {
+    /// Finds the package manager of the repository at `root_path`. The
+    /// `packageManager` field of the root package.json is used if it is set,
+    /// otherwise the package manager is inferred from the lockfile.
+    pub fn detect(root_path: &Path, root_package_json: &PackageJson) -> Result<Self> {
+        if let Some(package_manager) = &root_package_json.package_manager {
+            return Self::parse_package_manager_string(package_manager);
+        }
+
+        if root_path.join("pnpm-lock.yaml").exists() {
+            Ok(PackageManager::Pnpm)
+        } else if root_path.join("package-lock.json").exists() {
+            Ok(PackageManager::Npm)
+        } else if let Ok(yarn_lock) = fs::read_to_string(root_path.join("yarn.lock")) {
+            // Only lockfiles written by yarn 2 and newer have metadata
+            if yarn_lock.contains("\n__metadata:") {
+                Ok(PackageManager::Berry)
+            } else {
+                Ok(PackageManager::Yarn)
+            }
+        } else {
+            Err(anyhow!(
+                "We did not detect an in-use package manager for your project. Please set the \
+                 \"packageManager\" property in your root package.json \
+                 (https://nodejs.org/api/packages.html#packagemanager) or run `npx @turbo/codemod \
+                 add-package-manager` in the root of your monorepo."
+            ))
+        }
+    }
+
+    /// Parses the `packageManager` field of a package.json, e.g.
+    /// `pnpm@7.15.0`.
+    fn parse_package_manager_string(package_manager: &str) -> Result<Self> {
+        let (manager, version) = package_manager.split_once('@').ok_or_else(|| {
+            anyhow!(
+                "We could not parse packageManager field in package.json, expected: \
+                 <manager>@<version>, received: {}",
+                package_manager
+            )
+        })?;
+        // Drop the hash of the corepack format, e.g. `yarn@3.2.3+sha224.953c8233`
+        let version = version
+            .split_once('+')
+            .map_or(version, |(version, _)| version);
+        let version = Version::parse(version)
+            .map_err(|e| anyhow!("could not parse {} version: {}", manager, e))?;
+
+        match manager {
+            "npm" => Ok(PackageManager::Npm),
+            "yarn" if version.major >= 2 => Ok(PackageManager::Berry),
+            "yarn" => Ok(PackageManager::Yarn),
+            "pnpm" if version.major >= 7 => Ok(PackageManager::Pnpm),
+            "pnpm" => Ok(PackageManager::Pnpm6),
+            _ => Err(anyhow!("unsupported package manager {}", manager)),
+        }
+    }
+
+    pub fn lockfile_name(&self) -> &'static str {
+        match self {
+            PackageManager::Berry | PackageManager::Yarn => "yarn.lock",
+            PackageManager::Npm => "package-lock.json",
+            PackageManager::Pnpm | PackageManager::Pnpm6 => "pnpm-lock.yaml",
+        }
+    }
+
+    /// The file the workspaces are configured in, if it isn't the root
+    /// package.json.
+    pub fn workspace_configuration_path(&self) -> Option<&'static str> {
+        match self {
+            PackageManager::Pnpm | PackageManager::Pnpm6 => Some("pnpm-workspace.yaml"),
+            PackageManager::Berry | PackageManager::Npm | PackageManager::Yarn => None,
+        }
+    }
+
+    pub fn read_lockfile(&self, root_path: &Path) -> Result<Box<dyn Lockfile>> {
+        let lockfile_path = root_path.join(self.lockfile_name());
+        let contents = fs::read(&lockfile_path)
+            .map_err(|e| anyhow!("could not read {}: {}", lockfile_path.display(), e))?;
+        Ok(match self {
+            PackageManager::Berry => Box::new(BerryLockfile::load(&contents)?),
+            PackageManager::Npm => Box::new(NpmLockfile::load(&contents)?),
+            PackageManager::Pnpm | PackageManager::Pnpm6 => {
+                Box::new(PnpmLockfile::load(&contents)?)
+            }
+            PackageManager::Yarn => Box::new(YarnLockfile::load(&contents)?),
+        })
+    }
+
+    /// Returns an error if pruning isn't supported for the repository.
+    pub fn check_can_prune(&self, root_path: &Path) -> Result<()> {
+        if let PackageManager::Berry = self {
+            // Without `node_modules` the layout of the installation depends on
+            // the whole repository
+            let yarnrc = fs::read_to_string(root_path.join(".yarnrc.yml")).unwrap_or_default();
+            let yarnrc: Option<YarnRc> = serde_yaml::from_str(&yarnrc).ok();
+            let node_linker = yarnrc.and_then(|yarnrc| yarnrc.node_linker);
+            if node_linker.as_deref() != Some("node-modules") {
+                return Err(anyhow!(
+                    "only yarn v2/v3 with `nodeLinker: node-modules` is supported at this time"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the patches that aren't in `patches` from the package.json, as
+    /// package managers fail to install if a patched package is missing.
+    pub fn prune_patched_packages(
+        &self,
+        package_json: &mut serde_json::Map<String, serde_json::Value>,
+        patches: &[String],
+    ) -> Result<()> {
+        match self {
+            PackageManager::Pnpm | PackageManager::Pnpm6 => {
+                let patched_dependencies = package_json
+                    .get_mut("pnpm")
+                    .and_then(|pnpm| pnpm.as_object_mut())
+                    .ok_or_else(|| anyhow!("Invalid structure for pnpm field in package.json"))?
+                    .get_mut("patchedDependencies")
+                    .and_then(|patched| patched.as_object_mut())
+                    .ok_or_else(|| {
+                        anyhow!("Invalid structure for patchedDependencies field in package.json")
+                    })?;
+                for (dependency, patch) in patched_dependencies.iter() {
+                    if !patch.is_string() {
+                        return Err(anyhow!(
+                            "Expected only strings in patchedDependencies. Got {} for {}",
+                            patch,
+                            dependency
+                        ));
+                    }
+                }
+                patched_dependencies.retain(|_, patch| {
+                    patches
+                        .iter()
+                        .any(|wanted| Some(wanted.as_str()) == patch.as_str())
+                });
+            }
+            PackageManager::Berry => {
+                let resolutions = package_json
+                    .get_mut("resolutions")
+                    .and_then(|resolutions| resolutions.as_object_mut())
+                    .ok_or_else(|| {
+                        anyhow!("Invalid structure for resolutions field in package.json")
+                    })?;
+                for (dependency, patch) in resolutions.iter() {
+                    if !patch.is_string() {
+                        return Err(anyhow!(
+                            "Expected value of {} in package.json to be a string, got {}",
+                            dependency,
+                            patch
+                        ));
+                    }
+                }
+                // Only unused patches need to be removed, as they are the only
+                // resolutions that fail the installation
+                resolutions.retain(|_, patch| {
+                    let patch = patch.as_str().unwrap_or_default();
+                    !patch.ends_with(".patch")
+                        || patches
+                            .iter()
+                            .any(|wanted| patch.ends_with(wanted.as_str()))
+                });
+            }
+            PackageManager::Npm | PackageManager::Yarn => {}
+        }
+        Ok(())
+    }
+
+    /// Returns the paths of the package.json files of all workspaces.
+    pub fn get_package_jsons(&self, root_path: &Path) -> Result<Vec<PathBuf>> {
+        let globs = self.get_workspace_globs(root_path)?;
+        let exclusions = globs
+            .exclusions
+            .iter()
+            .map(|exclusion| glob::Pattern::new(&exclusion.to_string_lossy()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let node_modules = glob::Pattern::new("**/node_modules/**")?;
+
+        let mut package_jsons = Vec::new();
+        for inclusion in &globs.inclusions {
+            let pattern = root_path.join(inclusion).join("package.json");
+            for package_json in glob::glob(&pattern.to_string_lossy())? {
+                let package_json = package_json?;
+                let Some(workspace) = package_json
+                    .parent()
+                    .and_then(|dir| dir.strip_prefix(root_path).ok())
+                else {
+                    continue;
+                };
+                let is_excluded = node_modules.matches_path(workspace)
+                    || exclusions
+                        .iter()
+                        .any(|exclusion| exclusion.matches_path(workspace));
+                if !is_excluded && !package_jsons.contains(&package_json) {
+                    package_jsons.push(package_json);
+                }
+            }
+        }
+
+        Ok(package_jsons)
+    }
+
     /// Returns a list of globs for the package workspace.
     /// NOTE: We return a `Vec<PathBuf>` instead of a `GlobSet` because we
     /// may need to iterate through these globs and a `GlobSet` doesn't allow
@@ -0,0 +1,468 @@
+use std::{
+    collections::BTreeSet,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use glob::{MatchOptions, Pattern};
+
+use crate::{package_graph::PackageGraph, task_graph::ROOT_PKG_NAME};
+
+/// The options used to select packages before `--filter` existed. They are
+/// translated into filter patterns.
+#[derive(Debug, Default)]
+pub struct LegacyFilter<'a> {
+    /// Also select the dependencies of the entrypoints.
+    pub include_dependencies: bool,
+    /// Don't select the packages that depend on the entrypoints.
+    pub skip_dependents: bool,
+    /// The `--scope` patterns.
+    pub entrypoints: &'a [String],
+    /// The git ref to compare against to find changed packages.
+    pub since: Option<&'a str>,
+}
+
+impl LegacyFilter<'_> {
+    pub fn as_filter_patterns(&self) -> Vec<String> {
+        let prefix = if self.skip_dependents { "" } else { "..." };
+        let suffix = if self.include_dependencies { "..." } else { "" };
+        let since = self.since.map(|since| format!("[{since}]"));
+
+        if !self.entrypoints.is_empty() {
+            // --scope implies our tweaked syntax to see if any dependency matches
+            let since = since.map(|since| format!("...{since}")).unwrap_or_default();
+            self.entrypoints
+                .iter()
+                .map(|pattern| {
+                    if pattern.starts_with('!') {
+                        pattern.clone()
+                    } else {
+                        format!("{prefix}{pattern}{since}{suffix}")
+                    }
+                })
+                .collect()
+        } else if let Some(since) = since {
+            vec![format!("{prefix}{since}{suffix}")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// A parsed `--filter` pattern. The syntax mirrors pnpm's.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TargetSelector {
+    pub include_dependencies: bool,
+    pub match_dependencies: bool,
+    pub include_dependents: bool,
+    pub exclude: bool,
+    pub exclude_self: bool,
+    /// A glob of package directories, relative to the repository root.
+    pub parent_dir: Option<PathBuf>,
+    pub name_pattern: String,
+    /// The git ref to compare against to find changed packages.
+    pub from_ref: Option<String>,
+    pub raw: String,
+}
+
+impl TargetSelector {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut selector = raw;
+        let exclude = match selector.strip_prefix('!') {
+            Some(rest) => {
+                selector = rest;
+                true
+            }
+            None => false,
+        };
+
+        let mut exclude_self = false;
+        let include_dependencies = match selector.strip_suffix("...") {
+            Some(rest) => {
+                selector = rest;
+                if let Some(rest) = selector.strip_suffix('^') {
+                    exclude_self = true;
+                    selector = rest;
+                }
+                true
+            }
+            None => false,
+        };
+        let include_dependents = match selector.strip_prefix("...") {
+            Some(rest) => {
+                selector = rest;
+                if let Some(rest) = selector.strip_prefix('^') {
+                    exclude_self = true;
+                    selector = rest;
+                }
+                true
+            }
+            None => false,
+        };
+
+        let mut target = TargetSelector {
+            include_dependencies,
+            include_dependents,
+            exclude,
+            exclude_self,
+            raw: raw.to_string(),
+            ..TargetSelector::default()
+        };
+
+        let Some((name_pattern, parent_dir, from_ref)) = split_selector(selector) else {
+            if is_selector_by_location(selector) {
+                target.parent_dir = Some(normalize(Path::new(selector)));
+            } else {
+                target.name_pattern = selector.to_string();
+            }
+            return Ok(target);
+        };
+
+        target.name_pattern = name_pattern.to_string();
+        target.parent_dir = parent_dir.map(|dir| normalize(Path::new(dir)));
+        if let Some(from_ref) = from_ref {
+            let from_ref = match from_ref.strip_prefix("...") {
+                Some(from_ref) => {
+                    if target.parent_dir.is_none() && target.name_pattern.is_empty() {
+                        return Err(anyhow!(
+                            "cannot use match dependencies without specifying either a directory \
+                             or package"
+                        ));
+                    }
+                    target.match_dependencies = true;
+                    from_ref
+                }
+                None => from_ref,
+            };
+            target.from_ref = Some(from_ref[1..from_ref.len() - 1].to_string());
+        }
+
+        Ok(target)
+    }
+}
+
+/// Splits a selector into the name pattern, the directory in `{}` and the git
+/// ref in `[]`. Returns `None` if the selector doesn't have that shape.
+fn split_selector(selector: &str) -> Option<(&str, Option<&str>, Option<&str>)> {
+    let mut rest = selector;
+
+    let mut from_ref = None;
+    if rest.ends_with(']') {
+        let start = rest.rfind('[')?;
+        if start + 2 == rest.len() {
+            return None;
+        }
+        let start = if rest[..start].ends_with("...") {
+            start - 3
+        } else {
+            start
+        };
+        from_ref = Some(&rest[start..]);
+        rest = &rest[..start];
+    }
+
+    let mut parent_dir = None;
+    if rest.ends_with('}') {
+        let start = rest.rfind('{')?;
+        if start + 2 == rest.len() {
+            return None;
+        }
+        parent_dir = Some(&rest[start + 1..rest.len() - 1]);
+        rest = &rest[..start];
+    }
+
+    // Names can't start with a `.`, which makes `./apps` a path rather than a name
+    let name_is_valid = rest.is_empty()
+        || (!rest.starts_with('.') && !rest.ends_with('.') && !rest.contains(['{', '}', '[', ']']));
+    if !name_is_valid {
+        return None;
+    }
+    Some((rest, parent_dir, from_ref))
+}
+
+/// Checks if the selector is a path, i.e. starts with `.` or `..`.
+fn is_selector_by_location(selector: &str) -> bool {
+    let Some(rest) = selector.strip_prefix('.') else {
+        return false;
+    };
+    let rest = rest.strip_prefix('.').unwrap_or(rest);
+    rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\')
+}
+
+/// Lexically resolves `.` and `..` in a path relative to the repository root.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Selects packages based on `--filter` patterns.
+pub struct Resolver<'a> {
+    pub graph: &'a PackageGraph,
+}
+
+impl<'a> Resolver<'a> {
+    /// Returns the packages that match the patterns. Packages that match
+    /// any of the patterns are selected, unless they match an exclusion
+    /// pattern.
+    pub fn packages_from_patterns(&self, patterns: &[String]) -> Result<BTreeSet<String>> {
+        let selectors = patterns
+            .iter()
+            .map(|pattern| TargetSelector::parse(pattern))
+            .collect::<Result<Vec<_>>>()?;
+        if selectors.is_empty() {
+            return Ok(BTreeSet::new());
+        }
+
+        let (exclude, include): (Vec<_>, Vec<_>) =
+            selectors.into_iter().partition(|selector| selector.exclude);
+        let included = if include.is_empty() {
+            self.graph.workspace_names().collect()
+        } else {
+            self.filter_graph_with_selectors(&include)?
+        };
+        let excluded = self.filter_graph_with_selectors(&exclude)?;
+
+        Ok(included
+            .difference(&excluded)
+            .map(|name| name.to_string())
+            .collect())
+    }
+
+    fn filter_graph_with_selectors(
+        &self,
+        selectors: &[TargetSelector],
+    ) -> Result<BTreeSet<&'a str>> {
+        let mut selected = BTreeSet::new();
+        for selector in selectors {
+            for package in self.filter_nodes_with_selector(selector)? {
+                if selector.include_dependencies {
+                    selected.extend(self.graph.transitive_dependencies(package));
+                }
+                if selector.include_dependents {
+                    for dependent in self.graph.transitive_dependents(package) {
+                        selected.insert(dependent);
+                        if selector.include_dependencies {
+                            selected.extend(self.graph.transitive_dependencies(dependent));
+                        }
+                    }
+                }
+                let is_cherry_picked =
+                    !selector.include_dependencies && !selector.include_dependents;
+                if is_cherry_picked || !selector.exclude_self {
+                    selected.insert(package);
+                }
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Returns the packages that match a single selector.
+    fn filter_nodes_with_selector(&self, selector: &TargetSelector) -> Result<BTreeSet<&'a str>> {
+        if let Some(from_ref) = &selector.from_ref {
+            return Err(anyhow!(
+                "filtering by changed packages ({}) isn't supported yet",
+                from_ref
+            ));
+        }
+
+        let mut entry_packages = BTreeSet::new();
+        let mut selector_was_used = false;
+        if let Some(parent_dir) = &selector.parent_dir {
+            selector_was_used = true;
+            if parent_dir.as_os_str().is_empty() {
+                entry_packages.insert(ROOT_PKG_NAME);
+            } else {
+                let pattern = Pattern::new(&parent_dir.to_string_lossy())
+                    .map_err(|e| anyhow!("invalid directory filter {}: {}", selector.raw, e))?;
+                let options = MatchOptions {
+                    require_literal_separator: true,
+                    ..MatchOptions::default()
+                };
+                for (name, info) in &self.graph.workspaces {
+                    if pattern.matches_path_with(&info.dir, options) {
+                        entry_packages.insert(name.as_str());
+                    }
+                }
+            }
+        }
+
+        if !selector.name_pattern.is_empty() {
+            let candidates = if selector_was_used {
+                entry_packages
+            } else {
+                self.graph.workspace_names().collect()
+            };
+            entry_packages = match_package_names(&selector.name_pattern, candidates)?;
+            selector_was_used = true;
+        }
+
+        if !selector_was_used {
+            return Err(anyhow!("invalid selector: {}", selector.raw));
+        }
+        Ok(entry_packages)
+    }
+}
+
+/// Matches package names against a pattern where `*` matches any characters.
+/// Unscoped patterns also match a single scoped package, e.g. `web` matches
+/// `@acme/web` if there is no other `@*/web` package.
+fn match_package_names<'a>(
+    pattern: &str,
+    packages: BTreeSet<&'a str>,
+) -> Result<BTreeSet<&'a str>> {
+    let matcher = name_matcher(pattern)?;
+    let matched = packages
+        .iter()
+        .copied()
+        .filter(|package| matcher.matches(package))
+        .collect::<BTreeSet<_>>();
+    if !matched.is_empty() || pattern.starts_with('@') || pattern.contains('/') {
+        return Ok(matched);
+    }
+
+    let scoped_matcher = name_matcher(&format!("@*/{pattern}"))?;
+    let scoped = packages
+        .into_iter()
+        .filter(|package| scoped_matcher.matches(package))
+        .collect::<BTreeSet<_>>();
+    // Multiple scoped packages are ambiguous
+    if scoped.len() == 1 {
+        Ok(scoped)
+    } else {
+        Ok(BTreeSet::new())
+    }
+}
+
+fn name_matcher(pattern: &str) -> Result<Pattern> {
+    let escaped = pattern
+        .split('*')
+        .map(Pattern::escape)
+        .collect::<Vec<_>>()
+        .join("*");
+    Pattern::new(&escaped).map_err(|e| anyhow!("invalid filter pattern {}: {}", pattern, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target_selector() -> Result<()> {
+        let cases = [
+            (
+                "foo",
+                TargetSelector {
+                    name_pattern: "foo".to_string(),
+                    ..TargetSelector::default()
+                },
+            ),
+            (
+                "foo...",
+                TargetSelector {
+                    name_pattern: "foo".to_string(),
+                    include_dependencies: true,
+                    ..TargetSelector::default()
+                },
+            ),
+            (
+                "...^foo",
+                TargetSelector {
+                    name_pattern: "foo".to_string(),
+                    include_dependents: true,
+                    exclude_self: true,
+                    ..TargetSelector::default()
+                },
+            ),
+            (
+                "!./apps/*",
+                TargetSelector {
+                    parent_dir: Some(PathBuf::from("apps/*")),
+                    exclude: true,
+                    ..TargetSelector::default()
+                },
+            ),
+            (
+                "@acme/*{packages/**}",
+                TargetSelector {
+                    name_pattern: "@acme/*".to_string(),
+                    parent_dir: Some(PathBuf::from("packages/**")),
+                    ..TargetSelector::default()
+                },
+            ),
+            (
+                "foo...[main]",
+                TargetSelector {
+                    name_pattern: "foo".to_string(),
+                    match_dependencies: true,
+                    from_ref: Some("main".to_string()),
+                    ..TargetSelector::default()
+                },
+            ),
+            (
+                "[HEAD^1]",
+                TargetSelector {
+                    from_ref: Some("HEAD^1".to_string()),
+                    ..TargetSelector::default()
+                },
+            ),
+        ];
+
+        for (raw, expected) in cases {
+            let expected = TargetSelector {
+                raw: raw.to_string(),
+                ..expected
+            };
+            assert_eq!(TargetSelector::parse(raw)?, expected, "{raw}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_filter_patterns() {
+        let entrypoints = vec!["web".to_string(), "!docs".to_string()];
+        let filter = LegacyFilter {
+            include_dependencies: true,
+            entrypoints: &entrypoints,
+            ..LegacyFilter::default()
+        };
+        assert_eq!(
+            filter.as_filter_patterns(),
+            vec!["...web...".to_string(), "!docs".to_string()]
+        );
+
+        let filter = LegacyFilter {
+            skip_dependents: true,
+            since: Some("main"),
+            ..LegacyFilter::default()
+        };
+        assert_eq!(filter.as_filter_patterns(), vec!["[main]".to_string()]);
+    }
+
+    #[test]
+    fn test_match_package_names() -> Result<()> {
+        let packages = BTreeSet::from(["@acme/web", "@acme/docs", "@other/docs", "utils"]);
+        let cases: [(&str, &[&str]); 4] = [
+            ("@acme/*", &["@acme/docs", "@acme/web"]),
+            ("web", &["@acme/web"]),
+            ("docs", &[]),
+            ("util*", &["utils"]),
+        ];
+        for (pattern, expected) in cases {
+            assert_eq!(
+                match_package_names(pattern, packages.clone())?,
+                expected.iter().copied().collect(),
+                "{pattern}"
+            );
+        }
+        Ok(())
+    }
+}
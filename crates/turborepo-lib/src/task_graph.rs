@@ -0,0 +1,572 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    package_graph::PackageGraph,
+    turbo_json::{task_definition, Pipeline},
+};
+
+/// A placeholder node that all tasks without dependencies depend on.
+pub const ROOT_NODE_NAME: &str = "___ROOT___";
+/// The name of the root package in task ids, e.g. `//#build`.
+pub const ROOT_PKG_NAME: &str = "//";
+const TASK_DELIMITER: char = '#';
+
+/// Returns the id of `task` in `package`, e.g. `web#build`. Tasks that already
+/// name a package are returned unchanged.
+pub fn task_id(package: &str, task: &str) -> String {
+    if is_package_task(task) {
+        task.to_string()
+    } else {
+        format!("{package}{TASK_DELIMITER}{task}")
+    }
+}
+
+/// Returns the id of `task` in the root package.
+pub fn root_task_id(task: &str) -> String {
+    task_id(ROOT_PKG_NAME, task)
+}
+
+/// Checks if `task` is a task of a specific package, e.g. `web#build`.
+pub fn is_package_task(task: &str) -> bool {
+    task.contains(TASK_DELIMITER)
+}
+
+/// Splits a task id into the package and task name.
+pub fn package_and_task(task_id: &str) -> (&str, &str) {
+    task_id.split_once(TASK_DELIMITER).unwrap_or(("", task_id))
+}
+
+/// Returns the task name of a task id, or the task itself if it doesn't name
+/// a package.
+pub fn task_name(task_id: &str) -> &str {
+    package_and_task(task_id).1
+}
+
+/// The tasks of a pipeline entry that a task depends on.
+#[derive(Debug, Default)]
+struct Task {
+    /// Tasks of the same package, e.g. `build` -> `codegen`.
+    deps: BTreeSet<String>,
+    /// Tasks of the package dependencies, e.g. `build` -> `^build`.
+    topo_deps: BTreeSet<String>,
+}
+
+/// The options that select which tasks are part of the graph.
+#[derive(Debug)]
+pub struct TaskGraphOptions<'a> {
+    /// The packages in scope.
+    pub packages: &'a BTreeSet<String>,
+    /// The tasks to run in each package.
+    pub tasks: &'a [String],
+    /// Only include the given tasks, but not the tasks they depend on.
+    pub tasks_only: bool,
+}
+
+/// The graph of the package tasks (`web#build`) that are part of a run, with
+/// edges from each task to the tasks it depends on.
+#[derive(Debug, Default)]
+pub struct TaskGraph {
+    edges: BTreeMap<String, BTreeSet<String>>,
+    /// The reversed edges, from each task to the tasks that directly depend on
+    /// it.
+    dependents: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TaskGraph {
+    /// Builds the task graph of `options.tasks` in `options.packages` based
+    /// on the dependencies declared in the pipeline.
+    pub fn build(
+        package_graph: &PackageGraph,
+        pipeline: &Pipeline,
+        options: TaskGraphOptions,
+    ) -> Result<Self> {
+        let mut tasks = BTreeMap::new();
+        let mut package_task_deps: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        let mut root_enabled_tasks = HashSet::new();
+        for (name, definition) in pipeline {
+            let mut task = Task {
+                topo_deps: definition
+                    .topological_dependencies
+                    .iter()
+                    .cloned()
+                    .collect(),
+                ..Task::default()
+            };
+            for dependency in &definition.task_dependencies {
+                // Dependencies between package tasks are tracked separately since
+                // they don't apply to other packages
+                if is_package_task(name) && is_package_task(dependency) {
+                    let (package, _) = package_and_task(dependency);
+                    if package != ROOT_PKG_NAME && !package_graph.workspaces.contains_key(package) {
+                        return Err(anyhow!(
+                            "found reference to unknown package: {} in task {}",
+                            package,
+                            dependency
+                        ));
+                    }
+                    package_task_deps
+                        .entry(name.as_str())
+                        .or_default()
+                        .push(dependency.as_str());
+                } else {
+                    task.deps.insert(dependency.clone());
+                }
+            }
+
+            if is_package_task(name) {
+                let (package, task_name) = package_and_task(name);
+                if package == ROOT_PKG_NAME {
+                    root_enabled_tasks.insert(task_name);
+                }
+            }
+            tasks.insert(name.as_str(), task);
+        }
+        let get_task = |task_id: &str| {
+            tasks
+                .get(task_id)
+                .or_else(|| tasks.get(task_name(task_id)))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Missing task definition, configure \"{}\" or \"{}\" in turbo.json",
+                        task_name(task_id),
+                        task_id
+                    )
+                })
+        };
+
+        let mut queue = VecDeque::with_capacity(options.packages.len() * options.tasks.len());
+        for package in options.packages {
+            let is_root = package == ROOT_PKG_NAME;
+            for task in options.tasks {
+                if is_root && !root_enabled_tasks.contains(task.as_str()) {
+                    continue;
+                }
+                // The requested tasks don't need to be defined for every package, as
+                // long as some package defines them. Dependencies do need a definition.
+                let id = task_id(package, task);
+                if get_task(&id).is_ok() {
+                    queue.push_back(id);
+                }
+            }
+        }
+
+        let mut graph = TaskGraph::default();
+        let mut visited = HashSet::new();
+        while let Some(id) = queue.pop_front() {
+            let (package, name) = package_and_task(&id);
+            if package == ROOT_PKG_NAME && !root_enabled_tasks.contains(name) {
+                return Err(anyhow!(
+                    "{} needs an entry in turbo.json before it can be depended on because it is a \
+                     task run from the root package",
+                    id
+                ));
+            }
+            let task = get_task(&id)?;
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+
+            let in_scope = |dependency: &&String| {
+                !options.tasks_only || options.tasks.iter().any(|task| task == *dependency)
+            };
+            let mut dependencies = BTreeSet::new();
+            for topo_dep in task.topo_deps.iter().filter(in_scope) {
+                for dependency_package in package_graph.dependencies(package) {
+                    dependencies.insert(task_id(dependency_package, topo_dep));
+                }
+            }
+            for dep in task.deps.iter().filter(in_scope) {
+                dependencies.insert(task_id(package, dep));
+            }
+            for dep in package_task_deps.get(id.as_str()).into_iter().flatten() {
+                dependencies.insert(dep.to_string());
+            }
+
+            queue.extend(dependencies.iter().cloned());
+            if dependencies.is_empty() {
+                dependencies.insert(ROOT_NODE_NAME.to_string());
+            }
+            graph.edges.entry(id).or_default().extend(dependencies);
+        }
+
+        for (task, dependencies) in &graph.edges {
+            for dependency in dependencies {
+                graph
+                    .dependents
+                    .entry(dependency.clone())
+                    .or_default()
+                    .insert(task.clone());
+            }
+        }
+
+        graph.validate()?;
+        graph.validate_persistent_dependencies(package_graph, pipeline)?;
+        Ok(graph)
+    }
+
+    /// Returns all tasks of the graph, excluding the root node.
+    pub fn tasks(&self) -> impl Iterator<Item = &str> {
+        self.edges.keys().map(String::as_str)
+    }
+
+    /// Returns the tasks that `task_id` directly depends on.
+    pub fn dependencies(&self, task_id: &str) -> impl Iterator<Item = &str> {
+        self.edges
+            .get(task_id)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|dependency| *dependency != ROOT_NODE_NAME)
+    }
+
+    /// Returns the tasks that directly depend on `task_id`.
+    pub fn dependents(&self, task_id: &str) -> impl Iterator<Item = &str> {
+        self.dependents
+            .get(task_id)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Returns the tasks that `task_id` depends on, directly or transitively.
+    pub fn transitive_dependencies(&self, task_id: &str) -> BTreeSet<&str> {
+        Self::reachable(self.dependencies(task_id), |task| self.dependencies(task))
+    }
+
+    /// Returns the tasks that depend on `task_id`, directly or transitively.
+    pub fn transitive_dependents(&self, task_id: &str) -> BTreeSet<&str> {
+        Self::reachable(self.dependents(task_id), |task| self.dependents(task))
+    }
+
+    /// Returns the `start` tasks and all tasks reachable from them through
+    /// `neighbors`.
+    fn reachable<'a, I>(
+        start: impl Iterator<Item = &'a str>,
+        neighbors: impl Fn(&'a str) -> I,
+    ) -> BTreeSet<&'a str>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        for task in start {
+            if visited.insert(task) {
+                queue.push_back(task);
+            }
+        }
+        while let Some(task) = queue.pop_front() {
+            for neighbor in neighbors(task) {
+                if visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        visited
+    }
+
+    /// Returns the tasks in the order they can be executed in, i.e. every
+    /// task comes after its dependencies. Independent tasks are sorted by
+    /// their id.
+    pub fn sorted_tasks(&self) -> Vec<&str> {
+        let mut remaining_dependencies = self
+            .tasks()
+            .map(|task| (task, self.dependencies(task).count()))
+            .collect::<HashMap<_, _>>();
+
+        // Tasks are sorted level by level, so that independent tasks keep
+        // their order. Tasks of cycles are never ready, and are left out.
+        let mut sorted = Vec::with_capacity(self.edges.len());
+        let mut ready = self
+            .tasks()
+            .filter(|task| remaining_dependencies[task] == 0)
+            .collect::<Vec<_>>();
+        while !ready.is_empty() {
+            let mut next = Vec::new();
+            for task in &ready {
+                for dependent in self.dependents(task) {
+                    if let Some(count) = remaining_dependencies.get_mut(dependent) {
+                        *count -= 1;
+                        if *count == 0 {
+                            next.push(dependent);
+                        }
+                    }
+                }
+            }
+            next.sort_unstable();
+            sorted.append(&mut ready);
+            ready = next;
+        }
+        sorted
+    }
+
+    /// Renders the graph in the Graphviz dot format.
+    pub fn to_dot(&self) -> String {
+        let mut edges = self
+            .edges
+            .iter()
+            .flat_map(|(task, dependencies)| {
+                dependencies.iter().map(move |dependency| {
+                    format!("\t\t\"[root] {task}\" -> \"[root] {dependency}\"")
+                })
+            })
+            .collect::<Vec<_>>();
+        edges.sort();
+
+        let mut dot = String::from("digraph {\n\tcompound = \"true\"\n\tnewrank = \"true\"\n");
+        dot.push_str("\tsubgraph \"root\" {\n");
+        for edge in edges {
+            dot.push_str(&edge);
+            dot.push('\n');
+        }
+        dot.push_str("\t}\n}\n");
+        dot
+    }
+
+    /// Checks that the graph doesn't contain cycles.
+    fn validate(&self) -> Result<()> {
+        for (task, dependencies) in &self.edges {
+            if dependencies.contains(task) {
+                return Err(anyhow!(
+                    "Invalid task dependency graph:\n{} depends on itself",
+                    task
+                ));
+            }
+        }
+
+        let sorted = self.sorted_tasks();
+        if sorted.len() < self.edges.len() {
+            let sorted = sorted.into_iter().collect::<HashSet<_>>();
+            let cycle = self
+                .tasks()
+                .filter(|task| !sorted.contains(task))
+                .collect::<Vec<_>>();
+            return Err(anyhow!(
+                "Invalid task dependency graph:\ncyclic dependency detected:\n\t{}",
+                cycle.join(",")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that no task depends on a persistent task, since persistent
+    /// tasks never finish.
+    fn validate_persistent_dependencies(
+        &self,
+        package_graph: &PackageGraph,
+        pipeline: &Pipeline,
+    ) -> Result<()> {
+        for task in self.tasks() {
+            for dependency in self.dependencies(task) {
+                let (package, name) = package_and_task(dependency);
+                let definition = task_definition(pipeline, dependency).ok_or_else(|| {
+                    anyhow!(
+                        "Cannot find task definition for {} in package {}",
+                        dependency,
+                        package
+                    )
+                })?;
+                let workspace = package_graph
+                    .workspace(package)
+                    .ok_or_else(|| anyhow!("Cannot find package {}", package))?;
+                if definition.persistent && workspace.package_json.scripts.contains_key(name) {
+                    return Err(anyhow!(
+                        "Invalid persistent task dependency:\n\"{}\" is a persistent task, \"{}\" \
+                         cannot depend on it",
+                        dependency,
+                        task
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::{
+        package_graph::WorkspaceInfo, package_json::PackageJson, package_manager::PackageManager,
+        turbo_json::TaskDefinition,
+    };
+
+    fn workspace(dir: &str, scripts: &[&str], dependencies: &[&str]) -> WorkspaceInfo {
+        WorkspaceInfo {
+            package_json: PackageJson {
+                scripts: scripts
+                    .iter()
+                    .map(|script| (script.to_string(), format!("run {script}")))
+                    .collect(),
+                ..PackageJson::default()
+            },
+            package_json_path: Path::new(dir).join("package.json"),
+            dir: PathBuf::from(dir),
+            internal_dependencies: dependencies.iter().map(|dep| dep.to_string()).collect(),
+            unresolved_external_dependencies: BTreeMap::new(),
+            transitive_dependencies: None,
+            external_deps_hash: None,
+        }
+    }
+
+    fn package_graph() -> PackageGraph {
+        PackageGraph {
+            package_manager: PackageManager::Npm,
+            lockfile: None,
+            root: workspace("", &[], &[]),
+            workspaces: BTreeMap::from([
+                (
+                    "web".to_string(),
+                    workspace("apps/web", &["build", "dev"], &["ui"]),
+                ),
+                ("ui".to_string(), workspace("packages/ui", &["build"], &[])),
+            ]),
+        }
+    }
+
+    fn pipeline(tasks: &[(&str, TaskDefinition)]) -> Pipeline {
+        tasks
+            .iter()
+            .map(|(name, definition)| (name.to_string(), definition.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_topological_dependencies() -> Result<()> {
+        let pipeline = pipeline(&[(
+            "build",
+            TaskDefinition {
+                topological_dependencies: vec!["build".to_string()],
+                ..TaskDefinition::default()
+            },
+        )]);
+        let packages = BTreeSet::from(["web".to_string(), "ui".to_string()]);
+        let graph = TaskGraph::build(
+            &package_graph(),
+            &pipeline,
+            TaskGraphOptions {
+                packages: &packages,
+                tasks: &["build".to_string()],
+                tasks_only: false,
+            },
+        )?;
+
+        assert_eq!(graph.sorted_tasks(), vec!["ui#build", "web#build"]);
+        assert_eq!(
+            graph.dependents("ui#build").collect::<Vec<_>>(),
+            vec!["web#build"]
+        );
+        assert_eq!(
+            graph.transitive_dependencies("web#build"),
+            BTreeSet::from(["ui#build"])
+        );
+        assert_eq!(
+            graph.transitive_dependents("ui#build"),
+            BTreeSet::from(["web#build"])
+        );
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n\tcompound = \"true\"\n\tnewrank = \"true\"\n\tsubgraph \"root\" \
+             {\n\t\t\"[root] ui#build\" -> \"[root] ___ROOT___\"\n\t\t\"[root] web#build\" -> \
+             \"[root] ui#build\"\n\t}\n}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sorted_tasks_by_level() -> Result<()> {
+        let pipeline = pipeline(&[
+            (
+                "build",
+                TaskDefinition {
+                    topological_dependencies: vec!["build".to_string()],
+                    ..TaskDefinition::default()
+                },
+            ),
+            ("lint", TaskDefinition::default()),
+        ]);
+        let packages = BTreeSet::from(["web".to_string(), "ui".to_string()]);
+        let graph = TaskGraph::build(
+            &package_graph(),
+            &pipeline,
+            TaskGraphOptions {
+                packages: &packages,
+                tasks: &["build".to_string(), "lint".to_string()],
+                tasks_only: false,
+            },
+        )?;
+
+        assert_eq!(
+            graph.sorted_tasks(),
+            vec!["ui#build", "ui#lint", "web#lint", "web#build"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_persistent_dependency() {
+        let pipeline = pipeline(&[
+            (
+                "build",
+                TaskDefinition {
+                    task_dependencies: vec!["dev".to_string()],
+                    ..TaskDefinition::default()
+                },
+            ),
+            (
+                "dev",
+                TaskDefinition {
+                    persistent: true,
+                    ..TaskDefinition::default()
+                },
+            ),
+        ]);
+        let packages = BTreeSet::from(["web".to_string()]);
+        let result = TaskGraph::build(
+            &package_graph(),
+            &pipeline,
+            TaskGraphOptions {
+                packages: &packages,
+                tasks: &["build".to_string()],
+                tasks_only: false,
+            },
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cycle() {
+        let pipeline = pipeline(&[
+            (
+                "build",
+                TaskDefinition {
+                    task_dependencies: vec!["lint".to_string()],
+                    ..TaskDefinition::default()
+                },
+            ),
+            (
+                "lint",
+                TaskDefinition {
+                    task_dependencies: vec!["build".to_string()],
+                    ..TaskDefinition::default()
+                },
+            ),
+        ]);
+        let packages = BTreeSet::from(["web".to_string()]);
+        let result = TaskGraph::build(
+            &package_graph(),
+            &pipeline,
+            TaskGraphOptions {
+                packages: &packages,
+                tasks: &["build".to_string()],
+                tasks_only: false,
+            },
+        );
+
+        assert!(result.is_err());
+    }
+}
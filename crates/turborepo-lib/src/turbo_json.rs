@@ -0,0 +1,329 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use serde::Deserialize;
+
+use crate::{
+    cli::OutputLogsMode,
+    package_json::PackageJson,
+    task_graph::{is_package_task, root_task_id, task_name},
+};
+
+const CONFIG_FILE: &str = "turbo.json";
+const ENV_PIPELINE_DELIMITER: &str = "$";
+const TOPOLOGICAL_PIPELINE_DELIMITER: &str = "^";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTurboJson {
+    #[serde(default)]
+    pipeline: BTreeMap<String, RawTaskDefinition>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTaskDefinition {
+    outputs: Option<Vec<String>>,
+    cache: Option<bool>,
+    #[serde(default)]
+    depends_on: Vec<String>,
+    #[serde(default)]
+    inputs: Vec<String>,
+    output_mode: Option<OutputLogsMode>,
+    #[serde(default)]
+    env: Vec<String>,
+    #[serde(default)]
+    persistent: bool,
+}
+
+/// The contents of `turbo.json`. `globalDependencies` and `globalEnv` are
+/// only used by the global hash, which is still computed by Go.
+#[derive(Debug, Default)]
+pub struct TurboJson {
+    pub pipeline: Pipeline,
+}
+
+/// Task definitions keyed by task names (`build`) or package tasks
+/// (`web#build`).
+pub type Pipeline = BTreeMap<String, TaskDefinition>;
+
+/// The globs of the files a task produces.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskOutputs {
+    pub inclusions: Vec<String>,
+    pub exclusions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskDefinition {
+    pub outputs: TaskOutputs,
+    pub should_cache: bool,
+    pub env_var_dependencies: Vec<String>,
+    /// Tasks of the dependencies of a package that this task depends on,
+    /// written as `^build` in `dependsOn`.
+    pub topological_dependencies: Vec<String>,
+    /// Tasks of the same package (`build`) or of a specific package
+    /// (`web#build`) that this task depends on.
+    pub task_dependencies: Vec<String>,
+    /// The files that affect the hash of the task. All files of the package
+    /// if empty.
+    pub inputs: Vec<String>,
+    pub output_mode: OutputLogsMode,
+    /// Whether the task doesn't exit, e.g. a dev server.
+    pub persistent: bool,
+}
+
+impl Default for TaskDefinition {
+    fn default() -> Self {
+        TaskDefinition {
+            outputs: TaskOutputs::default(),
+            should_cache: true,
+            env_var_dependencies: Vec::new(),
+            topological_dependencies: Vec::new(),
+            task_dependencies: Vec::new(),
+            inputs: Vec::new(),
+            output_mode: OutputLogsMode::Full,
+            persistent: false,
+        }
+    }
+}
+
+impl TryFrom<RawTaskDefinition> for TaskDefinition {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawTaskDefinition) -> Result<Self> {
+        let (mut exclusions, mut inclusions): (Vec<_>, Vec<_>) = raw
+            .outputs
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|glob| glob.starts_with('!'));
+        for exclusion in &mut exclusions {
+            exclusion.remove(0);
+        }
+        inclusions.sort();
+        exclusions.sort();
+
+        let mut env_var_dependencies = BTreeSet::new();
+        let mut topological_dependencies = Vec::new();
+        let mut task_dependencies = Vec::new();
+        for dependency in raw.depends_on {
+            if let Some(env_var) = dependency.strip_prefix(ENV_PIPELINE_DELIMITER) {
+                warn!(
+                    "[DEPRECATED] Declaring an environment variable in \"dependsOn\" is \
+                     deprecated, found {}. Use the \"env\" key or use `npx @turbo/codemod \
+                     migrate-env-var-dependencies`.",
+                    dependency
+                );
+                env_var_dependencies.insert(env_var.to_string());
+            } else if let Some(task) = dependency.strip_prefix(TOPOLOGICAL_PIPELINE_DELIMITER) {
+                topological_dependencies.push(task.to_string());
+            } else {
+                task_dependencies.push(dependency);
+            }
+        }
+        topological_dependencies.sort();
+        task_dependencies.sort();
+
+        for env_var in raw.env {
+            if env_var.starts_with(ENV_PIPELINE_DELIMITER) {
+                return Err(anyhow!(
+                    "You specified \"{}\" in the \"env\" key. You should not prefix your \
+                     environment variables with \"{}\"",
+                    env_var,
+                    ENV_PIPELINE_DELIMITER
+                ));
+            }
+            env_var_dependencies.insert(env_var);
+        }
+
+        Ok(TaskDefinition {
+            outputs: TaskOutputs {
+                inclusions,
+                exclusions,
+            },
+            should_cache: raw.cache.unwrap_or(true),
+            env_var_dependencies: env_var_dependencies.into_iter().collect(),
+            topological_dependencies,
+            task_dependencies,
+            inputs: raw.inputs,
+            output_mode: raw.output_mode.unwrap_or(OutputLogsMode::Full),
+            persistent: raw.persistent,
+        })
+    }
+}
+
+impl TryFrom<RawTurboJson> for TurboJson {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawTurboJson) -> Result<Self> {
+        Ok(TurboJson {
+            pipeline: raw
+                .pipeline
+                .into_iter()
+                .map(|(task, definition)| Ok((task, definition.try_into()?)))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl TurboJson {
+    /// Reads the `turbo.json` in the repository root. Comments are allowed.
+    pub fn read(repo_root: &Path, root_package_json: &PackageJson) -> Result<Self> {
+        let path = repo_root.join(CONFIG_FILE);
+        if !path.exists() {
+            if root_package_json.legacy_turbo_config.is_some() {
+                warn!(
+                    "[DEPRECATED] \"turbo\" in package.json is deprecated. Migrate to {} by \
+                     running \"npx @turbo/codemod create-turbo-config\"",
+                    CONFIG_FILE
+                );
+            }
+            return Err(anyhow!(
+                "Could not find {}. Follow directions at https://turbo.build/repo/docs to create \
+                 one",
+                CONFIG_FILE
+            ));
+        }
+        if root_package_json.legacy_turbo_config.is_some() {
+            warn!(
+                "[WARNING] Ignoring \"turbo\" key in package.json, using {} instead.",
+                CONFIG_FILE
+            );
+        }
+
+        let contents = fs::read(&path).map_err(|e| anyhow!("{}: {}", CONFIG_FILE, e))?;
+        let raw: RawTurboJson =
+            serde_json::from_reader(json_comments::StripComments::new(contents.as_slice()))
+                .map_err(|e| anyhow!("{}: {}", CONFIG_FILE, e))?;
+        raw.try_into()
+            .map_err(|e: anyhow::Error| anyhow!("{}: {}", CONFIG_FILE, e))
+    }
+
+    /// Loads the configuration for a run. In single package mode every task
+    /// is a task of the root package, and scripts of the root package.json
+    /// don't need to be configured in `turbo.json`.
+    pub fn load(
+        repo_root: &Path,
+        root_package_json: &PackageJson,
+        single_package: bool,
+    ) -> Result<Self> {
+        if !single_package {
+            return Self::read(repo_root, root_package_json);
+        }
+
+        let mut turbo_json = if repo_root.join(CONFIG_FILE).exists() {
+            let mut turbo_json = Self::read(repo_root, root_package_json)?;
+            let mut pipeline = Pipeline::new();
+            for (task_id, definition) in turbo_json.pipeline {
+                if is_package_task(&task_id) {
+                    return Err(anyhow!(
+                        "Package tasks (<package>#<task>) are not allowed in single-package \
+                         repositories: found {}",
+                        task_id
+                    ));
+                }
+                pipeline.insert(root_task_id(&task_id), definition);
+            }
+            turbo_json.pipeline = pipeline;
+            turbo_json
+        } else {
+            TurboJson::default()
+        };
+
+        for script in root_package_json.scripts.keys() {
+            if !pipeline_has_task(&turbo_json.pipeline, script) {
+                turbo_json
+                    .pipeline
+                    .insert(root_task_id(script), TaskDefinition::default());
+            }
+        }
+        Ok(turbo_json)
+    }
+}
+
+/// Returns the definition of `task_id`, falling back to the definition of the
+/// task for all packages.
+pub fn task_definition<'a>(pipeline: &'a Pipeline, task_id: &str) -> Option<&'a TaskDefinition> {
+    pipeline
+        .get(task_id)
+        .or_else(|| pipeline.get(task_name(task_id)))
+}
+
+/// Checks if `task` is defined for all packages or for a specific package.
+pub fn pipeline_has_task(pipeline: &Pipeline, task: &str) -> bool {
+    pipeline
+        .keys()
+        .any(|key| key == task || (is_package_task(key) && task_name(key) == task))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        Path::new("../../cli/internal/fs/testdata").join(name)
+    }
+
+    fn read_test_config(name: &str) -> Result<TurboJson> {
+        let dir = test_dir(name);
+        let package_json = PackageJson::load(&dir.join("package.json"))?;
+        TurboJson::read(&dir, &package_json)
+    }
+
+    #[test]
+    fn test_read_turbo_config() -> Result<()> {
+        let turbo_json = read_test_config("correct")?;
+
+        assert_eq!(
+            turbo_json.pipeline["build"],
+            TaskDefinition {
+                outputs: TaskOutputs {
+                    inclusions: vec![".next/**".to_string(), "dist/**".to_string()],
+                    exclusions: vec!["dist/assets/**".to_string()],
+                },
+                topological_dependencies: vec!["build".to_string()],
+                output_mode: OutputLogsMode::NewOnly,
+                ..TaskDefinition::default()
+            }
+        );
+        assert_eq!(
+            turbo_json.pipeline["lint"].env_var_dependencies,
+            vec!["MY_VAR".to_string()]
+        );
+        assert!(!turbo_json.pipeline["dev"].should_cache);
+        assert_eq!(
+            turbo_json.pipeline["publish"],
+            TaskDefinition {
+                outputs: TaskOutputs {
+                    inclusions: vec!["dist/**".to_string()],
+                    exclusions: vec![],
+                },
+                should_cache: false,
+                topological_dependencies: vec!["build".to_string(), "publish".to_string()],
+                task_dependencies: vec!["admin#lint".to_string(), "build".to_string()],
+                inputs: vec!["build/**/*".to_string()],
+                ..TaskDefinition::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_env() {
+        for name in ["invalid-env-1", "invalid-env-2"] {
+            assert!(read_test_config(name).is_err(), "{name} should be invalid");
+        }
+    }
+
+    #[test]
+    fn test_missing_turbo_json() {
+        assert!(read_test_config("legacy-only").is_err());
+    }
+}